@@ -47,6 +47,58 @@ fn bench_serialize_serdejson(b: &mut Bencher) {
     });
 }
 
+// Long strings with few escapes; compare with and without the `simd` feature.
+fn input_strings() -> Vec<String> {
+    (0..1000)
+        .map(|i| format!("{} \"{}\"\n", "lorem ipsum dolor sit amet ".repeat(20), i))
+        .collect()
+}
+
+#[bench]
+fn bench_deserialize_miniserde_strings(b: &mut Bencher) {
+    let j = miniserde::json::to_string(&input_strings()).unwrap();
+    b.iter(|| {
+        miniserde::json::from_str::<Vec<String>>(&j).unwrap();
+    });
+}
+
+#[bench]
+fn bench_serialize_miniserde_strings(b: &mut Bencher) {
+    let s = input_strings();
+    b.iter(|| {
+        miniserde::json::to_string(&s).unwrap();
+    });
+}
+
+#[bench]
+fn bench_deserialize_miniserde_cbor(b: &mut Bencher) {
+    let c = miniserde::cbor::to_vec(&input_struct()).unwrap();
+    b.iter(|| {
+        miniserde::cbor::from_slice::<Twitter>(&c).unwrap();
+    });
+}
+
+// Decodes without allocating; see tests/test_zero_alloc.rs.
+#[bench]
+fn bench_deserialize_miniserde_cbor_flat(b: &mut Bencher) {
+    let point = Point {
+        x: 1,
+        y: -2,
+        visible: true,
+    };
+    let c = miniserde::cbor::to_vec(&point).unwrap();
+    b.iter(|| {
+        miniserde::cbor::from_slice::<Point>(&c).unwrap();
+    });
+}
+
+#[derive(MiniSerialize, MiniDeserialize)]
+struct Point {
+    x: i32,
+    y: i32,
+    visible: bool,
+}
+
 #[derive(Serialize, MiniSerialize, Deserialize, MiniDeserialize)]
 struct Twitter {
     statuses: Vec<Status>,
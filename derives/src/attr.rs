@@ -1,35 +1,88 @@
 use ::core::ops::Not as _;
-use ::syn::{spanned::Spanned, Result, *};
+use ::quote::ToTokens;
+use ::syn::{punctuated::Punctuated, spanned::Spanned, Result, *};
 
-/// Find the value of a #[serde(rename = "...")] attribute.
-fn attr_rename(attrs: &[Attribute]) -> Result<Option<String>> {
-    let mut ret = None;
+use crate::bound::Side;
+
+/// The names set by a #[serde(rename = "...")] attribute, or by a
+/// #[serde(rename(serialize = "...", deserialize = "..."))] one, per side.
+#[derive(Default)]
+struct Rename {
+    serialize: Option<String>,
+    deserialize: Option<String>,
+}
+
+/// Find the value of a #[serde(rename ...)] attribute.
+fn attr_rename(attrs: &[Attribute]) -> Result<Rename> {
+    let mut ret = Rename::default();
 
     for_each_serde_attr!( attrs =>
         #[serde( rename = $new_name )] => {
-            let prev = ret.replace(new_name);
-            if prev.is_some() {
-                return Err(Error::new_spanned(rename, "duplicate `rename` attribute"));
+            set_rename(&mut ret.serialize, new_name.clone(), rename)?;
+            set_rename(&mut ret.deserialize, new_name, rename)?;
+        },
+        _ if is_list(meta!(), "rename") => {
+            let list = match meta!() {
+                Meta::List(list) => list,
+                _ => unreachable!(),
+            };
+            for nested in &list.nested {
+                match *nested {
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        ref path,
+                        lit: Lit::Str(ref s),
+                        ..
+                    })) if path.is_ident("serialize") => {
+                        set_rename(&mut ret.serialize, s.value(), list)?;
+                    }
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        ref path,
+                        lit: Lit::Str(ref s),
+                        ..
+                    })) if path.is_ident("deserialize") => {
+                        set_rename(&mut ret.deserialize, s.value(), list)?;
+                    }
+                    ref other => {
+                        let msg = "expected `serialize = \"...\"` or `deserialize = \"...\"`";
+                        return Err(Error::new_spanned(other, msg));
+                    }
+                }
             }
         },
+        #[serde( alias = $name )] => {
+            let _ = name;
+        },
+        #[serde(flatten)] => {},
 
         #[serde( with = "serde_bytes" )] => {
             // Thanks to `view_seq` and the impl for `u8`, we have already specialized
             // the "sequence of u8s" case, so no need for `serde_bytes`.
             // Thus, nothing to do.
         },
+        #[serde( with = $module )] => {
+            let _ = module;
+        },
+        #[serde( serialize_with = $function )] => {
+            let _ = function;
+        },
+        #[serde( deserialize_with = $function )] => {
+            let _ = function;
+        },
 
-        // TODO
-        // #[serde(other)] => {
-        //     // This is sometimes correct; ignore it since it will be correctly
-        //     // handled somewhere else. FIXME: do this better.
-        // },
+        #[serde(other)] => {},
 
         #[serde(skip)] => {},
         #[serde(skip_deserializing)] => {},
         #[serde(skip_serializing)] => {},
 
         #[serde(default)] => {},
+        #[serde(trim)] => {},
+        #[serde(trim = $mode)] => {
+            let _ = mode;
+        },
+        #[serde(deprecated_field)] => {},
+        _ if is_list(meta!(), "range") || is_list(meta!(), "len") => {},
+        _ if is_int(meta!(), "id") => {},
         #[serde(skip_serializing_if = $condition)] => {
             let _ = condition;
         },
@@ -38,6 +91,23 @@ fn attr_rename(attrs: &[Attribute]) -> Result<Option<String>> {
     Ok(ret)
 }
 
+fn set_rename(slot: &mut Option<String>, name: String, spanned: impl ToTokens) -> Result<()> {
+    if slot.replace(name).is_some() {
+        return Err(Error::new_spanned(spanned, "duplicate `rename` attribute"));
+    }
+    Ok(())
+}
+
+/// The values of the #[serde(alias = "...")] attributes, in order.
+fn attr_aliases(attrs: &[Attribute]) -> Result<Vec<String>> {
+    let mut ret = vec![];
+    for_each_serde_attr!( attrs =>
+        #[serde( alias = $name )] => ret.push(name),
+        _ => {},
+    )?;
+    Ok(ret)
+}
+
 pub fn has_skip_deserializing(attrs: &[Attribute]) -> bool {
     let mut ret = false;
     let _ = for_each_serde_attr! { attrs =>
@@ -58,18 +128,902 @@ pub fn has_skip_serializing(attrs: &[Attribute]) -> bool {
     ret
 }
 
-/// Determine the name of a field, respecting a rename attribute.
+/// Whether a field has a #[serde(flatten)] attribute.
+pub fn has_flatten(attrs: &[Attribute]) -> bool {
+    let mut ret = false;
+    let _ = for_each_serde_attr! { attrs =>
+        #[serde(flatten)] => ret = true,
+        _ => {},
+    };
+    ret
+}
+
+/// Find the #[serde(flatten)] field among `fields`, of which there may be only
+/// one.
+pub fn flatten_field<'a>(fields: impl IntoIterator<Item = &'a Field>) -> Result<Option<&'a Field>> {
+    let mut ret = None;
+    for field in fields {
+        if has_flatten(&field.attrs).not() {
+            continue;
+        }
+        if ret.replace(field).is_some() {
+            return Err(Error::new_spanned(
+                field,
+                "only one #[serde(flatten)] field is supported",
+            ));
+        }
+        for &side in &[Side::Serialize, Side::Deserialize] {
+            if with_of_field(field, side)?.is_some() {
+                return Err(Error::new_spanned(
+                    field,
+                    "#[serde(flatten)] cannot be used with #[serde(with)] and the like",
+                ));
+            }
+        }
+    }
+    Ok(ret)
+}
+
+/// Whether a field has a `#[serde(deprecated_field)]` attribute.
+pub fn has_deprecated_field(attrs: &[Attribute]) -> bool {
+    let mut ret = false;
+    let _ = for_each_serde_attr! { attrs =>
+        #[serde(deprecated_field)] => ret = true,
+        _ => {},
+    };
+    ret
+}
+
+/// Whether a container has a `#[serde(case_insensitive)]` attribute.
+pub fn has_case_insensitive(attrs: &[Attribute]) -> bool {
+    let mut ret = false;
+    let _ = for_each_serde_attr! { attrs =>
+        #[serde(case_insensitive)] => ret = true,
+        _ => {},
+    };
+    ret
+}
+
+/// Whether a container has a `#[serde(accept_array)]` attribute.
+pub fn has_accept_array(attrs: &[Attribute]) -> bool {
+    let mut ret = false;
+    let _ = for_each_serde_attr! { attrs =>
+        #[serde(accept_array)] => ret = true,
+        _ => {},
+    };
+    ret
+}
+
+/// Whether a container has a `#[serde(presence_bitmap)]` attribute.
+pub fn has_presence_bitmap(attrs: &[Attribute]) -> bool {
+    let mut ret = false;
+    let _ = for_each_serde_attr! { attrs =>
+        #[serde(presence_bitmap)] => ret = true,
+        _ => {},
+    };
+    ret
+}
+
+/// Whether a container has a #[serde(transparent)] attribute.
+pub fn has_transparent(attrs: &[Attribute]) -> bool {
+    let mut ret = false;
+    let _ = for_each_serde_attr! { attrs =>
+        #[serde(transparent)] => ret = true,
+        _ => {},
+    };
+    ret
+}
+
+/// Whether a container has a `#[serde(sort_keys)]` attribute.
+pub fn has_sort_keys(attrs: &[Attribute]) -> bool {
+    let mut ret = false;
+    let _ = for_each_serde_attr! { attrs =>
+        #[serde(sort_keys)] => ret = true,
+        _ => {},
+    };
+    ret
+}
+
+/// Whether a container has a #[serde(borrow)] attribute.
+pub fn has_borrow(attrs: &[Attribute]) -> bool {
+    let mut ret = false;
+    let _ = for_each_serde_attr! { attrs =>
+        #[serde(borrow)] => ret = true,
+        _ => {},
+    };
+    ret
+}
+
+/// For each field of a `#[serde(presence_bitmap)]` struct but the skipped ones,
+/// in order, the mask of its bit in the bitmap if its type is spelled
+/// `Option<...>`.
+pub fn presence_bits(input: &DeriveInput, fields: &FieldsNamed) -> Result<Vec<Option<u64>>> {
+    if has_sort_keys(&input.attrs) || has_accept_array(&input.attrs) {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "#[serde(presence_bitmap)] cannot be used with #[serde(sort_keys)] \
+             nor #[serde(accept_array)]",
+        ));
+    }
+    let mut bits = 0..64;
+    let mut ret = vec![];
+    for field in &fields.named {
+        let skip = has_skip_serializing(&field.attrs);
+        if skip != has_skip_deserializing(&field.attrs) {
+            return Err(Error::new_spanned(
+                field,
+                "fields of a #[serde(presence_bitmap)] struct can only be skipped \
+                 on both sides, with #[serde(skip)]",
+            ));
+        }
+        if skip {
+            continue;
+        }
+        if has_flatten(&field.attrs) || skip_serializing_if(field)?.is_some() {
+            return Err(Error::new_spanned(
+                field,
+                "#[serde(flatten)] and #[serde(skip_serializing_if)] cannot be used \
+                 with #[serde(presence_bitmap)]",
+            ));
+        }
+        let is_option = match &field.ty {
+            Type::Path(TypePath { qself: None, path }) => {
+                path.segments.last().map_or(false, |last| {
+                    last.ident == "Option"
+                        && matches!(last.arguments, PathArguments::AngleBracketed(_))
+                })
+            }
+            _ => false,
+        };
+        ret.push(if is_option {
+            match bits.next() {
+                Some(bit) => Some(1 << bit),
+                None => {
+                    return Err(Error::new_spanned(
+                        field,
+                        "#[serde(presence_bitmap)] supports at most 64 `Option` fields",
+                    ))
+                }
+            }
+        } else {
+            None
+        });
+    }
+    Ok(ret)
+}
+
+/// How a string field gets cleaned up after being deserialized.
+pub enum Trim {
+    /// `#[serde(trim)]`: strip leading and trailing whitespace.
+    Whitespace,
+    /// `#[serde(trim = "nfc")]`: also normalize to Unicode NFC.
+    Nfc,
+}
+
+/// Find the value of a #[serde(trim)] or #[serde(trim = "...")] attribute.
+pub fn trim_of_field(field: &Field) -> Result<Option<Trim>> {
+    let mut ret = None;
+
+    for_each_serde_attr!( &field.attrs =>
+        #[serde(trim)] => {
+            if ret.replace(Trim::Whitespace).is_some() {
+                return Err(Error::new_spanned(trim, "duplicate `trim` attribute"));
+            }
+        },
+        #[serde(trim = $mode)] => {
+            if mode != "nfc" {
+                return Err(Error::new_spanned(trim, "expected `trim` or `trim = \"nfc\"`"));
+            }
+            if ret.replace(Trim::Nfc).is_some() {
+                return Err(Error::new_spanned(trim, "duplicate `trim` attribute"));
+            }
+        },
+        _ => {},
+    )?;
+
+    Ok(ret)
+}
+
+/// Determine the name of a field when serializing, respecting a rename
+/// attribute.
 pub fn name_of_field(field: &Field) -> Result<String> {
-    let rename = attr_rename(&field.attrs)?;
+    let rename = attr_rename(&field.attrs)?.serialize;
     Ok(rename.unwrap_or_else(|| field.ident.as_ref().unwrap().to_string()))
 }
 
-/// Determine the name of a variant, respecting a rename attribute.
+/// Determine the names a field accepts when deserializing: its name,
+/// respecting a rename attribute, followed by its aliases.
+pub fn de_names_of_field(field: &Field) -> Result<Vec<String>> {
+    let rename = attr_rename(&field.attrs)?.deserialize;
+    let name = rename.unwrap_or_else(|| field.ident.as_ref().unwrap().to_string());
+    let mut names = vec![name];
+    names.extend(attr_aliases(&field.attrs)?);
+    Ok(names)
+}
+
+/// Whether an enum has a `#[serde(variant_name)]` attribute.
+pub fn has_variant_name(attrs: &[Attribute]) -> bool {
+    let mut ret = false;
+    let _ = for_each_serde_attr! { attrs =>
+        #[serde(variant_name)] => ret = true,
+        _ => {},
+    };
+    ret
+}
+
+/// Find the value of a `#[serde(type_tag = "...")]` attribute.
+pub fn type_tag(attrs: &[Attribute]) -> Result<Option<String>> {
+    let mut ret = None;
+    for_each_serde_attr!( attrs =>
+        #[serde( type_tag = $name )] => {
+            if ret.replace(name).is_some() {
+                return Err(Error::new_spanned(type_tag, "duplicate `type_tag` attribute"));
+            }
+        },
+        _ => {},
+    )?;
+    Ok(ret)
+}
+
+/// Find the value of a #[serde(id = N)] variant attribute.
+pub fn id_of_variant(var: &Variant) -> Result<Option<LitInt>> {
+    attr_int(&var.attrs, "id")
+}
+
+/// Whether an enum has a `#[serde(variant_ints)]` attribute.
+pub fn has_variant_ints(attrs: &[Attribute]) -> bool {
+    let mut ret = false;
+    let _ = for_each_serde_attr! { attrs =>
+        #[serde(variant_ints)] => ret = true,
+        _ => {},
+    };
+    ret
+}
+
+/// The integer codes of the unit variants of an enum, paired with their
+/// identifiers. With `#[serde(variant_ints)]` or `#[serde(repr)]` every variant
+/// has a code, which defaults to its discriminant; otherwise only the variants
+/// carrying a #[serde(id = N)] attribute do.
+pub fn ids_of_variants<'a>(
+    attrs: &[Attribute],
+    Enum: &Ident,
+    enumeration: &'a DataEnum,
+) -> Result<Vec<(Expr, &'a Ident)>> {
+    let variant_ints = has_variant_ints(attrs) || int_repr(attrs)?.is_some();
+    let mut ret = vec![];
+    for var in &enumeration.variants {
+        let ident = &var.ident;
+        match id_of_variant(var)? {
+            Some(id) => ret.push((parse_quote!(#id), ident)),
+            None if variant_ints => ret.push((parse_quote!(#Enum::#ident), ident)),
+            None => {}
+        }
+    }
+    Ok(ret)
+}
+
+const INT_REPRS: &[&str] = &[
+    "u8", "u16", "u32", "u64", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+];
+
+/// The integer type of a #[serde(repr = "u8")] enum, or of a bare
+/// #[serde(repr)] one, taken from its #[repr(u8)] attribute.
+pub fn int_repr(attrs: &[Attribute]) -> Result<Option<Ident>> {
+    let meta = match find_meta(attrs, "repr")? {
+        Some(meta) => meta,
+        None => return Ok(None),
+    };
+    let repr = match &meta {
+        Meta::NameValue(MetaNameValue {
+            lit: Lit::Str(s), ..
+        }) => s.parse::<Ident>().ok(),
+        Meta::Path(_) => rust_int_repr(attrs)?,
+        _ => None,
+    };
+    match repr {
+        Some(repr) if INT_REPRS.iter().any(|int| repr == int) => Ok(Some(repr)),
+        Some(repr) => Err(Error::new_spanned(
+            repr,
+            format!("expected one of: {}", INT_REPRS.join(", ")),
+        )),
+        None => Err(Error::new_spanned(
+            meta,
+            "expected #[serde(repr = \"u8\")], or #[serde(repr)] with #[repr(u8)]",
+        )),
+    }
+}
+
+/// The integer type in a #[repr(...)] attribute, such as #[repr(C, u8)].
+fn rust_int_repr(attrs: &[Attribute]) -> Result<Option<Ident>> {
+    for attr in attrs {
+        if attr.path.is_ident("repr").not() {
+            continue;
+        }
+        if let Meta::List(list) = attr.parse_meta()? {
+            for nested in list.nested {
+                if let NestedMeta::Meta(Meta::Path(path)) = nested {
+                    match path.get_ident() {
+                        Some(ident) if INT_REPRS.iter().any(|int| ident == int) => {
+                            return Ok(Some(ident.clone()))
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Find the predicate of a `#[serde(skip_serializing_if = "path")]` attribute.
+pub fn skip_serializing_if(field: &Field) -> Result<Option<ExprPath>> {
+    let mut ret = None;
+    for meta in serde_metas(&field.attrs)? {
+        match meta {
+            Meta::NameValue(MetaNameValue {
+                path,
+                lit: Lit::Str(s),
+                ..
+            }) if path.is_ident("skip_serializing_if") => {
+                if ret.is_some() {
+                    return Err(Error::new_spanned(
+                        path,
+                        "duplicate `skip_serializing_if` attribute",
+                    ));
+                }
+                ret = Some(s.parse()?);
+            }
+            _ => {}
+        }
+    }
+    Ok(ret)
+}
+
+/// Find the function a field is serialized or deserialized with, per `side`:
+/// the one of a `#[serde(serialize_with = "path")]` or
+/// `#[serde(deserialize_with = "path")]` attribute, or else `module::serialize`
+/// or `module::deserialize` for #[serde(with = "module")].
+///
+/// `with = "serde_bytes"` is still ignored, as the derives already handle
+/// bytes.
+pub fn with_of_field(field: &Field, side: Side) -> Result<Option<ExprPath>> {
+    let (name, function) = match side {
+        Side::Serialize => ("serialize_with", "serialize"),
+        Side::Deserialize => ("deserialize_with", "deserialize"),
+    };
+    let mut ret = None;
+    for meta in serde_metas(&field.attrs)? {
+        let path = match meta {
+            Meta::NameValue(MetaNameValue {
+                ref path,
+                lit: Lit::Str(ref s),
+                ..
+            }) if path.is_ident(name) => s.parse()?,
+            Meta::NameValue(MetaNameValue {
+                ref path,
+                lit: Lit::Str(ref s),
+                ..
+            }) if path.is_ident("with") && s.value() != "serde_bytes" => {
+                let mut module: ExprPath = s.parse()?;
+                module
+                    .path
+                    .segments
+                    .push(Ident::new(function, s.span()).into());
+                module
+            }
+            _ => continue,
+        };
+        if ret.replace(path).is_some() {
+            return Err(Error::new_spanned(
+                meta,
+                format!("duplicate `with` or `{}` attribute", name),
+            ));
+        }
+    }
+    Ok(ret)
+}
+
+/// Reject #[serde(with)] and the like outside of the fields of structs with
+/// named fields, whose derives are the only ones to call the functions.
+pub fn check_with(input: &DeriveInput) -> Result<()> {
+    let supported = match &input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(_),
+            ..
+        }) => ["into", "from", "try_from", "bits", "transparent"]
+            .iter()
+            .all(|name| matches!(find_meta(&input.attrs, name), Ok(None))),
+        _ => false,
+    };
+    let fields: Vec<&Field> = match &input.data {
+        Data::Struct(data) => data.fields.iter().collect(),
+        Data::Enum(data) => data.variants.iter().flat_map(|v| &v.fields).collect(),
+        Data::Union(data) => data.fields.named.iter().collect(),
+    };
+    for field in fields {
+        for &side in &[Side::Serialize, Side::Deserialize] {
+            if supported.not() && with_of_field(field, side)?.is_some() {
+                return Err(Error::new_spanned(
+                    field,
+                    "#[serde(with)] and the like are only supported on the fields of structs \
+                     with named fields, without a container conversion",
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Find the type of a #[serde(from = "Type")] container attribute, or of the
+/// `try_from` or `into` one, per `name`.
+pub fn conversion_type(attrs: &[Attribute], name: &str) -> Result<Option<Type>> {
+    let mut ret = None;
+    for meta in serde_metas(attrs)? {
+        match meta {
+            Meta::NameValue(MetaNameValue {
+                path,
+                lit: Lit::Str(s),
+                ..
+            }) if path.is_ident(name) => {
+                if ret.is_some() {
+                    let msg = format!("duplicate `{}` attribute", name);
+                    return Err(Error::new_spanned(path, msg));
+                }
+                ret = Some(s.parse()?);
+            }
+            _ => {}
+        }
+    }
+    Ok(ret)
+}
+
+/// How a container with a `Repr` type gets deserialized from it.
+pub enum FromRepr {
+    /// `#[serde(from = "Repr")]`.
+    From(Type),
+    /// `#[serde(try_from = "Repr")]`.
+    TryFrom(Type),
+}
+
+/// Find a `#[serde(from = "...")]` or `#[serde(try_from = "...")]` attribute.
+pub fn from_repr(attrs: &[Attribute]) -> Result<Option<FromRepr>> {
+    match (
+        conversion_type(attrs, "from")?,
+        conversion_type(attrs, "try_from")?,
+    ) {
+        (Some(_), Some(_)) => Err(Error::new_spanned(
+            find_meta(attrs, "try_from")?,
+            "#[serde(from)] cannot be used with #[serde(try_from)]",
+        )),
+        (Some(Repr), None) => Ok(Some(FromRepr::From(Repr))),
+        (None, Some(Repr)) => Ok(Some(FromRepr::TryFrom(Repr))),
+        (None, None) => Ok(None),
+    }
+}
+
+/// The where predicates set by a #[serde(bound = "...")] attribute, or by a
+/// #[serde(bound(serialize = "...", deserialize = "..."))] one, per side.
+#[derive(Default)]
+pub struct BoundOverride {
+    pub serialize: Option<Vec<WherePredicate>>,
+    pub deserialize: Option<Vec<WherePredicate>>,
+}
+
+pub fn bound_override(attrs: &[Attribute]) -> Result<BoundOverride> {
+    fn set(
+        slot: &mut Option<Vec<WherePredicate>>,
+        s: &LitStr,
+        spanned: impl ToTokens,
+    ) -> Result<()> {
+        let predicates = s.parse_with(Punctuated::<WherePredicate, Token![,]>::parse_terminated)?;
+        if slot.replace(predicates.into_iter().collect()).is_some() {
+            return Err(Error::new_spanned(spanned, "duplicate `bound` attribute"));
+        }
+        Ok(())
+    }
+
+    let mut ret = BoundOverride::default();
+    for meta in serde_metas(attrs)? {
+        match meta {
+            Meta::NameValue(MetaNameValue {
+                path,
+                lit: Lit::Str(s),
+                ..
+            }) if path.is_ident("bound") => {
+                set(&mut ret.serialize, &s, &path)?;
+                set(&mut ret.deserialize, &s, &path)?;
+            }
+            Meta::List(list) if list.path.is_ident("bound") => {
+                for nested in &list.nested {
+                    match nested {
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(s),
+                            ..
+                        })) if path.is_ident("serialize") => set(&mut ret.serialize, s, &list)?,
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                            path,
+                            lit: Lit::Str(s),
+                            ..
+                        })) if path.is_ident("deserialize") => set(&mut ret.deserialize, s, &list)?,
+                        other => {
+                            let msg = "expected `serialize = \"...\"` or `deserialize = \"...\"`";
+                            return Err(Error::new_spanned(other, msg));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(ret)
+}
+
+/// Find the variant of an enum marked #[serde(other)], which unknown variant
+/// names deserialize to.
+pub fn other_variant(enumeration: &DataEnum) -> Result<Option<&Ident>> {
+    let mut ret = None;
+    for var in &enumeration.variants {
+        let mut is_other = false;
+        let _ = for_each_serde_attr! { &var.attrs =>
+            #[serde(other)] => is_other = true,
+            _ => {},
+        };
+        if is_other.not() {
+            continue;
+        }
+        match var.fields {
+            Fields::Unit => {}
+            // The unit variants of data-carrying enums get a placeholder field
+            // in the helper enum generated for them.
+            Fields::Unnamed(FieldsUnnamed { ref unnamed, .. })
+                if unnamed.len() == 1 && is_placeholder(&unnamed[0].ty) => {}
+            _ => {
+                return Err(Error::new_spanned(
+                    var,
+                    "#[serde(other)] must be on a unit variant",
+                ))
+            }
+        }
+        if ret.replace(&var.ident).is_some() {
+            return Err(Error::new_spanned(var, "duplicate #[serde(other)] variant"));
+        }
+    }
+    Ok(ret)
+}
+
+/// Whether `ty` is the `__::Empty` placeholder of the unit variants of the
+/// helper enums, rather than the type of a field of the user's enum.
+fn is_placeholder(ty: &Type) -> bool {
+    let segments = match ty {
+        Type::Path(TypePath { qself: None, path }) => &path.segments,
+        _ => return false,
+    };
+    let mut idents = segments.iter().rev().map(|segment| &segment.ident);
+    matches!(
+        (idents.next(), idents.next()),
+        (Some(empty), Some(private)) if empty == "Empty" && private == "__"
+    )
+}
+
+/// Determine the name of a variant when serializing, respecting a rename
+/// attribute.
 pub fn name_of_variant(var: &Variant) -> Result<String> {
-    let rename = attr_rename(&var.attrs)?;
+    let rename = attr_rename(&var.attrs)?.serialize;
+    Ok(rename.unwrap_or_else(|| var.ident.to_string()))
+}
+
+/// Determine the name of a variant when deserializing, respecting a rename
+/// attribute.
+pub fn de_name_of_variant(var: &Variant) -> Result<String> {
+    if attr_aliases(&var.attrs)?.is_empty().not() {
+        return Err(Error::new_spanned(
+            &var.ident,
+            "`alias` is only supported on fields",
+        ));
+    }
+    let rename = attr_rename(&var.attrs)?.deserialize;
     Ok(rename.unwrap_or_else(|| var.ident.to_string()))
 }
 
+/// Find a #[serde(name(...))] list attribute.
+fn attr_list(attrs: &[Attribute], name: &str) -> Result<Option<MetaList>> {
+    let mut ret = None;
+
+    for meta in serde_metas(attrs)? {
+        match meta {
+            Meta::List(list) if list.path.is_ident(name) => {
+                if ret.is_some() {
+                    let msg = format!("duplicate `{}` attribute", name);
+                    return Err(Error::new_spanned(list, msg));
+                }
+                ret = Some(list);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(ret)
+}
+
+/// Find the value of a #[serde(name = 42)] integer attribute.
+fn attr_int(attrs: &[Attribute], name: &str) -> Result<Option<LitInt>> {
+    let mut ret = None;
+
+    for meta in serde_metas(attrs)? {
+        match meta {
+            Meta::NameValue(MetaNameValue {
+                path,
+                lit: Lit::Int(int),
+                ..
+            }) if path.is_ident(name) => {
+                if ret.is_some() {
+                    let msg = format!("duplicate `{}` attribute", name);
+                    return Err(Error::new_spanned(path, msg));
+                }
+                ret = Some(int);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(ret)
+}
+
+/// The metas inside all the #[serde(...)] attributes.
+fn serde_metas(attrs: &[Attribute]) -> Result<Vec<Meta>> {
+    let mut metas = vec![];
+    for attr in attrs {
+        if attr.path.is_ident("serde").not() {
+            continue;
+        }
+        if let Meta::List(list) = attr.parse_meta()? {
+            metas.extend(list.nested.into_iter().filter_map(|nested| match nested {
+                NestedMeta::Meta(meta) => Some(meta),
+                NestedMeta::Lit(_) => None,
+            }));
+        }
+    }
+    Ok(metas)
+}
+
+/// Find a #[serde(name ...)] attribute, of any form, to point an error at.
+pub fn find_meta(attrs: &[Attribute], name: &str) -> Result<Option<Meta>> {
+    Ok(serde_metas(attrs)?
+        .into_iter()
+        .find(|meta| meta.path().is_ident(name)))
+}
+
+/// Whether `meta` is a #[serde(name = 42)] integer attribute.
+fn is_int(meta: &Meta, name: &str) -> bool {
+    match *meta {
+        Meta::NameValue(MetaNameValue {
+            ref path,
+            lit: Lit::Int(_),
+            ..
+        }) => path.is_ident(name),
+        _ => false,
+    }
+}
+
+/// Whether `meta` is a #[serde(name(...))] list attribute.
+fn is_list(meta: &Meta, name: &str) -> bool {
+    match *meta {
+        Meta::List(ref list) => list.path.is_ident(name),
+        _ => false,
+    }
+}
+
+/// Find the wire names listed in a container-level
+/// `#[serde(expect_fields("...", ...))]` attribute.
+///
+/// The names are either the same for both sides, as in
+/// `expect_fields("a", "b")`, or given per side, as in
+/// `expect_fields(serialize("a", "b"), deserialize("a", "c"))`, where a side
+/// left out is not checked.
+fn attr_expect_fields(attrs: &[Attribute], side: Side) -> Result<Option<(Vec<String>, MetaList)>> {
+    let list = match attr_list(attrs, "expect_fields")? {
+        Some(list) => list,
+        None => return Ok(None),
+    };
+    let names_of = |nested: &Punctuated<NestedMeta, Token![,]>| {
+        nested
+            .iter()
+            .map(|name| match *name {
+                NestedMeta::Lit(Lit::Str(ref s)) => Ok(s.value()),
+                ref other => Err(Error::new_spanned(other, "expected a string literal")),
+            })
+            .collect::<Result<Vec<_>>>()
+    };
+    if !matches!(list.nested.first(), Some(NestedMeta::Meta(_))) {
+        let names = names_of(&list.nested)?;
+        return Ok(Some((names, list)));
+    }
+    let wanted = match side {
+        Side::Serialize => "serialize",
+        Side::Deserialize => "deserialize",
+    };
+    let mut ret = None;
+    for nested in &list.nested {
+        match *nested {
+            NestedMeta::Meta(Meta::List(ref side_list))
+                if side_list.path.is_ident("serialize")
+                    || side_list.path.is_ident("deserialize") =>
+            {
+                if side_list.path.is_ident(wanted) {
+                    let names = names_of(&side_list.nested)?;
+                    if ret.replace((names, side_list.clone())).is_some() {
+                        return Err(Error::new_spanned(side_list, "duplicate side"));
+                    }
+                }
+            }
+            ref other => {
+                let msg = "expected `serialize(\"...\", ...)` or `deserialize(\"...\", ...)`";
+                return Err(Error::new_spanned(other, msg));
+            }
+        }
+    }
+    Ok(ret)
+}
+
+/// The bounds of a #[serde(range(...))] or #[serde(len(...))] field attribute.
+pub struct Bounds {
+    pub min: Option<Lit>,
+    pub max: Option<Lit>,
+}
+
+/// Find the value of a #[serde(name(min = ..., max = ...))] attribute, such
+/// as `range` or `len`.
+pub fn bounds_of_field(field: &Field, name: &str) -> Result<Option<Bounds>> {
+    let list = match attr_list(&field.attrs, name)? {
+        Some(list) => list,
+        None => return Ok(None),
+    };
+    let mut bounds = Bounds {
+        min: None,
+        max: None,
+    };
+    for meta in &list.nested {
+        let (bound, value) = match *meta {
+            NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                ref path,
+                lit: ref value @ Lit::Int(_),
+                ..
+            }))
+            | NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                ref path,
+                lit: ref value @ Lit::Float(_),
+                ..
+            })) => match path.get_ident() {
+                Some(ident) if ident == "min" => (&mut bounds.min, value),
+                Some(ident) if ident == "max" => (&mut bounds.max, value),
+                _ => return Err(Error::new_spanned(path, "expected `min` or `max`")),
+            },
+            ref other => {
+                return Err(Error::new_spanned(
+                    other,
+                    "expected `min = <number>` or `max = <number>`",
+                ))
+            }
+        };
+        if bound.replace(value.clone()).is_some() {
+            return Err(Error::new_spanned(meta, "duplicate bound"));
+        }
+    }
+    if bounds.min.is_none() && bounds.max.is_none() {
+        return Err(Error::new_spanned(list, "expected a `min` or `max` bound"));
+    }
+    Ok(Some(bounds))
+}
+
+/// What deserializing a #[serde(bits(...))] struct does with the bits outside
+/// of its mask.
+pub enum UnknownBits {
+    Error,
+    Truncate,
+    Preserve,
+}
+
+/// A #[serde(bits)] or #[serde(bits(mask = "...", unknown = "..."))]
+/// container attribute.
+pub struct Bits {
+    pub mask: Option<Expr>,
+    pub unknown: UnknownBits,
+}
+
+/// Find the value of a #[serde(bits ...)] attribute.
+pub fn bits(attrs: &[Attribute]) -> Result<Option<Bits>> {
+    let mut ret = None;
+    for meta in serde_metas(attrs)? {
+        if meta.path().is_ident("bits").not() {
+            continue;
+        }
+        let bits = match meta {
+            Meta::Path(_) => Bits {
+                mask: None,
+                unknown: UnknownBits::Preserve,
+            },
+            Meta::List(ref list) => bits_of_list(list)?,
+            Meta::NameValue(_) => {
+                let msg = "expected `bits` or `bits(mask = \"...\")`";
+                return Err(Error::new_spanned(meta, msg));
+            }
+        };
+        if ret.replace(bits).is_some() {
+            return Err(Error::new_spanned(meta, "duplicate `bits` attribute"));
+        }
+    }
+    Ok(ret)
+}
+
+fn bits_of_list(list: &MetaList) -> Result<Bits> {
+    let mut mask = None;
+    let mut unknown = None;
+    for nested in &list.nested {
+        match *nested {
+            NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                ref path,
+                lit: Lit::Str(ref s),
+                ..
+            })) if path.is_ident("mask") => {
+                if mask.replace(s.parse::<Expr>()?).is_some() {
+                    return Err(Error::new_spanned(nested, "duplicate `mask`"));
+                }
+            }
+            NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                ref path,
+                lit: Lit::Str(ref s),
+                ..
+            })) if path.is_ident("unknown") => {
+                let value = match &*s.value() {
+                    "error" => UnknownBits::Error,
+                    "truncate" => UnknownBits::Truncate,
+                    "preserve" => UnknownBits::Preserve,
+                    _ => {
+                        let msg = "expected `\"error\"`, `\"truncate\"` or `\"preserve\"`";
+                        return Err(Error::new_spanned(s, msg));
+                    }
+                };
+                if unknown.replace(value).is_some() {
+                    return Err(Error::new_spanned(nested, "duplicate `unknown`"));
+                }
+            }
+            ref other => {
+                let msg = "expected `mask = \"...\"` or `unknown = \"...\"`";
+                return Err(Error::new_spanned(other, msg));
+            }
+        }
+    }
+    let unknown = match (&mask, unknown) {
+        (None, Some(UnknownBits::Error)) | (None, Some(UnknownBits::Truncate)) => {
+            return Err(Error::new_spanned(
+                list,
+                "expected a `mask` of the known bits",
+            ));
+        }
+        (_, Some(unknown)) => unknown,
+        (Some(_), None) => UnknownBits::Error,
+        (None, None) => UnknownBits::Preserve,
+    };
+    Ok(Bits { mask, unknown })
+}
+
+/// Fail compilation if the effective wire field names of `side` (in order) do
+/// not match the ones pinned by a `#[serde(expect_fields(...))]` attribute, if
+/// any.
+pub fn check_expect_fields(attrs: &[Attribute], side: Side, found: &[String]) -> Result<()> {
+    match attr_expect_fields(attrs, side)? {
+        Some((ref expected, ref list)) if expected[..] != *found => Err(Error::new_spanned(
+            list,
+            format!(
+                "wire field names changed: expected {:?}, found {:?}",
+                expected, found,
+            ),
+        )),
+        _ => Ok(()),
+    }
+}
+
 #[derive(Debug)] // FIXME: remove this.
 pub enum EnumTaggingMode {
     ExternallyTagged,
@@ -96,8 +1050,16 @@ impl EnumTaggingMode {
                     content_name: last_content.take().map(|(it, _)| it),
                 });
 
-                if prev.is_some() {
-                    return Err(Error::new_spanned(tag, "duplicate `tag` attribute"));
+                match prev {
+                    None => {},
+                    Some(EnumTaggingMode::Untagged) => return Err(Error::new_spanned(
+                        tag,
+                        "`tag` contradicts a previous #[serde(untagged)] attribute; \
+                         drop one of the two attributes",
+                    )),
+                    Some(_) => {
+                        return Err(Error::new_spanned(tag, "duplicate `tag` attribute"));
+                    },
                 }
             },
 
@@ -111,24 +1073,72 @@ impl EnumTaggingMode {
                 }) => {
                     *out_content_name = Some(content_name);
                 },
+                Some(EnumTaggingMode::Untagged) => {
+                    return Err(Error::new_spanned(
+                        content,
+                        "`content` cannot be used with #[serde(untagged)]; \
+                         drop one of the two attributes",
+                    ));
+                },
                 Some(_) => {
-                    return Err(Error::new_spanned(content, "Extraneous `content` attribute"));
+                    return Err(Error::new_spanned(content, "duplicate `content` attribute"));
                 },
             },
 
+            #[serde( variant_ints )] => {},
+
+            #[serde( repr )] => {},
+
+            #[serde( repr = $repr_ty )] => {
+                let _ = repr_ty;
+            },
+
+            #[serde( variant_name )] => {},
+
+            #[serde( type_tag = $type_tag )] => {
+                let _ = type_tag;
+            },
+
+            #[serde( bound = $bound_predicates )] => {
+                let _ = bound_predicates;
+            },
+
+            _ if is_list(meta!(), "bound") => {},
+
+            #[serde( from = $from_ty )] => {
+                let _ = from_ty;
+            },
+
+            #[serde( try_from = $try_from_ty )] => {
+                let _ = try_from_ty;
+            },
+
+            #[serde( into = $into_ty )] => {
+                let _ = into_ty;
+            },
+
             #[serde( untagged )] => {
-                let prev = ret.replace(EnumTaggingMode::Untagged);
-                if prev.is_some() {
-                    return Err(Error::new_spanned(
+                match ret.replace(EnumTaggingMode::Untagged) {
+                    None => {},
+                    Some(EnumTaggingMode::Untagged) => return Err(Error::new_spanned(
                         untagged,
-                        "Contradicts a previously-encountered `tag` attribute",
-                    ));
+                        "duplicate `untagged` attribute",
+                    )),
+                    Some(_) => return Err(Error::new_spanned(
+                        untagged,
+                        "#[serde(untagged)] contradicts a previous `tag` attribute; \
+                         drop one of the two attributes",
+                    )),
                 }
             },
         )?;
 
         if let Some((_, span)) = last_content {
-            Err(Error::new(span, "Extraneous `content` attribute"))
+            Err(Error::new(
+                span,
+                "`content` requires a `tag` attribute; \
+                 add #[serde(tag = \"...\")] next to it",
+            ))
         } else {
             Ok(ret.unwrap_or_else(|| EnumTaggingMode::ExternallyTagged))
         }
@@ -0,0 +1,122 @@
+use ::proc_macro2::{Span, TokenStream};
+use ::quote::quote;
+use ::syn::{Result, *};
+
+use crate::attr::{Bits, UnknownBits};
+use crate::bound::{self, Side};
+
+/// The integer field of a #[serde(bits)] struct.
+fn bits_field(input: &DeriveInput) -> Result<(Member, &Type)> {
+    let fields = match &input.data {
+        Data::Struct(DataStruct { fields, .. }) if fields.len() == 1 => fields,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "#[serde(bits)] is only supported on structs with a single field, \
+                 holding the bits",
+            ))
+        }
+    };
+    let field = fields.iter().next().unwrap();
+    let member = match &field.ident {
+        Some(ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(Index::from(0)),
+    };
+    Ok((member, &field.ty))
+}
+
+pub fn derive_serialize(input: &DeriveInput) -> Result<TokenStream> {
+    let c = crate::frontend();
+
+    let ident = &input.ident;
+    let (member, _) = bits_field(input)?;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let bounded_where_clause =
+        bound::where_clause_with_bound(input, quote!(#c::Serialize), Side::Serialize)?;
+    let dummy = Ident::new(&format!("_IMPL_SERIALIZE_FOR_{}", ident), Span::call_site());
+
+    Ok(quote! {
+        #[allow(non_upper_case_globals)]
+        const #dummy: () = {
+            impl #impl_generics #c::Serialize for #ident #ty_generics #bounded_where_clause {
+                fn view(&self) -> #c::ser::ValueView<'_> {
+                    #c::Serialize::view(&self.#member)
+                }
+            }
+        };
+    })
+}
+
+pub fn derive_deserialize(input: &DeriveInput, bits: Bits) -> Result<TokenStream> {
+    let c = crate::frontend();
+
+    let ident = &input.ident;
+    let (member, BitsTy) = bits_field(input)?;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let bounded_where_clause =
+        bound::where_clause_with_bound(input, quote!(#c::Deserialize), Side::Deserialize)?;
+    let dummy = Ident::new(
+        &format!("_IMPL_DESERIALIZE_FOR_{}", ident),
+        Span::call_site(),
+    );
+
+    let check = match (bits.mask, bits.unknown) {
+        (Some(mask), UnknownBits::Error) => quote!(
+            let unknown = bits & !(#mask);
+            if unknown != 0 {
+                #c::__::err!("Unknown bits {:#x} in {}", unknown, #c::__::stringify!(#ident));
+            }
+        ),
+        (Some(mask), UnknownBits::Truncate) => quote!(
+            let bits = bits & (#mask);
+        ),
+        _ => quote!(),
+    };
+
+    Ok(quote! {
+        #[allow(non_upper_case_globals)]
+        const #dummy: () = {
+            #c::make_place!(__Place);
+
+            // `Self` in the mask stands for the struct.
+            trait __FromBits: Sized {
+                type Bits;
+                fn from_bits(bits: Self::Bits) -> #c::Result<Self>;
+            }
+
+            impl #impl_generics __FromBits for #ident #ty_generics #bounded_where_clause {
+                type Bits = #BitsTy;
+                fn from_bits(bits: #BitsTy) -> #c::Result<Self> {
+                    #check
+                    #c::__::Ok(#ident { #member: bits })
+                }
+            }
+
+            impl #impl_generics #c::de::Visitor for __Place<#ident #ty_generics>
+            #bounded_where_clause
+            {
+                fn int(&mut self, i: #c::__::i128) -> #c::Result<()> {
+                    let mut bits = #c::__::None;
+                    <#BitsTy as #c::Deserialize>::begin(&mut bits).int(i)?;
+                    let bits = bits.ok_or(#c::Error)?;
+                    self.out = #c::__::Some(__FromBits::from_bits(bits)?);
+                    #c::__::Ok(())
+                }
+
+                fn raw_number(&mut self, raw: &#c::__::str) -> #c::Result<()> {
+                    let mut bits = #c::__::None;
+                    <#BitsTy as #c::Deserialize>::begin(&mut bits).raw_number(raw)?;
+                    let bits = bits.ok_or(#c::Error)?;
+                    self.out = #c::__::Some(__FromBits::from_bits(bits)?);
+                    #c::__::Ok(())
+                }
+            }
+
+            impl #impl_generics #c::Deserialize for #ident #ty_generics #bounded_where_clause {
+                fn begin(out: &mut #c::__::Option<Self>) -> &mut dyn #c::de::Visitor {
+                    __Place::new(out)
+                }
+            }
+        };
+    })
+}
@@ -1,10 +1,14 @@
 use proc_macro2::{Span, TokenStream};
+use std::collections::HashSet;
 use syn::punctuated::Punctuated;
 use syn::{
-    parse_quote, GenericParam, Generics, Lifetime, LifetimeDef, TypeParamBound, WhereClause,
-    WherePredicate,
+    parse_quote, Data, DeriveInput, Fields, GenericArgument, GenericParam, Generics, Ident,
+    Lifetime, LifetimeDef, Path, PathArguments, Result, ReturnType, Type, TypeParamBound,
+    WhereClause, WherePredicate,
 };
 
+use crate::attr;
+
 pub fn with_lifetime_bound(generics: &Generics, lifetime: &str) -> Generics {
     let bound = Lifetime::new(lifetime, Span::call_site());
     let def = LifetimeDef {
@@ -36,16 +40,157 @@ pub fn with_lifetime_bound(generics: &Generics, lifetime: &str) -> Generics {
     }
 }
 
-pub fn where_clause_with_bound(generics: &Generics, bound: TokenStream) -> WhereClause {
-    let new_predicates = generics.type_params().map::<WherePredicate, _>(|param| {
-        let param = &param.ident;
-        parse_quote!(#param : #bound)
-    });
+/// Which derive a where clause is for.
+#[derive(Clone, Copy)]
+pub enum Side {
+    Serialize,
+    Deserialize,
+}
+
+/// Bound the type parameters used by the fields of `input`, except within
+/// `PhantomData`, which implements the traits whatever its parameter.
+///
+/// A #[serde(bound = "...")] attribute replaces these bounds, for the given
+/// side.
+pub fn where_clause_with_bound(
+    input: &DeriveInput,
+    bound: TokenStream,
+    side: Side,
+) -> Result<WhereClause> {
+    let overridden = attr::bound_override(&input.attrs)?;
+    let overridden = match side {
+        Side::Serialize => overridden.serialize,
+        Side::Deserialize => overridden.deserialize,
+    };
+    if let Some(predicates) = overridden {
+        let mut generics = input.generics.clone();
+        generics.make_where_clause().predicates.extend(predicates);
+        return Ok(generics.where_clause.unwrap());
+    }
+
+    let mut used = TypeParams {
+        params: input
+            .generics
+            .type_params()
+            .map(|param| &param.ident)
+            .collect(),
+        used: HashSet::new(),
+    };
+    match &input.data {
+        Data::Struct(data) => used.fields(&data.fields, side),
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .for_each(|v| used.fields(&v.fields, side)),
+        Data::Union(data) => data.fields.named.iter().for_each(|f| used.ty(&f.ty)),
+    }
+    let new_predicates = input
+        .generics
+        .type_params()
+        .filter(|param| used.used.contains(&param.ident))
+        .map::<WherePredicate, _>(|param| {
+            let param = &param.ident;
+            parse_quote!(#param : #bound)
+        });
 
-    let mut generics = generics.clone();
+    let mut generics = input.generics.clone();
     generics
         .make_where_clause()
         .predicates
         .extend(new_predicates);
-    generics.where_clause.unwrap()
+    Ok(generics.where_clause.unwrap())
+}
+
+/// Which of `params` the types of some fields mention.
+struct TypeParams<'a> {
+    params: Vec<&'a Ident>,
+    used: HashSet<Ident>,
+}
+
+impl TypeParams<'_> {
+    fn fields(&mut self, fields: &Fields, side: Side) {
+        fields
+            .iter()
+            // The function of a `with` field asks for its own bounds.
+            .filter(|field| matches!(attr::with_of_field(field, side), Ok(None)))
+            .for_each(|field| self.ty(&field.ty));
+    }
+
+    fn ty(&mut self, ty: &Type) {
+        match ty {
+            Type::Array(ty) => self.ty(&ty.elem),
+            Type::BareFn(ty) => {
+                ty.inputs.iter().for_each(|arg| self.ty(&arg.ty));
+                self.return_type(&ty.output);
+            }
+            Type::Group(ty) => self.ty(&ty.elem),
+            Type::ImplTrait(ty) => self.bounds(&ty.bounds),
+            Type::Never(_) => {}
+            Type::Paren(ty) => self.ty(&ty.elem),
+            Type::Path(ty) => {
+                if let Some(qself) = &ty.qself {
+                    self.ty(&qself.ty);
+                }
+                self.path(&ty.path);
+            }
+            Type::Ptr(ty) => self.ty(&ty.elem),
+            Type::Reference(ty) => self.ty(&ty.elem),
+            Type::Slice(ty) => self.ty(&ty.elem),
+            Type::TraitObject(ty) => self.bounds(&ty.bounds),
+            Type::Tuple(ty) => ty.elems.iter().for_each(|elem| self.ty(elem)),
+            // Macros and the like may expand to anything.
+            _ => self.used.extend(self.params.iter().copied().cloned()),
+        }
+    }
+
+    fn path(&mut self, path: &Path) {
+        if path
+            .segments
+            .last()
+            .map_or(false, |segment| segment.ident == "PhantomData")
+        {
+            return;
+        }
+        if path.leading_colon.is_none() {
+            let first = &path.segments[0].ident;
+            if self.params.contains(&first) {
+                self.used.insert(first.clone());
+            }
+        }
+        for segment in &path.segments {
+            match &segment.arguments {
+                PathArguments::None => {}
+                PathArguments::AngleBracketed(args) => {
+                    for arg in &args.args {
+                        match arg {
+                            GenericArgument::Type(ty) => self.ty(ty),
+                            GenericArgument::Binding(binding) => self.ty(&binding.ty),
+                            GenericArgument::Constraint(constraint) => {
+                                self.bounds(&constraint.bounds);
+                            }
+                            GenericArgument::Lifetime(_) | GenericArgument::Const(_) => {}
+                        }
+                    }
+                }
+                PathArguments::Parenthesized(args) => {
+                    args.inputs.iter().for_each(|ty| self.ty(ty));
+                    self.return_type(&args.output);
+                }
+            }
+        }
+    }
+
+    fn return_type(&mut self, output: &ReturnType) {
+        if let ReturnType::Type(_, ty) = output {
+            self.ty(ty);
+        }
+    }
+
+    fn bounds<P>(&mut self, bounds: &Punctuated<TypeParamBound, P>) {
+        for bound in bounds {
+            if let TypeParamBound::Trait(bound) = bound {
+                self.path(&bound.path);
+            }
+        }
+    }
 }
@@ -0,0 +1,121 @@
+use ::proc_macro2::{Span, TokenStream};
+use ::quote::quote;
+use ::syn::{Result, *};
+
+use crate::attr::{self, FromRepr};
+use crate::bound::Side;
+
+fn check_not_transparent(input: &DeriveInput, name: &str) -> Result<()> {
+    match attr::find_meta(&input.attrs, "transparent")? {
+        Some(transparent) => Err(Error::new_spanned(
+            transparent,
+            format!(
+                "#[serde(transparent)] cannot be used with #[serde({})]",
+                name
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The where clause of a conversion: `Repr` implements `Trait`, unless a
+/// #[serde(bound = "...")] attribute sets the predicates for the side, such as
+/// for a recursive `Repr`.
+fn repr_where_clause(
+    input: &DeriveInput,
+    Repr: &Type,
+    Trait: TokenStream,
+    side: Side,
+) -> Result<WhereClause> {
+    let overridden = attr::bound_override(&input.attrs)?;
+    let overridden = match side {
+        Side::Serialize => overridden.serialize,
+        Side::Deserialize => overridden.deserialize,
+    };
+    let mut generics = input.generics.clone();
+    let where_clause = generics.make_where_clause();
+    match overridden {
+        Some(predicates) => where_clause.predicates.extend(predicates),
+        None => where_clause.predicates.push(parse_quote!(#Repr: #Trait)),
+    }
+    Ok(where_clause.clone())
+}
+
+/// #[serde(into = "Repr")]: serialize a clone of the container, converted.
+pub fn derive_serialize(input: &DeriveInput, Repr: &Type) -> Result<TokenStream> {
+    check_not_transparent(input, "into")?;
+    let c = crate::frontend();
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let mut where_clause = repr_where_clause(input, Repr, quote!(#c::Serialize), Side::Serialize)?;
+    where_clause.predicates.push(parse_quote!(
+        #ident #ty_generics: #c::__::Clone + #c::__::Into<#Repr>
+    ));
+    let dummy = Ident::new(&format!("_IMPL_SERIALIZE_FOR_{}", ident), Span::call_site());
+
+    Ok(quote! {
+        #[allow(non_upper_case_globals)]
+        const #dummy: () = {
+            impl #impl_generics #c::Serialize for #ident #ty_generics #where_clause {
+                fn view(&self) -> #c::ser::ValueView<'_> {
+                    let repr: #Repr = #c::__::Into::into(#c::__::Clone::clone(self));
+                    #c::__::view_owned(repr)
+                }
+            }
+        };
+    })
+}
+
+/// `#[serde(from = "Repr")]` or `#[serde(try_from = "Repr")]`: deserialize a
+/// `Repr`, then convert it.
+pub fn derive_deserialize(input: &DeriveInput, from: FromRepr) -> Result<TokenStream> {
+    let c = crate::frontend();
+
+    let ident = &input.ident;
+    let (Repr, convert) = match &from {
+        FromRepr::From(Repr) => {
+            check_not_transparent(input, "from")?;
+            (Repr, quote!(#c::__::Ok(#c::__::From::from(repr))))
+        }
+        FromRepr::TryFrom(Repr) => {
+            check_not_transparent(input, "try_from")?;
+            (
+                Repr,
+                quote!(match #c::__::TryFrom::try_from(repr) {
+                    #c::__::Ok(value) => #c::__::Ok(value),
+                    #c::__::Err(err) => #c::__::err!(
+                        "Invalid {}: {}",
+                        #c::__::stringify!(#ident),
+                        err,
+                    ),
+                }),
+            )
+        }
+    };
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let where_clause = repr_where_clause(input, Repr, quote!(#c::Deserialize), Side::Deserialize)?;
+    let dummy = Ident::new(
+        &format!("_IMPL_DESERIALIZE_FOR_{}", ident),
+        Span::call_site(),
+    );
+
+    Ok(quote! {
+        #[allow(non_upper_case_globals)]
+        const #dummy: () = {
+            impl #impl_generics #c::__::FromRepr for #ident #ty_generics #where_clause {
+                type Repr = #Repr;
+
+                fn from_repr(repr: #Repr) -> #c::Result<Self> {
+                    #convert
+                }
+            }
+
+            impl #impl_generics #c::Deserialize for #ident #ty_generics #where_clause {
+                fn begin(out: &mut #c::__::Option<Self>) -> &mut dyn #c::de::Visitor {
+                    #c::__::ReprPlace::new(out)
+                }
+            }
+        };
+    })
+}
@@ -3,9 +3,34 @@ use ::proc_macro2::{Span, TokenStream};
 use ::quote::{format_ident, quote, ToTokens};
 use ::syn::{spanned::Spanned, Result, *};
 
-use crate::{attr, bound};
+use crate::attr;
+use crate::bound::{self, Side};
 
 pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    attr::check_with(&input)?;
+    if let Some(from) = attr::from_repr(&input.attrs)? {
+        return crate::convert::derive_deserialize(&input, from);
+    }
+    if let Some(bits) = attr::bits(&input.attrs)? {
+        return crate::bits::derive_deserialize(&input, bits);
+    }
+    if attr::has_transparent(&input.attrs) {
+        return crate::transparent::derive_deserialize(&input);
+    }
+    match &input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(_),
+            ..
+        }) => {}
+        _ if attr::has_borrow(&input.attrs) => {
+            return Err(Error::new_spanned(
+                attr::find_meta(&input.attrs, "borrow")?,
+                "#[serde(borrow)] is only supported on structs with named fields; \
+                 borrow from the fields of such a struct instead",
+            ));
+        }
+        _ => {}
+    }
     match &input.data {
         Data::Struct(DataStruct {
             fields: Fields::Named(fields),
@@ -24,9 +49,9 @@ pub fn derive(input: DeriveInput) -> Result<TokenStream> {
             ..
         }) => derive_struct_unnamed(&input, fields),
         Data::Enum(enumeration) => derive_enum(&input, enumeration),
-        _ => Err(Error::new(
-            Span::call_site(),
-            "currently only structs with named fields are supported",
+        Data::Union(union) => Err(Error::new_spanned(
+            union.union_token,
+            "unions cannot be deserialized; use a struct or an enum instead",
         )),
     }
 }
@@ -52,21 +77,174 @@ pub fn derive_struct_named(input: &DeriveInput, fields: &FieldsNamed) -> Result<
             .named
             .iter()
             .filter(|f| attr::has_skip_deserializing(&f.attrs).not())
+            .filter(|f| attr::has_flatten(&f.attrs).not())
     };
+    let flatten = attr::flatten_field(
+        fields
+            .named
+            .iter()
+            .filter(|f| attr::has_skip_deserializing(&f.attrs).not()),
+    )?;
 
     let each_skipped_field = skipped_fields().map(|f| &f.ident);
     let each_field = non_skipped_fields().map(|f| &f.ident).collect::<Vec<_>>();
     let EachFieldTy = non_skipped_fields().map(|f| &f.ty);
-    let each_field_str = fields
-        .named
-        .iter()
-        .map(attr::name_of_field)
+    let each_post_process = non_skipped_fields()
+        .map(|f| {
+            let field = &f.ident;
+            let trim = match attr::trim_of_field(f)? {
+                None => None,
+                Some(attr::Trim::Whitespace) => Some(quote!(
+                    let #field = #c::__::Trim::trim(#field);
+                )),
+                Some(attr::Trim::Nfc) => Some(quote!(
+                    let #field = #c::__::Trim::trim_nfc(#field);
+                )),
+            };
+            let bound = |name, check: TokenStream| -> Result<_> {
+                let attr::Bounds { min, max } = match attr::bounds_of_field(f, name)? {
+                    Some(bounds) => bounds,
+                    None => return Ok(None),
+                };
+                let min =
+                    min.map_or_else(|| quote!(#c::__::None), |min| quote!(#c::__::Some(#min)));
+                let max =
+                    max.map_or_else(|| quote!(#c::__::None), |max| quote!(#c::__::Some(#max)));
+                Ok(Some(quote!(
+                    let #field = #check(#field, #min, #max)?;
+                )))
+            };
+            let range = bound("range", quote!(#c::__::check_range))?;
+            let len = bound("len", quote!(#c::__::check_len))?;
+            Ok(quote!(#trim #range #len))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let each_field_names = non_skipped_fields()
+        .map(attr::de_names_of_field)
         .collect::<Result<Vec<_>>>()?;
+    let each_notice = non_skipped_fields()
+        .zip(&each_field_names)
+        .map(|(f, names)| {
+            if attr::has_deprecated_field(&f.attrs) {
+                let container = ident.to_string();
+                let name = &names[0];
+                Some(quote!(#c::__::deprecated_field(#container, #name);))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    attr::check_expect_fields(
+        &input.attrs,
+        Side::Deserialize,
+        &each_field_names
+            .iter()
+            .map(|names| names[0].clone())
+            .collect::<Vec<_>>(),
+    )?;
+
+    // With `borrow`, the struct implements `DeserializeBorrowed` for its
+    // lifetime instead, and so do its fields.
+    let borrow = if attr::has_borrow(&input.attrs) {
+        let mut lifetimes = input.generics.lifetimes();
+        match (lifetimes.next(), lifetimes.next()) {
+            (Some(def), None) => Some(&def.lifetime),
+            (None, _) => {
+                return Err(Error::new_spanned(
+                    attr::find_meta(&input.attrs, "borrow")?,
+                    "#[serde(borrow)] requires a lifetime parameter to borrow for; \
+                     add one, or drop the attribute to deserialize owned data",
+                ))
+            }
+            (Some(_), Some(extra)) => {
+                return Err(Error::new_spanned(
+                    extra,
+                    "#[serde(borrow)] requires exactly one lifetime parameter; \
+                     borrow everything for a single lifetime instead",
+                ))
+            }
+        }
+    } else {
+        None
+    };
+    let Trait = match borrow {
+        None => quote!(#c::Deserialize),
+        Some(lifetime) => quote!(#c::de::DeserializeBorrowed<#lifetime>),
+    };
+    // Sound since, with `borrow`, the visitor is itself only handed out by the
+    // unsafe `begin_borrowed`, under the same contract.
+    let begin = |place: TokenStream| {
+        if borrow.is_none() {
+            quote!(#c::Deserialize::begin(#place))
+        } else {
+            quote!(unsafe { <_ as #Trait>::begin_borrowed(#place) })
+        }
+    };
+    let default = if borrow.is_none() {
+        quote!(#c::Deserialize::default())
+    } else {
+        quote!(<_ as #Trait>::default_borrowed())
+    };
+    // A #[serde(with)] field has no `Deserialize` impl to begin with, nor to
+    // default to when missing.
+    let each_with = non_skipped_fields()
+        .map(|f| attr::with_of_field(f, Side::Deserialize))
+        .collect::<Result<Vec<_>>>()?;
+    let each_begin = each_field
+        .iter()
+        .zip(&each_with)
+        .map(|(field, with)| match with {
+            Some(function) => quote!(#function(&mut self.#field)),
+            None => begin(quote!(&mut self.#field)),
+        })
+        .collect::<Vec<_>>();
+    // With `presence_bitmap`, the array holding the fields starts with the
+    // bitmap of the `Option` fields it holds, and leaves out the others: those
+    // with `with` start out as `None` values, which the array may not set.
+    let presence_bitmap = if attr::has_presence_bitmap(&input.attrs) {
+        Some(attr::presence_bits(input, fields)?)
+    } else {
+        None
+    };
+    let each_default = each_with
+        .iter()
+        .enumerate()
+        .map(|(i, with)| {
+            let in_bitmap = presence_bitmap.as_ref().and_then(|masks| masks[i]);
+            if with.is_none() {
+                default.clone()
+            } else if in_bitmap.is_some() {
+                quote!(#c::__::Some(#c::__::None))
+            } else {
+                quote!(#c::__::None)
+            }
+        })
+        .collect::<Vec<_>>();
 
     let wrapper_generics = bound::with_lifetime_bound(&input.generics, "'__a");
     let (wrapper_impl_generics, wrapper_ty_generics, _) = wrapper_generics.split_for_impl();
-    let bound = parse_quote!(#c::Deserialize);
-    let bounded_where_clause = bound::where_clause_with_bound(&input.generics, bound);
+    let bounded_where_clause =
+        bound::where_clause_with_bound(input, Trait.clone(), Side::Deserialize)?;
+
+    let key_arms = |each_begin: &[TokenStream]| {
+        if attr::has_case_insensitive(&input.attrs) {
+            quote!(#(
+                _ if #( __k.eq_ignore_ascii_case(#each_field_names) )||* => {
+                    #each_notice
+                    #c::__::Ok(#each_begin)
+                }
+            )*)
+        } else {
+            quote!(#(
+                #( #each_field_names )|* => {
+                    #each_notice
+                    #c::__::Ok(#each_begin)
+                }
+            )*)
+        }
+    };
+    let state_key_arms = key_arms(&each_begin);
 
     let mb_deserialize_null = if fields.named.is_empty() {
         Some(quote!(
@@ -79,6 +257,281 @@ pub fn derive_struct_named(input: &DeriveInput, fields: &FieldsNamed) -> Result<
         None
     };
 
+    // With `accept_array`, the fields may also come positionally, in
+    // declaration order, as for a tuple struct. Field-less structs accept
+    // `null`, `{}` and `[]` alike, whichever of `struct S;`, `struct S {}` and
+    // `struct S()` they are. With `presence_bitmap`, as above.
+    let (mb_position_field, mb_position, mb_deserialize_seq, mb_impl_seq) =
+        if attr::has_accept_array(&input.attrs)
+            || presence_bitmap.is_some()
+            || fields.named.is_empty()
+        {
+            if let Some(flatten) = flatten {
+                return Err(Error::new_spanned(
+                    flatten,
+                    "#[serde(flatten)] cannot be used with #[serde(accept_array)]",
+                ));
+            }
+            let each_index = 0..each_field.len();
+            let n = each_field.len();
+            let (element, finish) = match &presence_bitmap {
+                None => (
+                    quote!(
+                        let __position = self.__position;
+                        self.__position += 1;
+                        match __position {
+                            #(
+                                #each_index => #c::__::Ok(#each_begin),
+                            )*
+                            _ => #c::__::err!("Attempted to deserialize more than {} elements", #n),
+                        }
+                    ),
+                    None,
+                ),
+                Some(masks) => {
+                    let each_absent = masks.iter().map(|mask| {
+                        mask.map(|mask| {
+                            quote!(
+                                let __bitmap = self.__bitmap.unwrap_or(0);
+                                if __bitmap & #mask == 0 {
+                                    continue;
+                                }
+                                self.__bitmap = #c::__::Some(__bitmap & !#mask);
+                            )
+                        })
+                    });
+                    (
+                        quote!(
+                            // The bitmap comes first, and then only keeps the bits of
+                            // the fields still to come.
+                            if self.__bitmap.is_none() {
+                                return #c::__::Ok(#c::Deserialize::begin(&mut self.__bitmap));
+                            }
+                            loop {
+                                let __position = self.__position;
+                                self.__position += 1;
+                                match __position {
+                                    #(
+                                        #each_index => {
+                                            #each_absent
+                                            return #c::__::Ok(#each_begin);
+                                        }
+                                    )*
+                                    _ => #c::__::err!("Attempted to deserialize more than {} elements", #n),
+                                }
+                            }
+                        ),
+                        Some(quote!(
+                            if self.__bitmap != #c::__::Some(0) {
+                                #c::__::err!("Missing fields of the presence bitmap");
+                            }
+                        )),
+                    )
+                }
+            };
+            let mb_bitmap_field = presence_bitmap
+                .as_ref()
+                .map(|_| quote!(__bitmap: #c::__::Option<u64>,));
+            let mb_bitmap = presence_bitmap
+                .as_ref()
+                .map(|_| quote!(__bitmap: #c::__::None,));
+            (
+                Some(quote!(
+                    __position: usize,
+                    #mb_bitmap_field
+                )),
+                Some(quote!(
+                    __position: 0,
+                    #mb_bitmap
+                )),
+                Some(quote!(
+                    fn seq(&mut self) -> #c::Result<#c::__::Box<dyn #c::de::Seq + '_>> {
+                        #c::__::Ok(#c::__::Box::new(__State {
+                            #(
+                                #each_field: #each_default,
+                            )*
+                            __position: 0,
+                            #mb_bitmap
+                            out: &mut self.out,
+                        }))
+                    }
+                )),
+                Some(quote!(
+                    impl #wrapper_impl_generics #c::de::Seq for __State #wrapper_ty_generics #bounded_where_clause {
+                        fn element(&mut self) -> #c::Result<&mut dyn #c::de::Visitor> {
+                            #element
+                        }
+
+                        fn finish(self: #c::__::Box<Self>) -> #c::Result<()> {
+                            #finish
+                            #c::de::StrKeyMap::finish(self)
+                        }
+                    }
+                )),
+            )
+        } else {
+            (None, None, None, None)
+        };
+
+    // The keys matching no field go to the map of the flattened field, if any.
+    let (mb_flatten_begin, mb_flatten_field, unknown_key, mb_flatten_finish) = match flatten {
+        None => (
+            None,
+            None,
+            quote!(#c::__::Ok(#c::de::Visitor::ignore())),
+            None,
+        ),
+        Some(flatten) => {
+            let field = &flatten.ident;
+            let FieldTy = &flatten.ty;
+            let begin_flatten = begin(quote!(__flatten_place));
+            (
+                Some(quote!(
+                    let __flatten_slot = #c::__::AliasedBox::new(#c::__::None::<#FieldTy>);
+                    let __flatten_place = unsafe { &mut *__flatten_slot.ptr() };
+                    let __flatten_map = #begin_flatten.map()?;
+                )),
+                Some(quote!(
+                    // Safety: refers to `__flatten_slot`, so it must be dropped before it.
+                    __flatten_map: #c::__::Box<dyn #c::de::Map + '__a>,
+                    __flatten_slot: #c::__::AliasedBox<#c::__::Option<#FieldTy>>,
+                )),
+                quote!(self.__flatten_map.val_with_key(&mut |__v| __v?.string(__k))),
+                Some(quote!(
+                    self.__flatten_map.finish()?;
+                    let #field = (*self.__flatten_slot.assume_unique()).ok_or(#c::Error)?;
+                )),
+            )
+        }
+    };
+    let mb_flatten_init = flatten.map(|_| quote!(__flatten_map, __flatten_slot,));
+
+    let cast_out = quote!(unsafe {
+        &mut *{
+            out
+            as *mut #c::__::Option<Self>
+            as *mut __Visitor #ty_generics
+        }
+    });
+    // Merging a map into the struct in place, for `json::merge_from_str` and
+    // `cbor::merge_from_slice`: the fields that merge themselves do so, the
+    // others are staged and only replaced in `finish`, and unknown keys are
+    // ignored. Borrowing and flattened structs are replaced whole.
+    let merge = if borrow.is_none() && flatten.is_none() {
+        let each_merge_begin = non_skipped_fields()
+            .zip(&each_begin)
+            .zip(each_with.iter().zip(&each_post_process))
+            .map(|((f, begin), (with, post_process))| {
+                let field = &f.ident;
+                let FieldTy = &f.ty;
+                if with.is_none() && post_process.is_empty() {
+                    quote!(
+                        match <#FieldTy as #c::Deserialize>::begin_merge(&mut self.target.#field) {
+                            #c::__::Some(__v) => __v,
+                            #c::__::None => #begin,
+                        }
+                    )
+                } else {
+                    begin.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+        let merge_key_arms = key_arms(&each_merge_begin);
+        let EachFieldTy = non_skipped_fields().map(|f| &f.ty);
+        Some(quote!(
+            #[repr(C)]
+            struct __Merge #impl_generics #where_clause {
+                target: #ident #ty_generics,
+            }
+
+            impl #impl_generics #c::de::Visitor for __Merge #ty_generics #bounded_where_clause {
+                fn map(&mut self) -> #c::Result<#c::__::Box<dyn #c::de::Map + '_>> {
+                    #c::__::Ok(#c::__::Box::new(__MergeState {
+                        #(
+                            #each_field: #c::__::None,
+                        )*
+                        target: &mut self.target,
+                    }))
+                }
+            }
+
+            struct __MergeState #wrapper_impl_generics #where_clause {
+                #(
+                    #each_field: #c::__::Option<#EachFieldTy>,
+                )*
+                target: &'__a mut #ident #ty_generics,
+            }
+
+            impl #wrapper_impl_generics #c::de::StrKeyMap for __MergeState #wrapper_ty_generics #bounded_where_clause {
+                fn key(&mut self, __k: &#c::__::str) -> #c::Result<&mut dyn #c::de::Visitor> {
+                    match __k {
+                        #merge_key_arms
+                        _ => #c::__::Ok(#c::de::Visitor::ignore()),
+                    }
+                }
+
+                fn finish(self: #c::__::Box<Self>) -> #c::Result<()> {
+                    let __state = *self;
+                    #(
+                        if let #c::__::Some(#each_field) = __state.#each_field {
+                            #each_post_process
+                            __state.target.#each_field = #each_field;
+                        }
+                    )*
+                    #c::__::Ok(())
+                }
+            }
+        ))
+    } else {
+        None
+    };
+    let mb_begin_merge = merge.as_ref().map(|_| {
+        quote!(
+            fn begin_merge(target: &'_ mut Self) -> #c::__::Option<&'_ mut dyn #c::de::Visitor> {
+                #c::__::Some(unsafe {
+                    &mut *{
+                        target
+                        as *mut Self
+                        as *mut __Merge #ty_generics
+                    }
+                })
+            }
+        )
+    });
+    let impl_begin = if borrow.is_none() {
+        quote!(
+            impl #impl_generics #c::Deserialize for #ident #ty_generics #bounded_where_clause {
+                fn begin(out: &'_ mut #c::__::Option<Self>) -> &'_ mut dyn #c::de::Visitor {
+                    #cast_out
+                }
+
+                #mb_begin_merge
+            }
+        )
+    } else {
+        quote!(
+            impl #impl_generics #Trait for #ident #ty_generics #bounded_where_clause {
+                unsafe fn begin_borrowed(
+                    out: &'_ mut #c::__::Option<Self>,
+                ) -> &'_ mut dyn #c::de::Visitor {
+                    #cast_out
+                }
+            }
+        )
+    };
+    let new_state = quote!(__State {
+        #(
+            #each_field: #each_default,
+        )*
+        #mb_flatten_init
+        #mb_position
+        out: &mut self.out,
+    });
+    let mb_flatten_ident = flatten.map(|flatten| {
+        let field = &flatten.ident;
+        quote!(#field,)
+    });
+
     Ok(quote! {
         #[allow(non_upper_case_globals)]
         const #dummy: () = {
@@ -87,56 +540,68 @@ pub fn derive_struct_named(input: &DeriveInput, fields: &FieldsNamed) -> Result<
                 out: #c::__::Option<#ident #ty_generics>,
             }
 
-            impl #impl_generics #c::Deserialize for #ident #ty_generics #bounded_where_clause {
-                fn begin(out: &'_ mut #c::__::Option<Self>) -> &'_ mut dyn #c::de::Visitor {
-                    unsafe {
-                        &mut *{
-                            out
-                            as *mut #c::__::Option<Self>
-                            as *mut __Visitor #ty_generics
-                        }
-                    }
-                }
-            }
+            #impl_begin
 
             impl #impl_generics #c::de::Visitor for __Visitor #ty_generics #bounded_where_clause {
                 #mb_deserialize_null
 
                 fn map(&mut self) -> #c::Result<#c::__::Box<dyn #c::de::Map + '_>> {
-                    #c::__::Ok(#c::__::Box::new(__State {
-                        #(
-                            #each_field: #c::Deserialize::default(),
-                        )*
-                        out: &mut self.out,
-                    }))
+                    #mb_flatten_begin
+                    #c::__::Ok(#c::__::Box::new(#new_state))
+                }
+
+                // Keeps the `__State` on the stack.
+                fn map_with(
+                    &mut self,
+                    __with_map: &mut dyn #c::__::FnMut(&mut dyn #c::de::Map) -> #c::Result<()>,
+                ) -> #c::Result<()> {
+                    #mb_flatten_begin
+                    let mut __state = #new_state;
+                    __with_map(&mut __state)?;
+                    __state.finish()
                 }
+
+                #mb_deserialize_seq
             }
 
             struct __State #wrapper_impl_generics #where_clause {
                 #(
                     #each_field: #c::__::Option<#EachFieldTy>,
                 )*
+                #mb_flatten_field
+                #mb_position_field
                 out: &'__a mut #c::__::Option<#ident #ty_generics>,
             }
 
+            #mb_impl_seq
+
+            #merge
+
             impl #wrapper_impl_generics #c::de::StrKeyMap for __State #wrapper_ty_generics #bounded_where_clause {
                 fn key(&mut self, __k: &#c::__::str) -> #c::Result<&mut dyn #c::de::Visitor> {
                     match __k {
-                        #(
-                            #each_field_str => #c::__::Ok(#c::Deserialize::begin(&mut self.#each_field)),
-                        )*
-                        _ => #c::__::Ok(#c::de::Visitor::ignore()),
+                        #state_key_arms
+                        _ => #unknown_key,
                     }
                 }
 
                 fn finish(self: #c::__::Box<Self>) -> #c::Result<()> {
+                    (*self).finish()
+                }
+            }
+
+            impl #wrapper_impl_generics __State #wrapper_ty_generics #bounded_where_clause {
+                fn finish(self) -> #c::Result<()> {
                     #(
                         let #each_field = self.#each_field.ok_or(#c::Error)?;
+                        #each_post_process
                     )*
+                    #mb_flatten_finish
                     *self.out = #c::__::Some(#ident {
                         #(
                             #each_field,
                         )*
+                        #mb_flatten_ident
                         #(
                             #each_skipped_field: #c::__::Default::default(),
                         )*
@@ -154,22 +619,21 @@ pub fn derive_struct_unnamed(input: &DeriveInput, fields: &FieldsUnnamed) -> Res
     let ident = &input.ident;
     let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
     let bound = parse_quote!(#c::Deserialize);
-    let bounded_where_clause = bound::where_clause_with_bound(&input.generics, bound);
+    let bounded_where_clause = bound::where_clause_with_bound(input, bound, Side::Deserialize)?;
     let dummy = Ident::new(
         &format!("_IMPL_DESERIALIZE_FOR_{}", ident),
         Span::call_site(),
     );
 
-    let skipped_fields = || {
-        fields
-            .unnamed
-            .iter()
-            .filter(|f| attr::has_skip_deserializing(&f.attrs))
-    };
-    if skipped_fields().next().is_some() {
-        return Err(Error::new(
-            Span::call_site(),
-            "`#[serde(skip)]` is not yet supported on tuple structs",
+    let skipped_field = fields
+        .unnamed
+        .iter()
+        .find(|f| attr::has_skip_deserializing(&f.attrs));
+    if let Some(field) = skipped_field {
+        return Err(Error::new_spanned(
+            field,
+            "#[serde(skip)] is not supported on the fields of tuple structs when \
+             deserializing; use a struct with named fields instead",
         ));
     }
     let non_skipped_fields = fields
@@ -291,14 +755,22 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> Result<TokenS
 
     let (intro_generics, fwd_generics, _) = input.generics.split_for_impl();
     let bound = parse_quote!(#c::Deserialize);
-    let where_clause = bound::where_clause_with_bound(&input.generics, bound);
+    let where_clause = bound::where_clause_with_bound(input, bound, Side::Deserialize)?;
     let tagging_mode = EnumTaggingMode::from_attrs(&input.attrs)?;
     let Enum = &input.ident;
+    let other = attr::other_variant(enumeration)?;
 
     let is_trivial_enum = enumeration
         .variants
         .iter()
         .all(|variant| matches!(variant.fields, Fields::Unit));
+    let int_repr = attr::int_repr(&input.attrs)?;
+    if int_repr.is_some() && is_trivial_enum.not() {
+        return Err(Error::new_spanned(
+            attr::find_meta(&input.attrs, "repr")?,
+            "#[serde(repr)] is only supported on enums without fields",
+        ));
+    }
     let ret = if is_trivial_enum {
         let each_var_ident = enumeration
             .variants
@@ -314,28 +786,111 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> Result<TokenS
         let each_name = enumeration
             .variants
             .iter()
-            .map(attr::name_of_variant)
+            .map(attr::de_name_of_variant)
             .collect::<Result<Vec<_>>>()?;
-
-        quote!(
-            impl #intro_generics
-                #c::de::Visitor
-            for
-                __Visitor #fwd_generics
-            {
+        let unknown = if let Some(Other) = other {
+            quote!(#Enum::#Other)
+        } else {
+            quote!({ return #c::__::Err(#c::Error) })
+        };
+        // An enum with an integer representation is never named on the wire.
+        let string = if int_repr.is_some() {
+            None
+        } else {
+            Some(quote!(
                 fn string (self: &'_ mut Self, s: &'_ #c::__::str)
                   -> #c::Result<()>
                 {
                     let value = match s {
                         #( #each_name => #Enum::#each_var_ident, )*
-                        _ => { return #c::__::Err(#c::Error) },
+                        _ => #unknown,
+                    };
+                    self.out = #c::__::Some(value);
+                    #c::__::Ok(())
+                }
+            ))
+        };
+        let each_id = attr::ids_of_variants(&input.attrs, Enum, enumeration)?;
+        let int = if let Some(repr) = &int_repr {
+            let (each_id, each_id_ident): (Vec<_>, Vec<_>) = each_id.into_iter().unzip();
+            Some(quote!(
+                fn int (self: &'_ mut Self, i: #c::__::i128)
+                  -> #c::Result<()>
+                {
+                    let value = match <#repr as #c::__::TryFrom<#c::__::i128>>::try_from(i) {
+                        #c::__::Ok(i) => match i {
+                            #( _ if i == (#each_id) as #repr => #Enum::#each_id_ident, )*
+                            _ => #unknown,
+                        },
+                        #c::__::Err(_) => #unknown,
                     };
                     self.out = #c::__::Some(value);
                     #c::__::Ok(())
                 }
+            ))
+        } else if each_id.is_empty() {
+            None
+        } else {
+            let (each_id, each_id_ident): (Vec<_>, Vec<_>) = each_id.into_iter().unzip();
+            Some(quote!(
+                fn int (self: &'_ mut Self, i: #c::__::i128)
+                  -> #c::Result<()>
+                {
+                    let value = match i {
+                        #( _ if i == (#each_id) as #c::__::i128 => #Enum::#each_id_ident, )*
+                        _ => #unknown,
+                    };
+                    self.out = #c::__::Some(value);
+                    #c::__::Ok(())
+                }
+            ))
+        };
+
+        quote!(
+            impl #intro_generics
+                #c::de::Visitor
+            for
+                __Visitor #fwd_generics
+            {
+                #string
+
+                #int
             }
         )
     } else {
+        match tagging_mode {
+            EnumTaggingMode::ExternallyTagged => {}
+            EnumTaggingMode::InternallyTagged {
+                content_name: None, ..
+            } => {
+                let tuple_field = enumeration.variants.iter().find_map(|v| match v.fields {
+                    Fields::Unnamed(FieldsUnnamed { ref unnamed, .. }) => unnamed.iter().nth(1),
+                    _ => None,
+                });
+                if let Some(field) = tuple_field {
+                    return Err(Error::new_spanned(
+                        field,
+                        "#[serde(tag = \"...\")] cannot be used with non-newtype tuple \
+                         variants; use a variant with named fields instead",
+                    ));
+                }
+            }
+            EnumTaggingMode::InternallyTagged { .. } => {
+                return Err(Error::new_spanned(
+                    attr::find_meta(&input.attrs, "content")?,
+                    "adjacently tagged enums cannot be deserialized; \
+                     drop `content` to tag the variants internally",
+                ));
+            }
+            EnumTaggingMode::Untagged => {
+                return Err(Error::new_spanned(
+                    attr::find_meta(&input.attrs, "untagged")?,
+                    "untagged enums with data cannot be deserialized; \
+                     use #[serde(tag = \"...\")] or the default external tagging",
+                ));
+            }
+        }
+
         let is_serde_attr = |attr: &'_ &'_ Attribute| attr.path.is_ident("serde");
         let serde_enum_attrs = input.attrs.iter().filter(is_serde_attr);
 
@@ -363,12 +918,31 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> Result<TokenS
             let EachVariant_str = enumeration
                 .variants
                 .iter()
-                .map(attr::name_of_variant)
+                .map(attr::de_name_of_variant)
                 .collect::<Result<Vec<_>>>()?;
             let EachVariantTy = enumeration.variants.iter().map(|v| match v.fields {
                 Fields::Unnamed(FieldsUnnamed { ref unnamed, .. }) => unnamed.first().unwrap(),
                 _ => unreachable!(),
             });
+            let unknown_key = if let Some(Other) = other {
+                quote!({
+                    self.#Other = #c::__::Some(#c::__::Default::default());
+                    #c::Result::Ok(<dyn #c::de::Visitor>::ignore())
+                })
+            } else {
+                quote!(#c::Result::Err(#c::Error))
+            };
+            let unknown_tag = if let Some(Other) = other {
+                quote!({
+                    let current_variant_holder = unsafe { &mut *current_variant_holder };
+                    *current_variant_holder = __Helper_CurrentVariant::#Other(
+                        #c::__::Some(#c::__::Default::default()),
+                    );
+                    <dyn #c::de::Visitor>::ignore().map()?
+                })
+            } else {
+                quote!(#c::__::err!("Got a tag that matches not variant: {:?}", s))
+            };
             match tagging_mode {
                 EnumTaggingMode::ExternallyTagged => quote!(
                     struct __Map #intro_generics_map
@@ -377,6 +951,7 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> Result<TokenS
                         __serde_out: &'__serde_de_map mut #c::__::Option<
                             #Enum #fwd_generics,
                         >,
+                        __serde_has_key: bool,
                         #(
                             #EachVariant: #c::__::Option<
                                 #EachVariantTy,
@@ -395,13 +970,23 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> Result<TokenS
                             key: &'_ str,
                         ) -> #c::Result<&'_ mut dyn #c::de::Visitor>
                         {
+                            // Whatever the order the variants get checked in
+                            // `finish`, more than one key is an error.
+                            if self.__serde_has_key {
+                                #c::__::err!(
+                                    "Expected a single variant key for `{}`, got another one: {:?}",
+                                    #c::__::stringify!(#Enum),
+                                    key,
+                                );
+                            }
+                            self.__serde_has_key = true;
                             match key {
                             #(
                                 #EachVariant_str => #c::Result::Ok(
                                     #c::de::Deserialize::begin(&mut self.#EachVariant)
                                 ),
                             )*
-                                _ => #c::Result::Err(#c::Error),
+                                _ => #unknown_key,
                             }
                         }
 
@@ -424,6 +1009,7 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> Result<TokenS
 
                     let map: __Map #fwd_generics_map = __Map {
                         __serde_out: &mut self.out,
+                        __serde_has_key: false,
                         #(
                             #EachVariant : #c::__::None,
                         )*
@@ -498,9 +1084,7 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> Result<TokenS
                                                     .map()?
                                             },
                                         )*
-                                            _ => #c::__::err!(
-                                                "Got a tag that matches not variant: {:?}", s,
-                                            ),
+                                            _ => #unknown_tag,
                                         });
                                     }));
 
@@ -557,7 +1141,8 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> Result<TokenS
                     map
                 ),
 
-                _ => todo!("{:?}", tagging_mode),
+                // Rejected above.
+                _ => unreachable!(),
             }
         } else {
             // case `all_variants_are_newtypes.not()`.
@@ -596,6 +1181,7 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> Result<TokenS
                         };
                         define_helper_enum.extend(quote! {
                             #[derive(#c::Deserialize)]
+                            #[serde(bound(deserialize = ""))]
                             struct #__Helper_Variant #intro_generics
                             #where_clause
                             {
@@ -661,9 +1247,18 @@ pub fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> Result<TokenS
                 });
             });
             let each_Helper_Enum_variant = helper_variants.iter();
+            // The helpers are declared with the where clause of the enum, which
+            // already bounds the parameters of their fields: an inferred bound
+            // would only add to it, such as for the parameters of `PhantomData`.
+            let helper_bound = if attr::bound_override(&input.attrs)?.deserialize.is_some() {
+                quote!()
+            } else {
+                quote!(#[serde(bound(deserialize = ""))])
+            };
             define_helper_enum.extend(quote!(
                 #[derive(#c::Deserialize)]
                 #( #serde_enum_attrs )*
+                #helper_bound
                 enum #__Helper_Enum #intro_generics
                 #where_clause
                 {
@@ -8,9 +8,15 @@
 extern crate proc_macro;
 
 mod attr;
+mod bits;
 mod bound;
+mod convert;
 mod de;
 mod ser;
+#[cfg(test)]
+mod tests;
+mod transparent;
+mod type_tag;
 
 use proc_macro::TokenStream;
 use syn::{parse_macro_input, DeriveInput};
@@ -29,6 +35,13 @@ pub fn derive_deserialize(input: TokenStream) -> TokenStream {
         .into()
 }
 
+#[proc_macro_derive(TypeTag, attributes(serde))]
+pub fn derive_type_tag(input: TokenStream) -> TokenStream {
+    type_tag::derive(parse_macro_input!(input as DeriveInput))
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
 /// Our own (frontend) crate.
 fn frontend() -> ::proc_macro2::TokenStream {
     ::quote::quote!(miniserde_ditto)
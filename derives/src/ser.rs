@@ -3,9 +3,20 @@ use ::proc_macro2::{Span, TokenStream};
 use ::quote::{format_ident, quote};
 use ::syn::{spanned::Spanned, Result, *};
 
-use crate::{attr, bound};
+use crate::attr;
+use crate::bound::{self, Side};
 
 pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    attr::check_with(&input)?;
+    if let Some(Repr) = attr::conversion_type(&input.attrs, "into")? {
+        return crate::convert::derive_serialize(&input, &Repr);
+    }
+    if attr::bits(&input.attrs)?.is_some() {
+        return crate::bits::derive_serialize(&input);
+    }
+    if attr::has_transparent(&input.attrs) {
+        return crate::transparent::derive_serialize(&input);
+    }
     match &input.data {
         Data::Struct(DataStruct {
             fields: Fields::Named(fields),
@@ -20,9 +31,9 @@ pub fn derive(input: DeriveInput) -> Result<TokenStream> {
             ..
         }) => derive_struct_unnamed(&input, fields),
         Data::Enum(enumeration) => derive_enum(&input, enumeration),
-        _ => Err(Error::new(
-            Span::call_site(),
-            "currently only enums or structs with named fields are supported",
+        Data::Union(union) => Err(Error::new_spanned(
+            union.union_token,
+            "unions cannot be serialized; use a struct or an enum instead",
         )),
     }
 }
@@ -33,40 +44,196 @@ fn derive_struct_named(input: &DeriveInput, fields: &FieldsNamed) -> Result<Toke
     let ident = &input.ident;
     let dummy = Ident::new(&format!("_IMPL_SERIALIZE_FOR_{}", ident), Span::call_site());
 
-    let fields_named = fields
+    let serialized_fields = fields
         .named
         .iter()
-        .filter(|f| attr::has_skip_serializing(&f.attrs).not())
+        .filter(|f| attr::has_skip_serializing(&f.attrs).not());
+    let flatten = attr::flatten_field(serialized_fields.clone())?;
+    // The fields before the flattened one, if any, come first.
+    let flatten_at = serialized_fields
+        .clone()
+        .take_while(|f| attr::has_flatten(&f.attrs).not())
+        .count();
+    let mut fields_named = serialized_fields
+        .filter(|f| attr::has_flatten(&f.attrs).not())
         .collect::<Vec<_>>();
+    let mut each_fieldstr = fields_named
+        .iter()
+        .copied()
+        .map(attr::name_of_field)
+        .collect::<Result<Vec<_>>>()?;
+    attr::check_expect_fields(&input.attrs, Side::Serialize, &each_fieldstr)?;
+    // Declaration order, unless sorted by (serialized) name.
+    if attr::has_sort_keys(&input.attrs) {
+        if let Some(flatten) = flatten {
+            return Err(Error::new_spanned(
+                flatten,
+                "#[serde(flatten)] cannot be used with #[serde(sort_keys)]",
+            ));
+        }
+        let mut sorted = fields_named
+            .into_iter()
+            .zip(each_fieldstr)
+            .collect::<Vec<_>>();
+        sorted.sort_by(|(_, a), (_, b)| a.cmp(b));
+        let (fields, names) = sorted.into_iter().unzip();
+        fields_named = fields;
+        each_fieldstr = names;
+    }
     let fields_named = || fields_named.iter().copied();
 
     let each_fieldname = &fields_named().map(|f| &f.ident).collect::<Vec<_>>();
-    let each_fieldstr = fields_named()
-        .map(attr::name_of_field)
-        .collect::<Result<Vec<_>>>()?;
     let each_idx = 0usize..;
+    let each_skip_if = fields_named()
+        .map(attr::skip_serializing_if)
+        .collect::<Result<Vec<_>>>()?;
 
     let bound = parse_quote!(#c::Serialize);
-    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
-    let bounded_where_clause = bound::where_clause_with_bound(&input.generics, bound);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let bounded_where_clause = bound::where_clause_with_bound(input, bound, Side::Serialize)?;
+
+    // A #[serde(with)] field is viewed through a wrapper of the whole struct,
+    // whose `view` calls the function on the field.
+    let mut with_wrappers = vec![];
+    let each_field_dyn_serialize = fields_named()
+        .enumerate()
+        .map(|(i, f)| {
+            let fieldname = &f.ident;
+            let function = match attr::with_of_field(f, Side::Serialize)? {
+                Some(function) => function,
+                None => return Ok(quote!(&self.#fieldname as &dyn #c::Serialize)),
+            };
+            let wrapper = format_ident!("__SerializeWith{}", i);
+            with_wrappers.push(quote!(
+                #[repr(transparent)]
+                struct #wrapper #impl_generics (#ident #ty_generics) #where_clause;
+
+                impl #impl_generics #c::Serialize for #wrapper #ty_generics #bounded_where_clause {
+                    fn view(&self) -> #c::ser::ValueView<'_> {
+                        #function(&self.0.#fieldname)
+                    }
+                }
+            ));
+            // Sound since the wrapper is `repr(transparent)`.
+            Ok(quote!(
+                unsafe { &*(self as *const Self as *const #wrapper #ty_generics) }
+                    as &dyn #c::Serialize
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
 
     let n = fields_named().len();
+    let entry = quote!(
+        move |i| match i {
+            #(
+                #each_idx => (
+                    &#each_fieldstr as &dyn #c::Serialize,
+                    #each_field_dyn_serialize,
+                ),
+            )*
+            _ => #c::__::std::unreachable!(),
+        }
+    );
+    // With `presence_bitmap`, an array: a bitmap of the `Option` fields that
+    // are `Some`, then the values of those and of the other fields.
+    if attr::has_presence_bitmap(&input.attrs) {
+        let masks = attr::presence_bits(input, fields)?;
+        let each_option = each_fieldname
+            .iter()
+            .zip(&masks)
+            .filter_map(|(fieldname, mask)| mask.map(|_| fieldname));
+        let each_option_mask = masks.iter().flatten();
+        let each_present = each_fieldname.iter().zip(&masks).map(|(fieldname, mask)| {
+            if mask.is_some() {
+                quote!(self.#fieldname.is_some())
+            } else {
+                quote!(true)
+            }
+        });
+        let each_idx = 0usize..;
+        return Ok(quote! {
+            #[allow(non_upper_case_globals)]
+            const #dummy: () = {
+                #(#with_wrappers)*
+
+                #[repr(transparent)]
+                struct __PresenceBitmap #impl_generics (#ident #ty_generics) #where_clause;
+
+                impl #impl_generics #c::Serialize for __PresenceBitmap #ty_generics #bounded_where_clause {
+                    fn view(&self) -> #c::ser::ValueView<'_> {
+                        let mut bitmap = 0_u64;
+                        #(
+                            if self.0.#each_option.is_some() {
+                                bitmap |= #each_option_mask;
+                            }
+                        )*
+                        #c::ser::ValueView::Int(bitmap as _)
+                    }
+                }
+
+                impl #impl_generics #c::Serialize for #ident #ty_generics #bounded_where_clause {
+                    fn view(&self) -> #c::ser::ValueView<'_> {
+                        let present = [#(#each_present),*];
+                        // Sound since the wrapper is `repr(transparent)`.
+                        let bitmap = unsafe {
+                            &*(self as *const Self as *const __PresenceBitmap #ty_generics)
+                        };
+                        let element = move |i| match i {
+                            #(
+                                #each_idx => (#each_field_dyn_serialize),
+                            )*
+                            _ => #c::__::std::unreachable!(),
+                        };
+                        #c::ser::ValueView::Seq(#c::__::Box::new(#c::__::ExactFilter::new(
+                            #c::__::std::iter::once(bitmap as &dyn #c::Serialize)
+                                .chain((0 .. #n).filter(move |&i| present[i]).map(element)),
+                            1 + present.iter().filter(|&&present| present).count(),
+                        )))
+                    }
+                }
+            };
+        });
+    }
+    let (prelude, entries, before) = if each_skip_if.iter().all(Option::is_none) {
+        (None, quote!((0 .. #n).map(#entry)), quote!(#flatten_at))
+    } else {
+        let each_present = each_skip_if
+            .iter()
+            .zip(each_fieldname)
+            .map(|(skip_if, fieldname)| {
+                if let Some(skip_if) = skip_if {
+                    quote!((#skip_if)(&self.#fieldname).not())
+                } else {
+                    quote!(true)
+                }
+            });
+        let prelude = quote!(
+            use #c::__::std::ops::Not as _;
+            let present = [#(#each_present),*];
+        );
+        let entries = quote!(#c::__::ExactFilter::new(
+            (0 .. #n).filter(move |&i| present[i]).map(#entry),
+            present.iter().filter(|&&present| present).count(),
+        ));
+        let before = quote!(present[..#flatten_at].iter().filter(|&&present| present).count());
+        (Some(prelude), entries, before)
+    };
+    let view = match flatten {
+        None => quote!(#c::ser::ValueView::Map(#c::__::Box::new(#entries))),
+        Some(flatten) => {
+            let flatten = &flatten.ident;
+            quote!(#c::__::Flatten::view(#entries, #before, &self.#flatten))
+        }
+    };
     Ok(quote! {
         #[allow(non_upper_case_globals)]
         const #dummy: () = {
+            #(#with_wrappers)*
+
             impl #impl_generics #c::Serialize for #ident #ty_generics #bounded_where_clause {
                 fn view(&self) -> #c::ser::ValueView<'_> {
-                    #c::ser::ValueView::Map(#c::__::Box::new({
-                        (0 .. #n).map(move |i| match i {
-                            #(
-                                #each_idx => (
-                                    &#each_fieldstr as &dyn #c::Serialize,
-                                    &self.#each_fieldname as &dyn #c::Serialize,
-                                ),
-                            )*
-                            _ => #c::__::std::unreachable!(),
-                        })
-                    }))
+                    #prelude
+                    #view
                 }
             }
         };
@@ -90,11 +257,14 @@ fn derive_struct_unnamed(input: &DeriveInput, fields: &FieldsUnnamed) -> Result<
 
     let bound = parse_quote!(#c::Serialize);
     let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
-    let bounded_where_clause = bound::where_clause_with_bound(&input.generics, bound);
+    let bounded_where_clause = bound::where_clause_with_bound(input, bound, Side::Serialize)?;
 
     let view = match fields_unnamed.len() {
+        // As Serde does, unlike for unit structs.
         0 => quote!(
-            #c::ser::ValueView::Null
+            #c::ser::ValueView::Seq(#c::__::Box::new(
+                #c::__::std::iter::empty::<&dyn #c::Serialize>(),
+            ))
         ),
         1 => {
             let idx = &fields_unnamed[0];
@@ -145,7 +315,7 @@ fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> Result<TokenStrea
     let Enum = &input.ident;
     let (intro_generics, fwd_generics, _) = input.generics.split_for_impl();
     let bound = parse_quote!(#c::Serialize);
-    let where_clause = bound::where_clause_with_bound(&input.generics, bound);
+    let where_clause = bound::where_clause_with_bound(input, bound, Side::Serialize)?;
     let dummy = Ident::new(&format!("_IMPL_SERIALIZE_FOR_{}", Enum), Span::call_site());
 
     let enumeration_variants = enumeration
@@ -157,19 +327,46 @@ fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> Result<TokenStrea
 
     let is_trivial_enum =
         enumeration_variants().all(|variant| matches!(variant.fields, Fields::Unit));
+    let int_repr = attr::int_repr(&input.attrs)?;
+    if int_repr.is_some() && is_trivial_enum.not() {
+        return Err(Error::new_spanned(
+            attr::find_meta(&input.attrs, "repr")?,
+            "#[serde(repr)] is only supported on enums without fields",
+        ));
+    }
     let view_body = if is_trivial_enum {
         let each_var_ident = enumeration_variants()
             .map(|it| &it.ident)
             .collect::<Vec<_>>();
-        let each_name = enumeration_variants()
-            .map(attr::name_of_variant)
-            .collect::<Result<Vec<_>>>()?;
+        let each_view = if attr::has_variant_ints(&input.attrs) || int_repr.is_some() {
+            let each_id = attr::ids_of_variants(&input.attrs, Enum, enumeration)?
+                .into_iter()
+                .filter(|(_, ident)| each_var_ident.contains(ident))
+                .map(|(id, _)| {
+                    if let Some(repr) = &int_repr {
+                        quote!((#id) as #repr)
+                    } else {
+                        quote!(#id)
+                    }
+                });
+            each_id
+                .map(|id| quote!(#c::ser::ValueView::Int((#id) as #c::__::i128)))
+                .collect::<Vec<_>>()
+        } else {
+            enumeration_variants()
+                .map(attr::name_of_variant)
+                .map(|name| {
+                    let name = name?;
+                    Ok(quote!(#c::ser::ValueView::Str(#c::__::Cow::Borrowed(#name))))
+                })
+                .collect::<Result<Vec<_>>>()?
+        };
 
         quote!(
             match self {
                 #(
                     #Enum::#each_var_ident => {
-                        #c::ser::ValueView::Str(#c::__::Cow::Borrowed(#each_name))
+                        #each_view
                     }
                 )*
                 _ => #c::__::std::panic!(
@@ -178,6 +375,17 @@ fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> Result<TokenStrea
             }
         )
     } else {
+        if let EnumTaggingMode::InternallyTagged {
+            content_name: Some(_),
+            ..
+        } = tagging_mode
+        {
+            return Err(Error::new_spanned(
+                attr::find_meta(&input.attrs, "content")?,
+                "adjacently tagged enums cannot be serialized; \
+                 drop `content` to tag the variants internally",
+            ));
+        }
         // Non-trivial enum case:
         let match_arms = enumeration_variants().map(|variant| Ok({
             let Variant = &variant.ident;
@@ -185,21 +393,30 @@ fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> Result<TokenStrea
             let mut each_binding_str = vec![];
             let (pattern, each_binding) = match variant.fields {
                 Fields::Named(FieldsNamed { ref named, .. }) => {
-                    let each_binding =
+                    if let Some(field) = named.iter().find(|f| attr::has_flatten(&f.attrs)) {
+                        return Err(Error::new_spanned(
+                            field,
+                            "#[serde(flatten)] is not supported in enum variants",
+                        ));
+                    }
+                    let each_serialized_field = || {
                         named
                             .iter()
+                            .filter(|f| attr::has_skip_serializing(&f.attrs).not())
+                    };
+                    let each_binding =
+                        each_serialized_field()
                             .map(|it| it.ident.as_ref().unwrap().clone())
                             .collect::<Vec<Ident>>()
                     ;
                     each_binding_str =
-                        named
-                            .iter()
+                        each_serialized_field()
                             .map(attr::name_of_field)
                             .collect::<Result<_>>()?
                     ;
                     (
                         quote!(
-                            #( #each_binding ),*
+                            #( #each_binding, )* ..
                         ),
                         each_binding,
                     )
@@ -396,7 +613,8 @@ fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> Result<TokenStrea
                             if unnamed.len() > 1
                         => return Err(Error::new_spanned(
                             unnamed.iter().nth(1).unwrap(),
-                            r#"`#[serde(tag = "…")]` cannot be used with non-newtype tuple variants"#,
+                            "#[serde(tag = \"...\")] cannot be used with non-newtype tuple \
+                             variants; use a variant with named fields instead",
                         )),
 
                         Fields::Unnamed(FieldsUnnamed { ref unnamed, .. })
@@ -448,7 +666,8 @@ fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> Result<TokenStrea
                     )
                 },
 
-                | EnumTaggingMode::InternallyTagged { content_name: Some(_), .. } => todo!(),
+                // Rejected above.
+                | EnumTaggingMode::InternallyTagged { content_name: Some(_), .. } => unreachable!(),
             }
         })).collect::<Result<Vec<_>>>()?;
 
@@ -464,6 +683,34 @@ fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> Result<TokenStrea
             }
         )
     };
+    let impl_variant_name = if attr::has_variant_name(&input.attrs) {
+        let each_var_ident = enumeration.variants.iter().map(|it| &it.ident);
+        let each_name = enumeration
+            .variants
+            .iter()
+            .map(attr::name_of_variant)
+            .collect::<Result<Vec<_>>>()?;
+        let own_where_clause = &input.generics.where_clause;
+        quote!(
+            impl #intro_generics
+                #Enum #fwd_generics
+            #own_where_clause
+            {
+                /// The name of the variant, as serialized.
+                pub fn variant_name (self: &'_ Self)
+                  -> &'static #c::__::str
+                {
+                    match *self {
+                        #(
+                            #Enum::#each_var_ident { .. } => #each_name,
+                        )*
+                    }
+                }
+            }
+        )
+    } else {
+        quote!()
+    };
     Ok(quote!(
         #[allow(non_upper_case_globals, unused_variables)]
         const #dummy: () = {
@@ -480,6 +727,8 @@ fn derive_enum(input: &DeriveInput, enumeration: &DataEnum) -> Result<TokenStrea
                     #view_body
                 }
             }
+
+            #impl_variant_name
         };
     ))
 }
@@ -0,0 +1,124 @@
+//! The derives run outside of a compiler here, so that their errors can be
+//! checked against the source they point at.
+
+use proc_macro2::TokenStream;
+use syn::{DeriveInput, Result};
+
+/// The message of the error `derive` fails with, and the code it spans.
+fn error(derive: fn(DeriveInput) -> Result<TokenStream>, src: &str) -> (String, String) {
+    let input = syn::parse_str(src).unwrap();
+    let err = match derive(input) {
+        Ok(_) => panic!("expected the derive to fail"),
+        Err(err) => err,
+    };
+    let (start, end) = (err.span().start(), err.span().end());
+    let lines = src.lines().collect::<Vec<_>>();
+    let spanned = if start.line == end.line {
+        lines[start.line - 1][start.column..end.column].to_owned()
+    } else {
+        let mut spanned = lines[start.line - 1][start.column..].to_owned();
+        for line in &lines[start.line..end.line - 1] {
+            spanned.push('\n');
+            spanned.push_str(line);
+        }
+        spanned.push('\n');
+        spanned.push_str(&lines[end.line - 1][..end.column]);
+        spanned
+    };
+    (err.to_string(), spanned)
+}
+
+#[test]
+fn test_union() {
+    let src = "union U { a: u8, b: i8 }";
+    let (msg, spanned) = error(crate::ser::derive, src);
+    assert_eq!(spanned, "union");
+    assert!(msg.starts_with("unions cannot be serialized"), "{}", msg);
+    let (msg, spanned) = error(crate::de::derive, src);
+    assert_eq!(spanned, "union");
+    assert!(msg.starts_with("unions cannot be deserialized"), "{}", msg);
+}
+
+#[test]
+fn test_borrow_shapes() {
+    let (msg, spanned) = error(crate::de::derive, "#[serde(borrow)] struct S<'a>(&'a str);");
+    assert_eq!(spanned, "borrow");
+    assert!(msg.contains("structs with named fields"), "{}", msg);
+
+    let (msg, spanned) = error(crate::de::derive, "#[serde(borrow)] struct S { s: String }");
+    assert_eq!(spanned, "borrow");
+    assert!(msg.contains("requires a lifetime parameter"), "{}", msg);
+
+    let src = "#[serde(borrow)] struct S<'a, 'b> { a: &'a str, b: &'b str }";
+    let (msg, spanned) = error(crate::de::derive, src);
+    assert_eq!(spanned, "'b");
+    assert!(msg.contains("exactly one lifetime parameter"), "{}", msg);
+}
+
+#[test]
+fn test_skip_in_tuple_struct() {
+    let src = "struct S(u8, #[serde(skip)] u8);";
+    let (msg, spanned) = error(crate::de::derive, src);
+    assert_eq!(spanned, "#[serde(skip)] u8");
+    assert!(msg.contains("named fields instead"), "{}", msg);
+}
+
+#[test]
+fn test_tagged_tuple_variant() {
+    let src = "#[serde(tag = \"t\")] enum E { A(u8), B(u8, i8) }";
+    for &derive in &[crate::ser::derive as fn(_) -> _, crate::de::derive] {
+        let (msg, spanned) = error(derive, src);
+        assert_eq!(spanned, "i8");
+        assert!(msg.contains("non-newtype tuple variants"), "{}", msg);
+    }
+}
+
+#[test]
+fn test_content_attribute() {
+    let src = "#[serde(tag = \"t\", content = \"c\")] enum E { A(u8) }";
+    let (msg, spanned) = error(crate::ser::derive, src);
+    assert_eq!(spanned, "content = \"c\"");
+    assert!(
+        msg.starts_with("adjacently tagged enums cannot be serialized"),
+        "{}",
+        msg
+    );
+    let (msg, spanned) = error(crate::de::derive, src);
+    assert_eq!(spanned, "content = \"c\"");
+    assert!(
+        msg.starts_with("adjacently tagged enums cannot be deserialized"),
+        "{}",
+        msg
+    );
+
+    let src = "#[serde(content = \"c\")] enum E { A(u8) }";
+    let (msg, spanned) = error(crate::ser::derive, src);
+    assert_eq!(spanned, "content");
+    assert!(
+        msg.starts_with("`content` requires a `tag` attribute"),
+        "{}",
+        msg
+    );
+
+    let src = "#[serde(untagged, content = \"c\")] enum E { A(u8) }";
+    let (msg, spanned) = error(crate::de::derive, src);
+    assert_eq!(spanned, "content");
+    assert!(msg.contains("#[serde(untagged)]"), "{}", msg);
+}
+
+#[test]
+fn test_untagged() {
+    let src = "#[serde(untagged)] enum E { A(u8) }";
+    let (msg, spanned) = error(crate::de::derive, src);
+    assert_eq!(spanned, "untagged");
+    assert!(
+        msg.starts_with("untagged enums with data cannot be deserialized"),
+        "{}",
+        msg
+    );
+
+    let src = "#[serde(tag = \"t\", untagged)] enum E { A(u8) }";
+    let (msg, spanned) = error(crate::ser::derive, src);
+    assert_eq!(spanned, "untagged");
+    assert!(msg.contains("contradicts a previous `tag`"), "{}", msg);
+}
@@ -0,0 +1,107 @@
+use ::core::ops::Not as _;
+use ::proc_macro2::{Span, TokenStream};
+use ::quote::quote;
+use ::syn::{Result, *};
+
+use crate::attr;
+use crate::bound::{self, Side};
+
+fn is_skipped(field: &Field) -> bool {
+    attr::has_skip_serializing(&field.attrs) && attr::has_skip_deserializing(&field.attrs)
+}
+
+/// The members of a #[serde(transparent)] struct: the one holding its
+/// representation, and the #[serde(skip)] ones.
+fn transparent_fields(input: &DeriveInput) -> Result<(Member, &Type, Vec<Member>)> {
+    let fields = match &input.data {
+        Data::Struct(DataStruct { fields, .. }) => fields,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "#[serde(transparent)] is only supported on structs",
+            ))
+        }
+    };
+    let member = |(i, field): (usize, &Field)| match &field.ident {
+        Some(ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(Index::from(i)),
+    };
+    let mut inner = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| is_skipped(f).not());
+    let (i, field) = match (inner.next(), inner.next()) {
+        (Some(field), None) => field,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "#[serde(transparent)] requires exactly one field without #[serde(skip)]",
+            ))
+        }
+    };
+    let skipped = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| is_skipped(f))
+        .map(member)
+        .collect();
+    Ok((member((i, field)), &field.ty, skipped))
+}
+
+pub fn derive_serialize(input: &DeriveInput) -> Result<TokenStream> {
+    let c = crate::frontend();
+
+    let ident = &input.ident;
+    let (member, _, _) = transparent_fields(input)?;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let bounded_where_clause =
+        bound::where_clause_with_bound(input, quote!(#c::Serialize), Side::Serialize)?;
+    let dummy = Ident::new(&format!("_IMPL_SERIALIZE_FOR_{}", ident), Span::call_site());
+
+    Ok(quote! {
+        #[allow(non_upper_case_globals)]
+        const #dummy: () = {
+            impl #impl_generics #c::Serialize for #ident #ty_generics #bounded_where_clause {
+                fn view(&self) -> #c::ser::ValueView<'_> {
+                    #c::Serialize::view(&self.#member)
+                }
+            }
+        };
+    })
+}
+
+pub fn derive_deserialize(input: &DeriveInput) -> Result<TokenStream> {
+    let c = crate::frontend();
+
+    let ident = &input.ident;
+    let (member, Repr, skipped) = transparent_fields(input)?;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let bounded_where_clause =
+        bound::where_clause_with_bound(input, quote!(#c::Deserialize), Side::Deserialize)?;
+    let dummy = Ident::new(
+        &format!("_IMPL_DESERIALIZE_FOR_{}", ident),
+        Span::call_site(),
+    );
+
+    Ok(quote! {
+        #[allow(non_upper_case_globals)]
+        const #dummy: () = {
+            impl #impl_generics #c::__::FromRepr for #ident #ty_generics #bounded_where_clause {
+                type Repr = #Repr;
+
+                fn from_repr(repr: #Repr) -> #c::Result<Self> {
+                    #c::__::Ok(#ident {
+                        #member: repr,
+                        #(#skipped: #c::__::Default::default(),)*
+                    })
+                }
+            }
+
+            impl #impl_generics #c::Deserialize for #ident #ty_generics #bounded_where_clause {
+                fn begin(out: &mut #c::__::Option<Self>) -> &mut dyn #c::de::Visitor {
+                    #c::__::ReprPlace::new(out)
+                }
+            }
+        };
+    })
+}
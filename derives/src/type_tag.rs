@@ -0,0 +1,19 @@
+use ::proc_macro2::TokenStream;
+use ::quote::quote;
+use ::syn::{DeriveInput, Result};
+
+use crate::attr;
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    let c = crate::frontend();
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let type_tag = attr::type_tag(&input.attrs)?.unwrap_or_else(|| ident.to_string());
+
+    Ok(quote! {
+        impl #impl_generics #c::TypeTag for #ident #ty_generics #where_clause {
+            const TYPE_TAG: &'static #c::__::str = #type_tag;
+        }
+    })
+}
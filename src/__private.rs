@@ -1,20 +1,22 @@
-pub use ::std::{
-    self,
-    borrow::Cow,
-    boxed::Box,
+pub use ::alloc::{borrow::Cow, boxed::Box, string::String, vec, vec::Vec};
+// Under the name the derives use.
+pub use ::core::{
+    self as std,
+    clone::Clone,
+    convert::{From, Into, TryFrom},
     default::Default,
     ops::FnMut,
     option::Option::{self, None, Some},
     result::Result::{Err, Ok},
-    string::String,
-    stringify, vec,
-    vec::Vec,
+    stringify,
 };
 
+pub use crate::de::convert::{FromRepr, ReprPlace};
 pub use crate::{__err__ as err, aliased_box::AliasedBox};
 
-pub use self::help::{Str as str, Usize as usize};
+pub use self::help::{Str as str, Usize as usize, I128 as i128};
 mod help {
+    pub type I128 = i128;
     pub type Str = str;
     pub type Usize = usize;
 }
@@ -27,5 +29,252 @@ impl<F: FnMut(&str) -> crate::Result<()>> crate::de::Visitor for StrVisitor<F> {
     }
 }
 
-#[derive(crate::Deserialize)]
+#[derive(crate::Deserialize, Default)]
 pub struct Empty;
+
+/// Prints the message of an error, with `MINISERDE_DEBUG_ERRORS=1` at compile
+/// time. Without the `std` feature, there is nowhere to print it.
+pub fn debug_error(message: ::core::fmt::Arguments<'_>) {
+    #[cfg(feature = "std")]
+    ::std::eprintln!("Serde error: {}", message);
+    #[cfg(not(feature = "std"))]
+    let _ = message;
+}
+
+/// Views a value built by the `Serialize` impl itself, such as the
+/// conversion of a `#[serde(into = "...")]` container.
+pub fn view_owned<'a, T: crate::Serialize + 'a>(value: T) -> crate::ser::ValueView<'a> {
+    crate::ser::ValueView::Owned(Box::new(value))
+}
+
+/// Called when a `#[serde(deprecated_field)]` field is found in the input.
+pub fn deprecated_field(container: &'static str, field: &'static str) {
+    crate::de::deprecated_field(container, field)
+}
+
+/// Post-processing of `#[serde(trim)]` fields.
+pub trait Trim: Sized {
+    fn trim(self) -> Self;
+
+    #[cfg(feature = "unicode-normalization")]
+    fn trim_nfc(self) -> Self;
+}
+
+impl Trim for String {
+    fn trim(mut self) -> String {
+        let end = self.trim_end().len();
+        self.truncate(end);
+        let start = self.len() - self.trim_start().len();
+        self.drain(..start);
+        self
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    fn trim_nfc(self) -> String {
+        use ::unicode_normalization::UnicodeNormalization;
+
+        Trim::trim(self.nfc().collect::<String>())
+    }
+}
+
+impl<T: Trim> Trim for Option<T> {
+    fn trim(self) -> Option<T> {
+        self.map(T::trim)
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    fn trim_nfc(self) -> Option<T> {
+        self.map(T::trim_nfc)
+    }
+}
+
+/// Validation of `#[serde(range(min = ..., max = ...))]` fields.
+pub fn check_range<T: Bounded>(
+    value: T,
+    min: Option<T::Bound>,
+    max: Option<T::Bound>,
+) -> crate::Result<T> {
+    if let Some(bounded) = value.bounded() {
+        let above_min = min.map_or(true, |min| min <= *bounded);
+        let below_max = max.map_or(true, |max| *bounded <= max);
+        if !(above_min && below_max) {
+            crate::__err__!("Value out of the field's range");
+        }
+    }
+    Ok(value)
+}
+
+/// The number checked by `#[serde(range)]`, and nothing for `None`.
+pub trait Bounded {
+    type Bound: PartialOrd;
+    fn bounded(&self) -> Option<&Self::Bound>;
+}
+
+impl<T: Bounded> Bounded for Option<T> {
+    type Bound = T::Bound;
+    fn bounded(&self) -> Option<&T::Bound> {
+        self.as_ref()?.bounded()
+    }
+}
+
+macro_rules! bounded {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Bounded for $ty {
+                type Bound = $ty;
+                fn bounded(&self) -> Option<&$ty> {
+                    Some(self)
+                }
+            }
+        )*
+    };
+}
+bounded!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+#[cfg(feature = "float")]
+bounded!(f32, f64);
+
+/// Validation of `#[serde(len(min = ..., max = ...))]` fields.
+pub fn check_len<T: Len>(value: T, min: Option<usize>, max: Option<usize>) -> crate::Result<T> {
+    if let Some(len) = value.length() {
+        if min.map_or(false, |min| len < min) || max.map_or(false, |max| len > max) {
+            crate::__err__!("Length {} out of the field's bounds", len);
+        }
+    }
+    Ok(value)
+}
+
+/// The length checked by `#[serde(len)]`: chars for strings, elements for
+/// collections, and nothing for `None`.
+pub trait Len {
+    fn length(&self) -> Option<usize>;
+}
+
+impl Len for String {
+    fn length(&self) -> Option<usize> {
+        Some(self.chars().count())
+    }
+}
+
+impl<T: Len> Len for Option<T> {
+    fn length(&self) -> Option<usize> {
+        self.as_ref()?.length()
+    }
+}
+
+macro_rules! len {
+    ($($ty:ty => [$($param:ident),*]),* $(,)?) => {
+        $(
+            impl<$($param),*> Len for $ty {
+                fn length(&self) -> Option<usize> {
+                    Some(self.len())
+                }
+            }
+        )*
+    };
+}
+len! {
+    Vec<T> => [T],
+    ::alloc::collections::BTreeMap<K, V> => [K, V],
+    ::alloc::collections::BTreeSet<T> => [T],
+    ::alloc::collections::BinaryHeap<T> => [T],
+    ::alloc::collections::LinkedList<T> => [T],
+    ::alloc::collections::VecDeque<T> => [T],
+}
+#[cfg(feature = "std")]
+len! {
+    ::std::collections::HashMap<K, V, S> => [K, V, S],
+    ::std::collections::HashSet<T, S> => [T, S],
+}
+
+/// An iterator over the fields of a struct that leaves some of them out, with
+/// `#[serde(presence_bitmap)]` or `#[serde(skip_serializing_if)]`, whose
+/// length is counted upfront so that `remaining` stays exact.
+pub struct ExactFilter<I> {
+    iter: I,
+    len: usize,
+}
+
+impl<I> ExactFilter<I> {
+    pub fn new(iter: I, len: usize) -> Self {
+        ExactFilter { iter, len }
+    }
+}
+
+impl<I: Iterator> Iterator for ExactFilter<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let next = self.iter.next()?;
+        self.len -= 1;
+        Some(next)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<I: Iterator> ExactSizeIterator for ExactFilter<I> {}
+
+/// The entries of a struct with a `#[serde(flatten)]` field: those of the
+/// flattened value, a map, are spliced in after the first `before` entries of
+/// the struct's own fields.
+pub struct Flatten<'view, I> {
+    own: I,
+    before: usize,
+    flat: Box<dyn crate::ser::Map<'view> + 'view>,
+}
+
+type Entry<'view> = (&'view dyn crate::Serialize, &'view dyn crate::Serialize);
+
+impl<'view, I: ExactSizeIterator<Item = Entry<'view>> + 'view> Flatten<'view, I> {
+    /// The view of the struct, which serializers reject if the flattened
+    /// value is not a map.
+    pub fn view(
+        own: I,
+        before: usize,
+        flat: &'view dyn crate::Serialize,
+    ) -> crate::ser::ValueView<'view> {
+        let flat: Box<dyn crate::ser::Map<'view> + 'view> = match flat.view() {
+            crate::ser::ValueView::Map(map) => map,
+            crate::ser::ValueView::Null => Box::new(::core::iter::empty()),
+            _ => return crate::ser::ValueView::Unrepresentable,
+        };
+        crate::ser::ValueView::Map(Box::new(Flatten { own, before, flat }))
+    }
+}
+
+impl<'view, I: ExactSizeIterator<Item = Entry<'view>>> Iterator for Flatten<'view, I> {
+    type Item = Entry<'view>;
+
+    fn next(&mut self) -> Option<Entry<'view>> {
+        if self.before > 0 {
+            self.before -= 1;
+            return self.own.next();
+        }
+        self.flat.next().or_else(|| self.own.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.own.len() + self.flat.remaining();
+        (len, Some(len))
+    }
+}
+
+impl<'view, I: ExactSizeIterator<Item = Entry<'view>>> ExactSizeIterator for Flatten<'view, I> {}
+
+/// The decoders built by `decoder!`.
+#[cfg(feature = "cbor")]
+pub fn decode_cbor<T: crate::Deserialize + 'static>(
+    bytes: &[u8],
+) -> crate::Result<Box<dyn ::core::any::Any>> {
+    Ok(Box::new(crate::cbor::from_slice::<T>(bytes)?))
+}
+
+#[cfg(feature = "json")]
+pub fn decode_json<T: crate::Deserialize + 'static>(
+    bytes: &[u8],
+) -> crate::Result<Box<dyn ::core::any::Any>> {
+    let j = ::core::str::from_utf8(bytes).map_err(|_| crate::Error)?;
+    Ok(Box::new(crate::json::from_str::<T>(j)?))
+}
@@ -1,4 +1,6 @@
-use ::std::ptr;
+use ::core::ptr;
+
+use crate::prelude::*;
 
 /// A `Box` that may be aliased after creation and before destruction.
 #[repr(transparent)]
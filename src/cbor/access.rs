@@ -0,0 +1,205 @@
+use ::core::convert::TryFrom;
+
+use super::{Array, Object, Value};
+use crate::prelude::*;
+use crate::Error;
+
+/// A type that can index into a [`Value`], with [`Value::get`].
+///
+/// Implemented for `usize`, which looks up an element of an array, and for
+/// strings and `Value`s, which look up an entry of a map by key.
+pub trait Index: sealed::Sealed {
+    #[doc(hidden)]
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value>;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+impl sealed::Sealed for usize {}
+impl Index for usize {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        value.as_array()?.get(*self)
+    }
+}
+
+impl sealed::Sealed for str {}
+impl Index for str {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        value.as_map()?.get(&Value::Text(self.to_owned()))
+    }
+}
+
+impl sealed::Sealed for String {}
+impl Index for String {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        self[..].index_into(value)
+    }
+}
+
+impl sealed::Sealed for Value {}
+impl Index for Value {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        value.as_map()?.get(self)
+    }
+}
+
+impl<T: ?Sized + Index> sealed::Sealed for &T {}
+impl<T: ?Sized + Index> Index for &T {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        (**self).index_into(value)
+    }
+}
+
+impl Value {
+    /// Look up an element of an array or an entry of a map, if `self` is one
+    /// and has it.
+    ///
+    /// ```rust
+    /// use miniserde_ditto::cbor::{self, Value};
+    ///
+    /// let mut map = cbor::Object::new();
+    /// map.insert(Value::from(String::from("id")), Value::from(7));
+    /// let value = Value::Map(map);
+    /// assert_eq!(value.get("id").and_then(Value::as_integer), Some(7));
+    /// assert_eq!(value.get(0), None);
+    /// ```
+    pub fn get<I: Index>(&self, index: I) -> Option<&Value> {
+        index.index_into(self)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i128> {
+        match *self {
+            Value::Integer(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "float")]
+    pub fn as_float(&self) -> Option<f64> {
+        match *self {
+            Value::Float(f) => Some(f),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Value::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Array> {
+        match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn as_array_mut(&mut self) -> Option<&mut Array> {
+        match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn as_map(&self) -> Option<&Object> {
+        match self {
+            Value::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn as_map_mut(&mut self) -> Option<&mut Object> {
+        match self {
+            Value::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// The tag number and the tagged value, if `self` is tagged.
+    pub fn as_tag(&self) -> Option<(u64, &Value)> {
+        match self {
+            Value::Tag(tag, value) => Some((*tag, value)),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! impl_TryFrom {(
+    $(
+        $(#[$attr:meta])*
+        $T:ty => $Variant:ident
+    ),* $(,)?
+) => (
+    $(
+        $(#[$attr])*
+        impl TryFrom<Value> for $T {
+            type Error = Error;
+
+            fn try_from (value: Value)
+              -> Result<$T, Error>
+            {
+                match value {
+                    Value::$Variant(it) => Ok(it),
+                    _ => Err(Error),
+                }
+            }
+        }
+    )*
+)}
+
+impl_TryFrom! {
+    bool => Bool,
+    i128 => Integer,
+    #[cfg(feature = "float")]
+    f64 => Float,
+    Vec<u8> => Bytes,
+    String => Text,
+    Array => Array,
+    Object => Map,
+}
+
+macro_rules! impl_TryFrom_int {(
+    $($int:ty),* $(,)?
+) => (
+    $(
+        impl TryFrom<Value> for $int {
+            type Error = Error;
+
+            fn try_from (value: Value)
+              -> Result<$int, Error>
+            {
+                match value {
+                    Value::Integer(i) => <$int>::try_from(i).map_err(|_| Error),
+                    _ => Err(Error),
+                }
+            }
+        }
+    )*
+)}
+
+impl_TryFrom_int! {
+    i8, i16, i32, i64,
+    u8, u16, u32, u64,
+}
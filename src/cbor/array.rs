@@ -1,10 +1,12 @@
-use std::iter::FromIterator;
-use std::mem::ManuallyDrop;
-use std::ops::{Deref, DerefMut};
-use std::ptr;
+use core::iter::FromIterator;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use core::ptr;
 
 use super::{drop, Value};
 
+use crate::prelude::*;
+
 /// A `Vec<Value>` with a non-recursive drop impl.
 #[derive(Clone, Debug, Default)]
 pub struct Array(pub Vec<Value>);
@@ -0,0 +1,172 @@
+/// Serialize a few simple values into CBOR in `const` contexts, such as fixed
+/// headers to be computed at compile time.
+///
+/// Every method takes and returns the serializer by value, so that calls can be
+/// chained in a `const` item. Arrays and maps are given their length upfront,
+/// followed by their elements (or keys and values). The output is the same as
+/// [`to_vec`](crate::cbor::to_vec) for the equivalent values, up to
+/// [`ConstSerializer::CAPACITY`] bytes.
+///
+/// ```rust
+/// use miniserde_ditto::cbor::{self, ConstSerializer};
+/// use miniserde_ditto::Serialize;
+///
+/// const HEADER: ConstSerializer = ConstSerializer::new()
+///     .map(2)
+///     .str("version")
+///     .uint(3)
+///     .str("compressed")
+///     .bool(false);
+/// const BYTES: [u8; HEADER.len()] = HEADER.finish();
+///
+/// #[derive(Serialize)]
+/// struct Header {
+///     version: u32,
+///     compressed: bool,
+/// }
+///
+/// let header = Header { version: 3, compressed: false };
+/// assert_eq!(BYTES[..], cbor::to_vec(&header).unwrap()[..]);
+/// ```
+///
+/// Exceeding the capacity, or calling [`finish`](ConstSerializer::finish)
+/// with the wrong length, fails to compile (or panics at runtime).
+#[derive(Clone, Copy)]
+pub struct ConstSerializer {
+    buf: [u8; ConstSerializer::CAPACITY],
+    len: usize,
+}
+
+impl ConstSerializer {
+    /// The maximum length of the output.
+    pub const CAPACITY: usize = 256;
+
+    pub const fn new() -> Self {
+        ConstSerializer {
+            buf: [0; Self::CAPACITY],
+            len: 0,
+        }
+    }
+
+    pub const fn null(self) -> Self {
+        self.push(0xf6)
+    }
+
+    pub const fn bool(self, b: bool) -> Self {
+        self.push(0xf4 | b as u8)
+    }
+
+    pub const fn uint(self, u: u64) -> Self {
+        self.head(0, u)
+    }
+
+    pub const fn int(self, i: i64) -> Self {
+        if i < 0 {
+            // -1 - i
+            self.head(1, i.unsigned_abs() - 1)
+        } else {
+            self.head(0, i.unsigned_abs())
+        }
+    }
+
+    pub const fn str(self, s: &str) -> Self {
+        self.head(3, s.len() as u64).extend(s.as_bytes())
+    }
+
+    pub const fn bytes(self, bytes: &[u8]) -> Self {
+        self.head(2, bytes.len() as u64).extend(bytes)
+    }
+
+    /// The start of an array of `len` elements, to be serialized next.
+    pub const fn array(self, len: usize) -> Self {
+        self.head(4, len as u64)
+    }
+
+    /// The start of a map of `len` entries, whose keys and values are to be
+    /// serialized next, alternately.
+    pub const fn map(self, len: usize) -> Self {
+        self.head(5, len as u64)
+    }
+
+    /// Tag the value serialized next.
+    pub const fn tag(self, tag: u64) -> Self {
+        self.head(6, tag)
+    }
+
+    /// The length of the output so far.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The output so far, outside of `const` contexts.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// The output, whose length `N` must be exactly [`len`](Self::len).
+    pub const fn finish<const N: usize>(self) -> [u8; N] {
+        // Out of bounds, hence a compile time error, on a length mismatch.
+        let _: u8 = [0][(self.len != N) as usize];
+        let mut out = [0; N];
+        let mut i = 0;
+        while i < N {
+            out[i] = self.buf[i];
+            i += 1;
+        }
+        out
+    }
+
+    /// The major type and its argument, in the shortest form, as `to_vec` does.
+    // The argument is only cast to `u8` when below 24.
+    #[allow(clippy::cast_possible_truncation)]
+    const fn head(self, major: u8, value: u64) -> Self {
+        let mask = major << 5;
+        if value < 24 {
+            self.push(mask | value as u8)
+        } else if value <= u8::MAX as u64 {
+            self.push(mask | 24).push_be(value, 1)
+        } else if value <= u16::MAX as u64 {
+            self.push(mask | 25).push_be(value, 2)
+        } else if value <= u32::MAX as u64 {
+            self.push(mask | 26).push_be(value, 4)
+        } else {
+            self.push(mask | 27).push_be(value, 8)
+        }
+    }
+
+    /// The `n` low bytes of `value`, big endian.
+    // Each cast keeps the low byte of what is left, as intended.
+    #[allow(clippy::cast_possible_truncation)]
+    const fn push_be(mut self, value: u64, mut n: u32) -> Self {
+        while n > 0 {
+            n -= 1;
+            self = self.push((value >> (8 * n)) as u8);
+        }
+        self
+    }
+
+    const fn extend(mut self, bytes: &[u8]) -> Self {
+        let mut i = 0;
+        while i < bytes.len() {
+            self = self.push(bytes[i]);
+            i += 1;
+        }
+        self
+    }
+
+    const fn push(mut self, byte: u8) -> Self {
+        self.buf[self.len] = byte;
+        self.len += 1;
+        self
+    }
+}
+
+impl Default for ConstSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
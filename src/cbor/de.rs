@@ -1,9 +1,13 @@
-use std::borrow::Cow;
+use alloc::collections::BTreeSet;
+use core::cell::Cell;
+use core::marker::PhantomData;
 
 use ::core::convert::TryFrom;
 
-use crate::de::{Deserialize, Visitor};
+use crate::de::{Deserialize, DeserializeBorrowed, DuplicateKeys, Map, Visitor, CANCEL_INTERVAL};
+use crate::envelope::{Message, Registry};
 use crate::error::{Error, Result};
+use crate::scratch::Chunks;
 
 /// Deserialize a CBOR byte sequence into any deserializable type.
 ///
@@ -41,46 +45,429 @@ use crate::error::{Error, Result};
 /// ```
 pub fn from_slice<T: Deserialize>(bytes: &[u8]) -> Result<T> {
     let mut out = None;
-    let ref mut cursor = bytes.iter();
-    from_slice_impl(cursor, T::begin(&mut out))
-        .and_then(|()| {
-            if cursor.as_slice().is_empty() {
-                out
-            } else {
-                err!(
-                    "Trailing bytes in CBOR deserialization. Remaining = {:#x?}",
-                    cursor.as_slice()
-                );
+    deserialize(bytes, T::begin(&mut out), &Options::default())?;
+    out.ok_or(Error)
+}
+
+/// Like [`from_slice`], but the strings and byte strings of the output may
+/// borrow from `bytes`.
+///
+/// See [`DeserializeBorrowed`] for the types able to do so. Strings of
+/// unknown length only borrow when sent as a single chunk.
+///
+/// ```rust
+/// use miniserde_ditto::{cbor, Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// #[serde(borrow)]
+/// struct Chunk<'a> {
+///     key: &'a str,
+///     data: &'a [u8],
+/// }
+///
+/// let bytes = cbor::to_vec(&Chunk { key: "k", data: &[1, 2, 3] }).unwrap();
+/// let chunk: Chunk<'_> = cbor::from_slice_borrowed(&bytes).unwrap();
+/// assert_eq!(chunk.key, "k");
+/// assert_eq!(chunk.data, [1, 2, 3]);
+/// ```
+pub fn from_slice_borrowed<'de, T: DeserializeBorrowed<'de>>(bytes: &'de [u8]) -> Result<T> {
+    let mut out = None;
+    let options = Options {
+        borrow: true,
+        ..Options::default()
+    };
+    // Safety: `bytes` lives for `'de`, and with `borrow` only its own slices
+    // are handed to `Visitor::borrowed_string` and `Visitor::borrowed_bytes`.
+    deserialize(bytes, unsafe { T::begin_borrowed(&mut out) }, &options)?;
+    out.ok_or(Error)
+}
+
+/// Update `target` with the fields present in a CBOR map, leaving the others
+/// as they are.
+///
+/// Merged in place as by [`json::merge_from_str`](crate::json::merge_from_str):
+/// nested structs merge, `null` clears an `Option`, and other fields are
+/// replaced whole. Malformed CBOR leaves `target` untouched.
+pub fn merge_from_slice<T: Deserialize>(target: &mut T, bytes: &[u8]) -> Result<()> {
+    check_raw_value(bytes)?;
+    let mut staged = None;
+    let visitor = match T::begin_merge(target) {
+        Some(visitor) => visitor,
+        None => T::begin(&mut staged),
+    };
+    deserialize(bytes, visitor, &Options::default())?;
+    if let Some(value) = staged {
+        *target = value;
+    }
+    Ok(())
+}
+
+/// Decode an [`Envelope`](crate::envelope::Envelope) whose payload may be of
+/// any of the types of `registry`.
+///
+/// See [`envelope`](crate::envelope) for an example, in JSON.
+pub fn decode_envelope(bytes: &[u8], registry: &Registry) -> Result<Message> {
+    let mut visitor = registry.visitor();
+    deserialize(bytes, &mut visitor, &Options::default())?;
+    visitor.out.ok_or(Error)
+}
+
+/// Iterate over the CBOR items of `bytes`, one after the other, as in a CBOR
+/// sequence (RFC 8742).
+///
+/// Each item comes with the number of bytes it took. The iterator stops after
+/// the first error.
+///
+/// ```rust
+/// use miniserde_ditto::cbor;
+///
+/// let mut bytes = cbor::to_vec("hello").unwrap();
+/// bytes.extend(cbor::to_vec("world").unwrap());
+///
+/// let mut items = cbor::from_slice_multi::<String>(&bytes);
+/// assert_eq!(items.next().unwrap()?, ("hello".to_owned(), 6));
+/// assert_eq!(items.next().unwrap()?, ("world".to_owned(), 6));
+/// assert!(items.next().is_none());
+/// # Ok::<(), miniserde_ditto::Error>(())
+/// ```
+pub fn from_slice_multi<T: Deserialize>(bytes: &[u8]) -> StreamDeserializer<'_, '_, T> {
+    Deserializer::new().from_slice_multi(bytes)
+}
+
+/// An iterator over the CBOR items of a byte slice, as returned by
+/// [`from_slice_multi`].
+pub struct StreamDeserializer<'a, 'bytes, T> {
+    deserializer: Deserializer<'a>,
+    input: &'bytes [u8],
+    pos: usize,
+    failed: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, 'bytes, T> StreamDeserializer<'a, 'bytes, T> {
+    /// How many bytes of the input were read so far: the end of the last item
+    /// deserialized.
+    pub fn byte_offset(&self) -> usize {
+        self.pos
+    }
+}
+
+impl<'a, 'bytes, T: Deserialize> Iterator for StreamDeserializer<'a, 'bytes, T> {
+    type Item = Result<(T, usize)>;
+
+    fn next(&mut self) -> Option<Result<(T, usize)>> {
+        if self.failed || self.pos == self.input.len() {
+            return None;
+        }
+        let ref mut cursor = self.input[self.pos..].iter();
+        let mut out = None;
+        let options = self.deserializer.options(self.input.len());
+        let result = from_slice_impl(cursor, T::begin(&mut out), &options)
+            .and(out)
+            .ok_or(Error);
+        match result {
+            Ok(value) => {
+                let len = self.input.len() - self.pos - cursor.as_slice().len();
+                self.pos += len;
+                Some(Ok((value, len)))
             }
-        })
-        .ok_or(Error)
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// A CBOR deserializer with non-default options.
+///
+/// ```rust
+/// use miniserde_ditto::cbor;
+/// use std::sync::atomic::{AtomicBool, Ordering};
+///
+/// let cancelled = AtomicBool::new(true);
+/// let should_cancel = || cancelled.load(Ordering::Relaxed);
+/// let deserializer = cbor::Deserializer::new().should_cancel(&should_cancel);
+///
+/// let bytes = cbor::to_vec(vec![0_u16; 10_000]).unwrap();
+/// assert!(deserializer.from_slice::<Vec<u16>>(&bytes).is_err());
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct Deserializer<'a> {
+    should_cancel: Option<&'a dyn Fn() -> bool>,
+    progress: Option<&'a dyn Fn(usize)>,
+    pub(in crate::cbor) limits: Limits,
+}
+
+impl<'a> Deserializer<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Abort deserializing, with an error, once `should_cancel` returns
+    /// `true`. It is called every 1024 values, so that other threads can
+    /// interrupt the decoding of huge inputs.
+    pub fn should_cancel(mut self, should_cancel: &'a dyn Fn() -> bool) -> Self {
+        self.should_cancel = Some(should_cancel);
+        self
+    }
+
+    /// Call `progress` with the number of bytes read so far each time an
+    /// array or map ends, such as to report the progress of a large import.
+    pub fn progress(mut self, progress: &'a dyn Fn(usize)) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// How deeply arrays, maps and tags may be nested; 256 by default, so
+    /// that `max_depth(1)` accepts `[1]` but not `[[1]]`. Each level takes
+    /// some native stack, so large values can overflow it.
+    pub fn max_depth(mut self, max_depth: u16) -> Self {
+        self.limits.max_depth = max_depth;
+        self
+    }
+
+    /// The most elements an array or map may have; unlimited by default.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.limits.max_len = max_len;
+        self
+    }
+
+    /// The longest a byte string or text string may be, in bytes; unlimited
+    /// by default.
+    pub fn max_bytes_len(mut self, max_bytes_len: usize) -> Self {
+        self.limits.max_bytes_len = max_bytes_len;
+        self
+    }
+
+    /// What to do with a key appearing twice in the same map.
+    pub fn duplicate_keys(mut self, policy: DuplicateKeys) -> Self {
+        self.limits.duplicate_keys = policy;
+        self
+    }
+
+    /// Deserialize a CBOR byte sequence into any deserializable type, as
+    /// [`from_slice`] does.
+    pub fn from_slice<T: Deserialize>(&self, bytes: &[u8]) -> Result<T> {
+        let mut out = None;
+        deserialize(bytes, T::begin(&mut out), &self.options(bytes.len()))?;
+        out.ok_or(Error)
+    }
+
+    /// Iterate over the CBOR items of `bytes`, as [`from_slice_multi`] does.
+    /// Progress is reported from the start of `bytes`.
+    pub fn from_slice_multi<'bytes, T: Deserialize>(
+        &self,
+        bytes: &'bytes [u8],
+    ) -> StreamDeserializer<'a, 'bytes, T> {
+        StreamDeserializer {
+            deserializer: *self,
+            input: bytes,
+            pos: 0,
+            failed: false,
+            _marker: PhantomData,
+        }
+    }
+
+    fn options(&self, len: usize) -> Options<'a> {
+        Options {
+            should_cancel: self.should_cancel,
+            progress: self.progress,
+            len,
+            limits: self.limits,
+            ..Options::default()
+        }
+    }
+}
+
+/// Decoding knobs, for the entry points other than `from_slice`.
+#[derive(Default)]
+struct Options<'a> {
+    /// Hand the slices of the input to `Visitor::borrowed_string` and
+    /// `Visitor::borrowed_bytes`. Only sound if the visitor may borrow from
+    /// the input, see `DeserializeBorrowed::begin_borrowed`.
+    borrow: bool,
+    /// See `Deserializer::should_cancel`.
+    should_cancel: Option<&'a dyn Fn() -> bool>,
+    /// The number of values decoded so far, for `should_cancel`.
+    count: Cell<u32>,
+    /// See `Deserializer::progress`.
+    progress: Option<&'a dyn Fn(usize)>,
+    /// The length of the whole input, for `progress`.
+    len: usize,
+    limits: Limits,
+    /// The nesting depth of the value being decoded.
+    depth: Cell<u16>,
+}
+
+/// See the methods of `Deserializer`.
+#[derive(Clone, Copy)]
+pub(in crate::cbor) struct Limits {
+    pub(in crate::cbor) max_depth: u16,
+    pub(in crate::cbor) max_len: usize,
+    pub(in crate::cbor) max_bytes_len: usize,
+    duplicate_keys: DuplicateKeys,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_depth: 256,
+            max_len: usize::MAX,
+            max_bytes_len: usize::MAX,
+            duplicate_keys: DuplicateKeys::default(),
+        }
+    }
 }
 
-const MAX_DEPTH: u16 = 256;
+impl<'a> Options<'a> {
+    /// The same options, at the same depth, without `should_cancel` and
+    /// `progress`: for scanning ahead over bytes that get decoded again.
+    fn without_hooks(&self) -> Options<'a> {
+        Options {
+            borrow: self.borrow,
+            len: self.len,
+            limits: self.limits,
+            depth: self.depth.clone(),
+            ..Options::default()
+        }
+    }
+
+    fn report_progress(&self, remaining: &[u8]) {
+        if let Some(progress) = self.progress {
+            progress(self.len - remaining.len());
+        }
+    }
+}
+
+/// Deserialize the whole of `bytes` into `visitor`.
+fn deserialize(bytes: &[u8], visitor: &mut dyn Visitor, options: &Options<'_>) -> Result<()> {
+    let ref mut cursor = bytes.iter();
+    from_slice_impl(cursor, visitor, options).ok_or(Error)?;
+    if !cursor.as_slice().is_empty() {
+        err!(
+            "Trailing bytes in CBOR deserialization. Remaining = {:#x?}",
+            cursor.as_slice()
+        );
+    }
+    Ok(())
+}
+
+/// Deserialize `bytes` into `visitor`, as [`from_slice`] does, such as to
+/// decode the item of a `ValueView::RawCbor`.
+pub(in crate) fn from_slice_into(bytes: &[u8], visitor: &mut dyn Visitor) -> Result<()> {
+    deserialize(bytes, visitor, &Options::default())
+}
+
+/// Checks that `bytes` holds a single well-formed data item, as held by
+/// `RawValue`.
+pub(in crate) fn check_raw_value(bytes: &[u8]) -> Result<()> {
+    from_slice_into(bytes, <dyn Visitor>::ignore())
+}
 
 fn from_slice_impl<'bytes>(
     bytes: &'_ mut ::core::slice::Iter<'bytes, u8>,
     visitor: &'_ mut dyn Visitor,
+    options: &'_ Options<'_>,
 ) -> Option<()> {
     use helpers::*;
 
-    // Avoid accidental unchecked recursion; use a thread local to track depth:
+    fn map_entry<'bytes>(
+        bytes: &'_ mut ::core::slice::Iter<'bytes, u8>,
+        map: &'_ mut dyn Map,
+        keys: &'_ mut KeysSeen<'bytes>,
+        options: &'_ Options<'_>,
+    ) -> Option<()> {
+        if let Some(seen) = keys {
+            // Find where the key ends, to compare its encoding. The key is
+            // decoded again below, so the hooks only see it then.
+            let start = bytes.as_slice();
+            let mut after_key = bytes.clone();
+            let scan_options = options.without_hooks();
+            recurse_checked(&mut after_key, <dyn Visitor>::ignore(), &scan_options)?;
+            let key = &start[..start.len() - after_key.as_slice().len()];
+            if !seen.insert(key) {
+                if options.limits.duplicate_keys == DuplicateKeys::Error {
+                    err!("Duplicate key in CBOR map: {:#x?}", key);
+                }
+                *bytes = after_key;
+                return recurse_checked(bytes, <dyn Visitor>::ignore(), options);
+            }
+        }
+        let out_v = map
+            .val_with_key(&mut |it| {
+                it.and_then(|out_k| recurse_checked(bytes, out_k, options).ok_or(crate::Error))
+            })
+            .ok()?;
+        recurse_checked(bytes, out_v, options)
+    }
+
+    /// The entries of a map of `len` entries, or up to the break code.
+    fn map_entries<'bytes>(
+        bytes: &'_ mut ::core::slice::Iter<'bytes, u8>,
+        map: &'_ mut dyn Map,
+        len: Option<usize>,
+        options: &'_ Options<'_>,
+    ) -> Option<()> {
+        let ref mut keys = keys_seen(options);
+        match len {
+            Some(len) => {
+                for _ in 0..len {
+                    map_entry(bytes, map, keys, options)?;
+                }
+            }
+            None => {
+                for len in 1.. {
+                    if major_and_tag(bytes.as_slice().get(0)?) == BREAK_CODE {
+                        bytes.next();
+                        break;
+                    }
+                    check_len(len, options)?;
+                    map_entry(bytes, map, keys, options)?;
+                }
+            }
+        }
+        Some(())
+    }
+
+    // Avoid accidental unchecked recursion; `options` tracks the depth:
     let from_slice_impl = ();
     drop(from_slice_impl);
     fn recurse_checked<'bytes>(
         bytes: &'_ mut ::core::slice::Iter<'bytes, u8>,
         visitor: &'_ mut dyn Visitor,
+        options: &'_ Options<'_>,
     ) -> Option<()> {
-        thread_local! {
-            static CUR_DEPTH: ::core::cell::Cell<u16> = 0.into();
+        // Checked by the arrays, maps and tags themselves, so that empty ones
+        // count as well.
+        let depth = options.depth.get();
+        options.depth.set(depth + 1);
+        let ret = self::from_slice_impl(bytes, visitor, options);
+        options.depth.set(depth);
+        ret
+    }
+
+    if let Some(should_cancel) = options.should_cancel {
+        let count = options.count.get().wrapping_add(1);
+        options.count.set(count);
+        if count % CANCEL_INTERVAL == 0 && should_cancel() {
+            err!("Cancelled CBOR deserialization");
         }
-        let ret = if CUR_DEPTH.with(|it| it.replace(it.get() + 1)) > MAX_DEPTH {
+    }
+
+    if visitor.wants_raw() {
+        let start = bytes.as_slice();
+        self::from_slice_impl(bytes, <dyn Visitor>::ignore(), options)?;
+        let item = &start[..start.len() - bytes.as_slice().len()];
+        visitor.raw_cbor(item).ok()?;
+        return Some(());
+    }
+
+    // Arrays, maps and tags open a new level of nesting.
+    if let (major::SEQ..=major::CUSTOM_TAG, _) = major_and_tag(bytes.as_slice().get(0)?) {
+        if options.depth.get() >= options.limits.max_depth {
             err!("Reached maximum depth / recursion when deserializing CBOR object.");
-        } else {
-            self::from_slice_impl(bytes, visitor)
-        };
-        CUR_DEPTH.with(|it| it.set(it.get() - 1));
-        ret
+        }
     }
 
     match major_and_tag(bytes.next()?) {
@@ -93,17 +480,14 @@ fn from_slice_impl<'bytes>(
         }
 
         (major::BYTE_SLICE, tag::UNKNOWN_LEN) => {
-            let ref mut acc_bytes: Cow<'bytes, [u8]> = vec![].into();
+            let mut acc_bytes = Chunks::default();
             loop {
                 match major_and_tag(bytes.next()?) {
                     BREAK_CODE => break,
                     (major::BYTE_SLICE, tag) => {
                         let chunk = parse_known_len_byte_seq(tag, bytes)?;
-                        if acc_bytes.is_empty() {
-                            *acc_bytes = chunk.into();
-                        } else {
-                            acc_bytes.to_mut().extend_from_slice(chunk);
-                        }
+                        check_bytes_len(acc_bytes.len() + chunk.len(), options)?;
+                        acc_bytes.push(chunk);
                     }
                     _ => err!(
                         r#"Expected \xff or a known-len byte slice. Remaining = {:#x?}"#,
@@ -111,26 +495,31 @@ fn from_slice_impl<'bytes>(
                     ),
                 }
             }
-            visitor.bytes(acc_bytes).ok()?;
+            match acc_bytes.borrowed() {
+                Some(slice) if options.borrow => visitor.borrowed_bytes(slice).ok()?,
+                _ => visitor.bytes(acc_bytes.as_slice()).ok()?,
+            }
         }
         (major::BYTE_SLICE, tag) => {
             let slice = parse_known_len_byte_seq(tag, bytes)?;
-            visitor.bytes(slice).ok()?;
+            check_bytes_len(slice.len(), options)?;
+            if options.borrow {
+                visitor.borrowed_bytes(slice).ok()?;
+            } else {
+                visitor.bytes(slice).ok()?;
+            }
         }
 
         (major::STR, tag::UNKNOWN_LEN) => {
-            let ref mut acc_str: Cow<'bytes, str> = String::new().into();
+            let mut acc_str = Chunks::default();
             loop {
                 match major_and_tag(bytes.next()?) {
                     BREAK_CODE => break,
                     (major::BYTE_SLICE, tag) => {
                         let chunk = parse_known_len_byte_seq(tag, bytes)?;
-                        let s = ::core::str::from_utf8(chunk).ok()?;
-                        if acc_str.is_empty() {
-                            *acc_str = s.into();
-                        } else {
-                            acc_str.to_mut().push_str(s);
-                        }
+                        check_bytes_len(acc_str.len() + chunk.len(), options)?;
+                        ::core::str::from_utf8(chunk).ok()?;
+                        acc_str.push(chunk);
                     }
                     _ => err!(
                         r#"Expected \xff or a known-len string. Remaining = {:#x?}"#,
@@ -138,74 +527,93 @@ fn from_slice_impl<'bytes>(
                     ),
                 }
             }
-            visitor.string(acc_str).ok()?;
+            // Each chunk was checked to be UTF-8.
+            let as_str = |chunks| unsafe { ::core::str::from_utf8_unchecked(chunks) };
+            match acc_str.borrowed() {
+                Some(s) if options.borrow => visitor.borrowed_string(as_str(s)).ok()?,
+                _ => visitor.string(as_str(acc_str.as_slice())).ok()?,
+            }
         }
         (major::STR, tag) => {
             let slice = parse_known_len_byte_seq(tag, bytes)?;
+            check_bytes_len(slice.len(), options)?;
             let s = ::core::str::from_utf8(slice).ok()?;
-            visitor.string(s).ok()?;
+            if options.borrow {
+                visitor.borrowed_string(s).ok()?;
+            } else {
+                visitor.string(s).ok()?;
+            }
         }
 
         (major::SEQ, tag::UNKNOWN_LEN) => {
             let mut seq = visitor.seq().ok()?;
-            loop {
+            for len in 1.. {
                 if major_and_tag(bytes.as_slice().get(0)?) == BREAK_CODE {
+                    bytes.next();
                     break;
                 }
-                recurse_checked(bytes, seq.element().ok()?)?;
+                check_len(len, options)?;
+                recurse_checked(bytes, seq.element().ok()?, options)?;
             }
             seq.finish().ok()?;
+            options.report_progress(bytes.as_slice());
         }
         (major::SEQ, tag) => {
             let len = usize::try_from(parse_u64(tag, bytes)?).ok()?;
+            check_len(len, options)?;
             let mut seq = visitor.seq().ok()?;
             for _ in 0..len {
-                recurse_checked(bytes, seq.element().ok()?)?;
+                recurse_checked(bytes, seq.element().ok()?, options)?;
             }
             seq.finish().ok()?;
+            options.report_progress(bytes.as_slice());
         }
         (major::MAP, tag::UNKNOWN_LEN) => {
-            let mut map = visitor.map().ok()?;
-            loop {
-                if major_and_tag(bytes.as_slice().get(0)?) == BREAK_CODE {
-                    break;
-                }
-
-                let out_v = map
-                    .val_with_key(&mut |it| {
-                        it.and_then(|out_k| recurse_checked(bytes, out_k).ok_or(crate::Error))
-                    })
-                    .ok()?;
-                recurse_checked(bytes, out_v)?;
-            }
-            map.finish().ok()?;
+            visitor
+                .map_with(&mut |map| map_entries(bytes, map, None, options).ok_or(crate::Error))
+                .ok()?;
+            options.report_progress(bytes.as_slice());
         }
         (major::MAP, tag) => {
             let len = usize::try_from(parse_u64(tag, bytes)?).ok()?;
-            let mut map = visitor.map().ok()?;
-            for _ in 0..len {
-                let out_v = map
-                    .val_with_key(&mut |it| {
-                        it.and_then(|out_k| recurse_checked(bytes, out_k).ok_or(crate::Error))
-                    })
-                    .ok()?;
-                recurse_checked(bytes, out_v)?;
-            }
-            map.finish().ok()?;
+            check_len(len, options)?;
+            visitor
+                .map_with(&mut |map| {
+                    map_entries(bytes, map, Some(len), options).ok_or(crate::Error)
+                })
+                .ok()?;
+            options.report_progress(bytes.as_slice());
         }
 
-        (major::CUSTOM_TAG, tag) => err!("Custom tag (tag = {:#x}) cannot be deserialized", tag),
+        (major::CUSTOM_TAG, tag) => {
+            let number = parse_u64(tag, bytes)?;
+            let ignored = match visitor.tag(number).ok()? {
+                Some(mut tagged) => {
+                    recurse_checked(bytes, tagged.element().ok()?, options)?;
+                    tagged.finish().ok()?;
+                    false
+                }
+                None => true,
+            };
+            if ignored {
+                recurse_checked(bytes, visitor, options)?;
+            }
+        }
 
         (major::FLOAT_BOOL_OR_UNIT, t @ tag::bool::TRUE)
         | (major::FLOAT_BOOL_OR_UNIT, t @ tag::bool::FALSE) => {
             visitor.boolean(t == tag::bool::TRUE).ok()?;
         }
 
-        (major::FLOAT_BOOL_OR_UNIT, tag::UNIT_CANONICAL)
-        | (major::FLOAT_BOOL_OR_UNIT, tag::UNIT_ALTERNATIVE) => {
+        (major::FLOAT_BOOL_OR_UNIT, tag::UNIT_CANONICAL) => {
             visitor.null().ok()?;
         }
 
+        (major::FLOAT_BOOL_OR_UNIT, tag::UNIT_ALTERNATIVE) => {
+            visitor.undefined().ok()?;
+        }
+
+        #[cfg(feature = "float")]
         (major::FLOAT_BOOL_OR_UNIT, t @ tag::FLOAT!()) => {
             let f: f64 = match t {
                 tag::FLOAT::_16 => {
@@ -223,6 +631,9 @@ fn from_slice_impl<'bytes>(
             visitor.float(f).ok()?;
         }
 
+        #[cfg(not(feature = "float"))]
+        (major::FLOAT_BOOL_OR_UNIT, tag::FLOAT!()) => err!("Unsupported float"),
+
         (major::FLOAT_BOOL_OR_UNIT, _) => err!(
             r#"Incorrect tag associated to major 7. Remaining = {:#x?}"#,
             bytes.as_slice(),
@@ -233,7 +644,34 @@ fn from_slice_impl<'bytes>(
     Some(())
 }
 
-mod helpers {
+/// The encoded keys of a map, unless duplicates are left to the `Map`.
+type KeysSeen<'bytes> = Option<BTreeSet<&'bytes [u8]>>;
+
+fn keys_seen<'bytes>(options: &Options<'_>) -> KeysSeen<'bytes> {
+    match options.limits.duplicate_keys {
+        DuplicateKeys::LastWins => None,
+        DuplicateKeys::FirstWins | DuplicateKeys::Error => Some(BTreeSet::new()),
+    }
+}
+
+fn check_len(len: usize, options: &Options<'_>) -> Option<()> {
+    if len > options.limits.max_len {
+        err!("CBOR array or map longer than {}", options.limits.max_len);
+    }
+    Some(())
+}
+
+fn check_bytes_len(len: usize, options: &Options<'_>) -> Option<()> {
+    if len > options.limits.max_bytes_len {
+        err!(
+            "CBOR string longer than {} bytes",
+            options.limits.max_bytes_len
+        );
+    }
+    Some(())
+}
+
+pub(in crate::cbor) mod helpers {
     use super::*;
 
     pub fn major_and_tag(&byte: &'_ u8) -> (u8, u8) {
@@ -298,6 +736,7 @@ mod helpers {
                 .as_ref()
         ).ok().or_else(|| err!("Expected {} bytes to deserialize an integer", $N))?
     })}
+    #[cfg(feature = "float")]
     pub(in crate) use multi_bytes;
 
     pub fn parse_u64(tag: u8, bytes: &'_ mut ::core::slice::Iter<'_, u8>) -> Option<u64> {
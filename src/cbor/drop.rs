@@ -1,4 +1,5 @@
 use super::Value;
+use crate::prelude::*;
 
 pub fn safely(value: Value) {
     match value {
@@ -4,13 +4,31 @@
 //! serializing and deserializing CBOR.
 
 mod ser;
-pub use self::ser::to_vec;
+#[cfg(feature = "json")]
+pub use self::ser::to_vec_from_json_str;
+pub use self::ser::{to_vec, to_vec_canonical, Serializer};
+
+mod const_ser;
+pub use self::const_ser::ConstSerializer;
 
 mod de;
-pub use self::de::from_slice;
+pub(in crate) use self::de::{check_raw_value, from_slice_into};
+pub use self::de::{
+    decode_envelope, from_slice, from_slice_borrowed, from_slice_multi, merge_from_slice,
+    Deserializer, StreamDeserializer,
+};
+
+mod stream;
+pub use self::stream::{Decoded, StreamDecoder};
 
 pub mod value;
-pub use self::value::Value;
+pub use self::value::{diff, from_value, to_value, Value};
+
+mod raw;
+pub use self::raw::RawValue;
+
+mod access;
+pub use self::access::Index;
 
 mod array;
 pub use self::array::Array;
@@ -1,10 +1,11 @@
-use std::collections::{btree_map, BTreeMap};
-use std::iter::FromIterator;
-use std::mem::{self, ManuallyDrop};
-use std::ops::{Deref, DerefMut};
-use std::ptr;
+use alloc::collections::{btree_map, BTreeMap};
+use core::iter::FromIterator;
+use core::mem::{self, ManuallyDrop};
+use core::ops::{Deref, DerefMut};
+use core::ptr;
 
 use super::{drop, Value};
+use crate::prelude::*;
 use crate::private;
 use crate::ser::{self, Serialize, ValueView};
 
@@ -0,0 +1,95 @@
+use alloc::borrow::Cow;
+
+use crate::de::{Deserialize, Visitor};
+use crate::error::Result;
+use crate::prelude::*;
+use crate::ser::{Serialize, ValueView};
+use crate::Place;
+
+/// A CBOR data item kept as the bytes it was deserialized from, such as a
+/// payload to pass along without decoding it.
+///
+/// As a field, it captures the bytes of that item exactly as in the input,
+/// and serializes back to them verbatim, unless to canonical CBOR, which
+/// re-encodes them. From JSON input, or from
+/// [`from_value`](crate::cbor::from_value), the value gets encoded as CBOR.
+///
+/// ```rust
+/// use miniserde_ditto::cbor::{self, RawValue};
+/// use miniserde_ditto::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Envelope {
+///     kind: String,
+///     payload: RawValue,
+/// }
+///
+/// let bytes = [
+///     0xa2, // 2-long map
+///         0x64, b'k', b'i', b'n', b'd',
+///         0x65, b'a', b'u', b'd', b'i', b't',
+///         0x67, b'p', b'a', b'y', b'l', b'o', b'a', b'd',
+///         // An indefinite-length array, which `to_vec` would not write.
+///         0x9f, 0x01, 0x02, 0xff,
+/// ];
+/// let envelope: Envelope = cbor::from_slice(&bytes)?;
+/// assert_eq!(envelope.payload.get(), [0x9f, 0x01, 0x02, 0xff]);
+/// assert_eq!(cbor::to_vec(&envelope)?, bytes);
+/// # Ok::<(), miniserde_ditto::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RawValue {
+    cbor: Box<[u8]>,
+}
+
+impl RawValue {
+    /// The raw value holding `cbor`, or an error if it is not a single
+    /// well-formed CBOR data item.
+    pub fn from_vec(cbor: Vec<u8>) -> Result<RawValue> {
+        crate::cbor::check_raw_value(&cbor)?;
+        Ok(RawValue {
+            cbor: cbor.into_boxed_slice(),
+        })
+    }
+
+    /// The encoded data item.
+    pub fn get(&self) -> &[u8] {
+        &self.cbor
+    }
+
+    pub fn into_vec(self) -> Vec<u8> {
+        self.cbor.into_vec()
+    }
+}
+
+impl Serialize for RawValue {
+    fn view(&self) -> ValueView<'_> {
+        ValueView::RawCbor(Cow::Borrowed(&self.cbor))
+    }
+}
+
+impl Deserialize for RawValue {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl Visitor for Place<RawValue> {
+            fn wants_raw(&mut self) -> bool {
+                true
+            }
+
+            #[cfg(feature = "json")]
+            fn raw_json(&mut self, j: &str) -> Result<()> {
+                let value: crate::cbor::Value = crate::json::from_str(j)?;
+                self.out = Some(RawValue {
+                    cbor: crate::cbor::to_vec(&value)?.into_boxed_slice(),
+                });
+                Ok(())
+            }
+
+            fn raw_cbor(&mut self, bytes: &[u8]) -> Result<()> {
+                self.out = Some(RawValue { cbor: bytes.into() });
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
+}
@@ -0,0 +1,215 @@
+use ::core::convert::TryFrom;
+
+use super::de::helpers::{major, major_and_tag, tag, BREAK_CODE};
+use super::de::{Deserializer, Limits};
+use crate::de::Deserialize;
+use crate::error::Result;
+use crate::prelude::*;
+use crate::scratch;
+
+/// A CBOR decoder fed with the chunks of a stream as they arrive, such as
+/// from a socket, and yielding its items once they are complete.
+///
+/// The header of each item is enough to tell how many more bytes it needs,
+/// so the bytes of an incomplete item are only kept, not decoded, until it
+/// can be decoded in one go. Those headers are scanned once each, however
+/// many chunks the item spans.
+///
+/// ```rust
+/// use miniserde_ditto::cbor::{self, Decoded, StreamDecoder};
+///
+/// let bytes = cbor::to_vec("hello world").unwrap();
+/// let (head, tail) = bytes.split_at(4);
+///
+/// let mut decoder = StreamDecoder::new();
+/// decoder.feed(head);
+/// assert_eq!(decoder.decode::<String>()?, Decoded::NeedMoreData(8));
+/// decoder.feed(tail);
+/// assert_eq!(decoder.decode::<String>()?, Decoded::Item("hello world".to_owned()));
+/// assert_eq!(decoder.buffered(), 0);
+/// # Ok::<(), miniserde_ditto::Error>(())
+/// ```
+#[derive(Default)]
+pub struct StreamDecoder<'a> {
+    deserializer: Deserializer<'a>,
+    buffer: Vec<u8>,
+    scanner: Scanner,
+}
+
+/// The outcome of [`StreamDecoder::decode`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Decoded<T> {
+    Item(T),
+    /// The next item needs at least that many more bytes.
+    NeedMoreData(usize),
+}
+
+impl<'a> StreamDecoder<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode the items with the options of `deserializer`. Its limits are
+    /// also checked as soon as the header of an item is buffered, so that a
+    /// peer cannot have a huge item buffered.
+    pub fn with_deserializer(deserializer: Deserializer<'a>) -> Self {
+        StreamDecoder {
+            deserializer,
+            buffer: Vec::new(),
+            scanner: Scanner::default(),
+        }
+    }
+
+    /// Append the next chunk of the stream.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        scratch::reserve(&mut self.buffer, bytes.len());
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Decode the next item, if all of it was fed.
+    ///
+    /// When the item is malformed, or over the limits, the stream cannot be
+    /// read any further. When it is well-formed but does not deserialize into
+    /// a `T`, it is skipped, and decoding may go on with the next one.
+    pub fn decode<T: Deserialize>(&mut self) -> Result<Decoded<T>> {
+        let len = match self.scanner.scan(&self.buffer, &self.deserializer.limits)? {
+            Scan::Complete(len) => len,
+            Scan::Incomplete(missing) => return Ok(Decoded::NeedMoreData(missing)),
+        };
+        self.scanner.reset();
+        let item = self.deserializer.from_slice(&self.buffer[..len]);
+        scratch::consume(&mut self.buffer, len);
+        item.map(Decoded::Item)
+    }
+
+    /// How many bytes were fed but not decoded yet.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+}
+
+impl Drop for StreamDecoder<'_> {
+    fn drop(&mut self) {
+        scratch::clear(&mut self.buffer);
+    }
+}
+
+/// How much of a buffer its first item takes.
+enum Scan {
+    Complete(usize),
+    /// How many more bytes are needed, at least.
+    Incomplete(usize),
+}
+
+/// Where the scan of the first item of the buffer stopped, so that it
+/// resumes there once more bytes are fed, rather than from the start.
+struct Scanner {
+    /// The start of the next header to read.
+    pos: usize,
+    /// The number of items left in each enclosing array, map or tag, or
+    /// `None` until a break code.
+    pending: Vec<Option<u64>>,
+}
+
+impl Default for Scanner {
+    fn default() -> Self {
+        Scanner {
+            pos: 0,
+            pending: vec![Some(1)],
+        }
+    }
+}
+
+impl Scanner {
+    fn reset(&mut self) {
+        self.pos = 0;
+        self.pending.clear();
+        self.pending.push(Some(1));
+    }
+
+    /// Find where the first item of `bytes` ends, from the headers alone.
+    /// Only whole headers, and whole strings, move the scan forward.
+    fn scan(&mut self, bytes: &[u8], limits: &Limits) -> Result<Scan> {
+        loop {
+            let may_break = match self.pending.last() {
+                None => return Ok(Scan::Complete(self.pos)),
+                Some(Some(0)) => {
+                    self.pending.pop();
+                    continue;
+                }
+                Some(Some(_)) => false,
+                Some(None) => true,
+            };
+            let mut pos = self.pos;
+            let header = match bytes.get(pos) {
+                Some(header) => header,
+                None => return Ok(Scan::Incomplete(1)),
+            };
+            pos += 1;
+            let (major, tag) = major_and_tag(header);
+            if (major, tag) == BREAK_CODE {
+                if !may_break {
+                    err!("Unexpected CBOR break code at index {}", pos - 1);
+                }
+                self.pending.pop();
+                self.pos = pos;
+                continue;
+            }
+            let arg = match tag {
+                0..=tag::SMALL_U8_MAX => Some(u64::from(tag)),
+                tag::U8..=tag::U64 => {
+                    let n = 1 << (tag - tag::U8);
+                    let arg = match bytes.get(pos..pos + n) {
+                        Some(arg) => arg,
+                        None => return Ok(Scan::Incomplete(pos + n - bytes.len())),
+                    };
+                    pos += n;
+                    Some(arg.iter().fold(0, |acc, &b| acc << 8 | u64::from(b)))
+                }
+                tag::UNKNOWN_LEN => None,
+                _ => err!("Incorrect CBOR header {:#x} at index {}", header, pos - 1),
+            };
+            let nested = match (major, arg) {
+                (major::POS_INT..=major::NEG_INT, None) => {
+                    err!("Incorrect CBOR integer at index {}", pos - 1);
+                }
+                (major::BYTE_SLICE..=major::STR, Some(len)) => {
+                    let len = match usize::try_from(len) {
+                        Ok(len) if len <= limits.max_bytes_len => len,
+                        _ => err!("CBOR string longer than {} bytes", limits.max_bytes_len),
+                    };
+                    if bytes.len() - pos < len {
+                        return Ok(Scan::Incomplete(len - (bytes.len() - pos)));
+                    }
+                    pos += len;
+                    None
+                }
+                (major::SEQ..=major::MAP, Some(len)) => {
+                    if len > limits.max_len as u64 {
+                        err!("CBOR array or map longer than {}", limits.max_len);
+                    }
+                    let factor = if major == major::MAP { 2 } else { 1 };
+                    Some(Some(len.saturating_mul(factor)))
+                }
+                (major::BYTE_SLICE..=major::MAP, None) => Some(None),
+                (major::CUSTOM_TAG, Some(_)) => Some(Some(1)),
+                (major::CUSTOM_TAG, None) => {
+                    err!("Incorrect CBOR tag at index {}", pos - 1);
+                }
+                _ => None,
+            };
+            // The whole header is in: commit to it.
+            if let Some(Some(left)) = self.pending.last_mut() {
+                *left -= 1;
+            }
+            self.pos = pos;
+            if let Some(nested) = nested {
+                self.pending.push(nested);
+                // The outermost entry stands for the item itself.
+                if self.pending.len() - 1 > usize::from(limits.max_depth) {
+                    err!("Exceeded the maximum depth of {}", limits.max_depth);
+                }
+            }
+        }
+    }
+}
@@ -2,6 +2,7 @@
 //! https://github.com/pyfisch/cbor/blob/a218403a52e60c991313f429e4acc05cce81ce25/tests/canonical.rs
 
 use super::*;
+use crate::prelude::*;
 
 #[test]
 fn integer_canonical_sort_order() {
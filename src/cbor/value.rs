@@ -1,10 +1,15 @@
-use ::std::{borrow::Cow, cmp::Ordering};
+use ::alloc::borrow::Cow;
+use ::core::cmp::Ordering;
+use ::core::mem;
 
 use super::{Array, Object};
 use crate::de::{Deserialize, Map, Seq, Visitor};
 use crate::error::Result;
+use crate::merge::{self, Merge};
+use crate::prelude::*;
 use crate::private;
 use crate::ser::{Serialize, ValueView};
+use crate::transcode::{transcode, Options};
 use crate::Place;
 
 // Taken (and maybe modified) https://github.com/pyfisch/cbor/blob/2f2d0253e2d30e5ba7812cf0b149838b0c95530d/src/value/mod.rs
@@ -19,8 +24,13 @@ use crate::Place;
 #[derive(Clone, Debug)]
 #[non_exhaustive] // This allows the enum to be extended with variants for tags and simple values.
 pub enum Value {
-    /// Represents the absence of a value or the value undefined.
+    /// Represents the absence of a value.
     Null,
+    /// Represents the value undefined, which other formats write as `null`.
+    ///
+    /// It marks the entries that a merge patch removes, as written by
+    /// [`diff`] and applied by [`Value::merge`].
+    Undefined,
     /// Represents a boolean value.
     Bool(bool),
     /// Integer CBOR numbers.
@@ -31,6 +41,7 @@ pub enum Value {
     /// and will cause an error.
     Integer(i128),
     /// Represents a floating point value.
+    #[cfg(feature = "float")]
     Float(f64),
     /// Represents a byte string.
     Bytes(Vec<u8>),
@@ -44,7 +55,9 @@ pub enum Value {
     /// While any value can be used as a CBOR key
     /// it is better to use only one type of key in a map
     /// to avoid ambiguity.
-    /// If floating point values are used as keys they are compared bit-by-bit for equality.
+    /// Floating point keys compare by their canonical (shortest) encoding:
+    /// `0.0` and `-0.0` are different keys, and all NaNs are the same key.
+    /// NaN and infinite keys are neither serialized nor deserialized, though.
     /// If arrays or maps are used as keys the comparisons
     /// to establish canonical order may be slow and therefore insertion
     /// and retrieval of values will be slow too.
@@ -74,7 +87,8 @@ impl Ord for Value {
         // 2. Shorter sequence sorts first.
         // 3. Compare integers by magnitude.
         // 4. Compare byte and text sequences lexically.
-        // 5. Compare the serializations of both types. (expensive)
+        // 5. Compare floats by their encodings, without serializing them.
+        // 6. Compare the serializations of both types. (expensive)
         use self::Value::*;
         if self.major_type() != other.major_type() {
             return self.major_type().cmp(&other.major_type());
@@ -87,6 +101,8 @@ impl Ord for Value {
             (Map(a), Map(b)) if a.len() != b.len() => a.len().cmp(&b.len()),
             (Bytes(a), Bytes(b)) => a.cmp(b),
             (Text(a), Text(b)) => a.cmp(b),
+            #[cfg(feature = "float")]
+            (Float(a), Float(b)) => float_encoding(*a).cmp(&float_encoding(*b)),
             (a, b) => {
                 let a = super::to_vec(a).expect("self is serializable");
                 let b = super::to_vec(b).expect("other is serializable");
@@ -96,6 +112,25 @@ impl Ord for Value {
     }
 }
 
+/// The initial byte and payload of the canonical encoding of a float, as
+/// written by the serializer: NaN is always `0xf97e00`, and other values use
+/// the shortest width representing them exactly.
+#[cfg(feature = "float")]
+fn float_encoding(f: f64) -> (u8, u64) {
+    if f.is_nan() {
+        return (0xf9, 0x7e00);
+    }
+    let f_16 = ::half::f16::from_f64(f);
+    if f64::from(f_16) == f {
+        return (0xf9, f_16.to_bits().into());
+    }
+    let f_32 = f as f32;
+    if f64::from(f_32) == f {
+        return (0xfa, f_32.to_bits().into());
+    }
+    (0xfb, f.to_bits())
+}
+
 impl Default for Value {
     /// The default value is null.
     fn default() -> Self {
@@ -107,14 +142,16 @@ impl Serialize for Value {
     fn view(&self) -> ValueView<'_> {
         match self {
             Value::Null => ValueView::Null,
+            Value::Undefined => ValueView::Undefined,
             Value::Bool(b) => ValueView::Bool(*b),
             &Value::Integer(i) => ValueView::Int(i),
+            #[cfg(feature = "float")]
             &Value::Float(f) => ValueView::F64(f),
-            Value::Bytes(bytes) => private::stream_slice(bytes),
+            Value::Bytes(bytes) => ValueView::Bytes(Cow::Borrowed(bytes)),
             Value::Text(s) => ValueView::Str(Cow::Borrowed(s)),
             Value::Array(array) => private::stream_slice(array),
             Value::Map(map) => private::stream_cbor_object(map),
-            Value::Tag(..) => unimplemented!("Serializing tags is not supported by this crate."),
+            Value::Tag(tag, value) => ValueView::Tagged(*tag, &**value),
         }
     }
 }
@@ -127,6 +164,11 @@ impl Deserialize for Value {
                 Ok(())
             }
 
+            fn undefined(&mut self) -> Result<()> {
+                self.out = Some(Value::Undefined);
+                Ok(())
+            }
+
             fn boolean(&mut self, b: bool) -> Result<()> {
                 self.out = Some(Value::Bool(b));
                 Ok(())
@@ -137,6 +179,11 @@ impl Deserialize for Value {
                 Ok(())
             }
 
+            fn bytes(&mut self, xs: &[u8]) -> Result<()> {
+                self.out = Some(Value::Bytes(xs.to_owned()));
+                Ok(())
+            }
+
             fn int(&mut self, i: i128) -> Result<()> {
                 const MIN: i128 = -(1_i128 << 64);
                 const MAX: i128 = (1_i128 << 64) - 1;
@@ -149,6 +196,7 @@ impl Deserialize for Value {
                 }
             }
 
+            #[cfg(feature = "float")]
             fn float(&mut self, f: f64) -> Result<()> {
                 self.out = Some(Value::Float(f));
                 Ok(())
@@ -170,6 +218,32 @@ impl Deserialize for Value {
                     value: None,
                 }))
             }
+
+            fn tag(&mut self, tag: u64) -> Result<Option<Box<dyn Seq + '_>>> {
+                Ok(Some(Box::new(TagBuilder {
+                    out: &mut self.out,
+                    tag,
+                    value: None,
+                })))
+            }
+        }
+
+        struct TagBuilder<'a> {
+            out: &'a mut Option<Value>,
+            tag: u64,
+            value: Option<Value>,
+        }
+
+        impl<'a> Seq for TagBuilder<'a> {
+            fn element(&mut self) -> Result<&mut dyn Visitor> {
+                Ok(Deserialize::begin(&mut self.value))
+            }
+
+            fn finish(self: Box<Self>) -> Result<()> {
+                let value = self.value.ok_or(crate::Error)?;
+                *self.out = Some(Value::Tag(self.tag, Box::new(value)));
+                Ok(())
+            }
         }
 
         struct ArrayBuilder<'a> {
@@ -221,6 +295,12 @@ impl Deserialize for Value {
             ) -> Result<&mut dyn Visitor> {
                 self.shift();
                 de_key(Ok(Deserialize::begin(&mut self.key)))?;
+                #[cfg(feature = "float")]
+                if let Some(Value::Float(f)) = self.key {
+                    if !f.is_finite() {
+                        err!("Non-finite float {} used as a map key", f);
+                    }
+                }
                 Ok(Deserialize::begin(&mut self.value))
             }
 
@@ -239,7 +319,7 @@ impl Value {
     fn major_type(&self) -> u8 {
         use self::Value::*;
         match self {
-            Null => 7,
+            Null | Undefined => 7,
             Bool(_) => 7,
             Integer(v) => {
                 if *v >= 0 {
@@ -249,6 +329,7 @@ impl Value {
                 }
             }
             Tag(_, _) => 6,
+            #[cfg(feature = "float")]
             Float(_) => 7,
             Bytes(_) => 2,
             Text(_) => 3,
@@ -256,6 +337,108 @@ impl Value {
             Map(_) => 5,
         }
     }
+
+    /// Encode this value as CBOR bytes; shorthand for [`cbor::to_vec`].
+    ///
+    /// [`cbor::to_vec`]: crate::cbor::to_vec
+    pub fn to_vec(&self) -> Result<Vec<u8>> {
+        super::to_vec(self)
+    }
+
+    /// Decode a value from CBOR bytes; shorthand for [`cbor::from_slice`].
+    ///
+    /// ```rust
+    /// use miniserde_ditto::cbor::Value;
+    ///
+    /// let value = Value::Array(vec![Value::from(1), Value::from(String::from("a"))].into_iter().collect());
+    /// let bytes = value.to_vec().unwrap();
+    /// assert_eq!(Value::from_slice(&bytes).unwrap(), value);
+    /// ```
+    ///
+    /// [`cbor::from_slice`]: crate::cbor::from_slice
+    pub fn from_slice(bytes: &[u8]) -> Result<Value> {
+        super::from_slice(bytes)
+    }
+
+    /// Applies `patch` to this value as a [JSON Merge Patch], with maps for
+    /// objects and `undefined` to remove entries: the entries of a map patch
+    /// are merged into a map, recursively, those set to
+    /// [`Undefined`](Value::Undefined) being removed, and any other patch,
+    /// such as an array or `null`, replaces the value as a whole. Keys may be
+    /// of any type, and stay in canonical order.
+    ///
+    /// ```rust
+    /// use miniserde_ditto::cbor::{Object, Value};
+    ///
+    /// let mut ports = Object::new();
+    /// ports.insert(Value::from(80), Value::from("http".to_owned()));
+    /// ports.insert(Value::from(443), Value::from("https".to_owned()));
+    /// let mut value = Value::Map(ports);
+    ///
+    /// let mut patch = Object::new();
+    /// patch.insert(Value::from(80), Value::Undefined);
+    /// patch.insert(Value::from(443), Value::Null);
+    /// patch.insert(Value::from(8080), Value::from("http".to_owned()));
+    /// value.merge(Value::Map(patch));
+    ///
+    /// let ports = value.as_map().unwrap();
+    /// let keys: Vec<_> = ports.keys().cloned().collect();
+    /// assert_eq!(keys, [Value::from(443), Value::from(8080)]);
+    /// assert_eq!(ports.get(&Value::from(443)), Some(&Value::Null));
+    /// ```
+    ///
+    /// [JSON Merge Patch]: https://tools.ietf.org/html/rfc7386
+    pub fn merge(&mut self, patch: Value) {
+        merge::merge(self, patch);
+    }
+}
+
+impl Merge for Value {
+    type Key = Value;
+    type Map = Object;
+
+    fn into_map(self) -> Result<Object, Value> {
+        match self {
+            Value::Map(map) => Ok(map),
+            value => Err(value),
+        }
+    }
+
+    fn from_map(map: Object) -> Value {
+        Value::Map(map)
+    }
+
+    fn make_map(&mut self) -> &mut Object {
+        if !matches!(self, Value::Map(_)) {
+            *self = Value::Map(Object::new());
+        }
+        match self {
+            Value::Map(map) => map,
+            _ => unreachable!(),
+        }
+    }
+
+    fn removes(&self) -> bool {
+        matches!(self, Value::Undefined)
+    }
+
+    fn contains_key(map: &Object, key: &Value) -> bool {
+        map.contains_key(key)
+    }
+
+    fn insert(map: &mut Object, key: Value, value: Value) {
+        map.insert(key, value);
+    }
+
+    fn remove(map: &mut Object, key: &Value) {
+        map.remove(key);
+    }
+
+    fn for_each_mut<'a>(map: &'a mut Object, f: &mut dyn FnMut(&Value, &'a mut Value)) {
+        for (key, child) in map.iter_mut() {
+            f(key, child);
+        }
+    }
 }
 
 impl_From! {
@@ -270,7 +453,9 @@ impl_From! {
     u32 => Integer,
     u64 => Integer,
     // u128 omitted because not all numbers fit in CBOR serialization
+    #[cfg(feature = "float")]
     f32 => Float,
+    #[cfg(feature = "float")]
     f64 => Float,
     // TODO: figure out if these impls should be more generic or removed.
     Vec<u8> => Bytes,
@@ -279,10 +464,12 @@ impl_From! {
 /// where:
 macro_rules! impl_From {(
     $(
+        $(#[$attr:meta])*
         $T:ty => $Variant:ident
     ),* $(,)?
 ) => (
     $(
+        $(#[$attr])*
         impl From<$T> for Value {
             fn from (it: $T)
               -> Value
@@ -294,11 +481,95 @@ macro_rules! impl_From {(
 )}
 use impl_From;
 
+/// Convert any serializable type into a [`Value`], as if serializing it to
+/// CBOR and parsing it back, but without going through bytes.
 pub fn to_value<T: crate::Serialize>(v: T) -> crate::Result<Value> {
-    use super::*;
-    from_slice(&to_vec(&v)?)
+    let mut out = None;
+    transcode(&v, Deserialize::begin(&mut out), Options::default())?;
+    out.ok_or(crate::Error)
+}
+
+/// The patch turning `old` into `new` when [merged](Value::merge) into it, or
+/// `None` if they are equal.
+///
+/// Maps are compared entry by entry, recursively, and any other value that
+/// changed is replaced as a whole. The patch, a map in canonical order when
+/// `old` and `new` are maps, is canonical CBOR itself, so that equal changes
+/// encode to the same bytes. The entries missing from `new` are set to
+/// [`Undefined`](Value::Undefined) in the patch, so that those set to `null`
+/// are kept as such.
+///
+/// ```rust
+/// use miniserde_ditto::cbor::{self, Value};
+/// use std::collections::BTreeMap;
+///
+/// let mut old = BTreeMap::new();
+/// old.insert("rev", cbor::to_value(1)?);
+/// old.insert("tags", cbor::to_value(["x"])?);
+/// let mut new = old.clone();
+/// new.insert("rev", cbor::to_value(2)?);
+/// let (mut old, new) = (cbor::to_value(&old)?, cbor::to_value(&new)?);
+///
+/// let patch = cbor::diff(&old, &new).unwrap();
+/// assert_eq!(patch.as_map().unwrap().len(), 1);
+/// old.merge(patch);
+/// assert_eq!(old, new);
+/// assert!(cbor::diff(&old, &new).is_none());
+/// # Ok::<(), miniserde_ditto::Error>(())
+/// ```
+pub fn diff(old: &Value, new: &Value) -> Option<Value> {
+    let (old, new) = match (old, new) {
+        (Value::Map(old), Value::Map(new)) => (old, new),
+        _ if old == new => return None,
+        _ => return Some(new.clone()),
+    };
+    // The pairs of maps to compare, each after its parent, with its index and
+    // key in it, so that the patches of children are done before their
+    // parent's, without recursion.
+    let mut maps = vec![(None, old, new)];
+    let mut i = 0;
+    while let Some(&(_, old, new)) = maps.get(i) {
+        for (key, new) in new.iter() {
+            if let (Some(Value::Map(old)), Value::Map(new)) = (old.get(key), new) {
+                maps.push((Some((i, key)), old, new));
+            }
+        }
+        i += 1;
+    }
+    let mut patches: Vec<Object> = maps.iter().map(|_| Object::new()).collect();
+    for (i, &(parent, old, new)) in maps.iter().enumerate().rev() {
+        let mut patch = mem::take(&mut patches[i]);
+        for key in old.keys() {
+            if !new.contains_key(key) {
+                patch.insert(key.clone(), Value::Undefined);
+            }
+        }
+        for (key, new) in new.iter() {
+            match (old.get(key), new) {
+                (Some(Value::Map(_)), Value::Map(_)) => {}
+                (Some(old), new) if old == new => {}
+                _ => {
+                    patch.insert(key.clone(), new.clone());
+                }
+            }
+        }
+        match parent {
+            Some((parent, key)) => {
+                if !patch.is_empty() {
+                    patches[parent].insert(key.clone(), Value::Map(patch));
+                }
+            }
+            None if patch.is_empty() => return None,
+            None => return Some(Value::Map(patch)),
+        }
+    }
+    unreachable!()
 }
+
+/// Convert a [`Value`] into any deserializable type, as if serializing it to
+/// CBOR and parsing it back, but without going through bytes.
 pub fn from_value<T: crate::Deserialize>(v: Value) -> crate::Result<T> {
-    use super::*;
-    from_slice(&to_vec(&v)?)
+    let mut out = None;
+    transcode(&v, T::begin(&mut out), Options::default())?;
+    out.ok_or(crate::Error)
 }
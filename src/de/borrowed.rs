@@ -0,0 +1,170 @@
+use alloc::borrow::Cow;
+
+use crate::de::{Deserialize, Visitor};
+use crate::error::Result;
+use crate::prelude::*;
+use crate::Place;
+
+/// Trait for data structures that can be deserialized while borrowing from
+/// the input, such as `&'de str`, with [`json::from_str_borrowed`] or
+/// [`cbor::from_slice_borrowed`].
+///
+/// Every [`Deserialize`] type implements it, by copying as usual. Besides
+/// them, it is implemented for `&'de str`, `&'de [u8]` and `Cow<'de, str>`,
+/// as well as `Option`s of them, and for structs deriving `Deserialize`
+/// with `#[serde(borrow)]`.
+///
+/// A `&'de str` or `&'de [u8]` can only be deserialized from a string or byte
+/// string appearing as is in the input: a JSON string with escape sequences,
+/// for instance, fails to deserialize into one. A `Cow<'de, str>` copies it
+/// instead.
+///
+/// ```rust
+/// use miniserde_ditto::{json, Deserialize};
+///
+/// #[derive(Deserialize, Debug)]
+/// #[serde(borrow)]
+/// struct Event<'a> {
+///     kind: &'a str,
+///     count: u32,
+/// }
+///
+/// let j = r#"{"kind": "click", "count": 2}"#;
+/// let event: Event<'_> = json::from_str_borrowed(j).unwrap();
+/// assert_eq!(event.kind, "click");
+/// ```
+///
+/// [`json::from_str_borrowed`]: crate::json::from_str_borrowed
+/// [`cbor::from_slice_borrowed`]: crate::cbor::from_slice_borrowed
+pub trait DeserializeBorrowed<'de>: Sized {
+    /// Like [`Deserialize::begin`].
+    ///
+    /// # Safety
+    ///
+    /// The returned visitor must only be driven by a deserializer whose
+    /// input lives for `'de`, and which calls [`Visitor::borrowed_string`]
+    /// and [`Visitor::borrowed_bytes`] with slices of that input only.
+    unsafe fn begin_borrowed(out: &mut Option<Self>) -> &mut dyn Visitor;
+
+    // Not public API, see `Deserialize::default`.
+    #[doc(hidden)]
+    #[inline]
+    fn default_borrowed() -> Option<Self> {
+        None
+    }
+}
+
+impl<'de, T: Deserialize> DeserializeBorrowed<'de> for T {
+    unsafe fn begin_borrowed(out: &mut Option<Self>) -> &mut dyn Visitor {
+        T::begin(out)
+    }
+
+    fn default_borrowed() -> Option<Self> {
+        T::default()
+    }
+}
+
+impl<'de> DeserializeBorrowed<'de> for &'de str {
+    unsafe fn begin_borrowed(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl Visitor for Place<&str> {
+            fn borrowed_string(&mut self, s: &str) -> Result<()> {
+                // Safety: `s` is part of the input, which lives for `'de`, as
+                // guaranteed by the caller of `begin_borrowed`.
+                self.out = Some(unsafe { &*(s as *const str) });
+                Ok(())
+            }
+
+            fn string(&mut self, s: &str) -> Result<()> {
+                err!(
+                    "Cannot borrow the string {:?}, which is not as is in the input",
+                    s
+                );
+            }
+        }
+        Place::new(out)
+    }
+}
+
+impl<'de> DeserializeBorrowed<'de> for &'de [u8] {
+    unsafe fn begin_borrowed(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl Visitor for Place<&[u8]> {
+            fn borrowed_bytes(&mut self, xs: &[u8]) -> Result<()> {
+                // Safety: `xs` is part of the input, which lives for `'de`, as
+                // guaranteed by the caller of `begin_borrowed`.
+                self.out = Some(unsafe { &*(xs as *const [u8]) });
+                Ok(())
+            }
+
+            fn bytes(&mut self, xs: &[u8]) -> Result<()> {
+                err!(
+                    "Cannot borrow the bytes {:#x?}, which are not as is in the input",
+                    xs
+                );
+            }
+        }
+        Place::new(out)
+    }
+}
+
+impl<'de> DeserializeBorrowed<'de> for Cow<'de, str> {
+    unsafe fn begin_borrowed(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl<'de> Visitor for Place<Cow<'de, str>> {
+            fn borrowed_string(&mut self, s: &str) -> Result<()> {
+                // Safety: `s` is part of the input, which lives for `'de`, as
+                // guaranteed by the caller of `begin_borrowed`.
+                self.out = Some(Cow::Borrowed(unsafe { &*(s as *const str) }));
+                Ok(())
+            }
+
+            fn string(&mut self, s: &str) -> Result<()> {
+                self.out = Some(Cow::Owned(s.to_owned()));
+                Ok(())
+            }
+        }
+        Place::new(out)
+    }
+}
+
+// `Option<T>` cannot be covered generically, since it would overlap with the
+// blanket impl.
+macro_rules! option {($($T:ty),* $(,)?) => ($(
+    impl<'de> DeserializeBorrowed<'de> for Option<$T> {
+        unsafe fn begin_borrowed(out: &mut Option<Self>) -> &mut dyn Visitor {
+            impl<'de> Visitor for Place<Option<$T>> {
+                fn null(&mut self) -> Result<()> {
+                    self.out = Some(None);
+                    Ok(())
+                }
+
+                fn string(&mut self, s: &str) -> Result<()> {
+                    self.out = Some(None);
+                    unsafe { <$T>::begin_borrowed(self.out.as_mut().unwrap()) }.string(s)
+                }
+
+                fn borrowed_string(&mut self, s: &str) -> Result<()> {
+                    self.out = Some(None);
+                    unsafe { <$T>::begin_borrowed(self.out.as_mut().unwrap()) }
+                        .borrowed_string(s)
+                }
+
+                fn bytes(&mut self, xs: &[u8]) -> Result<()> {
+                    self.out = Some(None);
+                    unsafe { <$T>::begin_borrowed(self.out.as_mut().unwrap()) }.bytes(xs)
+                }
+
+                fn borrowed_bytes(&mut self, xs: &[u8]) -> Result<()> {
+                    self.out = Some(None);
+                    unsafe { <$T>::begin_borrowed(self.out.as_mut().unwrap()) }
+                        .borrowed_bytes(xs)
+                }
+            }
+            Place::new(out)
+        }
+
+        fn default_borrowed() -> Option<Self> {
+            Some(None)
+        }
+    }
+)*)}
+
+option![&'de str, &'de [u8], Cow<'de, str>];
@@ -0,0 +1,154 @@
+use crate::aliased_box::AliasedBox;
+use crate::de::{Deserialize, Map, Seq, Visitor};
+use crate::prelude::*;
+use crate::{Error, Result};
+
+/// A type deserialized as its `Repr`, then converted.
+///
+/// Not public API: implemented by the smart pointers of this crate, and by
+/// the derives for `#[serde(transparent)]` containers.
+pub trait FromRepr: Sized {
+    type Repr: Deserialize;
+
+    fn from_repr(repr: Self::Repr) -> Result<Self>;
+
+    /// Converts the `Repr` staged on the heap while deserializing a sequence
+    /// or a map, which `Box` reuses rather than allocating anew.
+    #[allow(clippy::boxed_local)]
+    fn from_heap_slot(slot: Box<Option<Self::Repr>>) -> Result<Self> {
+        Self::from_repr((*slot).ok_or(Error)?)
+    }
+}
+
+/// The place of a [`FromRepr`] type: every [`Visitor`] method is forwarded to
+/// the visitor of the `Repr`, whose output is converted once complete.
+#[repr(transparent)]
+pub struct ReprPlace<T> {
+    out: Option<T>,
+}
+
+impl<T> ReprPlace<T> {
+    pub fn new(out: &mut Option<T>) -> &mut ReprPlace<T> {
+        unsafe { &mut *(out as *mut Option<T> as *mut ReprPlace<T>) }
+    }
+}
+
+fn convert<T: FromRepr>(out: &mut Option<T>, repr: Option<T::Repr>) -> Result<()> {
+    *out = Some(T::from_repr(repr.ok_or(Error)?)?);
+    Ok(())
+}
+
+macro_rules! forward {
+    ($($(#[$attr:meta])* fn $method:ident(&mut self $(, $arg:ident: $Arg:ty)?);)*) => {$(
+        $(#[$attr])*
+        fn $method(&mut self $(, $arg: $Arg)?) -> Result<()> {
+            let mut repr = None;
+            T::Repr::begin(&mut repr).$method($($arg)?)?;
+            convert(&mut self.out, repr)
+        }
+    )*};
+}
+
+impl<T: FromRepr> Visitor for ReprPlace<T> {
+    forward! {
+        fn null(&mut self);
+        fn undefined(&mut self);
+        fn boolean(&mut self, b: bool);
+        fn string(&mut self, s: &str);
+        fn borrowed_string(&mut self, s: &str);
+        fn bytes(&mut self, xs: &[u8]);
+        fn borrowed_bytes(&mut self, xs: &[u8]);
+        fn int(&mut self, i: i128);
+        #[cfg(feature = "float")]
+        fn float(&mut self, f: f64);
+        fn raw_number(&mut self, raw: &str);
+        fn big_integer(&mut self, raw: &str);
+        fn raw_json(&mut self, j: &str);
+        fn raw_cbor(&mut self, bytes: &[u8]);
+    }
+
+    fn wants_raw(&mut self) -> bool {
+        T::Repr::begin(&mut None).wants_raw()
+    }
+
+    fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
+        let heap_slot = AliasedBox::from(Box::new(None));
+        let at_slot = unsafe { &mut *heap_slot.ptr() };
+        Ok(Box::new(ReprSeq {
+            out: &mut self.out,
+            heap_slot,
+            seq: T::Repr::begin(at_slot).seq()?,
+        }))
+    }
+
+    fn map(&mut self) -> Result<Box<dyn Map + '_>> {
+        let heap_slot = AliasedBox::from(Box::new(None));
+        let at_slot = unsafe { &mut *heap_slot.ptr() };
+        Ok(Box::new(ReprMap {
+            out: &mut self.out,
+            heap_slot,
+            map: T::Repr::begin(at_slot).map()?,
+        }))
+    }
+
+    // The `Repr` stays on the stack, as the `Map` of a derived struct does.
+    fn map_with(&mut self, with_map: &mut dyn FnMut(&mut dyn Map) -> Result<()>) -> Result<()> {
+        let mut repr = None;
+        T::Repr::begin(&mut repr).map_with(with_map)?;
+        convert(&mut self.out, repr)
+    }
+
+    fn tag(&mut self, tag: u64) -> Result<Option<Box<dyn Seq + '_>>> {
+        let heap_slot = AliasedBox::from(Box::new(None));
+        let at_slot = unsafe { &mut *heap_slot.ptr() };
+        Ok(match T::Repr::begin(at_slot).tag(tag)? {
+            Some(seq) => Some(Box::new(ReprSeq {
+                out: &mut self.out,
+                heap_slot,
+                seq,
+            })),
+            None => None,
+        })
+    }
+}
+
+struct ReprSeq<'a, T: FromRepr> {
+    out: &'a mut Option<T>,
+    // Safety: refers to `heap_slot`, so it must be dropped before it.
+    seq: Box<dyn Seq + 'a>,
+    heap_slot: AliasedBox<Option<T::Repr>>,
+}
+
+impl<'a, T: FromRepr> Seq for ReprSeq<'a, T> {
+    fn element(&mut self) -> Result<&mut dyn Visitor> {
+        self.seq.element()
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        self.seq.finish()?;
+        *self.out = Some(T::from_heap_slot(self.heap_slot.assume_unique())?);
+        Ok(())
+    }
+}
+
+struct ReprMap<'a, T: FromRepr> {
+    out: &'a mut Option<T>,
+    // Safety: refers to `heap_slot`, so it must be dropped before it.
+    map: Box<dyn Map + 'a>,
+    heap_slot: AliasedBox<Option<T::Repr>>,
+}
+
+impl<'a, T: FromRepr> Map for ReprMap<'a, T> {
+    fn val_with_key(
+        &mut self,
+        de_key: &mut dyn FnMut(Result<&mut dyn Visitor>) -> Result<()>,
+    ) -> Result<&mut dyn Visitor> {
+        self.map.val_with_key(de_key)
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        self.map.finish()?;
+        *self.out = Some(T::from_heap_slot(self.heap_slot.assume_unique())?);
+        Ok(())
+    }
+}
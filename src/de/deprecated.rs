@@ -0,0 +1,48 @@
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+type Hook = fn(&'static str, &'static str);
+
+/// The registered hook, as a pointer to its code; null when unset.
+static HOOK: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// Registers the function called whenever a `#[serde(deprecated_field)]`
+/// field is found in the input, with the names of the struct and of the field.
+///
+/// This helps measuring whether a field is still sent before removing it from
+/// a protocol. Without a hook, such fields are deserialized silently.
+///
+/// ```rust
+/// use miniserde_ditto::{de, json, Deserialize};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static SEEN: AtomicUsize = AtomicUsize::new(0);
+///
+/// #[derive(Deserialize)]
+/// struct Request {
+///     id: u32,
+///     #[serde(deprecated_field)]
+///     legacy_id: Option<u32>,
+/// }
+///
+/// de::set_deprecated_field_hook(|_struct, _field| {
+///     SEEN.fetch_add(1, Ordering::Relaxed);
+/// });
+/// let _: Request = json::from_str(r#"{"id": 1, "legacy_id": 1}"#).unwrap();
+/// assert_eq!(SEEN.load(Ordering::Relaxed), 1);
+/// ```
+pub fn set_deprecated_field_hook(hook: Hook) {
+    HOOK.store(hook as *mut (), Ordering::Relaxed);
+}
+
+pub(crate) fn deprecated_field(container: &'static str, field: &'static str) {
+    let hook = HOOK.load(Ordering::Relaxed);
+    if !hook.is_null() {
+        let hook: Hook = unsafe {
+            // Safety: only `set_deprecated_field_hook` stores non-null
+            // pointers, which are such `fn` pointers.
+            ::core::mem::transmute::<*mut (), Hook>(hook)
+        };
+        hook(container, field)
+    }
+}
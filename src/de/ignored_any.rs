@@ -1,4 +1,5 @@
 use super::{Map, Seq, Visitor};
+use crate::prelude::*;
 use crate::{Place, Result};
 
 /// An efficient way of discarding data from a deserializer.
@@ -45,6 +46,7 @@ impl super::Deserialize for IgnoredAny {
                 Ok(())
             }
 
+            #[cfg(feature = "float")]
             fn float(&mut self, _n: f64) -> Result<()> {
                 self.mark_visited();
                 Ok(())
@@ -67,9 +69,16 @@ impl super::Deserialize for IgnoredAny {
 
 struct Ignore {}
 
+// `Ignore` must stay zero-sized: boxing it then allocates nothing, so that
+// neither the leaked `Box` of `ignore()` nor those of `seq` and `map` cost
+// any memory.
+const _: [(); 0] = [(); ::core::mem::size_of::<Ignore>()];
+
 impl dyn 'static + Visitor {
     /// Creates a dummy `Visitor` that ignores the received values with a dummy
     /// success each time.
+    ///
+    /// This neither allocates nor leaks memory, however often it is called.
     pub fn ignore() -> &'static mut dyn Visitor {
         Box::leak(Box::new(Ignore {}))
     }
@@ -92,6 +101,7 @@ impl Visitor for Ignore {
         Ok(())
     }
 
+    #[cfg(feature = "float")]
     fn float(&mut self, _: f64) -> Result<()> {
         Ok(())
     }
@@ -1,9 +1,27 @@
-use std::collections::{BTreeMap, HashMap};
-use std::hash::{BuildHasher, Hash};
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+#[cfg(feature = "allocator_api")]
+use core::alloc::Allocator;
+#[cfg(feature = "std")]
+use core::hash::{BuildHasher, Hash};
+use core::marker::PhantomData;
+use core::num::Wrapping;
+use core::ops::{Bound, Range, RangeInclusive};
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "std")]
+use std::ffi::OsString;
+#[cfg(feature = "std")]
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+#[cfg(feature = "std")]
+use std::path::PathBuf;
 
 use crate::aliased_box::AliasedBox;
-use crate::de::{Deserialize, Map, Seq, Visitor};
+use crate::de::convert::{FromRepr, ReprPlace};
+use crate::de::{Deserialize, Map, Seq, StrKeyMap, Visitor};
 use crate::error::Result;
+use crate::prelude::*;
 use crate::Place;
 
 impl Deserialize for () {
@@ -18,6 +36,23 @@ impl Deserialize for () {
     }
 }
 
+impl<T: ?Sized> Deserialize for PhantomData<T> {
+    // As for `Option`, a missing field is fine.
+    #[inline]
+    fn default() -> Option<Self> {
+        Some(PhantomData)
+    }
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl<T: ?Sized> Visitor for Place<PhantomData<T>> {
+            fn null(&mut self) -> Result<()> {
+                self.out = Some(PhantomData);
+                Ok(())
+            }
+        }
+        Place::new(out)
+    }
+}
+
 impl Deserialize for bool {
     fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
         impl Visitor for Place<bool> {
@@ -42,6 +77,89 @@ impl Deserialize for String {
     }
 }
 
+#[cfg(feature = "std")]
+impl Deserialize for PathBuf {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl Visitor for Place<PathBuf> {
+            fn string(&mut self, s: &str) -> Result<()> {
+                self.out = Some(PathBuf::from(s));
+                Ok(())
+            }
+        }
+        Place::new(out)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Deserialize for OsString {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl Visitor for Place<OsString> {
+            fn string(&mut self, s: &str) -> Result<()> {
+                self.out = Some(OsString::from(s));
+                Ok(())
+            }
+
+            #[cfg(unix)]
+            fn bytes(&mut self, xs: &[u8]) -> Result<()> {
+                use std::ffi::OsStr;
+                use std::os::unix::ffi::OsStrExt;
+                self.out = Some(OsStr::from_bytes(xs).to_owned());
+                Ok(())
+            }
+        }
+        Place::new(out)
+    }
+}
+
+#[cfg(feature = "std")]
+macro_rules! from_str {
+    ($($ty:ty),*) => {$(
+        impl Deserialize for $ty {
+            fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+                impl Visitor for Place<$ty> {
+                    fn string(&mut self, s: &str) -> Result<()> {
+                        match s.parse() {
+                            Ok(parsed) => {
+                                self.out = Some(parsed);
+                                Ok(())
+                            }
+                            Err(_) => err!("Cannot deserialize {:?} as a {}", s, stringify!($ty)),
+                        }
+                    }
+                }
+                Place::new(out)
+            }
+        }
+    )*};
+}
+#[cfg(feature = "std")]
+from_str!(
+    IpAddr,
+    Ipv4Addr,
+    Ipv6Addr,
+    SocketAddr,
+    SocketAddrV4,
+    SocketAddrV6
+);
+
+impl Deserialize for char {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl Visitor for Place<char> {
+            fn string(&mut self, s: &str) -> Result<()> {
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => {
+                        self.out = Some(c);
+                        Ok(())
+                    }
+                    _ => err!("Cannot deserialize {:?} as a char", s),
+                }
+            }
+        }
+        Place::new(out)
+    }
+}
+
 macro_rules! signed {
     ($ty:ident) => {
         impl Deserialize for $ty {
@@ -68,6 +186,28 @@ signed!(i32);
 signed!(i64);
 signed!(isize);
 
+impl Deserialize for i128 {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl Visitor for Place<i128> {
+            fn int(&mut self, i: i128) -> Result<()> {
+                self.out = Some(i);
+                Ok(())
+            }
+
+            fn raw_number(&mut self, raw: &str) -> Result<()> {
+                match raw.parse() {
+                    Ok(n) => {
+                        self.out = Some(n);
+                        Ok(())
+                    }
+                    Err(_) => err!("Cannot deserialize {} as a {}", raw, stringify!(i128)),
+                }
+            }
+        }
+        Place::new(out)
+    }
+}
+
 macro_rules! unsigned {
     ($ty:ident) => {
         impl Deserialize for $ty {
@@ -93,6 +233,32 @@ unsigned!(u32);
 unsigned!(u64);
 unsigned!(usize);
 
+impl Deserialize for u128 {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl Visitor for Place<u128> {
+            fn int(&mut self, i: i128) -> Result<()> {
+                if 0 <= i {
+                    self.out = Some(i as u128);
+                    Ok(())
+                } else {
+                    err!("Cannot deserialize {:?} as a {}", i, stringify!(u128));
+                }
+            }
+
+            fn raw_number(&mut self, raw: &str) -> Result<()> {
+                match raw.parse() {
+                    Ok(n) => {
+                        self.out = Some(n);
+                        Ok(())
+                    }
+                    Err(_) => err!("Cannot deserialize {} as a {}", raw, stringify!(u128)),
+                }
+            }
+        }
+        Place::new(out)
+    }
+}
+
 impl Deserialize for u8 {
     fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
         impl Visitor for Place<u8> {
@@ -135,6 +301,7 @@ impl Deserialize for u8 {
     }
 }
 
+#[cfg(feature = "float")]
 macro_rules! float {
     ($ty:ident) => {
         impl Deserialize for $ty {
@@ -155,105 +322,134 @@ macro_rules! float {
         }
     };
 }
+#[cfg(feature = "float")]
 float!(f32);
+#[cfg(feature = "float")]
 float!(f64);
 
-impl<T: Deserialize> Deserialize for Box<T> {
-    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
-        impl<T: Deserialize> Visitor for Place<Box<T>> {
-            fn null(&mut self) -> Result<()> {
-                let mut out = None;
-                Deserialize::begin(&mut out).null()?;
-                self.out = Some(Box::new(out.unwrap()));
-                Ok(())
-            }
+macro_rules! pointer {
+    (@new Wrapping($value:expr)) => {
+        Wrapping($value)
+    };
+    (@new $ptr:ident($value:expr)) => {
+        $ptr::new($value)
+    };
+    (@new $ptr:ident($value:expr), $A:ident) => {
+        $ptr::new_in($value, $A::default())
+    };
+    ($ptr:ident $(, $A:ident)? $({ $($item:item)* })?) => {
+        impl<T: Deserialize $(, $A: Allocator + Default)?> FromRepr for $ptr<T $(, $A)?> {
+            type Repr = T;
 
-            fn boolean(&mut self, b: bool) -> Result<()> {
-                let mut out = None;
-                Deserialize::begin(&mut out).boolean(b)?;
-                self.out = Some(Box::new(out.unwrap()));
-                Ok(())
+            fn from_repr(value: T) -> Result<Self> {
+                Ok(pointer!(@new $ptr(value) $(, $A)?))
             }
 
-            fn string(&mut self, s: &str) -> Result<()> {
-                let mut out = None;
-                Deserialize::begin(&mut out).string(s)?;
-                self.out = Some(Box::new(out.unwrap()));
-                Ok(())
-            }
+            $($($item)*)?
+        }
 
-            fn int(&mut self, i: i128) -> Result<()> {
-                let mut out = None;
-                Deserialize::begin(&mut out).int(i)?;
-                self.out = Some(Box::new(out.unwrap()));
-                Ok(())
+        impl<T: Deserialize $(, $A: Allocator + Default)?> Deserialize for $ptr<T $(, $A)?> {
+            fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+                ReprPlace::new(out)
+            }
+        }
+    };
+}
+#[cfg(not(feature = "allocator_api"))]
+pointer!(Box {
+    // The value is moved to the start of the slot, which is then shrunk to
+    // fit it, usually in place.
+    fn from_heap_slot(slot: Box<Option<T>>) -> Result<Self> {
+        use alloc::alloc::{handle_alloc_error, realloc, Layout};
+        use core::{mem, ptr};
+
+        if slot.is_none() {
+            return Err(crate::Error);
+        }
+        let size = mem::size_of::<T>();
+        if size == 0 {
+            return Self::from_repr((*slot).unwrap());
+        }
+        let layout = Layout::new::<Option<T>>();
+        unsafe {
+            let raw = Box::into_raw(slot);
+            let value = ptr::read(raw).unwrap();
+            // `Option<T>` is at least as large as `T`, with the same alignment.
+            ptr::write(raw as *mut T, value);
+            if layout.size() == size {
+                return Ok(Box::from_raw(raw as *mut T));
+            }
+            let shrunk = realloc(raw as *mut u8, layout, size);
+            if shrunk.is_null() {
+                handle_alloc_error(Layout::new::<T>());
+            }
+            Ok(Box::from_raw(shrunk as *mut T))
+        }
+    }
+});
+#[cfg(feature = "allocator_api")]
+pointer!(Box, A);
+pointer!(Rc);
+pointer!(Arc);
+// Not a pointer, but a newtype deserialized the same way.
+pointer!(Wrapping);
+
+macro_rules! str_pointer {
+    ($($ptr:ident),*) => {$(
+        impl Deserialize for $ptr<str> {
+            fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+                impl Visitor for Place<$ptr<str>> {
+                    fn string(&mut self, s: &str) -> Result<()> {
+                        self.out = Some($ptr::from(s));
+                        Ok(())
+                    }
+                }
+                Place::new(out)
             }
+        }
+    )*};
+}
+str_pointer!(Box, Rc, Arc);
 
-            fn float(&mut self, n: f64) -> Result<()> {
-                let mut out = None;
-                Deserialize::begin(&mut out).float(n)?;
-                self.out = Some(Box::new(out.unwrap()));
+impl<T: Deserialize> Deserialize for Box<[T]> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl<T: Deserialize> Visitor for Place<Box<[T]>> {
+            fn bytes(&mut self, xs: &[u8]) -> Result<()> {
+                let mut vec: Option<Vec<T>> = None;
+                Deserialize::begin(&mut vec).bytes(xs)?;
+                self.out = vec.map(Vec::into_boxed_slice);
                 Ok(())
             }
 
             fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
                 let heap_slot = AliasedBox::from(Box::new(None));
                 let at_slot = unsafe { &mut *heap_slot.ptr() };
-                Ok(Box::new(BoxSeq {
+                Ok(Box::new(BoxedSliceSeq {
                     out: &mut self.out,
                     heap_slot,
                     seq: Deserialize::begin(at_slot).seq()?,
                 }))
             }
-
-            fn map(&mut self) -> Result<Box<dyn Map + '_>> {
-                let heap_slot = AliasedBox::from(Box::new(None));
-                let at_slot = unsafe { &mut *heap_slot.ptr() };
-                Ok(Box::new(BoxMap {
-                    out: &mut self.out,
-                    heap_slot,
-                    map: Deserialize::begin(at_slot).map()?,
-                }))
-            }
         }
 
-        struct BoxSeq<'a, T: 'a> {
-            out: &'a mut Option<Box<T>>,
+        struct BoxedSliceSeq<'a, T: 'a> {
+            out: &'a mut Option<Box<[T]>>,
             // Safety: refers to `heap_slot`, so it must be dropped before it.
             seq: Box<dyn Seq + 'a>,
-            heap_slot: AliasedBox<Option<T>>,
+            heap_slot: AliasedBox<Option<Vec<T>>>,
         }
 
-        impl<'a, T: Deserialize> Seq for BoxSeq<'a, T> {
+        impl<'a, T: Deserialize> Seq for BoxedSliceSeq<'a, T> {
             fn element(&mut self) -> Result<&mut dyn Visitor> {
                 self.seq.element()
             }
 
             fn finish(self: Box<Self>) -> Result<()> {
                 self.seq.finish()?;
-                *self.out = Some(Box::new(self.heap_slot.assume_unique().unwrap()));
-                Ok(())
-            }
-        }
-
-        struct BoxMap<'a, T: 'a> {
-            out: &'a mut Option<Box<T>>,
-            // Safety: refers to `heap_slot`, so it must be dropped before it.
-            map: Box<dyn Map + 'a>,
-            heap_slot: AliasedBox<Option<T>>,
-        }
-
-        impl<'a, T: Deserialize> Map for BoxMap<'a, T> {
-            fn val_with_key(
-                &mut self,
-                de_key: &mut dyn FnMut(Result<&mut dyn Visitor>) -> Result<()>,
-            ) -> Result<&mut dyn Visitor> {
-                self.map.val_with_key(de_key)
-            }
-
-            fn finish(self: Box<Self>) -> Result<()> {
-                self.map.finish()?;
-                *self.out = Some(Box::new(self.heap_slot.assume_unique().unwrap()));
+                *self.out = self
+                    .heap_slot
+                    .assume_unique()
+                    .map(crate::scratch::into_boxed_slice);
                 Ok(())
             }
         }
@@ -262,11 +458,94 @@ impl<T: Deserialize> Deserialize for Box<T> {
     }
 }
 
+make_place!(MergePlace);
+
 impl<T: Deserialize> Deserialize for Option<T> {
     #[inline]
     fn default() -> Option<Self> {
         Some(None)
     }
+
+    // `null` clears the option, and a map merges into the value it holds.
+    fn begin_merge(target: &mut Self) -> Option<&mut dyn Visitor> {
+        impl<T: Deserialize> Visitor for MergePlace<T> {
+            fn null(&mut self) -> Result<()> {
+                self.out = None;
+                Ok(())
+            }
+
+            fn boolean(&mut self, b: bool) -> Result<()> {
+                T::begin(&mut self.out).boolean(b)
+            }
+
+            fn string(&mut self, s: &str) -> Result<()> {
+                T::begin(&mut self.out).string(s)
+            }
+
+            fn bytes(&mut self, xs: &[u8]) -> Result<()> {
+                T::begin(&mut self.out).bytes(xs)
+            }
+
+            fn int(&mut self, i: i128) -> Result<()> {
+                T::begin(&mut self.out).int(i)
+            }
+
+            #[cfg(feature = "float")]
+            fn float(&mut self, n: f64) -> Result<()> {
+                T::begin(&mut self.out).float(n)
+            }
+
+            fn raw_number(&mut self, raw: &str) -> Result<()> {
+                T::begin(&mut self.out).raw_number(raw)
+            }
+
+            fn big_integer(&mut self, raw: &str) -> Result<()> {
+                T::begin(&mut self.out).big_integer(raw)
+            }
+
+            fn wants_raw(&mut self) -> bool {
+                T::begin(&mut None).wants_raw()
+            }
+
+            fn raw_json(&mut self, j: &str) -> Result<()> {
+                match j {
+                    "null" => self.null(),
+                    _ => T::begin(&mut self.out).raw_json(j),
+                }
+            }
+
+            fn raw_cbor(&mut self, bytes: &[u8]) -> Result<()> {
+                match bytes {
+                    // `null` and `undefined`.
+                    [0xf6] | [0xf7] => self.null(),
+                    _ => T::begin(&mut self.out).raw_cbor(bytes),
+                }
+            }
+
+            fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
+                T::begin(&mut self.out).seq()
+            }
+
+            fn map(&mut self) -> Result<Box<dyn Map + '_>> {
+                // Asked twice, so as not to hold on to `self.out` when the
+                // value does not merge.
+                let merges = match &mut self.out {
+                    Some(value) => T::begin_merge(value).is_some(),
+                    None => false,
+                };
+                match (merges, &mut self.out) {
+                    (true, Some(value)) => T::begin_merge(value).unwrap().map(),
+                    (_, out) => T::begin(out).map(),
+                }
+            }
+
+            fn tag(&mut self, tag: u64) -> Result<Option<Box<dyn Seq + '_>>> {
+                T::begin(&mut self.out).tag(tag)
+            }
+        }
+
+        Some(MergePlace::new(target))
+    }
     fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
         impl<T: Deserialize> Visitor for Place<Option<T>> {
             fn null(&mut self) -> Result<()> {
@@ -289,11 +568,43 @@ impl<T: Deserialize> Deserialize for Option<T> {
                 Deserialize::begin(self.out.as_mut().unwrap()).int(i)
             }
 
+            #[cfg(feature = "float")]
             fn float(&mut self, n: f64) -> Result<()> {
                 self.out = Some(None);
                 Deserialize::begin(self.out.as_mut().unwrap()).float(n)
             }
 
+            fn raw_number(&mut self, raw: &str) -> Result<()> {
+                self.out = Some(None);
+                Deserialize::begin(self.out.as_mut().unwrap()).raw_number(raw)
+            }
+
+            fn big_integer(&mut self, raw: &str) -> Result<()> {
+                self.out = Some(None);
+                Deserialize::begin(self.out.as_mut().unwrap()).big_integer(raw)
+            }
+
+            fn wants_raw(&mut self) -> bool {
+                T::begin(&mut None).wants_raw()
+            }
+
+            fn raw_json(&mut self, j: &str) -> Result<()> {
+                self.out = Some(None);
+                match j {
+                    "null" => Ok(()),
+                    _ => Deserialize::begin(self.out.as_mut().unwrap()).raw_json(j),
+                }
+            }
+
+            fn raw_cbor(&mut self, bytes: &[u8]) -> Result<()> {
+                self.out = Some(None);
+                match bytes {
+                    // `null` and `undefined`.
+                    [0xf6] | [0xf7] => Ok(()),
+                    _ => Deserialize::begin(self.out.as_mut().unwrap()).raw_cbor(bytes),
+                }
+            }
+
             fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
                 self.out = Some(None);
                 Deserialize::begin(self.out.as_mut().unwrap()).seq()
@@ -303,6 +614,11 @@ impl<T: Deserialize> Deserialize for Option<T> {
                 self.out = Some(None);
                 Deserialize::begin(self.out.as_mut().unwrap()).map()
             }
+
+            fn tag(&mut self, tag: u64) -> Result<Option<Box<dyn Seq + '_>>> {
+                self.out = Some(None);
+                Deserialize::begin(self.out.as_mut().unwrap()).tag(tag)
+            }
         }
 
         Place::new(out)
@@ -350,63 +666,45 @@ impl<A: Deserialize, B: Deserialize> Deserialize for (A, B) {
     }
 }
 
-struct DefaultImpl;
-impl Visitor for DefaultImpl {}
+// The enums below are externally tagged, and the ranges are structs, as in
+// Serde's data model.
 
-impl<T: Deserialize> Deserialize for Vec<T> {
+impl<T: Deserialize, E: Deserialize> Deserialize for core::result::Result<T, E> {
     fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
-        impl<T: Deserialize> Visitor for Place<Vec<T>> {
-            fn bytes(self: &mut Place<Vec<T>>, xs: &'_ [u8]) -> Result<()> {
-                let mut out: Vec<T> = vec![];
-                let ret_out = T::with_bytes_visitor_vec(&mut out, super::Private, |mb_visitor| {
-                    match mb_visitor {
-                        Some(visit_bytes) => {
-                            visit_bytes(xs);
-                            true
-                        }
-                        None => false,
-                    }
-                });
-                if ret_out {
-                    self.out = Some(out);
-                    Ok(())
-                } else {
-                    DefaultImpl.bytes(xs)
-                }
-            }
-
-            fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
-                Ok(Box::new(VecBuilder {
+        impl<T: Deserialize, E: Deserialize> Visitor for Place<core::result::Result<T, E>> {
+            fn map(&mut self) -> Result<Box<dyn Map + '_>> {
+                Ok(Box::new(ResultBuilder {
                     out: &mut self.out,
-                    vec: Vec::new(),
-                    element: None,
+                    ok: None,
+                    err: None,
                 }))
             }
         }
 
-        struct VecBuilder<'a, T: 'a> {
-            out: &'a mut Option<Vec<T>>,
-            vec: Vec<T>,
-            element: Option<T>,
+        struct ResultBuilder<'a, T: 'a, E: 'a> {
+            out: &'a mut Option<core::result::Result<T, E>>,
+            ok: Option<T>,
+            err: Option<E>,
         }
 
-        impl<'a, T> VecBuilder<'a, T> {
-            fn shift(&mut self) {
-                if let Some(e) = self.element.take() {
-                    self.vec.push(e);
+        impl<'a, T: Deserialize, E: Deserialize> StrKeyMap for ResultBuilder<'a, T, E> {
+            fn key(&mut self, k: &str) -> Result<&mut dyn Visitor> {
+                if self.ok.is_some() || self.err.is_some() {
+                    err!("Expected a single key for a `Result`, got {:?}", k);
+                }
+                match k {
+                    "Ok" => Ok(Deserialize::begin(&mut self.ok)),
+                    "Err" => Ok(Deserialize::begin(&mut self.err)),
+                    _ => err!("Expected `Ok` or `Err`, got {:?}", k),
                 }
-            }
-        }
-
-        impl<'a, T: Deserialize> Seq for VecBuilder<'a, T> {
-            fn element(&mut self) -> Result<&mut dyn Visitor> {
-                self.shift();
-                Ok(Deserialize::begin(&mut self.element))
             }
 
-            fn finish(mut self: Box<Self>) -> Result<()> {
-                self.shift();
-                *self.out = Some(self.vec);
+            fn finish(self: Box<Self>) -> Result<()> {
+                *self.out = Some(match (self.ok, self.err) {
+                    (Some(ok), None) => Ok(ok),
+                    (None, Some(err)) => Err(err),
+                    _ => err!("Missing the `Ok` or `Err` key of a `Result`"),
+                });
                 Ok(())
             }
         }
@@ -415,88 +713,639 @@ impl<T: Deserialize> Deserialize for Vec<T> {
     }
 }
 
-crate::with_Ns! {( $($N:expr),* $(,)? ) => (
-  $(
-    impl<T : Deserialize> Deserialize for [T; $N] {
-        fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
-            impl<T: Deserialize> Visitor for Place<[T; $N]> {
-                fn bytes(self: &mut Place<[T; $N]>, xs: &'_ [u8]) -> Result<()> {
-                    let mut out: [::core::mem::MaybeUninit<T>; $N] =
-                        ::uninit::uninit_array![_; $N]
-                    ;
-                    let ret_out = T::with_bytes_visitor_slice(
-                        &mut out,
-                        super::Private,
-                        |mb_visitor| match mb_visitor {
-                            Some(visit_bytes) => visit_bytes(xs).map(|()| true),
-                            None => Ok(false),
-                        },
-                    )?;
-                    if ret_out {
-                        self.out = Some(unsafe {
-                            // # Safety
-                            //
-                            //   - The only way the `with_bytes…` call yields
-                            //     `Ok(())` is through a local impl,
-                            //     since the `Private` parameter makes it
-                            //     impossible for a downstream user to override.
-                            //
-                            //   - The only local override of that method is on
-                            //     `T = u8`, which does initialize the slice
-                            //     when yielding `Ok(())` (and `u8` is `Copy`).
-                            ::core::mem::transmute_copy(&out)
-                        });
-                        Ok(())
-                    } else {
-                        DefaultImpl.bytes(xs)
-                    }
-                }
-
-                fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
-                    Ok(Box::new(ArrayBuilder {
-                        out: &mut self.out,
-                        vec: Vec::new(), // FIXME: do not use an allocation
-                        element: None,
-                    }))
+impl<T: Deserialize> Deserialize for Bound<T> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl<T: Deserialize> Visitor for Place<Bound<T>> {
+            fn string(&mut self, s: &str) -> Result<()> {
+                match s {
+                    "Unbounded" => self.out = Some(Bound::Unbounded),
+                    _ => err!("Expected `Unbounded`, got {:?}", s),
                 }
+                Ok(())
             }
 
-            struct ArrayBuilder<'a, T: 'a> {
-                out: &'a mut Option<[T; $N]>,
-                vec: Vec<T>,
-                element: Option<T>,
+            fn map(&mut self) -> Result<Box<dyn Map + '_>> {
+                Ok(Box::new(BoundBuilder {
+                    out: &mut self.out,
+                    variant: None,
+                    bound: None,
+                }))
             }
+        }
 
-            impl<'a, T> ArrayBuilder<'a, T> {
-                fn shift(&mut self) {
-                    if let Some(e) = self.element.take() {
-                        self.vec.push(e);
-                    }
-                }
-            }
+        struct BoundBuilder<'a, T: 'a> {
+            out: &'a mut Option<Bound<T>>,
+            variant: Option<fn(T) -> Bound<T>>,
+            bound: Option<T>,
+        }
 
-            impl<'a, T: Deserialize> Seq for ArrayBuilder<'a, T> {
-                fn element(&mut self) -> Result<&mut dyn Visitor> {
-                    self.shift();
-                    Ok(Deserialize::begin(&mut self.element))
+        impl<'a, T: Deserialize> StrKeyMap for BoundBuilder<'a, T> {
+            fn key(&mut self, k: &str) -> Result<&mut dyn Visitor> {
+                if self.variant.is_some() {
+                    err!("Expected a single key for a `Bound`, got {:?}", k);
                 }
+                self.variant = Some(match k {
+                    "Included" => Bound::Included,
+                    "Excluded" => Bound::Excluded,
+                    _ => err!("Expected `Included` or `Excluded`, got {:?}", k),
+                });
+                Ok(Deserialize::begin(&mut self.bound))
+            }
 
-                fn finish(mut self: Box<Self>) -> Result<()> {
-                    self.shift();
-                    *self.out = Some(
-                        ::array_init::from_iter(self.vec.into_iter())
-                            .ok_or(crate::Error)?
-                    );
-                    Ok(())
+            fn finish(self: Box<Self>) -> Result<()> {
+                match (self.variant, self.bound) {
+                    (Some(variant), Some(bound)) => *self.out = Some(variant(bound)),
+                    _ => err!("Missing the `Included` or `Excluded` key of a `Bound`"),
                 }
+                Ok(())
             }
+        }
+
+        Place::new(out)
+    }
+}
+
+/// The `start` and `end` fields of a range, which `make` builds it from.
+struct RangeBuilder<'a, T: 'a, R: 'a> {
+    out: &'a mut Option<R>,
+    start: Option<T>,
+    end: Option<T>,
+    make: fn(T, T) -> R,
+}
+
+impl<'a, T: Deserialize, R> StrKeyMap for RangeBuilder<'a, T, R> {
+    fn key(&mut self, k: &str) -> Result<&mut dyn Visitor> {
+        match k {
+            "start" => Ok(Deserialize::begin(&mut self.start)),
+            "end" => Ok(Deserialize::begin(&mut self.end)),
+            // As for derived structs.
+            _ => Ok(<dyn Visitor>::ignore()),
+        }
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) => *self.out = Some((self.make)(start, end)),
+            _ => err!("Missing the `start` or `end` of a range"),
+        }
+        Ok(())
+    }
+}
+
+impl<T: Deserialize> Deserialize for Range<T> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl<T: Deserialize> Visitor for Place<Range<T>> {
+            fn map(&mut self) -> Result<Box<dyn Map + '_>> {
+                Ok(Box::new(RangeBuilder {
+                    out: &mut self.out,
+                    start: None,
+                    end: None,
+                    make: |start, end| start..end,
+                }))
+            }
+        }
 
-            Place::new(out)
+        Place::new(out)
+    }
+}
+
+impl<T: Deserialize> Deserialize for RangeInclusive<T> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl<T: Deserialize> Visitor for Place<RangeInclusive<T>> {
+            fn map(&mut self) -> Result<Box<dyn Map + '_>> {
+                Ok(Box::new(RangeBuilder {
+                    out: &mut self.out,
+                    start: None,
+                    end: None,
+                    make: RangeInclusive::new,
+                }))
+            }
         }
+
+        Place::new(out)
     }
-  )*
-)}
+}
+
+struct DefaultImpl;
+impl Visitor for DefaultImpl {}
+
+macro_rules! vector {
+    (@new) => {
+        Vec::new()
+    };
+    (@new, $A:ident) => {
+        Vec::new_in($A::default())
+    };
+    (@from_global $vec:ident) => {
+        $vec
+    };
+    (@from_global $vec:ident, $A:ident) => {{
+        let mut vec = Vec::with_capacity_in($vec.len(), $A::default());
+        vec.extend($vec.drain(..));
+        #[cfg(feature = "zeroize")]
+        unsafe {
+            crate::scratch::wipe_moved($vec.as_mut_ptr(), $vec.capacity())
+        };
+        vec
+    }};
+    (@push $vec:expr, $value:expr) => {
+        crate::scratch::push(&mut $vec, $value)
+    };
+    (@push $vec:expr, $value:expr, $A:ident) => {{
+        // As `scratch::push` does, in `A`.
+        #[cfg(feature = "zeroize")]
+        {
+            if $vec.len() == $vec.capacity() {
+                let capacity = ::core::cmp::max(4, 2 * $vec.capacity());
+                let mut grown = Vec::with_capacity_in(capacity, $A::default());
+                grown.append(&mut $vec);
+                unsafe { crate::scratch::wipe_moved($vec.as_mut_ptr(), $vec.capacity()) };
+                $vec = grown;
+            }
+        }
+        $vec.push($value)
+    }};
+    ($($A:ident)?) => {
+        impl<T: Deserialize $(, $A: Allocator + Default)?> Deserialize for Vec<T $(, $A)?> {
+            fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+                impl<T: Deserialize $(, $A: Allocator + Default)?> Visitor for Place<Vec<T $(, $A)?>> {
+                    fn bytes(&mut self, xs: &'_ [u8]) -> Result<()> {
+                        let mut out: Vec<T> = vec![];
+                        let ret_out = T::with_bytes_visitor_vec(&mut out, super::Private, |mb_visitor| {
+                            match mb_visitor {
+                                Some(visit_bytes) => {
+                                    visit_bytes(xs);
+                                    true
+                                }
+                                None => false,
+                            }
+                        });
+                        if ret_out {
+                            self.out = Some(vector!(@from_global out $(, $A)?));
+                            Ok(())
+                        } else {
+                            DefaultImpl.bytes(xs)
+                        }
+                    }
+
+                    fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
+                        Ok(Box::new(VecBuilder {
+                            out: &mut self.out,
+                            vec: vector!(@new $(, $A)?),
+                            element: None,
+                        }))
+                    }
+                }
+
+                struct VecBuilder<'a, T: 'a $(, $A: Allocator)?> {
+                    out: &'a mut Option<Vec<T $(, $A)?>>,
+                    vec: Vec<T $(, $A)?>,
+                    element: Option<T>,
+                }
+
+                impl<'a, T $(, $A: Allocator + Default)?> VecBuilder<'a, T $(, $A)?> {
+                    fn shift(&mut self) {
+                        if let Some(e) = self.element.take() {
+                            vector!(@push self.vec, e $(, $A)?);
+                        }
+                    }
+                }
+
+                impl<'a, T: Deserialize $(, $A: Allocator + Default)?> Seq
+                    for VecBuilder<'a, T $(, $A)?>
+                {
+                    fn element(&mut self) -> Result<&mut dyn Visitor> {
+                        self.shift();
+                        Ok(Deserialize::begin(&mut self.element))
+                    }
+
+                    fn finish(mut self: Box<Self>) -> Result<()> {
+                        self.shift();
+                        *self.out = Some(self.vec);
+                        Ok(())
+                    }
+                }
+
+                Place::new(out)
+            }
+        }
+    };
+}
+#[cfg(not(feature = "allocator_api"))]
+vector!();
+#[cfg(feature = "allocator_api")]
+vector!(A);
+
+impl<T: Deserialize, const N: usize> Deserialize for [T; N] {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl<T: Deserialize, const N: usize> Visitor for Place<[T; N]> {
+            fn bytes(self: &mut Place<[T; N]>, xs: &'_ [u8]) -> Result<()> {
+                let mut out: [::core::mem::MaybeUninit<T>; N] = unsafe {
+                    // # Safety
+                    //
+                    //   - An array of `MaybeUninit`s needs no initialization.
+                    ::core::mem::MaybeUninit::uninit().assume_init()
+                };
+                let ret_out =
+                    T::with_bytes_visitor_slice(&mut out, super::Private, |mb_visitor| {
+                        match mb_visitor {
+                            Some(visit_bytes) => visit_bytes(xs).map(|()| true),
+                            None => Ok(false),
+                        }
+                    })?;
+                if ret_out {
+                    self.out = Some(unsafe {
+                        // # Safety
+                        //
+                        //   - The only way the `with_bytes…` call yields
+                        //     `Ok(())` is through a local impl,
+                        //     since the `Private` parameter makes it
+                        //     impossible for a downstream user to override.
+                        //
+                        //   - The only local override of that method is on
+                        //     `T = u8`, which does initialize the slice
+                        //     when yielding `Ok(())` (and `u8` is `Copy`).
+                        ::core::mem::transmute_copy(&out)
+                    });
+                    Ok(())
+                } else {
+                    DefaultImpl.bytes(xs)
+                }
+            }
+
+            fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
+                Ok(Box::new(ArrayBuilder {
+                    out: &mut self.out,
+                    vec: Vec::with_capacity(N), // FIXME: do not use an allocation
+                    element: None,
+                }))
+            }
+        }
+
+        struct ArrayBuilder<'a, T: 'a, const N: usize> {
+            out: &'a mut Option<[T; N]>,
+            vec: Vec<T>,
+            element: Option<T>,
+        }
+
+        impl<'a, T, const N: usize> ArrayBuilder<'a, T, N> {
+            fn shift(&mut self) {
+                if let Some(e) = self.element.take() {
+                    crate::scratch::push(&mut self.vec, e);
+                }
+            }
+        }
+
+        impl<'a, T: Deserialize, const N: usize> Seq for ArrayBuilder<'a, T, N> {
+            fn element(&mut self) -> Result<&mut dyn Visitor> {
+                self.shift();
+                Ok(Deserialize::begin(&mut self.element))
+            }
+
+            fn finish(mut self: Box<Self>) -> Result<()> {
+                self.shift();
+                let vec = ::core::mem::take(&mut self.vec);
+                *self.out = Some(crate::scratch::into_array(vec).ok_or(crate::Error)?);
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
+}
+
+impl<T: Deserialize> Deserialize for VecDeque<T> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl<T: Deserialize> Visitor for Place<VecDeque<T>> {
+            fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
+                Ok(Box::new(VecDequeBuilder {
+                    out: &mut self.out,
+                    deque: VecDeque::new(),
+                    element: None,
+                }))
+            }
+        }
+
+        struct VecDequeBuilder<'a, T: 'a> {
+            out: &'a mut Option<VecDeque<T>>,
+            deque: VecDeque<T>,
+            element: Option<T>,
+        }
+
+        impl<'a, T> VecDequeBuilder<'a, T> {
+            fn shift(&mut self) {
+                if let Some(e) = self.element.take() {
+                    self.deque.push_back(e);
+                }
+            }
+        }
+
+        impl<'a, T: Deserialize> Seq for VecDequeBuilder<'a, T> {
+            fn element(&mut self) -> Result<&mut dyn Visitor> {
+                self.shift();
+                Ok(Deserialize::begin(&mut self.element))
+            }
+
+            fn finish(mut self: Box<Self>) -> Result<()> {
+                self.shift();
+                *self.out = Some(self.deque);
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
+}
+
+#[allow(clippy::linkedlist)]
+impl<T: Deserialize> Deserialize for LinkedList<T> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl<T: Deserialize> Visitor for Place<LinkedList<T>> {
+            fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
+                Ok(Box::new(LinkedListBuilder {
+                    out: &mut self.out,
+                    list: LinkedList::new(),
+                    element: None,
+                }))
+            }
+        }
+
+        struct LinkedListBuilder<'a, T: 'a> {
+            out: &'a mut Option<LinkedList<T>>,
+            list: LinkedList<T>,
+            element: Option<T>,
+        }
+
+        impl<'a, T> LinkedListBuilder<'a, T> {
+            fn shift(&mut self) {
+                if let Some(e) = self.element.take() {
+                    self.list.push_back(e);
+                }
+            }
+        }
+
+        impl<'a, T: Deserialize> Seq for LinkedListBuilder<'a, T> {
+            fn element(&mut self) -> Result<&mut dyn Visitor> {
+                self.shift();
+                Ok(Deserialize::begin(&mut self.element))
+            }
+
+            fn finish(mut self: Box<Self>) -> Result<()> {
+                self.shift();
+                *self.out = Some(self.list);
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
+}
+
+impl<T: Deserialize + Ord> Deserialize for BinaryHeap<T> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl<T: Deserialize + Ord> Visitor for Place<BinaryHeap<T>> {
+            fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
+                Ok(Box::new(BinaryHeapBuilder {
+                    out: &mut self.out,
+                    heap: BinaryHeap::new(),
+                    element: None,
+                }))
+            }
+        }
+
+        struct BinaryHeapBuilder<'a, T: 'a> {
+            out: &'a mut Option<BinaryHeap<T>>,
+            heap: BinaryHeap<T>,
+            element: Option<T>,
+        }
+
+        impl<'a, T: Ord> BinaryHeapBuilder<'a, T> {
+            fn shift(&mut self) {
+                if let Some(e) = self.element.take() {
+                    self.heap.push(e);
+                }
+            }
+        }
+
+        impl<'a, T: Deserialize + Ord> Seq for BinaryHeapBuilder<'a, T> {
+            fn element(&mut self) -> Result<&mut dyn Visitor> {
+                self.shift();
+                Ok(Deserialize::begin(&mut self.element))
+            }
+
+            fn finish(mut self: Box<Self>) -> Result<()> {
+                self.shift();
+                *self.out = Some(self.heap);
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, H> Deserialize for HashSet<T, H>
+where
+    T: Deserialize + Hash + Eq,
+    H: BuildHasher + Default,
+{
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl<T, H> Visitor for Place<HashSet<T, H>>
+        where
+            T: Deserialize + Hash + Eq,
+            H: BuildHasher + Default,
+        {
+            fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
+                Ok(Box::new(SetBuilder {
+                    out: &mut self.out,
+                    set: HashSet::with_hasher(H::default()),
+                    element: None,
+                }))
+            }
+        }
+
+        struct SetBuilder<'a, T: 'a, H: 'a> {
+            out: &'a mut Option<HashSet<T, H>>,
+            set: HashSet<T, H>,
+            element: Option<T>,
+        }
+
+        impl<'a, T: Hash + Eq, H: BuildHasher> SetBuilder<'a, T, H> {
+            fn shift(&mut self) {
+                if let Some(e) = self.element.take() {
+                    self.set.insert(e);
+                }
+            }
+        }
+
+        impl<'a, T, H> Seq for SetBuilder<'a, T, H>
+        where
+            T: Deserialize + Hash + Eq,
+            H: BuildHasher + Default,
+        {
+            fn element(&mut self) -> Result<&mut dyn Visitor> {
+                self.shift();
+                Ok(Deserialize::begin(&mut self.element))
+            }
+
+            fn finish(mut self: Box<Self>) -> Result<()> {
+                self.shift();
+                *self.out = Some(self.set);
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
+}
+
+impl<T: Deserialize + Ord> Deserialize for BTreeSet<T> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl<T: Deserialize + Ord> Visitor for Place<BTreeSet<T>> {
+            fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
+                Ok(Box::new(SetBuilder {
+                    out: &mut self.out,
+                    set: BTreeSet::new(),
+                    element: None,
+                }))
+            }
+        }
+
+        struct SetBuilder<'a, T: 'a> {
+            out: &'a mut Option<BTreeSet<T>>,
+            set: BTreeSet<T>,
+            element: Option<T>,
+        }
+
+        impl<'a, T: Ord> SetBuilder<'a, T> {
+            fn shift(&mut self) {
+                if let Some(e) = self.element.take() {
+                    self.set.insert(e);
+                }
+            }
+        }
+
+        impl<'a, T: Deserialize + Ord> Seq for SetBuilder<'a, T> {
+            fn element(&mut self) -> Result<&mut dyn Visitor> {
+                self.shift();
+                Ok(Deserialize::begin(&mut self.element))
+            }
+
+            fn finish(mut self: Box<Self>) -> Result<()> {
+                self.shift();
+                *self.out = Some(self.set);
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
+}
+
+/// The visitor of a map key, which also accepts the keys that JSON objects
+/// hold as strings, such as `"1"` for an integer or `"true"` for a boolean.
+struct MapKey<'a>(&'a mut dyn Visitor);
+
+impl<'a> Visitor for MapKey<'a> {
+    fn null(&mut self) -> Result<()> {
+        self.0.null()
+    }
+
+    fn undefined(&mut self) -> Result<()> {
+        self.0.undefined()
+    }
+
+    fn boolean(&mut self, b: bool) -> Result<()> {
+        self.0.boolean(b)
+    }
+
+    fn string(&mut self, s: &str) -> Result<()> {
+        self.0.string(s).or_else(|_| self.stringified(s))
+    }
+
+    fn borrowed_string(&mut self, s: &str) -> Result<()> {
+        self.0.borrowed_string(s).or_else(|_| self.stringified(s))
+    }
+
+    fn bytes(&mut self, xs: &[u8]) -> Result<()> {
+        self.0.bytes(xs)
+    }
+
+    fn borrowed_bytes(&mut self, xs: &[u8]) -> Result<()> {
+        self.0.borrowed_bytes(xs)
+    }
+
+    fn int(&mut self, i: i128) -> Result<()> {
+        self.0.int(i)
+    }
+
+    #[cfg(feature = "float")]
+    fn float(&mut self, n: f64) -> Result<()> {
+        self.0.float(n)
+    }
+
+    fn raw_number(&mut self, raw: &str) -> Result<()> {
+        self.0.raw_number(raw)
+    }
+
+    fn big_integer(&mut self, raw: &str) -> Result<()> {
+        self.0.big_integer(raw)
+    }
+
+    fn wants_raw(&mut self) -> bool {
+        self.0.wants_raw()
+    }
+
+    fn raw_json(&mut self, j: &str) -> Result<()> {
+        self.0.raw_json(j)
+    }
+
+    fn raw_cbor(&mut self, bytes: &[u8]) -> Result<()> {
+        self.0.raw_cbor(bytes)
+    }
+
+    fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
+        self.0.seq()
+    }
+
+    fn map(&mut self) -> Result<Box<dyn Map + '_>> {
+        self.0.map()
+    }
+
+    fn map_with(&mut self, with_map: &mut dyn FnMut(&mut dyn Map) -> Result<()>) -> Result<()> {
+        self.0.map_with(with_map)
+    }
+
+    fn tag(&mut self, tag: u64) -> Result<Option<Box<dyn Seq + '_>>> {
+        self.0.tag(tag)
+    }
+}
+
+impl MapKey<'_> {
+    /// A key that the visitor rejected as a string, parsed as the boolean or
+    /// integer it may stand for.
+    fn stringified(&mut self, s: &str) -> Result<()> {
+        match s {
+            "true" => self.0.boolean(true),
+            "false" => self.0.boolean(false),
+            _ if is_integer_key(s) => self.0.raw_number(s),
+            _ => err!("Expected a map key, got {:?}", s),
+        }
+    }
+}
+
+/// Whether `s` is an integer as JSON writes it, such as `-1` but neither `+1`
+/// nor `01`.
+fn is_integer_key(s: &str) -> bool {
+    match s.strip_prefix('-').unwrap_or(s).as_bytes() {
+        [b'0'] => true,
+        [b'1'..=b'9', rest @ ..] => rest.iter().all(u8::is_ascii_digit),
+        _ => false,
+    }
+}
 
+#[cfg(feature = "std")]
 impl<K, V, H> Deserialize for HashMap<K, V, H>
 where
     K: Deserialize + Hash + Eq,
@@ -546,7 +1395,7 @@ where
                 de_key: &mut dyn FnMut(Result<&mut dyn Visitor>) -> Result<()>,
             ) -> Result<&mut dyn Visitor> {
                 self.shift();
-                de_key(Ok(Deserialize::begin(&mut self.key)))?;
+                de_key(Ok(&mut MapKey(Deserialize::begin(&mut self.key))))?;
                 Ok(Deserialize::begin(&mut self.value))
             }
 
@@ -595,7 +1444,7 @@ impl<K: Deserialize + Ord, V: Deserialize> Deserialize for BTreeMap<K, V> {
                 de_key: &mut dyn FnMut(Result<&mut dyn Visitor>) -> Result<()>,
             ) -> Result<&mut dyn Visitor> {
                 self.shift();
-                de_key(Ok(Deserialize::begin(&mut self.key)))?;
+                de_key(Ok(&mut MapKey(Deserialize::begin(&mut self.key))))?;
                 Ok(Deserialize::begin(&mut self.value))
             }
 
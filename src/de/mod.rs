@@ -18,6 +18,9 @@
 //! Upon successful deserialization the output object is written as `Some(T)`
 //! into the `out` field of the place.
 //!
+//! Crates that would rather not define a place type at all can use the
+//! generic [`Slot`] instead, by implementing [`VisitSlot`] for their types.
+//!
 //! ## Deserializing a primitive
 //!
 //! The Visitor trait has a method corresponding to each supported primitive
@@ -182,13 +185,53 @@
 //! }
 //! ```
 
+pub use borrowed::DeserializeBorrowed;
+mod borrowed;
+
+pub(in crate) mod convert;
+
 pub use ignored_any::IgnoredAny;
 mod ignored_any;
 
+pub use slot::{Slot, VisitSlot};
+mod slot;
+
+pub(in crate) use deprecated::deprecated_field;
+pub use deprecated::set_deprecated_field_hook;
+mod deprecated;
+
 mod impls;
 
+use crate::prelude::*;
+use crate::ser::ValueView;
 use crate::Result;
 
+/// How many values the drivers decode between two calls to a `should_cancel`
+/// hook.
+#[cfg(any(feature = "json", feature = "cbor"))]
+pub(in crate) const CANCEL_INTERVAL: u32 = 1024;
+
+/// What deserializers do with a map holding the same key more than once.
+///
+/// CBOR keys are compared by their encoding in the input, so `1` and a longer
+/// encoding of the same integer are told apart, and JSON keys once unescaped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Hand every entry to the `Map`, so that the last one overwrites the
+    /// previous ones in all the impls of this crate. This is the default.
+    LastWins,
+    /// Skip the entries whose key was already seen.
+    FirstWins,
+    /// Fail deserialization.
+    Error,
+}
+
+impl Default for DuplicateKeys {
+    fn default() -> Self {
+        DuplicateKeys::LastWins
+    }
+}
+
 use private::Private;
 mod private {
     pub struct Private;
@@ -216,14 +259,22 @@ pub trait Deserialize: Sized {
     /// ```
     fn begin(out: &mut Option<Self>) -> &mut dyn Visitor;
 
-    // Not public API. This method is only intended for Option<T>, should not
-    // need to be implemented outside of this crate.
+    // Not public API. This method is only intended for Option<T> and
+    // PhantomData<T>, should not need to be implemented outside of this crate.
     #[doc(hidden)]
     #[inline]
     fn default() -> Option<Self> {
         None
     }
 
+    // Not public API. The visitor merging a map into `target` in place, for
+    // `json::merge_from_str` and `cbor::merge_from_slice`: derived structs
+    // and `Option`s of them merge, anything else is replaced whole.
+    #[doc(hidden)]
+    fn begin_merge(_: &mut Self) -> Option<&mut dyn Visitor> {
+        None
+    }
+
     // Specialization hacks to enable optimized deserialization into `u8` slices.
     // Not public API either, which is enforced thanks to the `Private`
     // parameter.
@@ -257,6 +308,13 @@ pub trait Visitor {
             .or_else(|_| err!("Failed to deserialize a `null` as an empty map at that position."))
     }
 
+    // Not public API. The CBOR `undefined` simple value, which only
+    // `cbor::Value` tells apart from `null`.
+    #[doc(hidden)]
+    fn undefined(&mut self) -> Result<()> {
+        self.null()
+    }
+
     fn boolean(&mut self, b: bool) -> Result<()> {
         err!(
             "Cannot deserialize a `boolean` (got {:?}) at that position.",
@@ -271,6 +329,17 @@ pub trait Visitor {
         );
     }
 
+    /// A string borrowed from the input.
+    ///
+    /// Only the borrowing entry points, such as [`json::from_str_borrowed`],
+    /// call this, for the strings that appear as is in their input; by
+    /// default it is forwarded to [`Visitor::string`].
+    ///
+    /// [`json::from_str_borrowed`]: crate::json::from_str_borrowed
+    fn borrowed_string(&mut self, s: &str) -> Result<()> {
+        self.string(s)
+    }
+
     fn bytes(&mut self, xs: &[u8]) -> Result<()> {
         self.seq()
             .and_then(|mut seq| {
@@ -287,10 +356,17 @@ pub trait Visitor {
             })
     }
 
+    /// Byte string counterpart of [`Visitor::borrowed_string`]; forwarded to
+    /// [`Visitor::bytes`] by default.
+    fn borrowed_bytes(&mut self, xs: &[u8]) -> Result<()> {
+        self.bytes(xs)
+    }
+
     fn int(&mut self, i: i128) -> Result<()> {
         err!("Cannot deserialize a `int` (got {:?}) at that position.", i);
     }
 
+    #[cfg(feature = "float")]
     fn float(&mut self, f: f64) -> Result<()> {
         err!(
             "Cannot deserialize a `float` (got {:?}) at that position.",
@@ -298,6 +374,59 @@ pub trait Visitor {
         );
     }
 
+    /// A JSON number as it was written in the input.
+    ///
+    /// Only [`json::from_str_raw_numbers`] calls this; by default the number
+    /// is parsed and forwarded to [`Visitor::int`] or [`Visitor::float`].
+    ///
+    /// [`json::from_str_raw_numbers`]: crate::json::from_str_raw_numbers
+    fn raw_number(&mut self, raw: &str) -> Result<()> {
+        match ValueView::parse_raw_number_lossy(raw)? {
+            ValueView::Int(i) => self.int(i),
+            #[cfg(feature = "float")]
+            ValueView::F64(f) => self.float(f),
+            _ => unreachable!(),
+        }
+    }
+
+    // Not public API. A JSON integer beyond `i128` but within `u128`, as
+    // written in the input: exact for `u128`, and a float for `json::Value`.
+    #[doc(hidden)]
+    fn big_integer(&mut self, raw: &str) -> Result<()> {
+        self.raw_number(raw)
+    }
+
+    /// Whether the value at that position should be handed over as it
+    /// appears in the input, to [`Visitor::raw_json`] or
+    /// [`Visitor::raw_cbor`], rather than parsed; `false` by default.
+    ///
+    /// This is how [`json::RawValue`] and [`cbor::RawValue`] capture the
+    /// payloads they pass along untouched.
+    ///
+    /// [`json::RawValue`]: crate::json::RawValue
+    /// [`cbor::RawValue`]: crate::cbor::RawValue
+    fn wants_raw(&mut self) -> bool {
+        false
+    }
+
+    /// The text of a whole JSON value, exactly as in the input. Only called
+    /// if [`Visitor::wants_raw`].
+    fn raw_json(&mut self, j: &str) -> Result<()> {
+        err!(
+            "Cannot deserialize raw JSON (got {:?}) at that position.",
+            j
+        );
+    }
+
+    /// A whole CBOR data item, exactly as in the input. Only called if
+    /// [`Visitor::wants_raw`].
+    fn raw_cbor(&mut self, bytes: &[u8]) -> Result<()> {
+        err!(
+            "Cannot deserialize raw CBOR (got {:#x?}) at that position.",
+            bytes
+        );
+    }
+
     fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
         err!("Cannot deserialize a `seq` at that position.");
     }
@@ -305,6 +434,27 @@ pub trait Visitor {
     fn map(&mut self) -> Result<Box<dyn Map + '_>> {
         err!("Cannot deserialize a `map` at that position.");
     }
+
+    /// Deserializes a map by handing it to `with_map`, then finishing it.
+    ///
+    /// Recursive decoders, such as the CBOR one, call this rather than
+    /// [`Visitor::map`], so that an implementor may override it and keep its
+    /// `Map` on the stack; by default the `Map` is the one boxed by
+    /// [`Visitor::map`]. The derived `Deserialize` of structs overrides it.
+    fn map_with(&mut self, with_map: &mut dyn FnMut(&mut dyn Map) -> Result<()>) -> Result<()> {
+        let mut map = self.map()?;
+        with_map(&mut *map)?;
+        map.finish()
+    }
+
+    /// A CBOR tag (major type 6), such as `1` for epoch timestamps.
+    ///
+    /// Returning `None`, as done by default, ignores the tag: the tagged value
+    /// is then written to `self`. Otherwise the tagged value is written to the
+    /// single element of the returned `Seq`.
+    fn tag(&mut self, tag: u64) -> Result<Option<Box<dyn Seq + '_>>> {
+        Ok(None)
+    }
 }
 
 /// Trait that can hand out places to write sequence elements.
@@ -335,6 +485,42 @@ pub trait Map {
     fn finish(self: Box<Self>) -> Result<()>;
 }
 
+/// The key of a [`StrKeyMap`] entry. Keys as short as most field names are
+/// stored inline, so that looking them up does not allocate.
+enum KeyBuf {
+    Missing,
+    Inline(usize, [u8; KeyBuf::INLINE_LEN]),
+    Spilled(String),
+}
+
+impl KeyBuf {
+    const INLINE_LEN: usize = 48;
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            KeyBuf::Missing => None,
+            // The bytes were copied from a `str`.
+            KeyBuf::Inline(len, buf) => {
+                Some(unsafe { ::core::str::from_utf8_unchecked(&buf[..*len]) })
+            }
+            KeyBuf::Spilled(s) => Some(s),
+        }
+    }
+}
+
+impl Visitor for KeyBuf {
+    fn string(&mut self, s: &str) -> Result<()> {
+        *self = if s.len() <= KeyBuf::INLINE_LEN {
+            let mut buf = [0; KeyBuf::INLINE_LEN];
+            buf[..s.len()].copy_from_slice(s.as_bytes());
+            KeyBuf::Inline(s.len(), buf)
+        } else {
+            KeyBuf::Spilled(s.to_owned())
+        };
+        Ok(())
+    }
+}
+
 /// Convenience trait to automagically implement the more complex [`Map`] trait
 /// in the case where only stringly-typed keys are to be deserialized (_e.g._,
 /// when dealing with `struct`s).
@@ -349,9 +535,9 @@ impl<T: StrKeyMap> Map for T {
         &mut self,
         de_key: &mut dyn FnMut(Result<&mut dyn Visitor>) -> Result<()>,
     ) -> Result<&mut dyn Visitor> {
-        let mut s = None::<String>;
-        de_key(Ok(Deserialize::begin(&mut s)))?;
-        match s.as_deref() {
+        let mut k = KeyBuf::Missing;
+        de_key(Ok(&mut k))?;
+        match k.as_str() {
             Some(k) => self.key(k),
             None => err!("Encountered a non-string key when deserializing"),
         }
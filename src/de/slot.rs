@@ -0,0 +1,166 @@
+use super::{Map, Seq, Visitor};
+use crate::prelude::*;
+use crate::ser::ValueView;
+use crate::Result;
+
+/// A crate-provided "place", usable without invoking [`make_place!`].
+///
+/// A `make_place!`-generated type is local to the crate that invokes the
+/// macro, so a downstream crate reusing it cannot write
+/// `impl Visitor for upstream::Place<MyType>`: the orphan rule forbids it.
+///
+/// `Slot` performs the pointer plumbing once, here, and forwards every
+/// [`Visitor`] method to the [`VisitSlot`] trait. Since that trait is
+/// implemented on the deserialized type itself, any crate may implement it
+/// for its own types.
+///
+/// ```rust
+/// use miniserde_ditto::Result;
+/// use miniserde_ditto::de::{Deserialize, Slot, VisitSlot, Visitor};
+///
+/// struct MyBoolean(bool);
+///
+/// impl VisitSlot for MyBoolean {
+///     fn boolean(out: &mut Option<Self>, b: bool) -> Result<()> {
+///         *out = Some(MyBoolean(b));
+///         Ok(())
+///     }
+/// }
+///
+/// impl Deserialize for MyBoolean {
+///     fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+///         Slot::new(out)
+///     }
+/// }
+/// ```
+///
+/// [`make_place!`]: crate::make_place
+#[repr(transparent)]
+pub struct Slot<T> {
+    pub out: Option<T>,
+}
+
+impl<T> Slot<T> {
+    pub fn new(out: &mut Option<T>) -> &mut Slot<T> {
+        unsafe { &mut *(out as *mut Option<T> as *mut Slot<T>) }
+    }
+}
+
+/// Counterpart of [`Visitor`] for types deserialized through a [`Slot`].
+///
+/// Each method receives the output place of the `Slot`, and the default
+/// implementations behave like the ones of [`Visitor`].
+#[allow(unused_variables)]
+pub trait VisitSlot: Sized {
+    fn null(out: &mut Option<Self>) -> Result<()> {
+        Self::map(out)
+            .and_then(|map| map.finish())
+            .or_else(|_| err!("Failed to deserialize a `null` as an empty map at that position."))
+    }
+
+    fn boolean(out: &mut Option<Self>, b: bool) -> Result<()> {
+        err!(
+            "Cannot deserialize a `boolean` (got {:?}) at that position.",
+            b
+        );
+    }
+
+    fn string(out: &mut Option<Self>, s: &str) -> Result<()> {
+        err!(
+            "Cannot deserialize a `string` (got {:?}) at that position.",
+            s
+        );
+    }
+
+    fn bytes(out: &mut Option<Self>, xs: &[u8]) -> Result<()> {
+        Self::seq(out)
+            .and_then(|mut seq| {
+                for &x in xs {
+                    seq.element()?.int(x as _)?;
+                }
+                seq.finish()
+            })
+            .or_else(|_| {
+                err!(
+                    "Failed to deserialize a `bytes` (got {:#x?}) as a int-seq at that position.",
+                    xs
+                )
+            })
+    }
+
+    fn int(out: &mut Option<Self>, i: i128) -> Result<()> {
+        err!("Cannot deserialize a `int` (got {:?}) at that position.", i);
+    }
+
+    #[cfg(feature = "float")]
+    fn float(out: &mut Option<Self>, f: f64) -> Result<()> {
+        err!(
+            "Cannot deserialize a `float` (got {:?}) at that position.",
+            f
+        );
+    }
+
+    fn raw_number(out: &mut Option<Self>, raw: &str) -> Result<()> {
+        match ValueView::parse_raw_number_lossy(raw)? {
+            ValueView::Int(i) => Self::int(out, i),
+            #[cfg(feature = "float")]
+            ValueView::F64(f) => Self::float(out, f),
+            _ => unreachable!(),
+        }
+    }
+
+    fn seq(out: &mut Option<Self>) -> Result<Box<dyn Seq + '_>> {
+        err!("Cannot deserialize a `seq` at that position.");
+    }
+
+    fn map(out: &mut Option<Self>) -> Result<Box<dyn Map + '_>> {
+        err!("Cannot deserialize a `map` at that position.");
+    }
+
+    fn tag(out: &mut Option<Self>, tag: u64) -> Result<Option<Box<dyn Seq + '_>>> {
+        Ok(None)
+    }
+}
+
+impl<T: VisitSlot> Visitor for Slot<T> {
+    fn null(&mut self) -> Result<()> {
+        T::null(&mut self.out)
+    }
+
+    fn boolean(&mut self, b: bool) -> Result<()> {
+        T::boolean(&mut self.out, b)
+    }
+
+    fn string(&mut self, s: &str) -> Result<()> {
+        T::string(&mut self.out, s)
+    }
+
+    fn bytes(&mut self, xs: &[u8]) -> Result<()> {
+        T::bytes(&mut self.out, xs)
+    }
+
+    fn int(&mut self, i: i128) -> Result<()> {
+        T::int(&mut self.out, i)
+    }
+
+    #[cfg(feature = "float")]
+    fn float(&mut self, f: f64) -> Result<()> {
+        T::float(&mut self.out, f)
+    }
+
+    fn raw_number(&mut self, raw: &str) -> Result<()> {
+        T::raw_number(&mut self.out, raw)
+    }
+
+    fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
+        T::seq(&mut self.out)
+    }
+
+    fn map(&mut self) -> Result<Box<dyn Map + '_>> {
+        T::map(&mut self.out)
+    }
+
+    fn tag(&mut self, tag: u64) -> Result<Option<Box<dyn Seq + '_>>> {
+        T::tag(&mut self.out, tag)
+    }
+}
@@ -0,0 +1,308 @@
+//! Field-level differences between two values, and applying them.
+//!
+//! [`Diff::diff`] compares two values of a type and returns a [`Delta`]
+//! holding only what changed, as a map of the changed fields (recursively, for
+//! nested structs and maps). [`Diff::apply_patch`] merges such a delta,
+//! possibly after it went through the wire, back into a value.
+//!
+//! A delta follows the [JSON Merge Patch] conventions: a field set to `null`
+//! in the delta is removed from the target, and anything that is not a map,
+//! such as a sequence, is replaced as a whole.
+//!
+//! ```rust
+//! use miniserde_ditto::delta::Diff;
+//! use miniserde_ditto::{json, Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, PartialEq, Debug)]
+//! struct Doc {
+//!     title: String,
+//!     meta: Meta,
+//! }
+//!
+//! #[derive(Serialize, Deserialize, PartialEq, Debug)]
+//! struct Meta {
+//!     views: u32,
+//!     author: Option<String>,
+//! }
+//!
+//! let mut doc = Doc {
+//!     title: "a".to_owned(),
+//!     meta: Meta { views: 1, author: Some("me".to_owned()) },
+//! };
+//! let new = Doc {
+//!     title: "a".to_owned(),
+//!     meta: Meta { views: 2, author: None },
+//! };
+//!
+//! let delta = json::to_string(&doc.diff(&new).unwrap()).unwrap();
+//! assert_eq!(delta, r#"{"meta":{"views":2,"author":null}}"#);
+//!
+//! let delta: json::Value = json::from_str(&delta).unwrap();
+//! doc.apply_patch(&delta).unwrap();
+//! assert_eq!(doc, new);
+//! ```
+//!
+//! Since `null` means removal, a map entry whose value is `null`, such as a
+//! `None` in a `HashMap<_, Option<_>>`, cannot be patched in; fields of
+//! structs are fine, as a missing `Option` field deserializes as `None`.
+//!
+//! [JSON Merge Patch]: https://tools.ietf.org/html/rfc7386
+
+use core::iter;
+
+use crate::prelude::*;
+use crate::ser::{self, Serialize, ValueView};
+use crate::{Deserialize, Error, Result};
+
+/// Past this nesting depth, [`Diff::diff`] stops looking for differences and
+/// replaces whole values, and [`Diff::apply_patch`] gives up.
+const MAX_DEPTH: usize = 128;
+
+/// Diffing and patching, for any type that can be both serialized and
+/// deserialized.
+pub trait Diff: Serialize + Deserialize {
+    /// The changes turning `self` into `other`, or `None` if they serialize
+    /// the same.
+    fn diff<'a>(&'a self, other: &'a Self) -> Option<Delta<'a>> {
+        diff(self, other, 0).map(Delta)
+    }
+
+    /// Merge `patch`, as produced by [`diff`](Diff::diff), into `self`.
+    ///
+    /// On error, `self` is left untouched.
+    fn apply_patch(&mut self, patch: &dyn Serialize) -> Result<()> {
+        let mut out = None;
+        {
+            let merged = merge(self, patch, 0)?;
+            let options = crate::transcode::Options::default();
+            crate::transcode::transcode(&merged, Self::begin(&mut out), options)?;
+        }
+        *self = out.ok_or(Error)?;
+        Ok(())
+    }
+}
+
+impl<T: Serialize + Deserialize> Diff for T {}
+
+/// The changes between two values, as returned by [`Diff::diff`]. Serialize
+/// it to send it over the wire.
+pub struct Delta<'a>(Node<'a>);
+
+enum Node<'a> {
+    Replace(&'a dyn Serialize),
+    Remove,
+    Fields(Vec<(&'a dyn Serialize, Node<'a>)>),
+}
+
+impl Serialize for Delta<'_> {
+    fn view(&self) -> ValueView<'_> {
+        self.0.view()
+    }
+}
+
+impl Serialize for Node<'_> {
+    fn view(&self) -> ValueView<'_> {
+        match self {
+            Node::Replace(value) => value.view(),
+            Node::Remove => ValueView::Null,
+            Node::Fields(fields) => ValueView::Map(Box::new(
+                fields
+                    .iter()
+                    .map(|(key, node)| (*key, node as &dyn Serialize)),
+            )),
+        }
+    }
+}
+
+fn diff<'a>(old: &'a dyn Serialize, new: &'a dyn Serialize, depth: usize) -> Option<Node<'a>> {
+    if depth < MAX_DEPTH {
+        if let (ValueView::Map(old_map), ValueView::Map(new_map)) = (old.view(), new.view()) {
+            let mut old_entries = Entries::new(old_map);
+            let mut fields = vec![];
+            for (key, new) in iter::from_fn(unbox(new_map)) {
+                match old_entries.take(key) {
+                    Some(old) => {
+                        if let Some(node) = diff(old, new, depth + 1) {
+                            fields.push((key, node));
+                        }
+                    }
+                    None => fields.push((key, Node::Replace(new))),
+                }
+            }
+            fields.extend(old_entries.rest().map(|(key, _)| (key, Node::Remove)));
+            return if fields.is_empty() {
+                None
+            } else {
+                Some(Node::Fields(fields))
+            };
+        }
+    }
+    if eq(old, new) {
+        None
+    } else {
+        Some(Node::Replace(new))
+    }
+}
+
+/// A value with a patch merged in, built before being deserialized.
+enum Merged<'a> {
+    Value(&'a dyn Serialize),
+    Map(Vec<(&'a dyn Serialize, Merged<'a>)>),
+}
+
+impl Serialize for Merged<'_> {
+    fn view(&self) -> ValueView<'_> {
+        match self {
+            Merged::Value(value) => value.view(),
+            Merged::Map(entries) => ValueView::Map(Box::new(
+                entries
+                    .iter()
+                    .map(|(key, merged)| (*key, merged as &dyn Serialize)),
+            )),
+        }
+    }
+}
+
+fn merge<'a>(
+    base: &'a dyn Serialize,
+    patch: &'a dyn Serialize,
+    depth: usize,
+) -> Result<Merged<'a>> {
+    let (base_map, patch_map) = match (base.view(), patch.view()) {
+        (ValueView::Map(base_map), ValueView::Map(patch_map)) => (base_map, patch_map),
+        _ => return Ok(Merged::Value(patch)),
+    };
+    if depth == MAX_DEPTH {
+        return Err(Error);
+    }
+    let is_null = |value: &dyn Serialize| matches!(value.view(), ValueView::Null);
+    let mut patches = Entries::new(patch_map);
+    let mut entries = vec![];
+    for (key, value) in iter::from_fn(unbox(base_map)) {
+        match patches.take(key) {
+            None => entries.push((key, Merged::Value(value))),
+            Some(patch) if is_null(patch) => {}
+            Some(patch) => entries.push((key, merge(value, patch, depth + 1)?)),
+        }
+    }
+    for (key, patch) in patches.rest() {
+        if !is_null(patch) {
+            entries.push((key, Merged::Value(patch)));
+        }
+    }
+    Ok(Merged::Map(entries))
+}
+
+fn unbox<'a>(
+    mut map: Box<dyn ser::Map<'a> + 'a>,
+) -> impl FnMut() -> Option<(&'a dyn Serialize, &'a dyn Serialize)> {
+    move || map.next()
+}
+
+/// The entries of a map, to be looked up by key in about the same order.
+struct Entries<'a> {
+    entries: Vec<Option<(&'a dyn Serialize, &'a dyn Serialize)>>,
+    cursor: usize,
+}
+
+impl<'a> Entries<'a> {
+    fn new(map: Box<dyn ser::Map<'a> + 'a>) -> Self {
+        Entries {
+            entries: iter::from_fn(unbox(map)).map(Some).collect(),
+            cursor: 0,
+        }
+    }
+
+    /// Remove the value for `key`. Starts looking where the previous one was
+    /// found, so that maps iterated in the same order take linear time.
+    fn take(&mut self, key: &dyn Serialize) -> Option<&'a dyn Serialize> {
+        let len = self.entries.len();
+        let found = (0..len)
+            .map(|i| (self.cursor + i) % len)
+            .find(|&i| matches!(self.entries[i], Some((k, _)) if eq(k, key)))?;
+        self.cursor = found + 1;
+        self.entries[found].take().map(|(_, value)| value)
+    }
+
+    fn rest(self) -> impl Iterator<Item = (&'a dyn Serialize, &'a dyn Serialize)> {
+        self.entries.into_iter().flatten()
+    }
+}
+
+/// Whether two values serialize the same. Maps only compare equal if their
+/// entries come in the same order.
+fn eq(a: &dyn Serialize, b: &dyn Serialize) -> bool {
+    eq_views(a.view(), b.view())
+}
+
+fn eq_views(mut a: ValueView<'_>, mut b: ValueView<'_>) -> bool {
+    enum Pair<'a, 'b> {
+        Seq(Box<dyn ser::Seq<'a> + 'a>, Box<dyn ser::Seq<'b> + 'b>),
+        Map(Box<dyn ser::Map<'a> + 'a>, Box<dyn ser::Map<'b> + 'b>),
+    }
+    let mut stack = vec![];
+
+    loop {
+        match (a, b) {
+            (ValueView::Null, ValueView::Null) => {}
+            (ValueView::Undefined, ValueView::Undefined) => {}
+            (ValueView::Bool(x), ValueView::Bool(y)) if x == y => {}
+            (ValueView::Str(x), ValueView::Str(y)) if x == y => {}
+            (ValueView::Bytes(x), ValueView::Bytes(y)) if x == y => {}
+            (ValueView::Base64(x), ValueView::Base64(y)) if x == y => {}
+            (ValueView::Timestamp(x), ValueView::Timestamp(y)) if x == y => {}
+            (ValueView::Int(x), ValueView::Int(y)) if x == y => {}
+            #[cfg(feature = "float")]
+            (ValueView::F64(x), ValueView::F64(y)) if x.to_bits() == y.to_bits() => {}
+            (ValueView::RawNumber(x), ValueView::RawNumber(y)) if x == y => {}
+            (ValueView::RawJson(x), ValueView::RawJson(y)) if x == y => {}
+            (ValueView::RawCbor(x), ValueView::RawCbor(y)) if x == y => {}
+            (ValueView::Tagged(tag_a, x), ValueView::Tagged(tag_b, y)) if tag_a == tag_b => {
+                a = x.view();
+                b = y.view();
+                continue;
+            }
+            // An owned value only lives as long as its view.
+            (ValueView::Owned(x), y) => {
+                if !eq_views(x.view(), y) {
+                    return false;
+                }
+            }
+            (x, ValueView::Owned(y)) => {
+                if !eq_views(x, y.view()) {
+                    return false;
+                }
+            }
+            (ValueView::Seq(x), ValueView::Seq(y)) => stack.push(Pair::Seq(x, y)),
+            (ValueView::Map(x), ValueView::Map(y)) => stack.push(Pair::Map(x, y)),
+            _ => return false,
+        }
+
+        loop {
+            match stack.last_mut() {
+                None => return true,
+                Some(Pair::Seq(x, y)) => match (x.next(), y.next()) {
+                    (Some(x), Some(y)) => {
+                        a = x.view();
+                        b = y.view();
+                        break;
+                    }
+                    (None, None) => drop(stack.pop()),
+                    _ => return false,
+                },
+                Some(Pair::Map(x, y)) => match (x.next(), y.next()) {
+                    (Some((xk, xv)), Some((yk, yv))) => {
+                        if !eq(xk, yk) {
+                            return false;
+                        }
+                        a = xv.view();
+                        b = yv.view();
+                        break;
+                    }
+                    (None, None) => drop(stack.pop()),
+                    _ => return false,
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//! Values tagged with their type, for the messages of several types sent over
+//! a single channel.
+//!
+//! An [`Envelope`] serializes as `{"type": <type tag>, "payload": <value>}`,
+//! with the tag of a [`TypeTag`] type. A receiver not knowing which type to
+//! expect decodes it with a [`Registry`] of the types it handles, into a
+//! [`Message`].
+//!
+//! When the type tag is sent apart from the bytes of the value, such as in a
+//! header, the [`decoder!`](crate::decoder)s of a [`Registry`] decode them
+//! with [`Registry::decode`].
+//!
+//! ```rust
+//! use miniserde_ditto::envelope::{Envelope, Registry};
+//! use miniserde_ditto::{json, Deserialize, Serialize, TypeTag};
+//!
+//! #[derive(Serialize, Deserialize, TypeTag, Debug, PartialEq)]
+//! struct Ping {
+//!     seq: u32,
+//! }
+//!
+//! #[derive(Serialize, Deserialize, TypeTag, Debug, PartialEq)]
+//! #[serde(type_tag = "shutdown")]
+//! struct Shutdown {
+//!     reason: String,
+//! }
+//!
+//! let j = json::to_string(&Envelope(Ping { seq: 1 })).unwrap();
+//! assert_eq!(j, r#"{"type":"Ping","payload":{"seq":1}}"#);
+//!
+//! let registry = Registry::new().register::<Ping>().register::<Shutdown>();
+//! let message = json::decode_envelope(&j, &registry)?;
+//! assert_eq!(message.type_tag(), "Ping");
+//! assert_eq!(message.downcast_ref::<Ping>(), Some(&Ping { seq: 1 }));
+//! # Ok::<(), miniserde_ditto::Error>(())
+//! ```
+
+use alloc::collections::BTreeMap;
+use core::any::Any;
+use core::fmt::{self, Debug};
+
+use crate::de::{Deserialize, Map, StrKeyMap, Visitor};
+use crate::error::{Error, Result};
+use crate::prelude::*;
+use crate::ser::{self, Serialize, ValueView};
+use crate::Place;
+
+/// A type with a name to tag its values with in an [`Envelope`].
+///
+/// `#[derive(TypeTag)]` uses the name of the type, or the one given with
+/// `#[serde(type_tag = "...")]`.
+pub trait TypeTag {
+    const TYPE_TAG: &'static str;
+}
+
+/// A value, serialized along with its [`TypeTag`].
+///
+/// Deserializing it fails if the tag is not the one of `T`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Envelope<T>(pub T);
+
+impl<T: TypeTag + Serialize> Serialize for Envelope<T> {
+    fn view(&self) -> ValueView<'_> {
+        ser::map_of([(&"type", &T::TYPE_TAG), (&"payload", &self.0)])
+    }
+}
+
+impl<T: TypeTag + Deserialize> Deserialize for Envelope<T> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl<T: TypeTag + Deserialize> Visitor for Place<Envelope<T>> {
+            fn map(&mut self) -> Result<Box<dyn Map + '_>> {
+                Ok(Box::new(EnvelopeBuilder {
+                    out: &mut self.out,
+                    type_tag: None,
+                    payload: None,
+                }))
+            }
+        }
+
+        struct EnvelopeBuilder<'a, T: 'a> {
+            out: &'a mut Option<Envelope<T>>,
+            type_tag: Option<String>,
+            payload: Option<T>,
+        }
+
+        impl<'a, T: TypeTag + Deserialize> StrKeyMap for EnvelopeBuilder<'a, T> {
+            fn key(&mut self, k: &str) -> Result<&mut dyn Visitor> {
+                match k {
+                    "type" => Ok(Deserialize::begin(&mut self.type_tag)),
+                    "payload" => Ok(Deserialize::begin(&mut self.payload)),
+                    _ => Ok(<dyn Visitor>::ignore()),
+                }
+            }
+
+            fn finish(self: Box<Self>) -> Result<()> {
+                match self.type_tag {
+                    Some(ref type_tag) if type_tag == T::TYPE_TAG => {}
+                    ref other => err!("Expected type {:?}, got {:?}", T::TYPE_TAG, other),
+                }
+                *self.out = Some(Envelope(self.payload.ok_or(Error)?));
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
+}
+
+/// Decodes the bytes of a value of some type, as made by [`decoder!`].
+///
+/// [`decoder!`]: crate::decoder
+pub type Decoder = fn(&[u8]) -> Result<Box<dyn Any>>;
+
+/// Makes the `(type tag, decoder)` of a [`TypeTag`] type, for
+/// [`Registry::register_decoder`], decoding `cbor` or `json` bytes.
+///
+/// ```rust
+/// use miniserde_ditto::envelope::Registry;
+/// use miniserde_ditto::{cbor, decoder, Deserialize, Serialize, TypeTag};
+///
+/// #[derive(Serialize, Deserialize, TypeTag, Debug, PartialEq)]
+/// #[serde(type_tag = "plugin.resize")]
+/// struct Resize {
+///     width: u32,
+///     height: u32,
+/// }
+///
+/// // Each plugin registers the messages it knows of.
+/// let registry = Registry::new().register_decoder(decoder!(cbor, Resize));
+///
+/// let bytes = cbor::to_vec(Resize { width: 640, height: 480 })?;
+/// let message = registry.decode("plugin.resize", &bytes)?;
+/// assert_eq!(message.downcast::<Resize>().unwrap().width, 640);
+/// assert!(registry.decode("plugin.crop", &bytes).is_err());
+/// # Ok::<(), miniserde_ditto::Error>(())
+/// ```
+#[macro_export]
+macro_rules! decoder {
+    (cbor, $T:ty) => {
+        (
+            <$T as $crate::TypeTag>::TYPE_TAG,
+            $crate::__private::decode_cbor::<$T> as $crate::envelope::Decoder,
+        )
+    };
+    (json, $T:ty) => {
+        (
+            <$T as $crate::TypeTag>::TYPE_TAG,
+            $crate::__private::decode_json::<$T> as $crate::envelope::Decoder,
+        )
+    };
+}
+
+/// The types an envelope may hold, when decoding one with
+/// `json::decode_envelope` or `cbor::decode_envelope`, and the decoders of
+/// [`Registry::decode`].
+#[derive(Default)]
+pub struct Registry {
+    types: BTreeMap<&'static str, fn() -> Box<dyn Slot>>,
+    decoders: BTreeMap<&'static str, Decoder>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `T` to the types of the registry.
+    ///
+    /// # Panics
+    ///
+    /// If the registry already has a type with the same tag.
+    pub fn register<T: TypeTag + Deserialize + 'static>(mut self) -> Self {
+        let prev = self.types.insert(T::TYPE_TAG, || Box::new(None::<T>));
+        assert!(prev.is_none(), "duplicate type tag {:?}", T::TYPE_TAG);
+        self
+    }
+
+    /// Add a decoder of the bytes of a type, as made by [`decoder!`], which
+    /// [`decode`](Self::decode) picks by its type tag.
+    ///
+    /// # Panics
+    ///
+    /// If the registry already has a decoder with the same tag.
+    ///
+    /// [`decoder!`]: crate::decoder
+    pub fn register_decoder(mut self, (type_tag, decoder): (&'static str, Decoder)) -> Self {
+        let prev = self.decoders.insert(type_tag, decoder);
+        assert!(prev.is_none(), "duplicate type tag {:?}", type_tag);
+        self
+    }
+
+    /// Decode `bytes` with the decoder registered for `type_tag`, such as
+    /// when the type of a message is sent apart from it.
+    pub fn decode(&self, type_tag: &str, bytes: &[u8]) -> Result<Message> {
+        let (&type_tag, decoder) = match self.decoders.get_key_value(type_tag) {
+            Some(entry) => entry,
+            None => err!("Unknown type tag {:?}", type_tag),
+        };
+        Ok(Message {
+            type_tag,
+            payload: decoder(bytes)?,
+        })
+    }
+
+    #[cfg(any(feature = "json", feature = "cbor"))]
+    pub(crate) fn visitor(&self) -> EnvelopeVisitor<'_> {
+        EnvelopeVisitor {
+            registry: self,
+            out: None,
+        }
+    }
+}
+
+impl Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Registry")
+            .field("types", &self.types.keys())
+            .field("decoders", &self.decoders.keys())
+            .finish()
+    }
+}
+
+/// A value of any of the types of a [`Registry`].
+pub struct Message {
+    type_tag: &'static str,
+    payload: Box<dyn Any>,
+}
+
+impl Message {
+    pub fn type_tag(&self) -> &'static str {
+        self.type_tag
+    }
+
+    pub fn is<T: Any>(&self) -> bool {
+        self.payload.is::<T>()
+    }
+
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.payload.downcast_ref()
+    }
+
+    /// The payload, if it is a `T`, or else the message back.
+    pub fn downcast<T: Any>(self) -> core::result::Result<T, Self> {
+        match self.payload.downcast() {
+            Ok(payload) => Ok(*payload),
+            Err(payload) => Err(Message {
+                type_tag: self.type_tag,
+                payload,
+            }),
+        }
+    }
+}
+
+impl Debug for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Message")
+            .field("type_tag", &self.type_tag)
+            .finish()
+    }
+}
+
+/// The place to deserialize a payload into, once its type is known.
+trait Slot {
+    fn visitor(&mut self) -> &mut dyn Visitor;
+    fn take(self: Box<Self>) -> Option<Box<dyn Any>>;
+}
+
+impl<T: Deserialize + 'static> Slot for Option<T> {
+    fn visitor(&mut self) -> &mut dyn Visitor {
+        T::begin(self)
+    }
+
+    fn take(self: Box<Self>) -> Option<Box<dyn Any>> {
+        let payload: Box<dyn Any> = Box::new((*self)?);
+        Some(payload)
+    }
+}
+
+/// Deserializes an envelope into a [`Message`]. The `type` of the envelope
+/// must come before its `payload`, as `Envelope` serializes them.
+pub(crate) struct EnvelopeVisitor<'a> {
+    registry: &'a Registry,
+    pub(crate) out: Option<Message>,
+}
+
+impl<'a> Visitor for EnvelopeVisitor<'a> {
+    fn map(&mut self) -> Result<Box<dyn Map + '_>> {
+        Ok(Box::new(MessageBuilder {
+            registry: self.registry,
+            out: &mut self.out,
+            type_tag: None,
+            payload: None,
+        }))
+    }
+}
+
+struct MessageBuilder<'a> {
+    registry: &'a Registry,
+    out: &'a mut Option<Message>,
+    type_tag: Option<String>,
+    payload: Option<(&'static str, Box<dyn Slot>)>,
+}
+
+impl<'a> StrKeyMap for MessageBuilder<'a> {
+    fn key(&mut self, k: &str) -> Result<&mut dyn Visitor> {
+        match k {
+            "type" if self.type_tag.is_some() => err!("Duplicate `type` in an envelope"),
+            "type" => Ok(Deserialize::begin(&mut self.type_tag)),
+            "payload" if self.payload.is_some() => err!("Duplicate `payload` in an envelope"),
+            "payload" => {
+                let type_tag = match self.type_tag {
+                    Some(ref type_tag) => type_tag.as_str(),
+                    None => err!("Expected the `type` of an envelope before its `payload`"),
+                };
+                let (&type_tag, new_slot) = match self.registry.types.get_key_value(type_tag) {
+                    Some(entry) => entry,
+                    None => err!("Unknown type tag {:?}", type_tag),
+                };
+                let (_, slot) = self.payload.get_or_insert((type_tag, new_slot()));
+                Ok(slot.visitor())
+            }
+            _ => Ok(<dyn Visitor>::ignore()),
+        }
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        let (type_tag, slot) = match self.payload {
+            Some(payload) => payload,
+            None => err!("Missing the `payload` of an envelope"),
+        };
+        *self.out = Some(Message {
+            type_tag,
+            payload: slot.take().ok_or(Error)?,
+        });
+        Ok(())
+    }
+}
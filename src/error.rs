@@ -1,4 +1,4 @@
-use std::fmt::{self, Display};
+use core::fmt::{self, Display};
 
 /// Error type when deserialization fails.
 ///
@@ -16,7 +16,7 @@ use std::fmt::{self, Display};
 pub struct Error;
 
 /// Result type returned by deserialization functions.
-pub type Result<Ok, Err = Error> = std::result::Result<Ok, Err>;
+pub type Result<Ok, Err = Error> = core::result::Result<Ok, Err>;
 
 impl Display for Error {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -24,6 +24,7 @@ impl Display for Error {
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for Error {
     fn description(&self) -> &str {
         "miniserde error"
@@ -0,0 +1,119 @@
+//! Byte strings as lowercase hex strings, such as the IDs, hashes and keys of
+//! JSON APIs, for `#[serde(with = "miniserde_ditto::hex")]` fields.
+//!
+//! A `Vec<u8>`, `Box<[u8]>` or `[u8; N]` field serializes to a string of two
+//! hex digits per byte, in every format, and deserializes back from one, in
+//! either case. An array also requires the string to hold exactly `N` bytes.
+//!
+//! ```rust
+//! use miniserde_ditto::{json, Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Commit {
+//!     #[serde(with = "miniserde_ditto::hex")]
+//!     id: [u8; 4],
+//!     #[serde(with = "miniserde_ditto::hex")]
+//!     signature: Vec<u8>,
+//! }
+//!
+//! let commit = Commit {
+//!     id: [0xde, 0xad, 0xbe, 0xef],
+//!     signature: vec![0x00, 0x0a],
+//! };
+//! let j = json::to_string(&commit)?;
+//! assert_eq!(j, r#"{"id":"deadbeef","signature":"000a"}"#);
+//! assert_eq!(json::from_str::<Commit>(r#"{"id":"DEADBEEF","signature":"000a"}"#)?, commit);
+//! assert!(json::from_str::<Commit>(r#"{"id":"dead","signature":""}"#).is_err());
+//! # Ok::<(), miniserde_ditto::Error>(())
+//! ```
+
+use alloc::borrow::Cow;
+
+use crate::de::Visitor;
+use crate::error::Result;
+use crate::prelude::*;
+use crate::ser::ValueView;
+
+// Not the crate's own, whose `Visitor` impls would overlap with the one for
+// any `FromHex`.
+make_place!(Place);
+
+/// The byte containers a hex string deserializes into.
+pub trait FromHex: Sized {
+    /// The container of the decoded `bytes`, or `None` if they do not fit,
+    /// such as an array of another length.
+    fn from_hex_bytes(bytes: Vec<u8>) -> Option<Self>;
+}
+
+impl FromHex for Vec<u8> {
+    fn from_hex_bytes(bytes: Vec<u8>) -> Option<Self> {
+        Some(bytes)
+    }
+}
+
+impl FromHex for Box<[u8]> {
+    fn from_hex_bytes(bytes: Vec<u8>) -> Option<Self> {
+        Some(bytes.into_boxed_slice())
+    }
+}
+
+impl<const N: usize> FromHex for [u8; N] {
+    fn from_hex_bytes(bytes: Vec<u8>) -> Option<Self> {
+        if bytes.len() != N {
+            return None;
+        }
+        let mut array = [0; N];
+        array.copy_from_slice(&bytes);
+        Some(array)
+    }
+}
+
+/// Views `bytes` as a lowercase hex string.
+pub fn serialize<T: AsRef<[u8]> + ?Sized>(bytes: &T) -> ValueView<'_> {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let bytes = bytes.as_ref();
+    let mut s = String::with_capacity(2 * bytes.len());
+    for &b in bytes {
+        s.push(DIGITS[usize::from(b >> 4)] as char);
+        s.push(DIGITS[usize::from(b & 0xf)] as char);
+    }
+    ValueView::Str(Cow::Owned(s))
+}
+
+/// The visitor of a hex string, decoded into `out`.
+pub fn deserialize<T: FromHex>(out: &mut Option<T>) -> &mut dyn Visitor {
+    Place::new(out)
+}
+
+impl<T: FromHex> Visitor for Place<T> {
+    fn string(&mut self, s: &str) -> Result<()> {
+        let bytes = match decode(s) {
+            Some(bytes) => bytes,
+            None => err!("Invalid hex string {:?}", s),
+        };
+        match T::from_hex_bytes(bytes) {
+            Some(value) => self.out = Some(value),
+            None => err!("Unexpected length of hex string {:?}", s),
+        }
+        Ok(())
+    }
+}
+
+fn decode(s: &str) -> Option<Vec<u8>> {
+    fn digit(c: u8) -> Option<u8> {
+        match c {
+            b'0'..=b'9' => Some(c - b'0'),
+            b'a'..=b'f' => Some(c - b'a' + 10),
+            b'A'..=b'F' => Some(c - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    let s = s.as_bytes();
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    s.chunks(2)
+        .map(|pair| Some(digit(pair[0])? << 4 | digit(pair[1])?))
+        .collect()
+}
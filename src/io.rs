@@ -0,0 +1,55 @@
+//! The parts of `std::io` that the serializers write through. Without the
+//! `std` feature, they only write to a `Vec<u8>`, which cannot fail.
+
+#[cfg(all(feature = "std", feature = "cbor"))]
+pub(in crate) use std::io::Result;
+#[cfg(feature = "std")]
+pub(in crate) use std::io::{Error, Write};
+
+// Not all of them are used with only one of the `cbor` and `json` features.
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+pub(in crate) use self::no_std::{Error, Result, Write};
+
+#[cfg(not(feature = "std"))]
+mod no_std {
+    use core::fmt;
+
+    use crate::prelude::*;
+
+    #[derive(Debug)]
+    pub enum Error {}
+
+    impl fmt::Display for Error {
+        fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match *self {}
+        }
+    }
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    pub trait Write {
+        fn write(&mut self, buf: &[u8]) -> Result<usize>;
+
+        fn flush(&mut self) -> Result<()>;
+
+        fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+            while !buf.is_empty() {
+                let n = self.write(buf)?;
+                buf = &buf[n..];
+            }
+            Ok(())
+        }
+    }
+
+    impl Write for Vec<u8> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+}
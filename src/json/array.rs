@@ -1,9 +1,10 @@
-use std::iter::FromIterator;
-use std::mem::ManuallyDrop;
-use std::ops::{Deref, DerefMut};
-use std::ptr;
+use core::iter::FromIterator;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use core::ptr;
 
 use crate::json::{drop, Value};
+use crate::prelude::*;
 
 /// A `Vec<Value>` with a non-recursive drop impl.
 #[derive(Clone, Debug, Default)]
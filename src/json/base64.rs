@@ -0,0 +1,153 @@
+use alloc::borrow::Cow;
+use core::fmt::{self, Write};
+use core::ops::{Deref, DerefMut};
+
+use crate::de::{Deserialize, Visitor};
+use crate::error::Result;
+use crate::prelude::*;
+use crate::ser::{Serialize, ValueView};
+use crate::Place;
+
+/// Bytes serialized as a base64 string in JSON, rather than as an array of
+/// numbers, and as a plain byte string in CBOR.
+///
+/// Deserializing accepts either: the standard alphabet, with or without
+/// padding, from a string, or the bytes of a byte string.
+///
+/// ```rust
+/// use miniserde_ditto::json::{self, Base64};
+/// use miniserde_ditto::{cbor, Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize, Debug, PartialEq)]
+/// struct Attachment {
+///     name: String,
+///     data: Base64,
+/// }
+///
+/// let attachment = Attachment {
+///     name: "logo.png".to_owned(),
+///     data: Base64(vec![0x89, b'P', b'N', b'G']),
+/// };
+/// let j = json::to_string(&attachment)?;
+/// assert_eq!(j, r#"{"name":"logo.png","data":"iVBORw=="}"#);
+/// assert_eq!(json::from_str::<Attachment>(&j)?, attachment);
+///
+/// let bytes = cbor::to_vec(&attachment)?;
+/// assert!(bytes.ends_with(&[0x44, 0x89, b'P', b'N', b'G']));
+/// # Ok::<(), miniserde_ditto::Error>(())
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Base64(pub Vec<u8>);
+
+impl Base64 {
+    pub fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl From<Vec<u8>> for Base64 {
+    fn from(bytes: Vec<u8>) -> Self {
+        Base64(bytes)
+    }
+}
+
+impl Deref for Base64 {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.0
+    }
+}
+
+impl DerefMut for Base64 {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.0
+    }
+}
+
+impl fmt::Debug for Base64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Base64(")?;
+        encode(&self.0).try_for_each(|c| f.write_char(c))?;
+        f.write_str(")")
+    }
+}
+
+impl Serialize for Base64 {
+    fn view(&self) -> ValueView<'_> {
+        ValueView::Base64(Cow::Borrowed(&self.0))
+    }
+}
+
+impl Deserialize for Base64 {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl Visitor for Place<Base64> {
+            fn string(&mut self, s: &str) -> Result<()> {
+                match decode(s) {
+                    Some(bytes) => {
+                        self.out = Some(Base64(bytes));
+                        Ok(())
+                    }
+                    None => err!("Invalid base64 string {:?}", s),
+                }
+            }
+
+            fn bytes(&mut self, xs: &[u8]) -> Result<()> {
+                self.out = Some(Base64(xs.to_owned()));
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
+}
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The characters of `bytes` in base64, padded.
+pub(crate) fn encode(bytes: &[u8]) -> impl Iterator<Item = char> + '_ {
+    bytes.chunks(3).flat_map(|chunk| {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |n, (i, &b)| n | (u32::from(b) << (16 - 8 * i)));
+        (0..4).map(move |i| match i {
+            _ if i > chunk.len() => '=',
+            _ => ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char,
+        })
+    })
+}
+
+/// The bytes of base64 `s`, if valid, with or without padding.
+fn decode(s: &str) -> Option<Vec<u8>> {
+    let padded = s.as_bytes();
+    let s = padded
+        .strip_suffix(b"==")
+        .or_else(|| padded.strip_suffix(b"="))
+        .unwrap_or(padded);
+    if s.len() % 4 == 1 || s.len() != padded.len() && padded.len() % 4 != 0 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(s.len() / 4 * 3 + 2);
+    for chunk in s.chunks(4) {
+        let mut n = 0;
+        for (i, &c) in chunk.iter().enumerate() {
+            let sextet = match c {
+                b'A'..=b'Z' => c - b'A',
+                b'a'..=b'z' => c - b'a' + 26,
+                b'0'..=b'9' => c - b'0' + 52,
+                b'+' => 62,
+                b'/' => 63,
+                _ => return None,
+            };
+            n |= u32::from(sextet) << (18 - 6 * i);
+        }
+        let len = chunk.len() - 1;
+        // Padding bits are zero in canonical encodings.
+        if n & (0xff_ffff >> (8 * len)) != 0 {
+            return None;
+        }
+        out.extend_from_slice(&n.to_be_bytes()[1..=len]);
+    }
+    Some(out)
+}
@@ -0,0 +1,231 @@
+/// Serialize a few simple values into JSON in `const` contexts, such as fixed
+/// messages to be computed at compile time.
+///
+/// Every method takes and returns the serializer by value, so that calls can
+/// be chained in a `const` item. Arrays and objects are opened, filled, then
+/// closed; the commas are inserted as needed. The output is the same as
+/// [`to_string`](crate::json::to_string) for the equivalent values, up to
+/// [`ConstSerializer::CAPACITY`] bytes.
+///
+/// ```rust
+/// use miniserde_ditto::json::{self, ConstSerializer};
+/// use miniserde_ditto::Serialize;
+///
+/// const HELLO: ConstSerializer = ConstSerializer::new()
+///     .begin_object()
+///     .key("kind")
+///     .str("hello")
+///     .key("versions")
+///     .begin_array()
+///     .uint(1)
+///     .uint(2)
+///     .end_array()
+///     .end_object();
+/// const BYTES: [u8; HELLO.len()] = HELLO.finish();
+///
+/// #[derive(Serialize)]
+/// struct Hello {
+///     kind: &'static str,
+///     versions: Vec<u32>,
+/// }
+///
+/// let hello = Hello { kind: "hello", versions: vec![1, 2] };
+/// assert_eq!(BYTES[..], *json::to_string(&hello).unwrap().as_bytes());
+/// ```
+///
+/// Exceeding the capacity, or calling [`finish`](ConstSerializer::finish)
+/// with the wrong length, fails to compile (or panics at runtime). Unbalanced
+/// calls are not detected.
+#[derive(Clone, Copy)]
+pub struct ConstSerializer {
+    buf: [u8; ConstSerializer::CAPACITY],
+    len: usize,
+    needs_comma: bool,
+}
+
+impl ConstSerializer {
+    /// The maximum length of the output.
+    pub const CAPACITY: usize = 256;
+
+    pub const fn new() -> Self {
+        ConstSerializer {
+            buf: [0; Self::CAPACITY],
+            len: 0,
+            needs_comma: false,
+        }
+    }
+
+    pub const fn null(self) -> Self {
+        self.value().extend(b"null")
+    }
+
+    pub const fn bool(self, b: bool) -> Self {
+        if b {
+            self.value().extend(b"true")
+        } else {
+            self.value().extend(b"false")
+        }
+    }
+
+    pub const fn uint(self, u: u64) -> Self {
+        self.value().digits(u)
+    }
+
+    pub const fn int(self, i: i64) -> Self {
+        let this = self.value();
+        if i < 0 {
+            this.push(b'-').digits(i.unsigned_abs())
+        } else {
+            this.digits(i.unsigned_abs())
+        }
+    }
+
+    pub const fn str(self, s: &str) -> Self {
+        self.value().escaped(s)
+    }
+
+    pub const fn begin_array(self) -> Self {
+        self.open(b'[')
+    }
+
+    pub const fn end_array(self) -> Self {
+        self.close(b']')
+    }
+
+    pub const fn begin_object(self) -> Self {
+        self.open(b'{')
+    }
+
+    pub const fn end_object(self) -> Self {
+        self.close(b'}')
+    }
+
+    /// The key of the object entry whose value is serialized next.
+    pub const fn key(self, key: &str) -> Self {
+        let mut this = self.comma().escaped(key).push(b':');
+        this.needs_comma = false;
+        this
+    }
+
+    /// The length of the output so far.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The output so far, outside of `const` contexts.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// The output, whose length `N` must be exactly [`len`](Self::len).
+    pub const fn finish<const N: usize>(self) -> [u8; N] {
+        // Out of bounds, hence a compile time error, on a length mismatch.
+        let _: u8 = [0][(self.len != N) as usize];
+        let mut out = [0; N];
+        let mut i = 0;
+        while i < N {
+            out[i] = self.buf[i];
+            i += 1;
+        }
+        out
+    }
+
+    const fn comma(self) -> Self {
+        if self.needs_comma {
+            self.push(b',')
+        } else {
+            self
+        }
+    }
+
+    /// Separate a value from the previous one, if any.
+    const fn value(self) -> Self {
+        let mut this = self.comma();
+        this.needs_comma = true;
+        this
+    }
+
+    const fn open(self, bracket: u8) -> Self {
+        let mut this = self.comma().push(bracket);
+        this.needs_comma = false;
+        this
+    }
+
+    const fn close(self, bracket: u8) -> Self {
+        let mut this = self.push(bracket);
+        this.needs_comma = true;
+        this
+    }
+
+    // Each digit, below 10, fits in a `u8`.
+    #[allow(clippy::cast_possible_truncation)]
+    const fn digits(self, mut u: u64) -> Self {
+        let mut digits = [0; 20];
+        let mut n = 0;
+        loop {
+            digits[n] = b'0' + (u % 10) as u8;
+            n += 1;
+            u /= 10;
+            if u == 0 {
+                break;
+            }
+        }
+        let mut this = self;
+        while n > 0 {
+            n -= 1;
+            this = this.push(digits[n]);
+        }
+        this
+    }
+
+    /// Like `escape_str`, with non-ASCII characters left as is.
+    const fn escaped(self, s: &str) -> Self {
+        const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+        let bytes = s.as_bytes();
+        let mut this = self.push(b'"');
+        let mut i = 0;
+        while i < bytes.len() {
+            let byte = bytes[i];
+            this = match byte {
+                b'"' | b'\\' => this.push(b'\\').push(byte),
+                0x08 => this.extend(b"\\b"),
+                0x09 => this.extend(b"\\t"),
+                0x0a => this.extend(b"\\n"),
+                0x0c => this.extend(b"\\f"),
+                0x0d => this.extend(b"\\r"),
+                0x00..=0x1f => this
+                    .extend(b"\\u00")
+                    .push(HEX_DIGITS[(byte >> 4) as usize])
+                    .push(HEX_DIGITS[(byte & 0xf) as usize]),
+                _ => this.push(byte),
+            };
+            i += 1;
+        }
+        this.push(b'"')
+    }
+
+    const fn extend(mut self, bytes: &[u8]) -> Self {
+        let mut i = 0;
+        while i < bytes.len() {
+            self = self.push(bytes[i]);
+            i += 1;
+        }
+        self
+    }
+
+    const fn push(mut self, byte: u8) -> Self {
+        self.buf[self.len] = byte;
+        self.len += 1;
+        self
+    }
+}
+
+impl Default for ConstSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
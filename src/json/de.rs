@@ -1,13 +1,25 @@
-use std::char;
-use std::mem;
-use std::str;
+use alloc::borrow::Cow;
+use alloc::collections::BTreeSet;
+use core::char;
+use core::marker::PhantomData;
+use core::mem;
+use core::str;
 
 use self::Event::*;
-use crate::de::{Deserialize, Map, Seq, Visitor};
+use crate::de::{
+    Deserialize, DeserializeBorrowed, DuplicateKeys, Map, Seq, Visitor, CANCEL_INTERVAL,
+};
+use crate::envelope::{Message, Registry};
 use crate::error::{Error, Result};
+use crate::json::Value;
+use crate::prelude::*;
+use crate::scratch;
 
 /// Deserialize a JSON string into any deserializable type.
 ///
+/// With the `arbitrary_precision` feature, numbers are kept as written, as
+/// with [`from_str_raw_numbers`].
+///
 /// ```rust
 /// use miniserde_ditto::{json, Deserialize};
 ///
@@ -28,41 +40,486 @@ use crate::error::{Error, Result};
 /// ```
 pub fn from_str<T: Deserialize>(j: &str) -> Result<T> {
     let mut out = None;
-    from_str_impl(j, T::begin(&mut out))?;
+    let options = Options {
+        raw_numbers: cfg!(feature = "arbitrary_precision"),
+        ..Options::default()
+    };
+    from_str_impl(j, T::begin(&mut out), options)?;
     out.ok_or(Error)
 }
 
-struct Deserializer<'a, 'b> {
+/// Like [`from_str`], but the strings of the output may borrow from `j`.
+///
+/// See [`DeserializeBorrowed`] for the types able to do so.
+///
+/// ```rust
+/// use std::borrow::Cow;
+/// use miniserde_ditto::{json, Deserialize};
+///
+/// #[derive(Deserialize, Debug)]
+/// #[serde(borrow)]
+/// struct Example<'a> {
+///     code: &'a str,
+///     message: Cow<'a, str>,
+/// }
+///
+/// let j = r#" {"code": "E200", "message": "reminiscent of \"Serde\""} "#;
+/// let out: Example<'_> = json::from_str_borrowed(j).unwrap();
+/// assert_eq!(out.code, "E200");
+/// assert!(matches!(out.message, Cow::Owned(_)));
+/// ```
+pub fn from_str_borrowed<'de, T: DeserializeBorrowed<'de>>(j: &'de str) -> Result<T> {
+    let mut out = None;
+    let options = Options {
+        borrow: true,
+        raw_numbers: cfg!(feature = "arbitrary_precision"),
+        ..Options::default()
+    };
+    // Safety: `j` lives for `'de`, and with `borrow` only its own slices are
+    // handed to `Visitor::borrowed_string`.
+    from_str_impl(j, unsafe { T::begin_borrowed(&mut out) }, options)?;
+    out.ok_or(Error)
+}
+
+/// Like [`from_str`], but numbers keep their original formatting.
+///
+/// Numbers are handed to [`Visitor::raw_number`] as written in the input,
+/// so that a [`Value`] gets [`Number::Raw`] numbers, which [`to_string`]
+/// emits verbatim. Other types parse the number as usual.
+///
+/// ```rust
+/// use miniserde_ditto::json::{self, Value};
+///
+/// let value: Value = json::from_str_raw_numbers(r#"{"price":1.10}"#).unwrap();
+/// assert_eq!(json::to_string(&value).unwrap(), r#"{"price":1.10}"#);
+/// ```
+///
+/// [`Value`]: crate::json::Value
+/// [`Number::Raw`]: crate::json::Number::Raw
+/// [`to_string`]: crate::json::to_string
+pub fn from_str_raw_numbers<T: Deserialize>(j: &str) -> Result<T> {
+    let mut out = None;
+    let options = Options {
+        raw_numbers: true,
+        ..Options::default()
+    };
+    from_str_impl(j, T::begin(&mut out), options)?;
+    out.ok_or(Error)
+}
+
+/// Update `target` with the fields present in a JSON object, leaving the
+/// others as they are.
+///
+/// The object is merged in place through the derived `Deserialize` of
+/// `target`: nested structs, and `Option`s holding one, are merged the same
+/// way, a field set to `null` clears an `Option`, and any other field, such as
+/// a map or a sequence, is replaced whole. Unknown fields are ignored.
+///
+/// Malformed JSON leaves `target` untouched; on a value of the wrong type, the
+/// fields before it are kept as merged.
+///
+/// ```rust
+/// use miniserde_ditto::{json, Deserialize};
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// struct Settings {
+///     volume: u8,
+///     theme: Option<String>,
+/// }
+///
+/// let mut settings = Settings { volume: 5, theme: Some("dark".to_owned()) };
+/// json::merge_from_str(&mut settings, r#"{"volume": 7}"#)?;
+/// assert_eq!(settings, Settings { volume: 7, theme: Some("dark".to_owned()) });
+/// # Ok::<(), miniserde_ditto::Error>(())
+/// ```
+pub fn merge_from_str<T: Deserialize>(target: &mut T, j: &str) -> Result<()> {
+    check_raw_value(j)?;
+    let mut staged = None;
+    let visitor = match T::begin_merge(target) {
+        Some(visitor) => visitor,
+        None => T::begin(&mut staged),
+    };
+    from_str_impl(j, visitor, Options::default())?;
+    if let Some(value) = staged {
+        *target = value;
+    }
+    Ok(())
+}
+
+/// Decode an [`Envelope`](crate::envelope::Envelope) whose payload may be of
+/// any of the types of `registry`.
+///
+/// See [`envelope`](crate::envelope) for an example.
+pub fn decode_envelope(j: &str, registry: &Registry) -> Result<Message> {
+    let mut visitor = registry.visitor();
+    let options = Options {
+        raw_numbers: cfg!(feature = "arbitrary_precision"),
+        ..Options::default()
+    };
+    from_str_impl(j, &mut visitor, options)?;
+    visitor.out.ok_or(Error)
+}
+
+/// Iterate over the JSON values of `j`, one after the other, such as the
+/// lines of a JSON Lines (NDJSON) document.
+///
+/// Values may be separated by any whitespace, or by nothing when that is
+/// unambiguous (`{}{}`). The iterator stops after the first error.
+///
+/// ```rust
+/// use miniserde_ditto::{json, Deserialize};
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// struct Entry {
+///     level: String,
+///     code: u32,
+/// }
+///
+/// let j = "{\"level\":\"info\",\"code\":1}\n{\"level\":\"warn\",\"code\":2}\n";
+/// let entries = json::from_str_multi::<Entry>(j).collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(entries[1].level, "warn");
+/// # Ok::<(), miniserde_ditto::Error>(())
+/// ```
+pub fn from_str_multi<T: Deserialize>(j: &str) -> StreamDeserializer<'_, '_, T> {
+    Deserializer::new().from_str_multi(j)
+}
+
+/// An iterator over the JSON values of a string, as returned by
+/// [`from_str_multi`].
+pub struct StreamDeserializer<'a, 'j, T> {
+    deserializer: Deserializer<'a>,
+    input: &'j str,
+    pos: usize,
+    failed: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, 'j, T> StreamDeserializer<'a, 'j, T> {
+    /// How many bytes of the input were read so far: the end of the last
+    /// value deserialized.
+    pub fn byte_offset(&self) -> usize {
+        self.pos
+    }
+}
+
+impl<'a, 'j, T: Deserialize> Iterator for StreamDeserializer<'a, 'j, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        if self.failed {
+            return None;
+        }
+        let rest = &self.input[self.pos..];
+        let value = rest.trim_start_matches(&[' ', '\t', '\n', '\r'][..]);
+        if value.is_empty() {
+            return None;
+        }
+        self.pos += rest.len() - value.len();
+        let mut out = None;
+        let options = Options {
+            trailing: TrailingData::Ignored,
+            ..self.deserializer.options()
+        };
+        let result = from_str_impl(value, T::begin(&mut out), options).and_then(|len| {
+            self.pos += len;
+            out.ok_or(Error)
+        });
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
+
+/// A JSON deserializer with non-default options.
+///
+/// ```rust
+/// use miniserde_ditto::json;
+/// use std::sync::atomic::{AtomicBool, Ordering};
+///
+/// let cancelled = AtomicBool::new(false);
+/// let should_cancel = || cancelled.load(Ordering::Relaxed);
+/// let deserializer = json::Deserializer::new().should_cancel(&should_cancel);
+///
+/// let j = format!("[{}0]", "0,".repeat(10_000));
+/// assert_eq!(deserializer.from_str::<Vec<u8>>(&j).unwrap().len(), 10_001);
+/// cancelled.store(true, Ordering::Relaxed);
+/// assert!(deserializer.from_str::<Vec<u8>>(&j).is_err());
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct Deserializer<'a> {
+    should_cancel: Option<&'a dyn Fn() -> bool>,
+    progress: Option<&'a dyn Fn(usize)>,
+    max_depth: Option<usize>,
+    trailing: TrailingData,
+    duplicate_keys: DuplicateKeys,
+}
+
+/// What may follow the JSON value, for [`Deserializer::trailing`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrailingData {
+    /// Only whitespace. This is the default.
+    Whitespace,
+    /// Nothing, not even a trailing newline.
+    Forbidden,
+    /// Anything, which is left unread.
+    Ignored,
+}
+
+impl Default for TrailingData {
+    fn default() -> Self {
+        TrailingData::Whitespace
+    }
+}
+
+impl<'a> Deserializer<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Abort deserializing, with an error, once `should_cancel` returns
+    /// `true`. It is called every 1024 values, so that other threads can
+    /// interrupt the decoding of huge documents.
+    pub fn should_cancel(mut self, should_cancel: &'a dyn Fn() -> bool) -> Self {
+        self.should_cancel = Some(should_cancel);
+        self
+    }
+
+    /// Call `progress` with the number of bytes read so far each time an
+    /// array or object is closed, such as to report the progress of a large
+    /// import.
+    pub fn progress(mut self, progress: &'a dyn Fn(usize)) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// How deeply arrays and objects may be nested; unlimited by default.
+    /// Parsing itself does not recurse, but a deep input still takes memory,
+    /// as can dropping the deserialized value.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// What may follow the JSON value.
+    pub fn trailing(mut self, trailing: TrailingData) -> Self {
+        self.trailing = trailing;
+        self
+    }
+
+    /// What to do with a key appearing twice in the same object.
+    pub fn duplicate_keys(mut self, policy: DuplicateKeys) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Deserialize a JSON string into any deserializable type, as
+    /// [`from_str`] does.
+    pub fn from_str<T: Deserialize>(&self, j: &str) -> Result<T> {
+        let mut out = None;
+        from_str_impl(j, T::begin(&mut out), self.options())?;
+        out.ok_or(Error)
+    }
+
+    /// Iterate over the JSON values of `j`, as [`from_str_multi`] does. The
+    /// `trailing` option is ignored.
+    pub fn from_str_multi<'j, T: Deserialize>(&self, j: &'j str) -> StreamDeserializer<'a, 'j, T> {
+        StreamDeserializer {
+            deserializer: *self,
+            input: j,
+            pos: 0,
+            failed: false,
+            _marker: PhantomData,
+        }
+    }
+
+    fn options(&self) -> Options<'a> {
+        Options {
+            raw_numbers: cfg!(feature = "arbitrary_precision"),
+            should_cancel: self.should_cancel,
+            progress: self.progress,
+            max_depth: self.max_depth,
+            trailing: self.trailing,
+            duplicate_keys: self.duplicate_keys,
+            ..Options::default()
+        }
+    }
+}
+
+/// Resolve the escape sequences of the contents of a JSON string, given
+/// without the surrounding quotes.
+///
+/// The result borrows from `s` when there is nothing to unescape. It is an
+/// error for `s` to hold an unescaped quote or control character, or an
+/// incorrect escape sequence. See [`escape`](crate::json::escape) for the other
+/// way around.
+///
+/// ```rust
+/// use miniserde_ditto::json;
+/// use std::borrow::Cow;
+///
+/// assert_eq!(json::unescape(r#"say \"hi\" \ud83d\ude00"#)?, "say \"hi\" 😀");
+/// assert!(matches!(json::unescape("plain")?, Cow::Borrowed("plain")));
+/// assert!(json::unescape(r"\x").is_err());
+/// # Ok::<(), miniserde_ditto::Error>(())
+/// ```
+pub fn unescape(s: &str) -> Result<Cow<'_, str>> {
+    let mut de = Parser {
+        input: s.as_bytes(),
+        pos: 0,
+        buffer: Vec::new(),
+        stack: Vec::new(),
+        options: Options::default(),
+    };
+    // Index of the first byte not yet copied into the scratch space.
+    let mut start = 0;
+    let mut escaped = false;
+    while let Some(byte) = de.next() {
+        match byte {
+            b'\\' => {
+                scratch::reserve(&mut de.buffer, de.pos - 1 - start);
+                de.buffer.extend_from_slice(&de.input[start..de.pos - 1]);
+                de.parse_escape()?;
+                start = de.pos;
+                escaped = true;
+            }
+            _ if ESCAPE[usize::from(byte)] => {
+                err!(
+                    "Unescaped character \\x{:02x} at index {}",
+                    byte,
+                    de.pos - 1
+                );
+            }
+            _ => {}
+        }
+    }
+    if !escaped {
+        return Ok(Cow::Borrowed(s));
+    }
+    scratch::reserve(&mut de.buffer, s.len() - start);
+    de.buffer.extend_from_slice(&de.input[start..]);
+    let buffer = mem::take(&mut de.buffer);
+    // Slices of `s` between escapes, and the characters they stand for.
+    Ok(Cow::Owned(unsafe { String::from_utf8_unchecked(buffer) }))
+}
+
+/// Deserialize `j` into `visitor`, as [`from_str`] does, such as to parse the
+/// text of a `ValueView::RawJson`.
+pub(crate) fn from_str_into(j: &str, visitor: &mut dyn Visitor) -> Result<()> {
+    let options = Options {
+        raw_numbers: cfg!(feature = "arbitrary_precision"),
+        ..Options::default()
+    };
+    from_str_impl(j, visitor, options).map(drop)
+}
+
+/// Checks that `j` holds a single JSON value, as held by `RawValue`.
+pub(crate) fn check_raw_value(j: &str) -> Result<()> {
+    from_str_into(j, <dyn Visitor>::ignore())
+}
+
+/// Checks the text of a single JSON number, as held by `Number::Raw`.
+pub(crate) fn check_raw_number(raw: &str) -> Result<()> {
+    let mut de = Parser {
+        input: raw.as_bytes(),
+        pos: 0,
+        buffer: Vec::new(),
+        stack: Vec::new(),
+        options: Options {
+            raw_numbers: true,
+            ..Options::default()
+        },
+    };
+    match de.event()? {
+        RawNumber(_) => {}
+        _ => err!("Expected a JSON number, got {:?}", raw),
+    }
+    if de.pos != raw.len() {
+        err!("Unexpected trailing content in JSON number {:?}", raw);
+    }
+    Ok(())
+}
+
+struct Parser<'a, 'b> {
     input: &'a [u8],
     pos: usize,
     buffer: Vec<u8>,
     stack: Vec<(&'b mut dyn Visitor, Layer<'b>)>,
+    options: Options<'a>,
+}
+
+/// Parsing knobs, for the entry points other than `from_str`.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Options<'a> {
+    /// Hand numbers to `Visitor::raw_number`.
+    pub raw_numbers: bool,
+    /// Accept the JSON5 extensions: comments, trailing commas, unquoted or
+    /// single-quoted keys, single-quoted strings, hexadecimal numbers,
+    /// `Infinity` and `NaN`.
+    pub json5: bool,
+    /// Hand the strings appearing as is in the input to
+    /// `Visitor::borrowed_string`. Only sound if the visitor may borrow
+    /// from the input, see `DeserializeBorrowed::begin_borrowed`.
+    pub borrow: bool,
+    /// See `Deserializer::should_cancel`.
+    pub should_cancel: Option<&'a dyn Fn() -> bool>,
+    /// See `Deserializer::progress`.
+    pub progress: Option<&'a dyn Fn(usize)>,
+    /// See `Deserializer::max_depth`.
+    pub max_depth: Option<usize>,
+    /// See `Deserializer::trailing`.
+    pub trailing: TrailingData,
+    /// See `Deserializer::duplicate_keys`.
+    pub duplicate_keys: DuplicateKeys,
 }
 
 enum Layer<'a> {
     Seq(Box<dyn Seq + 'a>),
-    Map(Box<dyn Map + 'a>),
+    Map(Box<dyn Map + 'a>, KeysSeen),
 }
 
-impl<'a, 'b> Drop for Deserializer<'a, 'b> {
+/// The keys of an object, unless duplicates are left to the `Map`.
+type KeysSeen = Option<BTreeSet<Box<str>>>;
+
+impl<'a, 'b> Drop for Parser<'a, 'b> {
     fn drop(&mut self) {
         // Drop layers in reverse order.
         while !self.stack.is_empty() {
             self.stack.pop();
         }
+        scratch::clear(&mut self.buffer);
     }
 }
 
-fn from_str_impl(j: &str, mut visitor: &mut dyn Visitor) -> Result<()> {
-    let mut de = Deserializer {
+/// Returns how many bytes were read, which is all of `j` unless trailing
+/// data is ignored.
+pub(crate) fn from_str_impl(
+    j: &str,
+    mut visitor: &mut dyn Visitor,
+    options: Options<'_>,
+) -> Result<usize> {
+    let mut de = Parser {
         input: j.as_bytes(),
         pos: 0,
         buffer: Vec::new(),
         stack: Vec::new(),
+        options,
     };
+    let input = j.as_bytes().as_ptr_range();
+    let mut count = 0_u32;
 
     'outer: loop {
-        let layer = match de.event()? {
+        if let Some(should_cancel) = options.should_cancel {
+            count = count.wrapping_add(1);
+            if count % CANCEL_INTERVAL == 0 && should_cancel() {
+                err!("Cancelled JSON deserialization at index {}", de.pos);
+            }
+        }
+        let depth = de.stack.len();
+        let event = if visitor.wants_raw() {
+            de.raw_value(depth)?
+        } else {
+            de.event()?
+        };
+        let layer = match event {
             Null => {
                 visitor.null()?;
                 None
@@ -75,21 +532,46 @@ fn from_str_impl(j: &str, mut visitor: &mut dyn Visitor) -> Result<()> {
                 visitor.int(i)?;
                 None
             }
+            #[cfg(feature = "float")]
             Float(n) => {
                 visitor.float(n)?;
                 None
             }
+            RawNumber(raw) => {
+                visitor.raw_number(raw)?;
+                None
+            }
+            BigInteger(raw) => {
+                visitor.big_integer(raw)?;
+                None
+            }
+            RawJson(raw) => {
+                visitor.raw_json(&raw)?;
+                None
+            }
             Str(s) => {
-                visitor.string(s)?;
+                // Unless copied into the scratch space to unescape it.
+                if options.borrow && input.contains(&s.as_ptr()) {
+                    visitor.borrowed_string(s)?;
+                } else {
+                    visitor.string(s)?;
+                }
                 None
             }
+            SeqStart | MapStart if Some(depth) == options.max_depth => {
+                err!("Exceeded the maximum depth at index {}", de.pos);
+            }
             SeqStart => {
                 let seq = careful!(visitor.seq()? as Box<dyn Seq>);
                 Some(Layer::Seq(seq))
             }
             MapStart => {
                 let map = careful!(visitor.map()? as Box<dyn Map>);
-                Some(Layer::Map(map))
+                let keys = match options.duplicate_keys {
+                    DuplicateKeys::LastWins => None,
+                    DuplicateKeys::FirstWins | DuplicateKeys::Error => Some(BTreeSet::new()),
+                };
+                Some(Layer::Map(map, keys))
             }
         };
 
@@ -113,15 +595,25 @@ fn from_str_impl(j: &str, mut visitor: &mut dyn Visitor) -> Result<()> {
             match de.parse_whitespace().unwrap_or(b'\0') {
                 b',' if accept_comma => {
                     de.bump();
+                    if de.options.json5 {
+                        // Trailing comma: let the next iteration close the layer.
+                        if let Some(b']') | Some(b'}') = de.parse_whitespace() {
+                            accept_comma = false;
+                            continue;
+                        }
+                    }
                     break;
                 }
                 close @ b']' | close @ b'}' => {
                     de.bump();
                     match layer {
                         Layer::Seq(seq) if close == b']' => seq.finish()?,
-                        Layer::Map(map) if close == b'}' => map.finish()?,
+                        Layer::Map(map, _) if close == b'}' => map.finish()?,
                         _ => err!("Incorrect closing delimeter at index {}", de.pos),
                     };
+                    if let Some(progress) = options.progress {
+                        progress(de.pos);
+                    }
                     let frame = match de.stack.pop() {
                         Some(frame) => frame,
                         None => break 'outer,
@@ -146,30 +638,46 @@ fn from_str_impl(j: &str, mut visitor: &mut dyn Visitor) -> Result<()> {
                 let outer = mem::replace(&mut visitor, inner);
                 de.stack.push((outer, Layer::Seq(seq)));
             }
-            Layer::Map(mut map) => {
-                match de.parse_whitespace() {
-                    Some(b'"') => de.bump(),
-                    _ => err!("Missing `\"` at index {}", de.pos),
-                }
+            Layer::Map(mut map, mut keys) => {
                 let inner = {
-                    let k = de.parse_str()?;
-                    let out_v = map.val_with_key(&mut |it| it.and_then(|out_k| out_k.string(k)))?;
-                    careful!(out_v as &mut dyn Visitor)
+                    let k = de.parse_key()?;
+                    let duplicate = match keys {
+                        Some(ref mut seen) => !seen.insert(k.into()),
+                        None => false,
+                    };
+                    if duplicate {
+                        if options.duplicate_keys == DuplicateKeys::Error {
+                            err!("Duplicate key {:?} in JSON object", k);
+                        }
+                        <dyn Visitor>::ignore()
+                    } else {
+                        let out_v =
+                            map.val_with_key(&mut |it| it.and_then(|out_k| out_k.string(k)))?;
+                        careful!(out_v as &mut dyn Visitor)
+                    }
                 };
                 match de.parse_whitespace() {
                     Some(b':') => de.bump(),
                     _ => err!("Missing `:` at index {}", de.pos),
                 }
                 let outer = mem::replace(&mut visitor, inner);
-                de.stack.push((outer, Layer::Map(map)));
+                de.stack.push((outer, Layer::Map(map, keys)));
             }
         }
     }
 
-    match de.parse_whitespace() {
-        Some(_) => err!("Unexpected trailing content at index {}", de.pos),
-        None => Ok(()),
+    match options.trailing {
+        TrailingData::Ignored => {}
+        TrailingData::Forbidden if de.pos < j.len() => {
+            err!("Unexpected trailing content at index {}", de.pos);
+        }
+        _ => {
+            if de.parse_whitespace().is_some() {
+                err!("Unexpected trailing content at index {}", de.pos);
+            }
+        }
     }
+    Ok(de.pos)
 }
 
 enum Event<'a> {
@@ -177,7 +685,13 @@ enum Event<'a> {
     Bool(bool),
     Str(&'a str),
     Int(i128),
+    #[cfg(feature = "float")]
     Float(f64),
+    RawNumber(&'a str),
+    /// An integer beyond `i128`, as written in the input.
+    BigInteger(&'a str),
+    /// A whole value, for a visitor that wants it raw.
+    RawJson(Cow<'a, str>),
     SeqStart,
     MapStart,
 }
@@ -188,7 +702,7 @@ macro_rules! overflow {
     };
 }
 
-impl<'a, 'b> Deserializer<'a, 'b> {
+impl<'a, 'b> Parser<'a, 'b> {
     fn next(&mut self) -> Option<u8> {
         if self.pos < self.input.len() {
             let ch = self.input[self.pos];
@@ -219,7 +733,8 @@ impl<'a, 'b> Deserializer<'a, 'b> {
         self.pos += 1;
     }
 
-    fn parse_str(&mut self) -> Result<&str> {
+    /// Parses a string, once past its opening `quote`.
+    fn parse_str(&mut self, quote: u8) -> Result<&str> {
         fn result(bytes: &[u8]) -> &str {
             // The input is assumed to be valid UTF-8 and the \u-escapes are
             // checked along the way, so don't need to check here.
@@ -228,17 +743,25 @@ impl<'a, 'b> Deserializer<'a, 'b> {
 
         // Index of the first byte not yet copied into the scratch space.
         let mut start = self.pos;
-        self.buffer.clear();
+        scratch::clear(&mut self.buffer);
 
         loop {
-            while self.pos < self.input.len() && !ESCAPE[usize::from(self.input[self.pos])] {
+            #[cfg(feature = "simd")]
+            {
+                let rest = &self.input[self.pos..];
+                self.pos += super::swar::plain_len(rest, &[quote, b'"', b'\\'], false);
+            }
+            while self.pos < self.input.len()
+                && !ESCAPE[usize::from(self.input[self.pos])]
+                && self.input[self.pos] != quote
+            {
                 self.pos += 1;
             }
             if self.pos == self.input.len() {
                 err!("Unexpected end of input");
             }
             match self.input[self.pos] {
-                b'"' => {
+                end if end == quote => {
                     if self.buffer.is_empty() {
                         // Fast path: return a slice of the raw JSON without any
                         // copying.
@@ -246,17 +769,21 @@ impl<'a, 'b> Deserializer<'a, 'b> {
                         self.pos += 1;
                         return Ok(result(borrowed));
                     } else {
+                        scratch::reserve(&mut self.buffer, self.pos - start);
                         self.buffer.extend_from_slice(&self.input[start..self.pos]);
                         self.pos += 1;
                         return Ok(result(&self.buffer));
                     }
                 }
                 b'\\' => {
+                    scratch::reserve(&mut self.buffer, self.pos - start);
                     self.buffer.extend_from_slice(&self.input[start..self.pos]);
                     self.pos += 1;
                     self.parse_escape()?;
                     start = self.pos;
                 }
+                // Within a single-quoted JSON5 string.
+                b'"' => self.pos += 1,
                 control_char => err!(
                     r#"Incorrect control character \x{:02x} at index {}"#,
                     control_char,
@@ -266,6 +793,32 @@ impl<'a, 'b> Deserializer<'a, 'b> {
         }
     }
 
+    /// Skips over the next value, whose text is handed to a visitor that
+    /// wants it raw: as is, or reformatted as JSON if it is JSON5.
+    fn raw_value(&mut self, depth: usize) -> Result<Event<'_>> {
+        self.parse_whitespace();
+        let input = self.input;
+        // The input is a `str`, and whitespace ends at a char boundary.
+        let rest = unsafe { str::from_utf8_unchecked(&input[self.pos..]) };
+        let options = Options {
+            borrow: false,
+            progress: None,
+            max_depth: self.options.max_depth.map(|max_depth| max_depth - depth),
+            trailing: TrailingData::Ignored,
+            ..self.options
+        };
+        if self.options.json5 {
+            let mut value = None;
+            self.pos += from_str_impl(rest, Value::begin(&mut value), options)?;
+            let j = crate::json::to_string(&value.ok_or(Error)?)?;
+            Ok(RawJson(Cow::Owned(j)))
+        } else {
+            let len = from_str_impl(rest, <dyn Visitor>::ignore(), options)?;
+            self.pos += len;
+            Ok(RawJson(Cow::Borrowed(&rest[..len])))
+        }
+    }
+
     fn next_or_eof(&mut self) -> Result<u8> {
         self.next().ok_or(Error)
     }
@@ -274,9 +827,12 @@ impl<'a, 'b> Deserializer<'a, 'b> {
     /// the previous byte read was a backslash.
     fn parse_escape(&mut self) -> Result<()> {
         let ch = self.next_or_eof()?;
+        // The longest unescaped character.
+        scratch::reserve(&mut self.buffer, 4);
 
         match ch {
             b'"' => self.buffer.push(b'"'),
+            b'\'' if self.options.json5 => self.buffer.push(b'\''),
             b'\\' => self.buffer.push(b'\\'),
             b'/' => self.buffer.push(b'/'),
             b'b' => self.buffer.push(b'\x08'),
@@ -360,6 +916,34 @@ impl<'a, 'b> Deserializer<'a, 'b> {
                 Some(b' ') | Some(b'\n') | Some(b'\t') | Some(b'\r') => {
                     self.bump();
                 }
+                Some(b'/') if self.options.json5 => match self.input.get(self.pos + 1) {
+                    Some(b'/') => {
+                        while let Some(ch) = self.next() {
+                            if ch == b'\n' {
+                                break;
+                            }
+                        }
+                    }
+                    Some(b'*') => {
+                        let start = self.pos;
+                        self.pos += 2;
+                        loop {
+                            match self.next() {
+                                Some(b'*') if self.peek() == Some(b'/') => {
+                                    self.bump();
+                                    break;
+                                }
+                                Some(_) => {}
+                                None => {
+                                    // Unterminated: report the comment itself.
+                                    self.pos = start;
+                                    return Some(b'/');
+                                }
+                            }
+                        }
+                    }
+                    _ => return Some(b'/'),
+                },
                 other => {
                     return other;
                 }
@@ -367,6 +951,32 @@ impl<'a, 'b> Deserializer<'a, 'b> {
         }
     }
 
+    fn parse_key(&mut self) -> Result<&str> {
+        match self.parse_whitespace() {
+            Some(b'"') => {
+                self.bump();
+                self.parse_str(b'"')
+            }
+            Some(b'\'') if self.options.json5 => {
+                self.bump();
+                self.parse_str(b'\'')
+            }
+            Some(b'a'..=b'z') | Some(b'A'..=b'Z') | Some(b'_') | Some(b'$')
+                if self.options.json5 =>
+            {
+                let start = self.pos;
+                while let Some(b'a'..=b'z') | Some(b'A'..=b'Z') | Some(b'0'..=b'9') | Some(b'_')
+                | Some(b'$') = self.peek()
+                {
+                    self.bump();
+                }
+                // Identifiers are ASCII, so this slice of the input is valid UTF-8.
+                Ok(unsafe { str::from_utf8_unchecked(&self.input[start..self.pos]) })
+            }
+            _ => err!("Missing `\"` at index {}", self.pos),
+        }
+    }
+
     fn parse_ident(&mut self, ident: &[u8]) -> Result<()> {
         for &expected in ident {
             if self.next() != Some(expected) {
@@ -376,7 +986,7 @@ impl<'a, 'b> Deserializer<'a, 'b> {
         Ok(())
     }
 
-    fn parse_integer(&mut self, nonnegative: bool, first_digit: u8) -> Result<Event<'_>> {
+    fn parse_integer(&mut self, nonnegative: bool, first_digit: u8) -> Result<Event<'static>> {
         match first_digit {
             b'0' => {
                 // There can be only one leading '0'.
@@ -396,15 +1006,9 @@ impl<'a, 'b> Deserializer<'a, 'b> {
 
                             // We need to be careful with overflow. If we can, try to keep the
                             // number as a `u64` until we grow too large. At that point, switch to
-                            // parsing the value as a `f64`.
+                            // parsing the value as a `u128`, and then as a `f64`.
                             if overflow!(res * 10 + digit, u64::max_value()) {
-                                return self
-                                    .parse_long_integer(
-                                        nonnegative,
-                                        res,
-                                        1, // res * 10^1
-                                    )
-                                    .map(Float);
+                                return self.parse_long_integer(nonnegative, res, digit);
                             }
 
                             res = res * 10 + digit;
@@ -419,37 +1023,94 @@ impl<'a, 'b> Deserializer<'a, 'b> {
         }
     }
 
+    /// Integers too big for `u64` are kept exact as far as `i128` goes, and
+    /// as far as `u128` goes as their text, in a `BigInteger` (see `event`).
+    /// Beyond that, they are floats.
     fn parse_long_integer(
         &mut self,
         nonnegative: bool,
         significand: u64,
-        mut exponent: i32,
-    ) -> Result<f64> {
+        digit: u64,
+    ) -> Result<Event<'static>> {
+        use core::convert::TryFrom;
+        let mut res = Some(u128::from(significand) * 10 + u128::from(digit));
+        #[cfg(feature = "float")]
+        let mut exponent = 1; // significand * 10^exponent
         loop {
             match self.peek_or_nul() {
-                b'0'..=b'9' => {
+                c @ b'0'..=b'9' => {
                     self.bump();
+                    res =
+                        res.and_then(|res| res.checked_mul(10)?.checked_add(u128::from(c - b'0')));
                     // This could overflow... if your integer is gigabytes long.
                     // Ignore that possibility.
-                    exponent += 1;
+                    #[cfg(feature = "float")]
+                    {
+                        exponent += 1;
+                    }
                 }
+                #[cfg(feature = "float")]
                 b'.' => {
-                    return self.parse_decimal(nonnegative, significand, exponent);
+                    return self
+                        .parse_decimal(nonnegative, significand, exponent)
+                        .map(Float);
                 }
+                #[cfg(feature = "float")]
                 b'e' | b'E' => {
-                    return self.parse_exponent(nonnegative, significand, exponent);
-                }
-                _ => {
-                    return f64_from_parts(nonnegative, significand, exponent);
+                    return self
+                        .parse_exponent(nonnegative, significand, exponent)
+                        .map(Float);
                 }
+                #[cfg(not(feature = "float"))]
+                b'.' | b'e' | b'E' => err!("Unsupported float at index {}", self.pos),
+                _ => break,
             }
         }
+        match res {
+            Some(res) if nonnegative => Ok(i128::try_from(res).map_or(BigInteger(""), Int)),
+            Some(res) if res <= 1 << 127 => Ok(Int((res as i128).wrapping_neg())),
+            #[cfg(feature = "float")]
+            _ => f64_from_parts(nonnegative, significand, exponent).map(Float),
+            #[cfg(not(feature = "float"))]
+            _ => err!("Integer out of range at index {}", self.pos),
+        }
     }
 
-    fn parse_number(&mut self, nonnegative: bool, significand: u64) -> Result<Event<'_>> {
+    fn parse_hex(&mut self, nonnegative: bool) -> Result<Event<'static>> {
+        let mut res: u64 = 0;
+        let mut at_least_one_digit = false;
+        loop {
+            let digit = match self.peek_or_nul() {
+                c @ b'0'..=b'9' => c - b'0',
+                c @ b'a'..=b'f' => c - b'a' + 10,
+                c @ b'A'..=b'F' => c - b'A' + 10,
+                _ => break,
+            };
+            self.bump();
+            at_least_one_digit = true;
+            if res > u64::max_value() >> 4 {
+                err!("Hexadecimal number out of range at index {}", self.pos);
+            }
+            res = res << 4 | u64::from(digit);
+        }
+        if !at_least_one_digit {
+            err!("Expected a hex digit at index {}", self.pos);
+        }
+        Ok(if nonnegative {
+            Int(res as i128)
+        } else {
+            Int(-(res as i128))
+        })
+    }
+
+    fn parse_number(&mut self, nonnegative: bool, significand: u64) -> Result<Event<'static>> {
         match self.peek_or_nul() {
+            #[cfg(feature = "float")]
             b'.' => self.parse_decimal(nonnegative, significand, 0).map(Float),
+            #[cfg(feature = "float")]
             b'e' | b'E' => self.parse_exponent(nonnegative, significand, 0).map(Float),
+            #[cfg(not(feature = "float"))]
+            b'.' | b'e' | b'E' => err!("Unsupported float at index {}", self.pos),
             _ => Ok(if nonnegative {
                 Int(significand as i128)
             } else {
@@ -458,6 +1119,47 @@ impl<'a, 'b> Deserializer<'a, 'b> {
         }
     }
 
+    /// Raw numbers are only checked to be valid JSON: their text is all that
+    /// matters, whatever their magnitude.
+    fn parse_raw_number(&mut self, first_digit: u8) -> Result<()> {
+        match first_digit {
+            // There can be only one leading '0'.
+            b'0' if self.peek_or_nul().is_ascii_digit() => {
+                err!("Incorrect leading `0` at index {}", self.pos)
+            }
+            b'0' => {}
+            b'1'..=b'9' => {
+                while self.peek_or_nul().is_ascii_digit() {
+                    self.bump();
+                }
+            }
+            _ => err!("Expected a digit at index {}", self.pos),
+        }
+        if self.peek_or_nul() == b'.' {
+            self.bump();
+            self.parse_digits()?;
+        }
+        if let b'e' | b'E' = self.peek_or_nul() {
+            self.bump();
+            if let b'+' | b'-' = self.peek_or_nul() {
+                self.bump();
+            }
+            self.parse_digits()?;
+        }
+        Ok(())
+    }
+
+    fn parse_digits(&mut self) -> Result<()> {
+        if !self.peek_or_nul().is_ascii_digit() {
+            err!("Expected a digit at index {}", self.pos);
+        }
+        while self.peek_or_nul().is_ascii_digit() {
+            self.bump();
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "float")]
     fn parse_decimal(
         &mut self,
         nonnegative: bool,
@@ -495,6 +1197,7 @@ impl<'a, 'b> Deserializer<'a, 'b> {
         }
     }
 
+    #[cfg(feature = "float")]
     fn parse_exponent(
         &mut self,
         nonnegative: bool,
@@ -547,6 +1250,7 @@ impl<'a, 'b> Deserializer<'a, 'b> {
     // exponent-parsing loop above.
     #[cold]
     #[inline(never)]
+    #[cfg(feature = "float")]
     fn parse_exponent_overflow(
         &mut self,
         nonnegative: bool,
@@ -571,11 +1275,40 @@ impl<'a, 'b> Deserializer<'a, 'b> {
         };
         self.bump();
         match peek {
-            b'"' => self.parse_str().map(Str),
-            digit @ b'0'..=b'9' => self.parse_integer(true, digit),
-            b'-' => {
-                let first_digit = self.next_or_nul();
-                self.parse_integer(false, first_digit)
+            b'"' => self.parse_str(b'"').map(Str),
+            b'\'' if self.options.json5 => self.parse_str(b'\'').map(Str),
+            b'-' | b'0'..=b'9' => {
+                let start = self.pos - 1;
+                let nonnegative = peek != b'-';
+                let first_digit = if nonnegative {
+                    peek
+                } else {
+                    self.next_or_nul()
+                };
+                let number = match (first_digit, self.peek_or_nul()) {
+                    (b'0', b'x') | (b'0', b'X') if self.options.json5 => {
+                        self.bump();
+                        self.parse_hex(nonnegative)?
+                    }
+                    #[cfg(feature = "float")]
+                    (b'I', _) if self.options.json5 && !nonnegative => {
+                        self.parse_ident(b"nfinity")?;
+                        Float(::core::f64::NEG_INFINITY)
+                    }
+                    _ if self.options.raw_numbers => {
+                        self.parse_raw_number(first_digit)?;
+                        Int(0)
+                    }
+                    _ => self.parse_integer(nonnegative, first_digit)?,
+                };
+                // Numbers are ASCII, so this slice of the input is valid UTF-8.
+                let raw = || unsafe { str::from_utf8_unchecked(&self.input[start..self.pos]) };
+                Ok(match number {
+                    _ if self.options.raw_numbers => RawNumber(raw()),
+                    // Integers beyond `i128` are handed over as their text too.
+                    BigInteger(_) => BigInteger(raw()),
+                    number => number,
+                })
             }
             b'{' => Ok(MapStart),
             b'[' => Ok(SeqStart),
@@ -591,11 +1324,22 @@ impl<'a, 'b> Deserializer<'a, 'b> {
                 self.parse_ident(b"alse")?;
                 Ok(Bool(false))
             }
+            #[cfg(feature = "float")]
+            b'I' if self.options.json5 => {
+                self.parse_ident(b"nfinity")?;
+                Ok(Float(::core::f64::INFINITY))
+            }
+            #[cfg(feature = "float")]
+            b'N' if self.options.json5 => {
+                self.parse_ident(b"aN")?;
+                Ok(Float(::core::f64::NAN))
+            }
             _ => err!(r#"Unexpected char \x{:02x} at index {}"#, peek, self.pos),
         }
     }
 }
 
+#[cfg(feature = "float")]
 fn f64_from_parts(nonnegative: bool, significand: u64, mut exponent: i32) -> Result<f64> {
     let mut f = significand as f64;
     loop {
@@ -627,6 +1371,7 @@ fn f64_from_parts(nonnegative: bool, significand: u64, mut exponent: i32) -> Res
 }
 
 // Clippy bug: https://github.com/rust-lang/rust-clippy/issues/5201
+#[cfg(feature = "float")]
 #[allow(clippy::excessive_precision)]
 static POW10: [f64; 309] = [
     1e000, 1e001, 1e002, 1e003, 1e004, 1e005, 1e006, 1e007, 1e008, 1e009, //
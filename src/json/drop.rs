@@ -1,4 +1,5 @@
 use crate::json::Value;
+use crate::prelude::*;
 
 pub fn safely(value: Value) {
     match value {
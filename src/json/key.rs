@@ -0,0 +1,197 @@
+use alloc::borrow::{Borrow, Cow};
+use core::cmp::Ordering;
+use core::fmt::{self, Debug, Display};
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+use core::str;
+
+use crate::de::{Deserialize, Visitor};
+use crate::error::Result;
+use crate::prelude::*;
+use crate::ser::{Serialize, ValueView};
+use crate::Place;
+
+/// The longest key stored inline, which keeps `Key` as small as a `String`.
+#[cfg(feature = "smallstring")]
+const INLINE: usize = 22;
+
+/// The key of an [`Object`](crate::json::Object) entry.
+///
+/// It dereferences to `str`, and an `Object` can be indexed by `&str`. With
+/// the `smallstring` feature, keys up to 22 bytes are stored inline rather
+/// than on the heap, which only changes how they are stored.
+#[derive(Clone)]
+pub struct Key(Repr);
+
+#[cfg(feature = "smallstring")]
+#[derive(Clone)]
+enum Repr {
+    Inline { len: u8, buf: [u8; INLINE] },
+    Heap(Box<str>),
+}
+
+#[cfg(not(feature = "smallstring"))]
+type Repr = String;
+
+impl Key {
+    #[cfg(feature = "smallstring")]
+    pub fn as_str(&self) -> &str {
+        match self.0 {
+            // Copied from a `&str` in `From<&str>`.
+            Repr::Inline { len, ref buf } => unsafe {
+                str::from_utf8_unchecked(&buf[..len as usize])
+            },
+            Repr::Heap(ref s) => s,
+        }
+    }
+
+    #[cfg(not(feature = "smallstring"))]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(feature = "smallstring")]
+impl From<&str> for Key {
+    fn from(s: &str) -> Self {
+        if s.len() > INLINE {
+            return Key(Repr::Heap(s.into()));
+        }
+        let mut buf = [0; INLINE];
+        buf[..s.len()].copy_from_slice(s.as_bytes());
+        Key(Repr::Inline {
+            len: s.len() as u8,
+            buf,
+        })
+    }
+}
+
+#[cfg(not(feature = "smallstring"))]
+impl From<&str> for Key {
+    fn from(s: &str) -> Self {
+        Key(s.to_owned())
+    }
+}
+
+#[cfg(feature = "smallstring")]
+impl From<String> for Key {
+    fn from(s: String) -> Self {
+        if s.len() > INLINE {
+            Key(Repr::Heap(s.into_boxed_str()))
+        } else {
+            Key::from(&*s)
+        }
+    }
+}
+
+#[cfg(not(feature = "smallstring"))]
+impl From<String> for Key {
+    fn from(s: String) -> Self {
+        Key(s)
+    }
+}
+
+#[cfg(feature = "smallstring")]
+impl From<Key> for String {
+    fn from(key: Key) -> Self {
+        match key.0 {
+            Repr::Heap(s) => s.into(),
+            Repr::Inline { .. } => key.as_str().to_owned(),
+        }
+    }
+}
+
+#[cfg(not(feature = "smallstring"))]
+impl From<Key> for String {
+    fn from(key: Key) -> Self {
+        key.0
+    }
+}
+
+impl Deref for Key {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Key {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl Borrow<str> for Key {
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for Key {}
+
+impl PartialEq<str> for Key {
+    fn eq(&self, other: &str) -> bool {
+        &**self == other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for Key {
+    fn eq(&self, other: &&'a str) -> bool {
+        &**self == *other
+    }
+}
+
+impl PartialOrd for Key {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Key {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl Hash for Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&**self, f)
+    }
+}
+
+impl Serialize for Key {
+    fn view(&self) -> ValueView<'_> {
+        ValueView::Str(Cow::Borrowed(self))
+    }
+}
+
+impl Deserialize for Key {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl Visitor for Place<Key> {
+            fn string(&mut self, s: &str) -> Result<()> {
+                self.out = Some(Key::from(s));
+                Ok(())
+            }
+        }
+        Place::new(out)
+    }
+}
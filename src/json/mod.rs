@@ -4,31 +4,83 @@
 //! serializing and deserializing JSON.
 
 mod ser;
-pub use self::ser::to_string;
+#[cfg(feature = "cbor")]
+pub use self::ser::to_string_from_cbor_slice;
+#[cfg(feature = "std")]
+pub use self::ser::to_writer;
+pub use self::ser::{escape, to_fmt_writer, to_string, to_vec, Serializer};
+
+mod const_ser;
+pub use self::const_ser::ConstSerializer;
 
 mod de;
-pub use self::de::from_str;
+pub(crate) use self::de::{check_raw_number, check_raw_value, from_str_into};
+pub use self::de::{
+    decode_envelope, from_str, from_str_borrowed, from_str_multi, from_str_raw_numbers,
+    merge_from_str, unescape, Deserializer, StreamDeserializer, TrailingData,
+};
+#[cfg(feature = "json5")]
+pub(crate) use self::de::{from_str_impl, Options};
 
 mod value;
 pub use self::value::Value;
 
+mod pointer;
+
+mod patch;
+pub use self::patch::{diff, patch, Operation, Patch};
+
+mod preview;
+
+#[cfg(feature = "simd")]
+mod swar;
+
 mod number;
 pub use self::number::Number;
 
+mod raw;
+pub use self::raw::RawValue;
+
+mod base64;
+pub(crate) use self::base64::encode as encode_base64;
+pub use self::base64::Base64;
+
 mod array;
 pub use self::array::Array;
 
-mod object;
-pub use self::object::Object;
+pub mod object;
+pub use self::object::{Entry, Object};
+
+mod key;
+pub use self::key::Key;
 
+/// Convert any serializable type into a [`Value`], as if serializing it to
+/// JSON and parsing it back, but without going through text.
 pub fn to_value<T: crate::Serialize>(v: T) -> crate::Result<Value> {
-    // Inefficient polyfill implementation.
-    from_str(&to_string(&v)?)
+    let mut out = None;
+    transcode(&v, crate::Deserialize::begin(&mut out))?;
+    out.ok_or(crate::Error)
 }
 
+/// Convert a [`Value`] into any deserializable type, as if serializing it to
+/// JSON and parsing it back, but without going through text.
 pub fn from_value<T: crate::Deserialize>(v: Value) -> crate::Result<T> {
-    // Inefficient polyfill implementation.
-    from_str(&to_string(&v)?)
+    let mut out = None;
+    transcode(&v, T::begin(&mut out))?;
+    out.ok_or(crate::Error)
+}
+
+fn transcode(
+    value: &dyn crate::Serialize,
+    visitor: &mut dyn crate::de::Visitor,
+) -> crate::Result<()> {
+    let options = crate::transcode::Options {
+        non_finite_as_null: true,
+        raw_json: true,
+        base64_as_str: true,
+        timestamp_as_str: true,
+    };
+    crate::transcode::transcode(value, visitor, options)
 }
 
 // for API compat with `::serde_json`
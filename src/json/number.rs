@@ -1,7 +1,220 @@
+use alloc::borrow::Cow;
+
+use crate::de::{Deserialize, Visitor};
+use crate::error::Result;
+use crate::json::Value;
+use crate::prelude::*;
+use crate::ser::{Serialize, ValueView};
+use crate::Place;
+
 /// A JSON number represented by some Rust primitive.
 #[derive(Clone, Debug)]
 pub enum Number {
     U64(u64),
     I64(i64),
+    #[cfg(feature = "float")]
     F64(f64),
+    /// The number exactly as it was written in the input, such as `1.10`.
+    ///
+    /// Produced by [`from_str_raw_numbers`], or by [`from_str`] with the
+    /// `arbitrary_precision` feature, and serialized back verbatim by
+    /// [`to_string`]. The text must be a valid JSON number.
+    ///
+    /// [`from_str_raw_numbers`]: crate::json::from_str_raw_numbers
+    /// [`from_str`]: crate::json::from_str
+    /// [`to_string`]: crate::json::to_string
+    Raw(String),
+}
+
+impl Number {
+    /// The number holding `f`, or `None` if `f` is NaN or infinite, which
+    /// JSON cannot represent.
+    #[cfg(feature = "float")]
+    pub fn from_f64(f: f64) -> Option<Number> {
+        if f.is_finite() {
+            Some(Number::F64(f))
+        } else {
+            None
+        }
+    }
+
+    /// Whether [`as_u64`](Number::as_u64) returns `Some`.
+    pub fn is_u64(&self) -> bool {
+        self.as_u64().is_some()
+    }
+
+    /// Whether [`as_i64`](Number::as_i64) returns `Some`.
+    pub fn is_i64(&self) -> bool {
+        self.as_i64().is_some()
+    }
+
+    /// Whether the number is neither a `u64` nor an `i64`, such as `1.5` or
+    /// an integer too big for both.
+    pub fn is_f64(&self) -> bool {
+        !self.is_u64() && !self.is_i64()
+    }
+
+    /// The number as a `u64`, if it is a non-negative integer that fits.
+    ///
+    /// Floats are never converted, even `1.0`.
+    pub fn as_u64(&self) -> Option<u64> {
+        use ::core::convert::TryFrom;
+        match self {
+            &Number::U64(n) => Some(n),
+            &Number::I64(i) => u64::try_from(i).ok(),
+            #[cfg(feature = "float")]
+            Number::F64(_) => None,
+            Number::Raw(raw) => raw.parse().ok(),
+        }
+    }
+
+    /// The number as an `i64`, if it is an integer that fits.
+    ///
+    /// Floats are never converted, even `1.0`.
+    pub fn as_i64(&self) -> Option<i64> {
+        use ::core::convert::TryFrom;
+        match self {
+            &Number::U64(n) => i64::try_from(n).ok(),
+            &Number::I64(i) => Some(i),
+            #[cfg(feature = "float")]
+            Number::F64(_) => None,
+            Number::Raw(raw) => raw.parse().ok(),
+        }
+    }
+
+    /// The number as an `f64`, possibly rounded.
+    ///
+    /// Only `None` for a [`Raw`](Number::Raw) number out of the `f64` range.
+    #[cfg(feature = "float")]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            &Number::U64(n) => Some(n as f64),
+            &Number::I64(i) => Some(i as f64),
+            &Number::F64(f) => Some(f),
+            Number::Raw(raw) => raw.parse().ok().filter(|f: &f64| f.is_finite()),
+        }
+    }
+
+    /// The number for an integer of any size: a `u64` or `i64` when it fits,
+    /// and otherwise what the JSON parser produces for such big integers.
+    pub(in crate) fn from_i128(i: i128) -> Number {
+        use ::core::convert::TryFrom;
+        if let Ok(u64) = u64::try_from(i) {
+            Number::U64(u64)
+        } else if let Ok(i64) = i64::try_from(i) {
+            Number::I64(i64)
+        } else {
+            Number::from_big_integer(&i.to_string())
+        }
+    }
+
+    /// The number for the text of an integer too big for `u64` or `i64`: a
+    /// float, unless there are no floats or precision is arbitrary.
+    pub(in crate) fn from_big_integer(raw: &str) -> Number {
+        #[cfg(all(feature = "float", not(feature = "arbitrary_precision")))]
+        if let Ok(f) = raw.parse() {
+            return Number::F64(f);
+        }
+        Number::Raw(raw.to_owned())
+    }
+}
+
+macro_rules! from_unsigned {
+    ($($ty:ident)*) => {$(
+        impl From<$ty> for Number {
+            fn from(n: $ty) -> Self {
+                Number::U64(n as u64)
+            }
+        }
+
+        impl From<$ty> for Value {
+            fn from(n: $ty) -> Self {
+                Value::Number(Number::from(n))
+            }
+        }
+    )*};
+}
+from_unsigned!(u8 u16 u32 u64 usize);
+
+macro_rules! from_signed {
+    ($($ty:ident)*) => {$(
+        impl From<$ty> for Number {
+            /// Non-negative integers become [`Number::U64`], as when parsed.
+            fn from(i: $ty) -> Self {
+                if i < 0 {
+                    Number::I64(i as i64)
+                } else {
+                    Number::U64(i as u64)
+                }
+            }
+        }
+
+        impl From<$ty> for Value {
+            fn from(i: $ty) -> Self {
+                Value::Number(Number::from(i))
+            }
+        }
+    )*};
+}
+from_signed!(i8 i16 i32 i64 isize);
+
+#[cfg(feature = "float")]
+macro_rules! from_float {
+    ($($ty:ident)*) => {$(
+        impl From<$ty> for Value {
+            /// NaN and infinities become [`Value::Null`], as they serialize.
+            fn from(f: $ty) -> Self {
+                Number::from_f64(f as f64).map_or(Value::Null, Value::Number)
+            }
+        }
+    )*};
+}
+#[cfg(feature = "float")]
+from_float!(f32 f64);
+
+impl From<Number> for Value {
+    fn from(n: Number) -> Self {
+        Value::Number(n)
+    }
+}
+
+impl Serialize for Number {
+    fn view(&self) -> ValueView<'_> {
+        match self {
+            &Number::U64(n) => ValueView::Int(n as _),
+            &Number::I64(i) => ValueView::Int(i as _),
+            #[cfg(feature = "float")]
+            &Number::F64(f) => ValueView::F64(f),
+            Number::Raw(raw) => ValueView::RawNumber(Cow::Borrowed(raw)),
+        }
+    }
+}
+
+impl Deserialize for Number {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl Visitor for Place<Number> {
+            fn int(&mut self, i: i128) -> Result<()> {
+                self.out = Some(Number::from_i128(i));
+                Ok(())
+            }
+
+            #[cfg(feature = "float")]
+            fn float(&mut self, n: f64) -> Result<()> {
+                self.out = Some(Number::F64(n));
+                Ok(())
+            }
+
+            fn raw_number(&mut self, raw: &str) -> Result<()> {
+                self.out = Some(Number::Raw(raw.to_owned()));
+                Ok(())
+            }
+
+            fn big_integer(&mut self, raw: &str) -> Result<()> {
+                self.out = Some(Number::from_big_integer(raw));
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
 }
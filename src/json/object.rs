@@ -1,66 +1,351 @@
-use std::collections::{btree_map, BTreeMap};
-use std::iter::FromIterator;
-use std::mem::{self, ManuallyDrop};
-use std::ops::{Deref, DerefMut};
-use std::ptr;
+//! The map of a JSON object, and its entries and iterators.
+//!
+//! It is sorted by key, unless the `preserve_order` feature keeps its entries
+//! in insertion order. Its API is the same either way.
 
-use crate::json::{drop, Value};
+#[cfg(not(feature = "preserve_order"))]
+use alloc::collections::btree_map as map;
+use alloc::collections::BTreeMap;
+use core::fmt::{self, Debug};
+use core::iter::{FromIterator, FusedIterator};
+use core::mem::{self, ManuallyDrop};
+use core::ops::{Index, IndexMut};
+use core::ptr;
+
+use crate::json::{drop, Key, Value};
+use crate::prelude::*;
 use crate::private;
 use crate::ser::{self, Serialize, ValueView};
+#[cfg(feature = "preserve_order")]
+use indexmap::map;
+
+#[cfg(not(feature = "preserve_order"))]
+type Map = BTreeMap<Key, Value>;
+#[cfg(feature = "preserve_order")]
+type Map = indexmap::IndexMap<Key, Value>;
 
-/// A `BTreeMap<String, Value>` with a non-recursive drop impl.
-#[derive(Clone, Debug, Default)]
+/// A map from [`Key`]s to [`Value`]s with a non-recursive drop impl.
+///
+/// The entries are sorted by key, or, with the `preserve_order` feature, kept
+/// in insertion order, and so in the order of the input when parsed.
+#[derive(Clone, Default)]
 pub struct Object {
-    inner: BTreeMap<String, Value>,
+    inner: Map,
 }
 
 impl Drop for Object {
     fn drop(&mut self) {
-        for (_, child) in mem::replace(&mut self.inner, BTreeMap::new()) {
+        for (_, child) in mem::take(&mut self.inner) {
             drop::safely(child);
         }
     }
 }
 
-fn take(object: Object) -> BTreeMap<String, Value> {
+fn take(object: Object) -> Map {
     let object = ManuallyDrop::new(object);
     unsafe { ptr::read(&object.inner) }
 }
 
 impl Object {
     pub fn new() -> Self {
-        Object {
-            inner: BTreeMap::new(),
+        Object { inner: Map::new() }
+    }
+
+    pub fn clear(&mut self) {
+        for (_, child) in mem::take(&mut self.inner) {
+            drop::safely(child);
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.inner.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.inner.get_mut(key)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.inner.contains_key(key)
+    }
+
+    /// Inserts a value, for any key convertible into a [`Key`]. An existing
+    /// entry keeps its place, and its previous value is returned.
+    pub fn insert(&mut self, key: impl Into<Key>, value: Value) -> Option<Value> {
+        self.inner.insert(key.into(), value)
+    }
+
+    /// Removes an entry, leaving the others in their order.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    /// Removes an entry, leaving the others in their order.
+    pub fn remove_entry(&mut self, key: &str) -> Option<(Key, Value)> {
+        #[cfg(not(feature = "preserve_order"))]
+        return self.inner.remove_entry(key);
+        #[cfg(feature = "preserve_order")]
+        return self.inner.shift_remove_entry(key);
+    }
+
+    /// Gets the entry of a key, for any key convertible into a [`Key`], to
+    /// insert or update it in place.
+    ///
+    /// ```rust
+    /// use miniserde_ditto::json::{Array, Object, Value};
+    ///
+    /// let mut object = Object::new();
+    /// for tag in &["a", "b"] {
+    ///     let tags = object.entry("tags").or_insert_with(|| Array::new().into());
+    ///     if let Value::Array(tags) = tags {
+    ///         tags.push(Value::from(*tag));
+    ///     }
+    /// }
+    /// ```
+    pub fn entry(&mut self, key: impl Into<Key>) -> Entry<'_> {
+        match self.inner.entry(key.into()) {
+            map::Entry::Vacant(vacant) => Entry::Vacant(VacantEntry { vacant }),
+            map::Entry::Occupied(occupied) => Entry::Occupied(OccupiedEntry { occupied }),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            iter: self.inner.iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
+        IterMut {
+            iter: self.inner.iter_mut(),
+        }
+    }
+
+    pub fn keys(&self) -> Keys<'_> {
+        Keys {
+            iter: self.inner.keys(),
+        }
+    }
+
+    pub fn values(&self) -> Values<'_> {
+        Values {
+            iter: self.inner.values(),
+        }
+    }
+
+    pub fn values_mut(&mut self) -> ValuesMut<'_> {
+        ValuesMut {
+            iter: self.inner.values_mut(),
+        }
+    }
+
+    /// Inserts the entry and returns the object, to build it in one
+    /// expression.
+    ///
+    /// ```rust
+    /// use miniserde_ditto::json::{self, Object, Value};
+    ///
+    /// let object = Object::new()
+    ///     .with("id", 3)
+    ///     .with("name", "demo")
+    ///     .with("tags", Object::new().with("ok", true));
+    /// assert_eq!(
+    ///     json::to_string(&Value::from(object)).unwrap(),
+    ///     r#"{"id":3,"name":"demo","tags":{"ok":true}}"#,
+    /// );
+    /// ```
+    pub fn with(mut self, key: impl Into<Key>, value: impl Into<Value>) -> Self {
+        self.inner.insert(key.into(), value.into());
+        self
+    }
+}
+
+impl Debug for Object {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Panics if the key is missing, like `BTreeMap`.
+impl Index<&str> for Object {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        self.get(key).expect("no entry found for key")
+    }
+}
+
+impl IndexMut<&str> for Object {
+    fn index_mut(&mut self, key: &str) -> &mut Value {
+        self.get_mut(key).expect("no entry found for key")
+    }
+}
+
+/// A view into a single entry of an [`Object`], from [`Object::entry`].
+pub enum Entry<'a> {
+    Vacant(VacantEntry<'a>),
+    Occupied(OccupiedEntry<'a>),
+}
+
+/// An entry of an [`Object`] without a value yet.
+pub struct VacantEntry<'a> {
+    vacant: map::VacantEntry<'a, Key, Value>,
+}
+
+/// An entry of an [`Object`] with a value.
+pub struct OccupiedEntry<'a> {
+    occupied: map::OccupiedEntry<'a, Key, Value>,
+}
+
+impl<'a> Entry<'a> {
+    pub fn key(&self) -> &Key {
+        match self {
+            Entry::Vacant(entry) => entry.key(),
+            Entry::Occupied(entry) => entry.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: Value) -> &'a mut Value {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> Value>(self, default: F) -> &'a mut Value {
+        match self {
+            Entry::Vacant(entry) => entry.insert(default()),
+            Entry::Occupied(entry) => entry.into_mut(),
+        }
+    }
+
+    pub fn and_modify<F: FnOnce(&mut Value)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
         }
+        self
     }
 }
 
-impl Deref for Object {
-    type Target = BTreeMap<String, Value>;
+impl<'a> VacantEntry<'a> {
+    pub fn key(&self) -> &Key {
+        self.vacant.key()
+    }
 
-    fn deref(&self) -> &Self::Target {
-        &self.inner
+    pub fn insert(self, value: Value) -> &'a mut Value {
+        self.vacant.insert(value)
     }
 }
 
-impl DerefMut for Object {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.inner
+impl<'a> OccupiedEntry<'a> {
+    pub fn key(&self) -> &Key {
+        self.occupied.key()
+    }
+
+    pub fn get(&self) -> &Value {
+        self.occupied.get()
+    }
+
+    pub fn get_mut(&mut self) -> &mut Value {
+        self.occupied.get_mut()
+    }
+
+    pub fn into_mut(self) -> &'a mut Value {
+        self.occupied.into_mut()
+    }
+
+    /// Replaces the value, and returns the previous one.
+    pub fn insert(&mut self, value: Value) -> Value {
+        self.occupied.insert(value)
+    }
+
+    /// Removes the entry, leaving the others in their order.
+    pub fn remove(self) -> Value {
+        #[cfg(not(feature = "preserve_order"))]
+        return self.occupied.remove();
+        #[cfg(feature = "preserve_order")]
+        return self.occupied.shift_remove();
     }
 }
 
+macro_rules! iterators {(
+    $(
+        $(#[$doc:meta])*
+        $Iter:ident $(<$a:lifetime>)? => $Item:ty,
+    )*
+) => (
+    $(
+        $(#[$doc])*
+        pub struct $Iter $(<$a>)? {
+            iter: map::$Iter<$($a,)? Key, Value>,
+        }
+
+        impl $(<$a>)? Iterator for $Iter $(<$a>)? {
+            type Item = $Item;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                self.iter.next()
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.iter.size_hint()
+            }
+        }
+
+        impl $(<$a>)? DoubleEndedIterator for $Iter $(<$a>)? {
+            #[inline]
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.iter.next_back()
+            }
+        }
+
+        impl $(<$a>)? ExactSizeIterator for $Iter $(<$a>)? {
+            #[inline]
+            fn len(&self) -> usize {
+                self.iter.len()
+            }
+        }
+
+        impl $(<$a>)? FusedIterator for $Iter $(<$a>)? {}
+    )*
+)}
+
+iterators! {
+    /// The entries of an [`Object`], from [`Object::iter`].
+    Iter<'a> => (&'a Key, &'a Value),
+    /// The entries of an [`Object`], from [`Object::iter_mut`].
+    IterMut<'a> => (&'a Key, &'a mut Value),
+    /// The entries of an [`Object`], by value.
+    IntoIter => (Key, Value),
+    /// The keys of an [`Object`], from [`Object::keys`].
+    Keys<'a> => &'a Key,
+    /// The values of an [`Object`], from [`Object::values`].
+    Values<'a> => &'a Value,
+    /// The values of an [`Object`], from [`Object::values_mut`].
+    ValuesMut<'a> => &'a mut Value,
+}
+
 impl IntoIterator for Object {
-    type Item = (String, Value);
-    type IntoIter = <BTreeMap<String, Value> as IntoIterator>::IntoIter;
+    type Item = (Key, Value);
+    type IntoIter = IntoIter;
 
     fn into_iter(self) -> Self::IntoIter {
-        take(self).into_iter()
+        IntoIter {
+            iter: take(self).into_iter(),
+        }
     }
 }
 
 impl<'a> IntoIterator for &'a Object {
-    type Item = (&'a String, &'a Value);
-    type IntoIter = <&'a BTreeMap<String, Value> as IntoIterator>::IntoIter;
+    type Item = (&'a Key, &'a Value);
+    type IntoIter = Iter<'a>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
@@ -68,28 +353,50 @@ impl<'a> IntoIterator for &'a Object {
 }
 
 impl<'a> IntoIterator for &'a mut Object {
-    type Item = (&'a String, &'a mut Value);
-    type IntoIter = <&'a mut BTreeMap<String, Value> as IntoIterator>::IntoIter;
+    type Item = (&'a Key, &'a mut Value);
+    type IntoIter = IterMut<'a>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter_mut()
     }
 }
 
-impl FromIterator<(String, Value)> for Object {
+impl<K: Into<Key>> FromIterator<(K, Value)> for Object {
     fn from_iter<I>(iter: I) -> Self
     where
-        I: IntoIterator<Item = (String, Value)>,
+        I: IntoIterator<Item = (K, Value)>,
     {
         Object {
-            inner: BTreeMap::from_iter(iter),
+            inner: iter.into_iter().map(|(k, v)| (k.into(), v)).collect(),
         }
     }
 }
 
+impl<K: Into<Key>> Extend<(K, Value)> for Object {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = (K, Value)>,
+    {
+        self.inner
+            .extend(iter.into_iter().map(|(k, v)| (k.into(), v)));
+    }
+}
+
+impl<K: Into<Key>> From<Vec<(K, Value)>> for Object {
+    fn from(entries: Vec<(K, Value)>) -> Self {
+        entries.into_iter().collect()
+    }
+}
+
+impl<K: Into<Key>> From<BTreeMap<K, Value>> for Object {
+    fn from(map: BTreeMap<K, Value>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
 impl private {
     pub fn stream_json_object(object: &Object) -> ValueView<'_> {
-        struct ObjectIter<'a>(btree_map::Iter<'a, String, Value>);
+        struct ObjectIter<'a>(Iter<'a>);
 
         impl<'a> ser::Map<'a> for ObjectIter<'a> {
             fn next(&mut self) -> Option<(&'a dyn Serialize, &'a dyn Serialize)> {
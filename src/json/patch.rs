@@ -0,0 +1,375 @@
+use crate::de::convert::{FromRepr, ReprPlace};
+use crate::de::{Deserialize, Visitor};
+use crate::json::pointer::{escape, index, unescape};
+use crate::json::{Number, Object, Value};
+use crate::prelude::*;
+use crate::ser::{Serialize, ValueView};
+use crate::Result;
+
+/// A [JSON Patch] document, the operations to apply in order with [`patch`].
+///
+/// It serializes as the array of its operations, such as
+/// `[{"op":"remove","path":"/a"}]`.
+///
+/// [JSON Patch]: https://tools.ietf.org/html/rfc6902
+#[derive(Clone, Debug, Default)]
+pub struct Patch(pub Vec<Operation>);
+
+/// An operation of a [`Patch`], on the values at the given [JSON Pointers],
+/// as looked up by [`Value::pointer`].
+///
+/// [JSON Pointers]: https://tools.ietf.org/html/rfc6901
+#[derive(Clone, Debug, crate::Serialize)]
+#[serde(tag = "op")]
+pub enum Operation {
+    /// Adds a member to an object, replacing any previous one, or inserts an
+    /// element into an array, before the given index or at the end for `-`.
+    #[serde(rename = "add")]
+    Add { path: String, value: Value },
+    #[serde(rename = "remove")]
+    Remove { path: String },
+    /// Replaces an existing value.
+    #[serde(rename = "replace")]
+    Replace { path: String, value: Value },
+    /// Removes a value, then adds it at `path`.
+    #[serde(rename = "move")]
+    Move { from: String, path: String },
+    /// Adds a copy of a value at `path`.
+    #[serde(rename = "copy")]
+    Copy { from: String, path: String },
+    /// Fails the patch unless the value is equal to the given one: numbers
+    /// compare by their value, and objects regardless of the order of their
+    /// members.
+    #[serde(rename = "test")]
+    Test { path: String, value: Value },
+}
+
+/// Applies the operations of a JSON Patch to `target`, in order. If any
+/// fails, `target` is left untouched, the patch being applied to a clone of
+/// it.
+///
+/// ```rust
+/// use miniserde_ditto::json::{self, Patch, Value};
+///
+/// let mut doc: Value = json::from_str(r#"{"name":"a","tags":["x"]}"#)?;
+/// let patch: Patch = json::from_str(
+///     r#"[
+///         {"op": "test", "path": "/name", "value": "a"},
+///         {"op": "add", "path": "/tags/-", "value": "y"},
+///         {"op": "move", "from": "/name", "path": "/title"}
+///     ]"#,
+/// )?;
+/// json::patch(&mut doc, &patch)?;
+/// assert_eq!(json::to_string(&doc)?, r#"{"tags":["x","y"],"title":"a"}"#);
+///
+/// // The name is gone, so this fails as a whole.
+/// let patch: Patch = json::from_str(
+///     r#"[{"op": "remove", "path": "/tags"}, {"op": "remove", "path": "/name"}]"#,
+/// )?;
+/// assert!(json::patch(&mut doc, &patch).is_err());
+/// assert!(doc.pointer("/tags").is_some());
+/// # Ok::<(), miniserde_ditto::Error>(())
+/// ```
+pub fn patch(target: &mut Value, patch: &Patch) -> Result<()> {
+    let mut doc = target.clone();
+    for operation in &patch.0 {
+        apply(&mut doc, operation)?;
+    }
+    *target = doc;
+    Ok(())
+}
+
+fn apply(doc: &mut Value, operation: &Operation) -> Result<()> {
+    match operation {
+        Operation::Add { path, value } => add(doc, path, value.clone()),
+        Operation::Remove { path } => remove(doc, path).map(drop),
+        Operation::Replace { path, value } => match doc.pointer_mut(path) {
+            Some(target) => {
+                *target = value.clone();
+                Ok(())
+            }
+            None => err!("No value at {:?} to replace", path),
+        },
+        Operation::Move { from, path } => {
+            if path.starts_with(&**from) && path[from.len()..].starts_with('/') {
+                err!("Cannot move {:?} into one of its children", from);
+            }
+            let value = remove(doc, from)?;
+            add(doc, path, value)
+        }
+        Operation::Copy { from, path } => match doc.pointer(from).cloned() {
+            Some(value) => add(doc, path, value),
+            None => err!("No value at {:?} to copy", from),
+        },
+        Operation::Test { path, value } => match doc.pointer(path) {
+            Some(actual) if equal(actual, value) => Ok(()),
+            _ => err!("The value at {:?} is not the expected one", path),
+        },
+    }
+}
+
+/// The JSON Patch turning `old` into `new`, for the receiver of a document to
+/// catch up with [`patch`].
+///
+/// Object members are compared one by one, and so are array elements, past
+/// those equal at both ends: an element inserted or removed anywhere but in
+/// the middle of these is added or removed, and otherwise shows up as
+/// replacements of the following ones. Values are compared as by a `test`
+/// operation, and ones of different kinds are replaced as a whole.
+///
+/// ```rust
+/// use miniserde_ditto::json::{self, Value};
+///
+/// let old: Value = json::from_str(r#"{"id":1,"meta":{"rev":1,"tags":["x","z"]}}"#)?;
+/// let new: Value = json::from_str(r#"{"id":1,"meta":{"rev":1,"tags":["x","y","z"]}}"#)?;
+/// let patch = json::diff(&old, &new);
+/// assert_eq!(
+///     json::to_string(&patch)?,
+///     r#"[{"op":"add","path":"/meta/tags/1","value":"y"}]"#,
+/// );
+///
+/// let mut doc = old;
+/// json::patch(&mut doc, &patch)?;
+/// assert_eq!(json::to_string(&doc)?, json::to_string(&new)?);
+/// # Ok::<(), miniserde_ditto::Error>(())
+/// ```
+pub fn diff(old: &Value, new: &Value) -> Patch {
+    let mut operations = Vec::new();
+    let mut stack = vec![(String::new(), old, new)];
+    while let Some((path, old, new)) = stack.pop() {
+        match (old, new) {
+            (Value::Object(old), Value::Object(new)) => {
+                let mut changed = Vec::new();
+                for (key, old) in old.iter() {
+                    let path = format!("{}/{}", path, escape(key));
+                    match new.get(&**key) {
+                        Some(new) => changed.push((path, old, new)),
+                        None => operations.push(Operation::Remove { path }),
+                    }
+                }
+                for (key, new) in new.iter() {
+                    if !old.contains_key(&**key) {
+                        operations.push(Operation::Add {
+                            path: format!("{}/{}", path, escape(key)),
+                            value: new.clone(),
+                        });
+                    }
+                }
+                stack.extend(changed.into_iter().rev());
+            }
+            (Value::Array(old), Value::Array(new)) => {
+                let start = old
+                    .iter()
+                    .zip(new.iter())
+                    .take_while(|(old, new)| equal(old, new))
+                    .count();
+                let end = old[start..]
+                    .iter()
+                    .rev()
+                    .zip(new[start..].iter().rev())
+                    .take_while(|(old, new)| equal(old, new))
+                    .count();
+                let (old, new) = (&old[start..old.len() - end], &new[start..new.len() - end]);
+                let common = old.len().min(new.len());
+                for i in (common..old.len()).rev() {
+                    operations.push(Operation::Remove {
+                        path: format!("{}/{}", path, start + i),
+                    });
+                }
+                for (i, new) in new.iter().enumerate().skip(common) {
+                    operations.push(Operation::Add {
+                        path: format!("{}/{}", path, start + i),
+                        value: new.clone(),
+                    });
+                }
+                let changed = old.iter().zip(new.iter()).enumerate();
+                let changed =
+                    changed.map(|(i, (old, new))| (format!("{}/{}", path, start + i), old, new));
+                stack.extend(changed.collect::<Vec<_>>().into_iter().rev());
+            }
+            _ if equal(old, new) => {}
+            _ => operations.push(Operation::Replace {
+                path,
+                value: new.clone(),
+            }),
+        }
+    }
+    Patch(operations)
+}
+
+/// The pointer to the parent of the value at `path`, and the reference token
+/// of the value in it.
+fn split(path: &str) -> Result<(&str, &str)> {
+    match path.rfind('/') {
+        Some(slash) => Ok((&path[..slash], &path[slash + 1..])),
+        None => err!("Invalid JSON Pointer {:?} to a child", path),
+    }
+}
+
+fn add(doc: &mut Value, path: &str, value: Value) -> Result<()> {
+    if path.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    let (parent, token) = split(path)?;
+    match doc.pointer_mut(parent) {
+        Some(Value::Object(object)) => match unescape(token) {
+            Some(key) => {
+                object.insert(key.into_owned(), value);
+            }
+            None => err!("Invalid JSON Pointer {:?}", path),
+        },
+        Some(Value::Array(array)) => {
+            let i = match token {
+                "-" => array.len(),
+                _ => match index(token) {
+                    Some(i) if i <= array.len() => i,
+                    _ => err!("No index {:?} to add at in {:?}", token, parent),
+                },
+            };
+            array.insert(i, value);
+        }
+        _ => err!("No object or array at {:?} to add to", parent),
+    }
+    Ok(())
+}
+
+fn remove(doc: &mut Value, path: &str) -> Result<Value> {
+    let (parent, token) = split(path)?;
+    let removed = match doc.pointer_mut(parent) {
+        Some(Value::Object(object)) => unescape(token).and_then(|key| object.remove(&key)),
+        Some(Value::Array(array)) => match index(token) {
+            Some(i) if i < array.len() => Some(array.remove(i)),
+            _ => None,
+        },
+        _ => None,
+    };
+    match removed {
+        Some(value) => Ok(value),
+        None => err!("No value at {:?} to remove", path),
+    }
+}
+
+/// Whether two values are equal as JSON Patch tests them.
+fn equal(a: &Value, b: &Value) -> bool {
+    let mut stack = vec![(a, b)];
+    while let Some(pair) = stack.pop() {
+        match pair {
+            (Value::Null, Value::Null) => {}
+            (Value::Bool(a), Value::Bool(b)) if a == b => {}
+            (Value::Number(a), Value::Number(b)) if same_number(a, b) => {}
+            (Value::String(a), Value::String(b)) if a == b => {}
+            (Value::Array(a), Value::Array(b)) if a.len() == b.len() => {
+                stack.extend(a.iter().zip(b.iter()));
+            }
+            (Value::Object(a), Value::Object(b)) if a.len() == b.len() => {
+                for (key, a) in a.iter() {
+                    match b.get(&**key) {
+                        Some(b) => stack.push((a, b)),
+                        None => return false,
+                    }
+                }
+            }
+            _ => return false,
+        }
+    }
+    true
+}
+
+fn same_number(a: &Number, b: &Number) -> bool {
+    let int = |n: &Number| {
+        n.as_i64()
+            .map(i128::from)
+            .or_else(|| n.as_u64().map(i128::from))
+    };
+    match (int(a), int(b)) {
+        (Some(a), Some(b)) => a == b,
+        #[cfg(feature = "float")]
+        _ => a.as_f64().is_some() && a.as_f64() == b.as_f64(),
+        #[cfg(not(feature = "float"))]
+        _ => matches!((a, b), (Number::Raw(a), Number::Raw(b)) if a == b),
+    }
+}
+
+impl Serialize for Patch {
+    fn view(&self) -> ValueView<'_> {
+        self.0.view()
+    }
+}
+
+impl FromRepr for Patch {
+    type Repr = Vec<Operation>;
+
+    fn from_repr(operations: Vec<Operation>) -> Result<Self> {
+        Ok(Patch(operations))
+    }
+}
+
+impl Deserialize for Patch {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        ReprPlace::new(out)
+    }
+}
+
+// By hand rather than derived, since the `op` may come after the other
+// members, and unknown members are ignored.
+impl FromRepr for Operation {
+    type Repr = Value;
+
+    fn from_repr(value: Value) -> Result<Self> {
+        let mut object = match value {
+            Value::Object(object) => object,
+            _ => err!("Expected an object as a JSON Patch operation"),
+        };
+        let op = string(&mut object, "op")?;
+        Ok(match op.as_str() {
+            "add" => Operation::Add {
+                path: string(&mut object, "path")?,
+                value: member(&mut object, "value")?,
+            },
+            "remove" => Operation::Remove {
+                path: string(&mut object, "path")?,
+            },
+            "replace" => Operation::Replace {
+                path: string(&mut object, "path")?,
+                value: member(&mut object, "value")?,
+            },
+            "move" => Operation::Move {
+                from: string(&mut object, "from")?,
+                path: string(&mut object, "path")?,
+            },
+            "copy" => Operation::Copy {
+                from: string(&mut object, "from")?,
+                path: string(&mut object, "path")?,
+            },
+            "test" => Operation::Test {
+                path: string(&mut object, "path")?,
+                value: member(&mut object, "value")?,
+            },
+            _ => err!("Unknown JSON Patch operation {:?}", op),
+        })
+    }
+}
+
+impl Deserialize for Operation {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        ReprPlace::new(out)
+    }
+}
+
+fn member(object: &mut Object, name: &str) -> Result<Value> {
+    match object.remove(name) {
+        Some(value) => Ok(value),
+        None => err!("Missing {:?} in a JSON Patch operation", name),
+    }
+}
+
+fn string(object: &mut Object, name: &str) -> Result<String> {
+    match member(object, name)? {
+        Value::String(s) => Ok(s),
+        _ => err!(
+            "Expected a string as the {:?} of a JSON Patch operation",
+            name
+        ),
+    }
+}
@@ -0,0 +1,95 @@
+use alloc::borrow::Cow;
+use core::str::Split;
+
+use crate::json::Value;
+use crate::prelude::*;
+
+impl Value {
+    /// Looks up a value by a [JSON Pointer], such as `/servers/0/host`: each
+    /// reference token is the key of an object member or the index of an
+    /// array element, `~1` and `~0` standing for `/` and `~` in keys. The
+    /// empty pointer is the value itself.
+    ///
+    /// ```rust
+    /// use miniserde_ditto::json::{self, Value};
+    ///
+    /// let value: Value = json::from_str(r#"{"servers":[{"host":"a"}],"a/b":1}"#)?;
+    /// assert!(matches!(value.pointer("/servers/0/host"), Some(Value::String(host)) if host == "a"));
+    /// assert!(value.pointer("/a~1b").is_some());
+    /// assert!(value.pointer("/servers/1").is_none());
+    /// # Ok::<(), miniserde_ditto::Error>(())
+    /// ```
+    ///
+    /// [JSON Pointer]: https://tools.ietf.org/html/rfc6901
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        let mut target = self;
+        for token in tokens(pointer)? {
+            target = match target {
+                Value::Object(object) => object.get(&*unescape(token)?)?,
+                Value::Array(array) => array.get(index(token)?)?,
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+
+    /// Like [`pointer`](Value::pointer), for a mutable reference.
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value> {
+        let mut target = self;
+        for token in tokens(pointer)? {
+            target = match target {
+                Value::Object(object) => object.get_mut(&*unescape(token)?)?,
+                Value::Array(array) => array.get_mut(index(token)?)?,
+                _ => return None,
+            };
+        }
+        Some(target)
+    }
+}
+
+/// The reference tokens of a pointer, still escaped, or `None` if it does not
+/// start with a `/`, unless empty.
+fn tokens(pointer: &str) -> Option<Split<'_, char>> {
+    let mut tokens = pointer.split('/');
+    match tokens.next() {
+        Some("") => Some(tokens),
+        _ => None,
+    }
+}
+
+/// The object key of a reference token.
+pub(in crate::json) fn unescape(token: &str) -> Option<Cow<'_, str>> {
+    if !token.contains('~') {
+        return Some(Cow::Borrowed(token));
+    }
+    let mut key = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        key.push(match c {
+            '~' => match chars.next()? {
+                '0' => '~',
+                '1' => '/',
+                _ => return None,
+            },
+            c => c,
+        });
+    }
+    Some(Cow::Owned(key))
+}
+
+/// The reference token of an object key.
+pub(in crate::json) fn escape(key: &str) -> Cow<'_, str> {
+    if key.contains(&['~', '/'][..]) {
+        Cow::Owned(key.replace('~', "~0").replace('/', "~1"))
+    } else {
+        Cow::Borrowed(key)
+    }
+}
+
+/// The array index of a reference token: digits, without leading zeros.
+pub(in crate::json) fn index(token: &str) -> Option<usize> {
+    if !token.bytes().all(|b| b.is_ascii_digit()) || token.len() > 1 && token.starts_with('0') {
+        return None;
+    }
+    token.parse().ok()
+}
@@ -0,0 +1,110 @@
+use core::fmt::Write;
+use core::slice;
+
+use crate::json::object::Iter as ObjectIter;
+use crate::json::{Number, Value};
+use crate::prelude::*;
+
+impl Value {
+    /// Render a single-line preview of this value, for logging documents of
+    /// any size.
+    ///
+    /// Once the output reaches `max_len` bytes, the remaining elements of
+    /// every open array or object are replaced by a `…(+N)` marker counting
+    /// them, and strings that do not fit are cut short with a `…`. Only those
+    /// markers and the closing brackets may go past `max_len`, so the nesting
+    /// depth shown is bounded by the budget as well.
+    ///
+    /// The output looks like JSON but is not meant to be parsed back.
+    ///
+    /// ```rust
+    /// use miniserde_ditto::json::{self, Value};
+    ///
+    /// let value: Value = json::from_str(r#"{"a": [1, 2, 3, 4], "b": "hello"}"#).unwrap();
+    /// assert_eq!(value.to_debug_string(100), r#"{"a": [1, 2, 3, 4], "b": "hello"}"#);
+    /// assert_eq!(value.to_debug_string(12), r#"{"a": [1, 2, …(+2)], …(+1)}"#);
+    /// ```
+    pub fn to_debug_string(&self, max_len: usize) -> String {
+        enum Layer<'a> {
+            Array(slice::Iter<'a, Value>),
+            Object(ObjectIter<'a>),
+        }
+
+        let mut out = String::new();
+        let mut stack: Vec<(Layer<'_>, bool)> = vec![];
+        let mut value = self;
+
+        loop {
+            match value {
+                Value::Null => out.push_str("null"),
+                Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+                Value::Number(Number::U64(n)) => out.push_str(itoa::Buffer::new().format(*n)),
+                Value::Number(Number::I64(i)) => out.push_str(itoa::Buffer::new().format(*i)),
+                #[cfg(feature = "float")]
+                Value::Number(Number::F64(f)) => out.push_str(ryu::Buffer::new().format(*f)),
+                Value::Number(Number::Raw(raw)) => out.push_str(raw),
+                Value::String(s) => preview_str(s, max_len.saturating_sub(out.len()), &mut out),
+                Value::Array(array) => {
+                    out.push('[');
+                    stack.push((Layer::Array(array.iter()), true));
+                }
+                Value::Object(object) => {
+                    out.push('{');
+                    stack.push((Layer::Object(object.iter()), true));
+                }
+            }
+
+            value = loop {
+                let (layer, first) = match stack.last_mut() {
+                    Some(top) => top,
+                    None => return out,
+                };
+                let remaining = match layer {
+                    Layer::Array(iter) => iter.len(),
+                    Layer::Object(iter) => iter.len(),
+                };
+                if remaining > 0 {
+                    if !*first {
+                        out.push_str(", ");
+                    }
+                    *first = false;
+                    if out.len() < max_len {
+                        match layer {
+                            Layer::Array(iter) => break iter.next().unwrap(),
+                            Layer::Object(iter) => {
+                                let (key, value) = iter.next().unwrap();
+                                preview_str(key, max_len.saturating_sub(out.len()), &mut out);
+                                out.push_str(": ");
+                                break value;
+                            }
+                        }
+                    }
+                    let _ = write!(out, "…(+{})", remaining);
+                }
+                match stack.pop() {
+                    Some((Layer::Array(_), _)) => out.push(']'),
+                    Some((Layer::Object(_), _)) => out.push('}'),
+                    None => unreachable!(),
+                }
+            };
+        }
+    }
+}
+
+/// Write `s` as a quoted string, cut short if it would not fit in `budget`
+/// bytes once escaped (not counting the closing quote). Only what fits is
+/// escaped, however long `s` is.
+fn preview_str(s: &str, budget: usize, out: &mut String) {
+    let start = out.len();
+    out.push('"');
+    for c in s.chars() {
+        let len = out.len();
+        super::ser::escape_contents(c.encode_utf8(&mut [0; 4]), false, out);
+        if out.len() - start > budget {
+            out.truncate(len);
+            out.push_str("…\"");
+            return;
+        }
+    }
+    out.push('"');
+}
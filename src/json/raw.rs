@@ -0,0 +1,101 @@
+use alloc::borrow::Cow;
+use core::fmt;
+
+use crate::de::{Deserialize, Visitor};
+use crate::error::Result;
+use crate::prelude::*;
+use crate::ser::{Serialize, ValueView};
+use crate::Place;
+
+/// A JSON value kept as the text it was deserialized from, such as a payload
+/// to pass along without decoding it.
+///
+/// As a field, it captures the text of that value exactly as in the input,
+/// and serializes back to it verbatim. From CBOR input, or from
+/// [`from_value`](crate::json::from_value), the value gets encoded as JSON.
+///
+/// ```rust
+/// use miniserde_ditto::json::{self, RawValue};
+/// use miniserde_ditto::{Deserialize, Serialize};
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Envelope {
+///     kind: String,
+///     payload: RawValue,
+/// }
+///
+/// let j = r#"{"kind":"audit","payload":{"b": 1.50, "a": [1,2]}}"#;
+/// let envelope: Envelope = json::from_str(j)?;
+/// assert_eq!(envelope.payload.get(), r#"{"b": 1.50, "a": [1,2]}"#);
+/// assert_eq!(json::to_string(&envelope)?, j);
+/// # Ok::<(), miniserde_ditto::Error>(())
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct RawValue {
+    json: Box<str>,
+}
+
+impl RawValue {
+    /// The raw value holding `json`, or an error if it is not a single JSON
+    /// value, optionally surrounded by whitespace.
+    pub fn from_string(json: String) -> Result<RawValue> {
+        crate::json::check_raw_value(&json)?;
+        Ok(RawValue {
+            json: json.into_boxed_str(),
+        })
+    }
+
+    /// The JSON text.
+    pub fn get(&self) -> &str {
+        &self.json
+    }
+
+    pub fn into_string(self) -> String {
+        self.json.into_string()
+    }
+}
+
+impl fmt::Debug for RawValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RawValue").field(&&*self.json).finish()
+    }
+}
+
+impl fmt::Display for RawValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.json)
+    }
+}
+
+impl Serialize for RawValue {
+    fn view(&self) -> ValueView<'_> {
+        ValueView::RawJson(Cow::Borrowed(&self.json))
+    }
+}
+
+impl Deserialize for RawValue {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl Visitor for Place<RawValue> {
+            fn wants_raw(&mut self) -> bool {
+                true
+            }
+
+            fn raw_json(&mut self, j: &str) -> Result<()> {
+                self.out = Some(RawValue { json: j.into() });
+                Ok(())
+            }
+
+            #[cfg(feature = "cbor")]
+            fn raw_cbor(&mut self, bytes: &[u8]) -> Result<()> {
+                let value: crate::cbor::Value = crate::cbor::from_slice(bytes)?;
+                let json = crate::json::to_string(&value)?;
+                self.out = Some(RawValue {
+                    json: json.into_boxed_str(),
+                });
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
+}
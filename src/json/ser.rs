@@ -1,4 +1,12 @@
-use crate::ser::{Map, Seq, Serialize, ValueView};
+use alloc::borrow::Cow;
+use core::fmt;
+
+#[cfg(feature = "cbor")]
+use crate::de;
+#[cfg(feature = "std")]
+use crate::io;
+use crate::prelude::*;
+use crate::ser::{Map, Seq, Serialize, SignedZero, ValueView};
 
 /// Serialize any serializable type into a JSON string.
 ///
@@ -21,8 +29,297 @@ use crate::ser::{Map, Seq, Serialize, ValueView};
 ///     println!("{}", j);
 /// }
 /// ```
-pub fn to_string<'value>(value: &'value dyn Serialize) -> crate::Result<String> {
-    let mut out = String::new();
+pub fn to_string(value: &dyn Serialize) -> crate::Result<String> {
+    Serializer::new().to_string(value)
+}
+
+/// A JSON serializer with non-default options.
+///
+/// ```rust
+/// use miniserde_ditto::json;
+/// use miniserde_ditto::ser::SignedZero;
+///
+/// # #[cfg(feature = "float")]
+/// # fn main() {
+/// let j = json::Serializer::new()
+///     .signed_zero(SignedZero::Normalize)
+///     .to_string(&[-0.0, 1.5])
+///     .unwrap();
+/// assert_eq!(j, "[0.0,1.5]");
+/// # }
+/// # #[cfg(not(feature = "float"))]
+/// # fn main() {}
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct Serializer<'a> {
+    #[cfg_attr(not(feature = "float"), allow(dead_code))]
+    signed_zero: SignedZero,
+    escape_non_bmp: bool,
+    json5: bool,
+    progress: Option<&'a dyn Fn(usize)>,
+}
+
+impl fmt::Debug for Serializer<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Serializer")
+            .field("signed_zero", &self.signed_zero)
+            .field("escape_non_bmp", &self.escape_non_bmp)
+            .field("json5", &self.json5)
+            .field("progress", &self.progress.map(|_| ..))
+            .finish()
+    }
+}
+
+impl<'a> Serializer<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indented JSON5 output, with unquoted keys and trailing commas.
+    #[cfg(feature = "json5")]
+    pub(crate) fn json5() -> Self {
+        Serializer {
+            json5: true,
+            ..Self::default()
+        }
+    }
+
+    /// How to write negative zero floats; preserved by default.
+    pub fn signed_zero(mut self, policy: SignedZero) -> Self {
+        self.signed_zero = policy;
+        self
+    }
+
+    /// Whether to write characters above U+FFFF as a pair of UTF-16
+    /// surrogate escapes, such as `"\ud83d\ude00"` for 😀, rather than as
+    /// raw UTF-8. Off by default. Useful for consumers that only handle the
+    /// Basic Multilingual Plane.
+    pub fn escape_non_bmp(mut self, escape: bool) -> Self {
+        self.escape_non_bmp = escape;
+        self
+    }
+
+    /// Call `progress` with the length of the output so far each time an
+    /// array or object is closed, such as to report the progress of a large
+    /// export.
+    pub fn progress(mut self, progress: &'a dyn Fn(usize)) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Serialize any serializable type into a JSON string.
+    pub fn to_string(&self, value: &dyn Serialize) -> crate::Result<String> {
+        let ref mut w = Writer::new(Sink::String);
+        to_writer_impl(value, 0, self, w)?;
+        Ok(::core::mem::take(&mut w.out))
+    }
+
+    /// Serialize any serializable type into the bytes of a JSON string.
+    pub fn to_vec(&self, value: &dyn Serialize) -> crate::Result<Vec<u8>> {
+        self.to_string(value).map(String::into_bytes)
+    }
+
+    /// Serialize any serializable type as JSON into a
+    /// [`Write`][io::Write]able sink. See [`to_writer`] for the result.
+    #[cfg(feature = "std")]
+    pub fn to_writer(
+        &self,
+        out: &'_ mut dyn io::Write,
+        value: &dyn Serialize,
+    ) -> Result<(), Option<io::Error>> {
+        let ref mut w = Writer::new(Sink::Io(out));
+        let ret = to_writer_impl(value, 0, self, w).and_then(|()| w.flush());
+        ret.map_err(|_| w.io_error.take())
+    }
+
+    /// Serialize any serializable type as JSON into a
+    /// [`fmt::Write`]able sink. See [`to_fmt_writer`] for the result.
+    pub fn to_fmt_writer(
+        &self,
+        out: &'_ mut dyn fmt::Write,
+        value: &dyn Serialize,
+    ) -> Result<(), Option<fmt::Error>> {
+        let ref mut w = Writer::new(Sink::Fmt(out));
+        let ret = to_writer_impl(value, 0, self, w).and_then(|()| w.flush());
+        ret.map_err(|_| if w.failed { Some(fmt::Error) } else { None })
+    }
+}
+
+/// Serialize any serializable type into the bytes of a JSON string.
+pub fn to_vec(value: &dyn Serialize) -> crate::Result<Vec<u8>> {
+    Serializer::new().to_vec(value)
+}
+
+/// Serialize any serializable type as JSON into a [`Write`][io::Write]able
+/// sink, such as a file or a socket, without first writing it all to a
+/// `String`.
+///
+/// The output is written in chunks of about 8 KiB, so that `out` need not be
+/// buffered.
+///
+/// Returns:
+///   - `Ok(())` on success.
+///   - `Err(Some(io_error))` on I/O failure.
+///   - `Err(None)` on serialization error (non-string map key, or
+///     malformed raw number).
+///
+/// ```rust
+/// use miniserde_ditto::json;
+///
+/// let mut out = vec![];
+/// json::to_writer(&mut out, &vec![1, 2, 3]).unwrap();
+/// assert_eq!(out, b"[1,2,3]");
+/// ```
+#[cfg(feature = "std")]
+pub fn to_writer(
+    out: &'_ mut dyn io::Write,
+    value: &dyn Serialize,
+) -> Result<(), Option<io::Error>> {
+    Serializer::new().to_writer(out, value)
+}
+
+/// Serialize any serializable type as JSON into a [`fmt::Write`]able sink,
+/// such as a fixed-capacity string, without allocating any `String`.
+///
+/// Each piece of the output is written to `out` as soon as it is known, and
+/// serialization stops at the first error of `out`, such as when it is full.
+///
+/// Returns:
+///   - `Ok(())` on success.
+///   - `Err(Some(fmt::Error))` on failure of `out`.
+///   - `Err(None)` on serialization error (non-string map key, or
+///     malformed raw number).
+///
+/// ```rust
+/// use miniserde_ditto::json;
+/// use std::fmt::Write;
+///
+/// let mut out = String::from("value: ");
+/// json::to_fmt_writer(&mut out, &("ok", 1)).unwrap();
+/// assert_eq!(out, r#"value: ["ok",1]"#);
+/// ```
+pub fn to_fmt_writer(
+    out: &'_ mut dyn fmt::Write,
+    value: &dyn Serialize,
+) -> Result<(), Option<fmt::Error>> {
+    Serializer::new().to_fmt_writer(out, value)
+}
+
+/// Where the text of [`Writer`] goes.
+enum Sink<'out> {
+    /// Only to `Writer::out`.
+    String,
+    /// To `Writer::out`, written out in chunks.
+    #[cfg(feature = "std")]
+    Io(&'out mut dyn io::Write),
+    /// Directly.
+    Fmt(&'out mut dyn fmt::Write),
+}
+
+/// The output of the serializer: the text not yet written to `sink`, if any.
+struct Writer<'out> {
+    out: String,
+    sink: Sink<'out>,
+    /// The length of the text already written to `sink`.
+    flushed: usize,
+    /// Whether writing to `sink` failed, with `io_error` for an `io::Write`.
+    failed: bool,
+    #[cfg(feature = "std")]
+    io_error: Option<io::Error>,
+}
+
+impl<'out> Writer<'out> {
+    #[cfg(feature = "std")]
+    const CHUNK_LEN: usize = 8 * 1024;
+
+    fn new(sink: Sink<'out>) -> Self {
+        Writer {
+            out: String::new(),
+            sink,
+            flushed: 0,
+            failed: false,
+            #[cfg(feature = "std")]
+            io_error: None,
+        }
+    }
+
+    fn flush_if_full(&mut self) -> crate::Result<()> {
+        #[cfg(feature = "std")]
+        if let Sink::Io(_) = self.sink {
+            if self.out.len() >= Self::CHUNK_LEN {
+                self.flush()?;
+            }
+        }
+        self.check()
+    }
+
+    /// Writes the pending text to the sink, keeping the I/O error if any.
+    fn flush(&mut self) -> crate::Result<()> {
+        #[cfg(feature = "std")]
+        if let Sink::Io(ref mut sink) = self.sink {
+            if let Err(error) = sink.write_all(self.out.as_bytes()) {
+                self.io_error = Some(error);
+                self.failed = true;
+            }
+            self.flushed += self.out.len();
+            self.out.clear();
+        }
+        self.check()
+    }
+
+    fn check(&self) -> crate::Result<()> {
+        if self.failed {
+            return Err(crate::Error);
+        }
+        Ok(())
+    }
+}
+
+/// What the serializer pushes text to: a `String`, or a [`Writer`].
+pub(super) trait Output {
+    fn push(&mut self, c: char);
+    fn push_str(&mut self, s: &str);
+}
+
+impl Output for String {
+    fn push(&mut self, c: char) {
+        String::push(self, c)
+    }
+
+    fn push_str(&mut self, s: &str) {
+        String::push_str(self, s)
+    }
+}
+
+impl Output for Writer<'_> {
+    fn push(&mut self, c: char) {
+        match self.sink {
+            Sink::Fmt(_) => self.push_str(c.encode_utf8(&mut [0; 4])),
+            _ => self.out.push(c),
+        }
+    }
+
+    fn push_str(&mut self, s: &str) {
+        match self.sink {
+            Sink::Fmt(ref mut sink) => {
+                if !self.failed {
+                    self.failed = sink.write_str(s).is_err();
+                    self.flushed += s.len();
+                }
+            }
+            _ => self.out.push_str(s),
+        }
+    }
+}
+
+/// Writes `value`, found `depth` levels deep into the whole output, which
+/// only matters to the indentation.
+fn to_writer_impl<'value>(
+    value: &'value dyn Serialize,
+    depth: usize,
+    config: &Serializer<'_>,
+    w: &mut Writer<'_>,
+) -> crate::Result<()> {
     let mut stack: Vec<Layer<'value>> = vec![];
     enum Layer<'value> {
         Seq(Box<dyn Seq<'value> + 'value>),
@@ -31,69 +328,74 @@ pub fn to_string<'value>(value: &'value dyn Serialize) -> crate::Result<String>
     let mut view = value.view();
 
     loop {
+        w.flush_if_full()?;
         match view {
-            ValueView::Null => out.push_str("null"),
-            ValueView::Bool(b) => out.push_str(if b { "true" } else { "false" }),
-            ValueView::Str(s) => escape_str(&s, &mut out),
-            ValueView::Bytes(bs) => {
-                out.push('[');
-                let mut bytes = bs.iter().copied();
-                if let Some(fst) = bytes.next() {
-                    fn fmt_byte<'buf>(mut byte: u8, buf: &'buf mut [u8; 3]) -> &'buf str {
-                        if byte == 0 {
-                            return "0";
-                        }
-                        let mut cursor = 3;
-                        while byte > 0 {
-                            cursor -= 1;
-                            buf[cursor] = b'0' + byte % 10;
-                            byte /= 10;
-                        }
-                        ::core::str::from_utf8(&buf[cursor..]).unwrap()
-                    }
-                    let ref mut buf = [0; 3];
-                    out.push_str(fmt_byte(fst, buf));
-                    bytes.for_each(|b| {
-                        out.push(',');
-                        out.push_str(fmt_byte(b, buf));
-                    });
-                }
-                out.push(']');
-            }
-            ValueView::Int(i) => out.push_str(itoa::Buffer::new().format(i)),
-            ValueView::F64(n) => {
-                if n.is_finite() {
-                    out.push_str(ryu::Buffer::new().format_finite(n))
-                } else {
-                    out.push_str("null")
-                }
+            ValueView::Null | ValueView::Undefined => w.push_str("null"),
+            ValueView::Bool(b) => w.push_str(if b { "true" } else { "false" }),
+            ValueView::Str(s) => escape_str(&s, config.escape_non_bmp, w),
+            ValueView::Bytes(bs) => write_bytes(&bs, w),
+            ValueView::Base64(bs) => {
+                w.push('"');
+                super::encode_base64(&bs).for_each(|c| w.push(c));
+                w.push('"');
+            }
+            ValueView::Timestamp(t) => {
+                w.push('"');
+                w.push_str(&t.to_string());
+                w.push('"');
+            }
+            ValueView::Int(i) => w.push_str(itoa::Buffer::new().format(i)),
+            #[cfg(feature = "float")]
+            ValueView::F64(n) => write_f64(config, n, w),
+            ValueView::RawNumber(raw) => {
+                crate::json::check_raw_number(&raw)?;
+                w.push_str(&raw)
+            }
+            ValueView::RawJson(raw) => {
+                crate::json::check_raw_value(&raw)?;
+                w.push_str(&raw)
+            }
+            ValueView::RawCbor(_) => {
+                let value = view.decode_raw()?;
+                to_writer_impl(&*value, depth + stack.len(), config, w)?;
+            }
+            ValueView::Owned(value) => to_writer_impl(&*value, depth + stack.len(), config, w)?,
+            ValueView::Unrepresentable => err!("Cannot serialize an unrepresentable value"),
+            ValueView::Tagged(_, inner) => {
+                view = inner.view();
+                continue;
             }
             ValueView::Seq(mut seq) => {
-                out.push('[');
+                w.push('[');
                 match seq.next() {
                     Some(first) => {
                         stack.push(Layer::Seq(seq));
+                        indent(config, depth + stack.len(), w);
                         view = first.view();
                         continue;
                     }
-                    None => out.push(']'),
+                    None => {
+                        w.push(']');
+                        report_progress(config, w);
+                    }
                 }
             }
             ValueView::Map(mut map) => {
-                out.push('{');
+                w.push('{');
                 match map.next() {
                     Some((key, first)) => {
                         let key = key.view();
-                        let key = key
-                            .as_str()
-                            .ok_or_else(|| err!("Expected string key for JSON serialization"))?;
-                        escape_str(key, &mut out);
-                        out.push(':');
+                        let key = key_str(&key)?;
                         stack.push(Layer::Map(map));
+                        indent(config, depth + stack.len(), w);
+                        write_key(config, &key, w);
                         view = first.view();
                         continue;
                     }
-                    None => out.push('}'),
+                    None => {
+                        w.push('}');
+                        report_progress(config, w);
+                    }
                 }
             }
         }
@@ -102,44 +404,366 @@ pub fn to_string<'value>(value: &'value dyn Serialize) -> crate::Result<String>
             match stack.last_mut() {
                 Some(Layer::Seq(seq)) => match seq.next() {
                     Some(next) => {
-                        out.push(',');
+                        w.push(',');
+                        indent(config, depth + stack.len(), w);
                         view = next.view();
                         break;
                     }
-                    None => out.push(']'),
+                    None => {
+                        close(config, depth + stack.len(), w);
+                        w.push(']');
+                        report_progress(config, w);
+                    }
                 },
                 Some(Layer::Map(map)) => match map.next() {
                     Some((key, next)) => {
                         let key = key.view();
-                        let key = key
-                            .as_str()
-                            .ok_or_else(|| err!("Expected string key for JSON serialization"))?;
-                        out.push(',');
-                        escape_str(key, &mut out);
-                        out.push(':');
+                        let key = key_str(&key)?;
+                        w.push(',');
+                        indent(config, depth + stack.len(), w);
+                        write_key(config, &key, w);
                         view = next.view();
                         break;
                     }
-                    None => out.push('}'),
+                    None => {
+                        close(config, depth + stack.len(), w);
+                        w.push('}');
+                        report_progress(config, w);
+                    }
                 },
-                None => return Ok(out),
+                None => return Ok(()),
             }
             stack.pop();
         }
     }
 }
 
-// Clippy false positive: https://github.com/rust-lang/rust-clippy/issues/5169
-#[allow(clippy::zero_prefixed_literal)]
-fn escape_str(value: &str, out: &mut String) {
+/// Serialize a CBOR byte sequence into JSON text, writing each data item as
+/// soon as it is decoded rather than deserializing the whole input into a
+/// [`Value`](crate::json::Value) first.
+///
+/// Values are written as by [`to_string`], map entries kept in the order of
+/// the input, and tags dropped.
+///
+/// ```rust
+/// use miniserde_ditto::json;
+///
+/// let bytes = [0xa1, 0x61, b'a', 0x82, 0x01, 0xf5];
+/// let j = json::to_string_from_cbor_slice(&bytes).unwrap();
+/// assert_eq!(j, r#"{"a":[1,true]}"#);
+/// ```
+#[cfg(feature = "cbor")]
+pub fn to_string_from_cbor_slice(bytes: &[u8]) -> crate::Result<String> {
+    let mut out = String::new();
+    let config = Serializer::new();
+    let emitter = &mut Emitter {
+        config: &config,
+        out: &mut out,
+    };
+    crate::cbor::from_slice_into(bytes, emitter)?;
+    Ok(out)
+}
+
+/// A [`Visitor`](de::Visitor) that writes what it is given to `out`.
+#[cfg(feature = "cbor")]
+struct Emitter<'a> {
+    config: &'a Serializer<'a>,
+    out: &'a mut String,
+}
+
+/// The elements, or the keys and values, of an array or object of
+/// [`Emitter`].
+#[cfg(feature = "cbor")]
+struct EmitterLayer<'a> {
+    item: Emitter<'a>,
+    empty: bool,
+}
+
+#[cfg(feature = "cbor")]
+impl Emitter<'_> {
+    fn layer(&mut self, open: char) -> EmitterLayer<'_> {
+        self.out.push(open);
+        EmitterLayer {
+            item: Emitter {
+                config: self.config,
+                out: self.out,
+            },
+            empty: true,
+        }
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl de::Visitor for Emitter<'_> {
+    fn null(&mut self) -> crate::Result<()> {
+        self.out.push_str("null");
+        Ok(())
+    }
+
+    fn boolean(&mut self, b: bool) -> crate::Result<()> {
+        self.out.push_str(if b { "true" } else { "false" });
+        Ok(())
+    }
+
+    fn string(&mut self, s: &str) -> crate::Result<()> {
+        escape_str(s, self.config.escape_non_bmp, self.out);
+        Ok(())
+    }
+
+    fn bytes(&mut self, xs: &[u8]) -> crate::Result<()> {
+        write_bytes(xs, self.out);
+        Ok(())
+    }
+
+    fn int(&mut self, i: i128) -> crate::Result<()> {
+        self.out.push_str(itoa::Buffer::new().format(i));
+        Ok(())
+    }
+
+    #[cfg(feature = "float")]
+    fn float(&mut self, f: f64) -> crate::Result<()> {
+        write_f64(self.config, f, self.out);
+        Ok(())
+    }
+
+    fn seq(&mut self) -> crate::Result<Box<dyn de::Seq + '_>> {
+        Ok(Box::new(self.layer('[')))
+    }
+
+    fn map(&mut self) -> crate::Result<Box<dyn de::Map + '_>> {
+        Ok(Box::new(self.layer('{')))
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl<'a> EmitterLayer<'a> {
+    fn next(&mut self) -> &mut Emitter<'a> {
+        if !::core::mem::take(&mut self.empty) {
+            self.item.out.push(',');
+        }
+        &mut self.item
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl de::Seq for EmitterLayer<'_> {
+    fn element(&mut self) -> crate::Result<&mut dyn de::Visitor> {
+        Ok(self.next())
+    }
+
+    fn finish(self: Box<Self>) -> crate::Result<()> {
+        self.item.out.push(']');
+        Ok(())
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl de::Map for EmitterLayer<'_> {
+    fn val_with_key(
+        &mut self,
+        with_key: &mut dyn FnMut(crate::Result<&mut dyn de::Visitor>) -> crate::Result<()>,
+    ) -> crate::Result<&mut dyn de::Visitor> {
+        let item = self.next();
+        with_key(Ok(&mut KeyEmitter(item)))?;
+        Ok(item)
+    }
+
+    fn finish(self: Box<Self>) -> crate::Result<()> {
+        self.item.out.push('}');
+        Ok(())
+    }
+}
+
+/// Writes a map key, followed by the colon, with the same conversions as
+/// [`key_str`].
+#[cfg(feature = "cbor")]
+struct KeyEmitter<'a, 'b>(&'a mut Emitter<'b>);
+
+#[cfg(feature = "cbor")]
+impl KeyEmitter<'_, '_> {
+    fn key(&mut self, key: &ValueView<'_>) -> crate::Result<()> {
+        write_key(self.0.config, &key_str(key)?, self.0.out);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl de::Visitor for KeyEmitter<'_, '_> {
+    fn boolean(&mut self, b: bool) -> crate::Result<()> {
+        self.key(&ValueView::Bool(b))
+    }
+
+    fn string(&mut self, s: &str) -> crate::Result<()> {
+        self.key(&ValueView::Str(Cow::Borrowed(s)))
+    }
+
+    fn bytes(&mut self, xs: &[u8]) -> crate::Result<()> {
+        self.key(&ValueView::Bytes(Cow::Borrowed(xs)))
+    }
+
+    fn int(&mut self, i: i128) -> crate::Result<()> {
+        self.key(&ValueView::Int(i))
+    }
+
+    #[cfg(feature = "float")]
+    fn float(&mut self, f: f64) -> crate::Result<()> {
+        self.key(&ValueView::F64(f))
+    }
+}
+
+/// Bytes are written as an array of numbers.
+fn write_bytes<O: Output + ?Sized>(bs: &[u8], out: &mut O) {
+    out.push('[');
+    let mut bytes = bs.iter().copied();
+    if let Some(fst) = bytes.next() {
+        fn fmt_byte(mut byte: u8, buf: &mut [u8; 3]) -> &str {
+            if byte == 0 {
+                return "0";
+            }
+            let mut cursor = 3;
+            while byte > 0 {
+                cursor -= 1;
+                buf[cursor] = b'0' + byte % 10;
+                byte /= 10;
+            }
+            ::core::str::from_utf8(&buf[cursor..]).unwrap()
+        }
+        let buf = &mut [0; 3];
+        out.push_str(fmt_byte(fst, buf));
+        bytes.for_each(|b| {
+            out.push(',');
+            out.push_str(fmt_byte(b, buf));
+        });
+    }
+    out.push(']');
+}
+
+#[cfg(feature = "float")]
+fn write_f64<O: Output + ?Sized>(config: &Serializer<'_>, n: f64, out: &mut O) {
+    if n.is_finite() {
+        let n = config.signed_zero.apply(n);
+        out.push_str(ryu::Buffer::new().format_finite(n))
+    } else if config.json5 {
+        out.push_str(if n.is_nan() {
+            "NaN"
+        } else if n > 0.0 {
+            "Infinity"
+        } else {
+            "-Infinity"
+        })
+    } else {
+        out.push_str("null")
+    }
+}
+
+/// Starts a new line at `depth` when pretty-printing.
+fn indent<O: Output + ?Sized>(config: &Serializer<'_>, depth: usize, out: &mut O) {
+    if config.json5 {
+        out.push('\n');
+        for _ in 0..depth {
+            out.push_str("  ");
+        }
+    }
+}
+
+/// Ends the last element of a non-empty sequence or map at `depth`.
+fn close<O: Output + ?Sized>(config: &Serializer<'_>, depth: usize, out: &mut O) {
+    if config.json5 {
+        out.push(',');
+        indent(config, depth - 1, out);
+    }
+}
+
+fn report_progress(config: &Serializer<'_>, w: &Writer<'_>) {
+    if let Some(progress) = config.progress {
+        progress(w.flushed + w.out.len());
+    }
+}
+
+/// The text of a map key. Like in serde_json, integers, booleans and finite
+/// floats are written as strings, since JSON object keys are.
+fn key_str<'a>(key: &'a ValueView<'_>) -> crate::Result<Cow<'a, str>> {
+    Ok(match *key {
+        ValueView::Str(ref s) => Cow::Borrowed(s),
+        ValueView::Bytes(ref xs) => match ::core::str::from_utf8(xs) {
+            Ok(s) => Cow::Borrowed(s),
+            Err(_) => err!("Expected UTF-8 bytes for a JSON key"),
+        },
+        ValueView::Int(i) => Cow::Owned(itoa::Buffer::new().format(i).to_owned()),
+        ValueView::Bool(b) => Cow::Borrowed(if b { "true" } else { "false" }),
+        #[cfg(feature = "float")]
+        ValueView::F64(n) if n.is_finite() => {
+            Cow::Owned(ryu::Buffer::new().format_finite(n).to_owned())
+        }
+        ValueView::RawNumber(ref raw) => {
+            crate::json::check_raw_number(raw)?;
+            Cow::Borrowed(raw)
+        }
+        _ => err!("Expected string key for JSON serialization"),
+    })
+}
+
+fn write_key<O: Output + ?Sized>(config: &Serializer<'_>, key: &str, out: &mut O) {
+    let is_identifier = |key: &str| match key.as_bytes().first() {
+        Some(b'a'..=b'z') | Some(b'A'..=b'Z') | Some(b'_') | Some(b'$') => key
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'$'),
+        _ => false,
+    };
+    if config.json5 && is_identifier(key) {
+        out.push_str(key);
+    } else {
+        escape_str(key, config.escape_non_bmp, out);
+    }
+    out.push(':');
+    if config.json5 {
+        out.push(' ');
+    }
+}
+
+/// Append `value` to `out`, escaped as the contents of a JSON string, that is
+/// without the surrounding quotes.
+///
+/// Quotes, backslashes and control characters are escaped, as `to_string`
+/// does. See [`unescape`](crate::json::unescape) for the other way around.
+///
+/// ```rust
+/// use miniserde_ditto::json;
+///
+/// let mut out = String::from("\"");
+/// json::escape("say \"hi\"\n", &mut out);
+/// out.push('"');
+/// assert_eq!(out, r#""say \"hi\"\n""#);
+/// ```
+pub fn escape(value: &str, out: &mut String) {
+    escape_contents(value, false, out);
+}
+
+pub(super) fn escape_str<O: Output + ?Sized>(value: &str, escape_non_bmp: bool, out: &mut O) {
+    out.push('"');
+    escape_contents(value, escape_non_bmp, out);
     out.push('"');
+}
 
+// Clippy false positive: https://github.com/rust-lang/rust-clippy/issues/5169
+#[allow(clippy::zero_prefixed_literal)]
+pub(super) fn escape_contents<O: Output + ?Sized>(value: &str, escape_non_bmp: bool, out: &mut O) {
     let bytes = value.as_bytes();
     let mut start = 0;
 
-    for (i, &byte) in bytes.iter().enumerate() {
+    let mut i = 0;
+    while i < bytes.len() {
+        #[cfg(feature = "simd")]
+        {
+            i += super::swar::plain_len(&bytes[i..], b"\"\\", escape_non_bmp);
+            if i == bytes.len() {
+                break;
+            }
+        }
+        let byte = bytes[i];
         let escape = ESCAPE[byte as usize];
-        if escape == 0 {
+        if escape == 0 || (escape == NB && !escape_non_bmp) {
+            i += 1;
             continue;
         }
 
@@ -155,23 +779,34 @@ fn escape_str(value: &str, out: &mut String) {
             self::RR => out.push_str("\\r"),
             self::QU => out.push_str("\\\""),
             self::BS => out.push_str("\\\\"),
-            self::U => {
-                static HEX_DIGITS: [u8; 16] = *b"0123456789abcdef";
-                out.push_str("\\u00");
-                out.push(HEX_DIGITS[(byte >> 4) as usize] as char);
-                out.push(HEX_DIGITS[(byte & 0xF) as usize] as char);
+            self::U => push_hex_escape(u16::from(byte), out),
+            self::NB => {
+                let c = value[i..].chars().next().unwrap();
+                for &unit in c.encode_utf16(&mut [0; 2]).iter() {
+                    push_hex_escape(unit, out);
+                }
+                start = i + c.len_utf8();
+                i = start;
+                continue;
             }
             _ => unreachable!(),
         }
 
         start = i + 1;
+        i = start;
     }
 
     if start != bytes.len() {
         out.push_str(&value[start..]);
     }
+}
 
-    out.push('"');
+fn push_hex_escape<O: Output + ?Sized>(unit: u16, out: &mut O) {
+    static HEX_DIGITS: [u8; 16] = *b"0123456789abcdef";
+    out.push_str("\\u");
+    for shift in [12, 8, 4, 0].iter() {
+        out.push(HEX_DIGITS[usize::from(unit >> shift & 0xF)] as char);
+    }
 }
 
 const BB: u8 = b'b'; // \x08
@@ -182,9 +817,11 @@ const RR: u8 = b'r'; // \x0D
 const QU: u8 = b'"'; // \x22
 const BS: u8 = b'\\'; // \x5C
 const U: u8 = b'u'; // \x00...\x1F except the ones above
+const NB: u8 = b'4'; // \xF0...\xF4, leading a 4-byte (non-BMP) character
 
 // Lookup table of escape sequences. A value of b'x' at index i means that byte
 // i is escaped as "\x" in JSON. A value of 0 means that byte i is not escaped.
+// NB bytes are only escaped, as a surrogate pair, with `escape_non_bmp`.
 #[rustfmt::skip]
 static ESCAPE: [u8; 256] = [
     //  1   2   3   4   5   6   7   8   9   A   B   C   D   E   F
@@ -203,5 +840,5 @@ static ESCAPE: [u8; 256] = [
     0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0, // C
     0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0, // D
     0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0, // E
-    0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0, // F
+   NB, NB, NB, NB, NB,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0,  0, // F
 ];
@@ -0,0 +1,34 @@
+//! Skipping over the plain bytes of a string eight at a time, by treating
+//! them as the bytes of a `u64` ("SIMD within a register").
+
+use core::convert::TryInto;
+
+const ONES: u64 = 0x0101_0101_0101_0101;
+const HIGHS: u64 = 0x8080_8080_8080_8080;
+
+/// Whether any byte of `word` is below `n`, which must be at most 0x80.
+fn has_less(word: u64, n: u8) -> bool {
+    word.wrapping_sub(ONES * u64::from(n)) & !word & HIGHS != 0
+}
+
+fn has_byte(word: u64, byte: u8) -> bool {
+    has_less(word ^ (ONES * u64::from(byte)), 1)
+}
+
+/// The length of the longest prefix of `bytes`, in whole words, holding no
+/// control character nor any of `special`, nor with `stop_at_non_ascii` any
+/// non-ASCII byte. The bytes past it are then to be looked at one by one.
+pub(in crate) fn plain_len(bytes: &[u8], special: &[u8], stop_at_non_ascii: bool) -> usize {
+    let mut len = 0;
+    for chunk in bytes.chunks_exact(8) {
+        let word = u64::from_le_bytes(chunk.try_into().unwrap());
+        if has_less(word, 0x20)
+            || special.iter().any(|&byte| has_byte(word, byte))
+            || (stop_at_non_ascii && word & HIGHS != 0)
+        {
+            break;
+        }
+        len += 8;
+    }
+    len
+}
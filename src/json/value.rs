@@ -1,8 +1,11 @@
-use std::borrow::Cow;
+use alloc::borrow::Cow;
+use core::iter::FromIterator;
 
 use crate::de::{Deserialize, Map, Seq, Visitor};
 use crate::error::Result;
-use crate::json::{Array, Number, Object};
+use crate::json::{Array, Key, Number, Object};
+use crate::merge::{self, Merge};
+use crate::prelude::*;
 use crate::private;
 use crate::ser::{Serialize, ValueView};
 use crate::Place;
@@ -40,14 +43,151 @@ impl Default for Value {
     }
 }
 
+impl Value {
+    /// Applies `patch` to this value as a [JSON Merge Patch]: the members of
+    /// an object patch are merged into an object, recursively, those set to
+    /// `null` being removed, and any other patch replaces the value as a
+    /// whole.
+    ///
+    /// ```rust
+    /// use miniserde_ditto::json::{self, Value};
+    ///
+    /// let mut config: Value = json::from_str(r#"{"port":80,"tls":{"cert":"a.pem","key":"a.key"}}"#)?;
+    /// let overlay: Value = json::from_str(r#"{"tls":{"key":null},"zones":["x"]}"#)?;
+    /// config.merge(overlay);
+    /// assert_eq!(
+    ///     json::to_string(&config)?,
+    ///     r#"{"port":80,"tls":{"cert":"a.pem"},"zones":["x"]}"#,
+    /// );
+    /// # Ok::<(), miniserde_ditto::Error>(())
+    /// ```
+    ///
+    /// This works on `Value`s in place, for any depth of nesting; to patch a
+    /// typed value, see [`delta`](crate::delta).
+    ///
+    /// [JSON Merge Patch]: https://tools.ietf.org/html/rfc7386
+    pub fn merge(&mut self, patch: Value) {
+        merge::merge(self, patch);
+    }
+}
+
+impl Merge for Value {
+    type Key = Key;
+    type Map = Object;
+
+    fn into_map(self) -> Result<Object, Value> {
+        match self {
+            Value::Object(object) => Ok(object),
+            value => Err(value),
+        }
+    }
+
+    fn from_map(object: Object) -> Value {
+        Value::Object(object)
+    }
+
+    fn make_map(&mut self) -> &mut Object {
+        if !matches!(self, Value::Object(_)) {
+            *self = Value::Object(Object::new());
+        }
+        match self {
+            Value::Object(object) => object,
+            _ => unreachable!(),
+        }
+    }
+
+    fn removes(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    fn contains_key(object: &Object, key: &Key) -> bool {
+        object.contains_key(key)
+    }
+
+    fn insert(object: &mut Object, key: Key, value: Value) {
+        object.insert(key, value);
+    }
+
+    fn remove(object: &mut Object, key: &Key) {
+        object.remove(key);
+    }
+
+    fn for_each_mut<'a>(object: &'a mut Object, f: &mut dyn FnMut(&Key, &'a mut Value)) {
+        for (key, child) in object.iter_mut() {
+            f(key, child);
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::String(s)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::String(s.to_owned())
+    }
+}
+
+impl From<Array> for Value {
+    fn from(array: Array) -> Self {
+        Value::Array(array)
+    }
+}
+
+impl From<Object> for Value {
+    fn from(object: Object) -> Self {
+        Value::Object(object)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(elements: Vec<T>) -> Self {
+        elements.into_iter().collect()
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    /// `None` becomes [`Value::Null`].
+    fn from(opt: Option<T>) -> Self {
+        opt.map_or(Value::Null, Into::into)
+    }
+}
+
+impl<T: Into<Value>> FromIterator<T> for Value {
+    /// Collects into a [`Value::Array`].
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Value::Array(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<K: Into<Key>, V: Into<Value>> FromIterator<(K, V)> for Value {
+    /// Collects into a [`Value::Object`].
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        Value::Object(iter.into_iter().map(|(k, v)| (k, v.into())).collect())
+    }
+}
+
 impl Serialize for Value {
     fn view(&self) -> ValueView<'_> {
         match self {
             Value::Null => ValueView::Null,
             Value::Bool(b) => ValueView::Bool(*b),
-            &Value::Number(Number::U64(n)) => ValueView::Int(n as _),
-            &Value::Number(Number::I64(i)) => ValueView::Int(i as _),
-            &Value::Number(Number::F64(f)) => ValueView::F64(f),
+            Value::Number(number) => number.view(),
             Value::String(s) => ValueView::Str(Cow::Borrowed(s)),
             Value::Array(array) => private::stream_slice(array),
             Value::Object(object) => private::stream_json_object(object),
@@ -74,22 +214,26 @@ impl Deserialize for Value {
             }
 
             fn int(&mut self, i: i128) -> Result<()> {
-                use ::core::convert::TryFrom;
-                self.out = Some(Value::Number(if let Ok(u64) = u64::try_from(i) {
-                    Number::U64(u64)
-                } else if let Ok(i64) = i64::try_from(i) {
-                    Number::I64(i64)
-                } else {
-                    return Err(crate::Error);
-                }));
+                self.out = Some(Value::Number(Number::from_i128(i)));
                 Ok(())
             }
 
+            #[cfg(feature = "float")]
             fn float(&mut self, n: f64) -> Result<()> {
                 self.out = Some(Value::Number(Number::F64(n)));
                 Ok(())
             }
 
+            fn raw_number(&mut self, raw: &str) -> Result<()> {
+                self.out = Some(Value::Number(Number::Raw(raw.to_owned())));
+                Ok(())
+            }
+
+            fn big_integer(&mut self, raw: &str) -> Result<()> {
+                self.out = Some(Value::Number(Number::from_big_integer(raw)));
+                Ok(())
+            }
+
             fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
                 Ok(Box::new(ArrayBuilder {
                     out: &mut self.out,
@@ -138,7 +282,7 @@ impl Deserialize for Value {
         struct ObjectBuilder<'a> {
             out: &'a mut Option<Value>,
             object: Object,
-            key: Option<String>,
+            key: Option<Key>,
             value: Option<Value>,
         }
 
@@ -0,0 +1,53 @@
+//! JSON5 data format, a relaxed JSON for human-maintained files.
+//!
+//! On top of JSON, the parser accepts comments, trailing commas, unquoted or
+//! single-quoted keys, single-quoted strings, hexadecimal integers, and the
+//! `Infinity` and `NaN` floats. Output is indented, with unquoted keys where
+//! possible and trailing commas.
+//!
+//! ```rust
+//! use miniserde_ditto::{json5, Deserialize, Serialize};
+//!
+//! #[derive(Deserialize, Serialize, Debug)]
+//! struct Config {
+//!     name: String,
+//!     ports: Vec<u16>,
+//! }
+//!
+//! fn main() -> miniserde_ditto::Result<()> {
+//!     let j = "{
+//!         // Service name.
+//!         name: 'web',
+//!         ports: [0x50, 443,],
+//!     }";
+//!
+//!     let config: Config = json5::from_str(j)?;
+//!     assert_eq!(config.ports, [80, 443]);
+//!
+//!     let expected = "{\n  name: \"web\",\n  ports: [\n    80,\n    443,\n  ],\n}";
+//!     assert_eq!(json5::to_string(&config)?, expected);
+//!     Ok(())
+//! }
+//! ```
+
+use crate::de::Deserialize;
+use crate::error::{Error, Result};
+use crate::json::{self, Options};
+use crate::prelude::*;
+use crate::ser::Serialize;
+
+/// Deserialize a JSON5 string into any deserializable type.
+pub fn from_str<T: Deserialize>(j: &str) -> Result<T> {
+    let mut out = None;
+    let options = Options {
+        json5: true,
+        ..Options::default()
+    };
+    json::from_str_impl(j, T::begin(&mut out), options)?;
+    out.ok_or(Error)
+}
+
+/// Serialize any serializable type into an indented JSON5 string.
+pub fn to_string(value: &dyn Serialize) -> Result<String> {
+    json::Serializer::json5().to_string(value)
+}
@@ -1,5 +1,44 @@
-#![cfg_attr(doc, feature(external_doc, doc_cfg))]
-#![cfg_attr(doc, doc(include = "../README.md"))]
+//! Data structure serialization with several opposite design goals from
+//! [Serde](https://serde.rs): no monomorphization, less recursion, and no
+//! deserialization error messages, for way smaller binaries.
+//!
+//! ```rust
+//! use miniserde_ditto::{json, Serialize, Deserialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug)]
+//! struct Example {
+//!     code: u32,
+//!     message: String,
+//! }
+//!
+//! fn main() -> miniserde_ditto::Result<()> {
+//!     let example = Example {
+//!         code: 200,
+//!         message: "reminiscent of Serde".to_owned(),
+//!     };
+//!
+//!     let j = json::to_string(&example)?;
+//!     let out: Example = json::from_str(&j)?;
+//!     assert_eq!(out.code, 200);
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! The formats are `json`, `cbor` and, behind their features, `msgpack`,
+//! `toml` and `json5`. The [README] goes through the differences from Serde,
+//! the [supported attributes], the [minimum supported Rust version], and
+//! features such as [canonical CBOR], [transcoding] and [`no_std`] support.
+//!
+//! [README]: https://github.com/getditto/miniserde#readme
+//! [supported attributes]: https://github.com/getditto/miniserde#list-of-supported-attributes
+//! [minimum supported Rust version]: https://github.com/getditto/miniserde#minimum-supported-rust-version
+//! [canonical CBOR]: https://github.com/getditto/miniserde#canonical-cbor
+//! [transcoding]: https://github.com/getditto/miniserde#transcoding
+//! [`no_std`]: https://github.com/getditto/miniserde#no_std
+
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
 #![allow(
     clippy::needless_doctest_main,
     clippy::vec_init_then_push,
@@ -17,6 +56,9 @@
     clippy::doc_markdown,
     clippy::enum_glob_use,
     clippy::missing_errors_doc,
+    // Most items are re-exported out of the private module that they are
+    // named after, such as `de::VisitSlot` from `de::slot`.
+    clippy::module_name_repetitions,
     clippy::must_use_candidate,
     clippy::redundant_else,
     clippy::shadow_unrelated,
@@ -25,16 +67,34 @@
 )]
 #![deny(rust_2018_idioms)]
 #![allow(explicit_outlives_requirements)]
+#![no_std]
+
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
 
 extern crate self as miniserde_ditto;
 
+// Test the examples of the README. Before 1.54, `#[doc = include_str!(..)]`
+// only parses with the macro call passed in as an expression.
+#[cfg(doctest)]
+macro_rules! doc_comment {
+    ($doc:expr, $($item:tt)*) => {
+        #[doc = $doc]
+        $($item)*
+    };
+}
+
+#[cfg(doctest)]
+doc_comment!(include_str!("../README.md"), struct ReadmeDoctests;);
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __err__ {(
     $($args:tt)*
 ) => ({
     if ::core::option_env!("MINISERDE_DEBUG_ERRORS") == Some("1") {
-        ::std::eprintln!("Serde error: {}", ::core::format_args!($($args)*));
+        $crate::__::debug_error(::core::format_args!($($args)*));
     }
     return $crate::ResultLike::ERROR;
 })}
@@ -72,6 +132,18 @@ pub mod __private;
 #[doc(hidden)]
 pub use __private as __;
 
+/// What the `std` prelude adds to the `core` one.
+mod prelude {
+    pub(in crate) use alloc::{
+        borrow::ToOwned,
+        boxed::Box,
+        format,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+}
+
 mod aliased_box;
 
 #[macro_use]
@@ -82,34 +154,59 @@ mod place;
 
 mod error;
 
+#[cfg(any(feature = "cbor", feature = "json"))]
+mod io;
+
+#[cfg(any(feature = "cbor", feature = "json"))]
+mod merge;
+
+mod scratch;
+
+#[cfg(feature = "zeroize")]
+mod sensitive;
+
+mod transcode;
+
+mod wire;
+
 #[cfg(feature = "cbor")]
-#[cfg_attr(doc, doc(cfg(feature = "cbor")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "cbor")))]
 pub mod cbor;
 pub mod de;
+pub mod delta;
+pub mod envelope;
+pub mod hex;
 #[cfg(feature = "json")]
-#[cfg_attr(doc, doc(cfg(feature = "json")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "json")))]
 pub mod json;
+#[cfg(feature = "json5")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json5")))]
+pub mod json5;
+#[cfg(feature = "msgpack")]
+#[cfg_attr(docsrs, doc(cfg(feature = "msgpack")))]
+pub mod msgpack;
+pub mod schema;
 pub mod ser;
+#[cfg(feature = "serde-compat")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde-compat")))]
+pub mod serde_compat;
+pub mod timestamp;
+#[cfg(feature = "toml")]
+#[cfg_attr(docsrs, doc(cfg(feature = "toml")))]
+pub mod toml;
 
 #[doc(inline)]
 pub use crate::de::Deserialize;
+pub use crate::envelope::TypeTag;
 pub use crate::error::{Error, Result};
+#[cfg(feature = "zeroize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+pub use crate::sensitive::Sensitive;
 #[doc(inline)]
 pub use crate::ser::Serialize;
+pub use crate::wire::{check_wire_compat, WIRE_COMPAT_VERSION};
 
 make_place!(Place);
 
 #[allow(non_camel_case_types)]
 struct private;
-
-macro_rules! with_Ns {( $($rules:tt)* ) => (
-    macro_rules! __helper__ { $($rules)* }
-    __helper__! {
-        00,
-        01, 02, 03, 04, 05, 06, 07, 08,
-        09, 10, 11, 12, 13, 14, 15, 16,
-        17, 18, 19, 20, 21, 22, 23, 24,
-        25, 26, 27, 28, 29, 30, 31, 32,
-    }
-)}
-pub(in crate) use with_Ns;
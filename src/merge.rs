@@ -0,0 +1,72 @@
+//! The merge patch algorithm of [RFC 7386], shared by the `Value`s of JSON and
+//! CBOR, which only differ in their maps and in how a patch removes an entry.
+//!
+//! [RFC 7386]: https://tools.ietf.org/html/rfc7386
+
+use alloc::collections::BTreeMap;
+
+use crate::prelude::*;
+
+/// A value that merge patches apply to, with maps of values of its own type.
+pub(crate) trait Merge: Default + Sized {
+    type Key: Ord + Clone;
+    type Map: IntoIterator<Item = (Self::Key, Self)>;
+
+    /// The entries of a map patch, or a patch that replaces the value whole.
+    fn into_map(self) -> Result<Self::Map, Self>;
+
+    fn from_map(map: Self::Map) -> Self;
+
+    /// The map of the value, which is first replaced by an empty map if it is
+    /// anything else.
+    fn make_map(&mut self) -> &mut Self::Map;
+
+    /// Whether a patch entry removes the entry of its key.
+    fn removes(&self) -> bool;
+
+    fn contains_key(map: &Self::Map, key: &Self::Key) -> bool;
+
+    fn insert(map: &mut Self::Map, key: Self::Key, value: Self);
+
+    fn remove(map: &mut Self::Map, key: &Self::Key);
+
+    /// Calls `f` with each entry of the map, for the values to outlive it.
+    fn for_each_mut<'a>(map: &'a mut Self::Map, f: &mut dyn FnMut(&Self::Key, &'a mut Self));
+}
+
+/// Applies `patch` to `target`, in place and without recursion.
+pub(crate) fn merge<V: Merge>(target: &mut V, patch: V) {
+    let mut stack = vec![(target, patch)];
+    while let Some((target, patch)) = stack.pop() {
+        let patch = match patch.into_map() {
+            Ok(patch) => patch,
+            Err(patch) => {
+                *target = patch;
+                continue;
+            }
+        };
+        let map = target.make_map();
+        let mut nested = BTreeMap::new();
+        for (key, value) in patch {
+            if value.removes() {
+                V::remove(map, &key);
+                continue;
+            }
+            match value.into_map() {
+                Ok(value) => {
+                    // Merged into below, as into an empty map when missing.
+                    if !V::contains_key(map, &key) {
+                        V::insert(map, key.clone(), V::default());
+                    }
+                    nested.insert(key, value);
+                }
+                Err(value) => V::insert(map, key, value),
+            }
+        }
+        V::for_each_mut(map, &mut |key, child| {
+            if let Some(patch) = nested.remove(key) {
+                stack.push((child, V::from_map(patch)));
+            }
+        });
+    }
+}
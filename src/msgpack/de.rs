@@ -0,0 +1,155 @@
+use ::core::convert::TryFrom;
+
+use super::marker;
+use crate::de::{Deserialize, Map, Visitor};
+use crate::error::{Error, Result};
+
+/// How deeply arrays and maps may be nested, since each level is decoded
+/// recursively.
+const MAX_DEPTH: u16 = 256;
+
+/// Deserialize a MessagePack byte sequence into any deserializable type.
+///
+/// ```rust
+/// use miniserde_ditto::msgpack;
+///
+/// let bytes = [0x92, 0xcd, 0x01, 0x00, 0xc0];
+/// let out: (u16, Option<bool>) = msgpack::from_slice(&bytes).unwrap();
+/// assert_eq!(out, (256, None));
+/// ```
+pub fn from_slice<T: Deserialize>(bytes: &[u8]) -> Result<T> {
+    let mut out = None;
+    let cursor = &mut bytes.iter();
+    from_slice_impl(cursor, T::begin(&mut out), 0).ok_or(Error)?;
+    if !cursor.as_slice().is_empty() {
+        err!(
+            "Trailing bytes in MessagePack deserialization. Remaining = {:#x?}",
+            cursor.as_slice()
+        );
+    }
+    out.ok_or(Error)
+}
+
+fn from_slice_impl(
+    bytes: &'_ mut ::core::slice::Iter<'_, u8>,
+    visitor: &'_ mut dyn Visitor,
+    depth: u16,
+) -> Option<()> {
+    if depth > MAX_DEPTH {
+        err!("Reached maximum depth / recursion when deserializing MessagePack.");
+    }
+
+    fn map_entries(
+        bytes: &'_ mut ::core::slice::Iter<'_, u8>,
+        map: &'_ mut dyn Map,
+        len: usize,
+        depth: u16,
+    ) -> Option<()> {
+        for _ in 0..len {
+            let out_v = map
+                .val_with_key(&mut |it| {
+                    it.and_then(|out_k| from_slice_impl(bytes, out_k, depth + 1).ok_or(Error))
+                })
+                .ok()?;
+            from_slice_impl(bytes, out_v, depth + 1)?;
+        }
+        Some(())
+    }
+
+    let marker = *bytes.next()?;
+    match marker {
+        0..=marker::POS_FIXINT_MAX => visitor.int(marker.into()).ok()?,
+        marker::NEG_FIXINT_MIN..=0xff => visitor.int((marker as i8).into()).ok()?,
+        marker::NIL => visitor.null().ok()?,
+        marker::FALSE => visitor.boolean(false).ok()?,
+        marker::TRUE => visitor.boolean(true).ok()?,
+
+        marker::UINT8 => visitor.int(read_uint(bytes, 1)?.into()).ok()?,
+        marker::UINT16 => visitor.int(read_uint(bytes, 2)?.into()).ok()?,
+        marker::UINT32 => visitor.int(read_uint(bytes, 4)?.into()).ok()?,
+        marker::UINT64 => visitor.int(read_uint(bytes, 8)?.into()).ok()?,
+        marker::INT8 => visitor.int(read_int(bytes, 1)?.into()).ok()?,
+        marker::INT16 => visitor.int(read_int(bytes, 2)?.into()).ok()?,
+        marker::INT32 => visitor.int(read_int(bytes, 4)?.into()).ok()?,
+        marker::INT64 => visitor.int(read_int(bytes, 8)?.into()).ok()?,
+
+        #[cfg(feature = "float")]
+        marker::FLOAT32 => {
+            let f = f32::from_bits(read_uint(bytes, 4)? as u32);
+            visitor.float(f.into()).ok()?;
+        }
+        #[cfg(feature = "float")]
+        marker::FLOAT64 => visitor.float(f64::from_bits(read_uint(bytes, 8)?)).ok()?,
+        #[cfg(not(feature = "float"))]
+        marker::FLOAT32 | marker::FLOAT64 => err!("Unsupported float"),
+
+        marker::FIXSTR..=0xbf | marker::STR8 | marker::STR16 | marker::STR32 => {
+            let len = match marker {
+                marker::STR8 => read_uint(bytes, 1)?,
+                marker::STR16 => read_uint(bytes, 2)?,
+                marker::STR32 => read_uint(bytes, 4)?,
+                _ => u64::from(marker & 0x1f),
+            };
+            let s = ::core::str::from_utf8(take(bytes, len)?).ok()?;
+            visitor.string(s).ok()?;
+        }
+        marker::BIN8 | marker::BIN16 | marker::BIN32 => {
+            let len = read_uint(bytes, 1 << (marker - marker::BIN8))?;
+            visitor.bytes(take(bytes, len)?).ok()?;
+        }
+
+        marker::FIXARRAY..=0x9f | marker::ARRAY16 | marker::ARRAY32 => {
+            let len = match marker {
+                marker::ARRAY16 => read_uint(bytes, 2)?,
+                marker::ARRAY32 => read_uint(bytes, 4)?,
+                _ => u64::from(marker & 0x0f),
+            };
+            let mut seq = visitor.seq().ok()?;
+            for _ in 0..len {
+                from_slice_impl(bytes, seq.element().ok()?, depth + 1)?;
+            }
+            seq.finish().ok()?;
+        }
+        marker::FIXMAP..=0x8f | marker::MAP16 | marker::MAP32 => {
+            let len = match marker {
+                marker::MAP16 => read_uint(bytes, 2)?,
+                marker::MAP32 => read_uint(bytes, 4)?,
+                _ => u64::from(marker & 0x0f),
+            };
+            let len = usize::try_from(len).ok()?;
+            visitor
+                .map_with(&mut |map| map_entries(bytes, map, len, depth).ok_or(Error))
+                .ok()?;
+        }
+
+        _ => err!(
+            "Unsupported MessagePack marker {:#x}. Remaining = {:#x?}",
+            marker,
+            bytes.as_slice(),
+        ),
+    }
+    Some(())
+}
+
+/// The next `len` bytes of the input.
+fn take<'input>(bytes: &'_ mut ::core::slice::Iter<'input, u8>, len: u64) -> Option<&'input [u8]> {
+    let len = usize::try_from(len).ok()?;
+    let slice = bytes.as_slice();
+    if slice.len() < len {
+        err!("Expected {} more bytes when deserializing MessagePack", len);
+    }
+    *bytes = slice[len..].iter();
+    Some(&slice[..len])
+}
+
+/// A big-endian unsigned integer of `len` bytes.
+fn read_uint(bytes: &'_ mut ::core::slice::Iter<'_, u8>, len: u8) -> Option<u64> {
+    let be_bytes = take(bytes, len.into())?;
+    Some(be_bytes.iter().fold(0, |acc, &b| acc << 8 | u64::from(b)))
+}
+
+/// A big-endian two's complement integer of `len` bytes.
+fn read_int(bytes: &'_ mut ::core::slice::Iter<'_, u8>, len: u8) -> Option<i64> {
+    let shift = 64 - 8 * u32::from(len);
+    Some((read_uint(bytes, len)? << shift) as i64 >> shift)
+}
@@ -0,0 +1,71 @@
+//! MessagePack data format.
+//!
+//! Values map to MessagePack the same way as to CBOR: integers and floats are
+//! written in their shortest exact form, `&[u8]` and `Vec<u8>` as binary, and
+//! structs as maps keyed by field name. CBOR tags, which have no MessagePack
+//! counterpart, are dropped, and extension types are rejected when
+//! deserializing.
+//!
+//! ```rust
+//! use miniserde_ditto::{msgpack, Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Example {
+//!     code: u32,
+//!     ok: bool,
+//! }
+//!
+//! let example = Example { code: 200, ok: true };
+//! let bytes = msgpack::to_vec(&example).unwrap();
+//! assert_eq!(bytes, [
+//!     0x82, // 2-long map
+//!         0xa4, b'c', b'o', b'd', b'e',
+//!         0xcc, 0xc8, // 200 as an 8-bit unsigned integer
+//!         0xa2, b'o', b'k',
+//!         0xc3, // true
+//! ]);
+//! assert_eq!(msgpack::from_slice::<Example>(&bytes).unwrap(), example);
+//! ```
+
+mod ser;
+pub use self::ser::to_vec;
+
+mod de;
+pub use self::de::from_slice;
+
+// for API compat with `::serde_json`
+#[doc(no_inline)]
+pub use crate::{Error, Result};
+
+/// The first byte of each kind of value.
+#[rustfmt::skip]
+mod marker {
+    pub const POS_FIXINT_MAX: u8 = 0x7f;
+    pub const FIXMAP: u8 = 0x80;
+    pub const FIXARRAY: u8 = 0x90;
+    pub const FIXSTR: u8 = 0xa0;
+    pub const NIL: u8 = 0xc0;
+    pub const FALSE: u8 = 0xc2;
+    pub const TRUE: u8 = 0xc3;
+    pub const BIN8: u8 = 0xc4;
+    pub const BIN16: u8 = 0xc5;
+    pub const BIN32: u8 = 0xc6;
+    pub const FLOAT32: u8 = 0xca;
+    pub const FLOAT64: u8 = 0xcb;
+    pub const UINT8: u8 = 0xcc;
+    pub const UINT16: u8 = 0xcd;
+    pub const UINT32: u8 = 0xce;
+    pub const UINT64: u8 = 0xcf;
+    pub const INT8: u8 = 0xd0;
+    pub const INT16: u8 = 0xd1;
+    pub const INT32: u8 = 0xd2;
+    pub const INT64: u8 = 0xd3;
+    pub const STR8: u8 = 0xd9;
+    pub const STR16: u8 = 0xda;
+    pub const STR32: u8 = 0xdb;
+    pub const ARRAY16: u8 = 0xdc;
+    pub const ARRAY32: u8 = 0xdd;
+    pub const MAP16: u8 = 0xde;
+    pub const MAP32: u8 = 0xdf;
+    pub const NEG_FIXINT_MIN: u8 = 0xe0;
+}
@@ -0,0 +1,193 @@
+use alloc::borrow::Cow;
+
+use super::marker;
+use crate::prelude::*;
+use crate::ser::{Map, Seq, Serialize, ValueView};
+use crate::Result;
+
+/// Serialize any serializable type into a MessagePack byte sequence.
+///
+/// ```rust
+/// use miniserde_ditto::msgpack;
+///
+/// let bytes = msgpack::to_vec(&(-1, "a")).unwrap();
+/// assert_eq!(bytes, [0x92, 0xff, 0xa1, b'a']);
+/// ```
+pub fn to_vec<T: Serialize>(value: T) -> Result<Vec<u8>> {
+    let mut out = vec![];
+    to_vec_impl(&value, &mut out)?;
+    Ok(out)
+}
+
+fn to_vec_impl<'value>(value: &'value dyn Serialize, out: &mut Vec<u8>) -> Result<()> {
+    // Use a manual stack to avoid (stack-allocated) recursion.
+    let mut stack: Vec<Layer<'value>> = vec![Layer::Single(value)];
+    // where:
+    enum Layer<'value> {
+        Seq(Box<dyn Seq<'value> + 'value>),
+        Map(Box<dyn Map<'value> + 'value>),
+        Single(&'value dyn Serialize),
+    }
+    while let Some(last) = stack.last_mut() {
+        let view: ValueView<'value> = match last {
+            &mut Layer::Single(value) => {
+                let view = value.view();
+                drop(stack.pop());
+                view
+            }
+            Layer::Seq(seq) => {
+                match seq.next() {
+                    Some(value) => stack.push(Layer::Single(value)),
+                    None => drop(stack.pop()),
+                }
+                continue;
+            }
+            Layer::Map(map) => {
+                match map.next() {
+                    Some((key, value)) => {
+                        stack.push(Layer::Single(value));
+                        stack.push(Layer::Single(key));
+                    }
+                    None => drop(stack.pop()),
+                }
+                continue;
+            }
+        };
+        let view = match view {
+            ValueView::RawNumber(raw) => ValueView::parse_raw_number(&raw)?,
+            ValueView::Timestamp(t) => ValueView::Str(Cow::Owned(t.to_string())),
+            view => view,
+        };
+        match view {
+            ValueView::Null | ValueView::Undefined => out.push(marker::NIL),
+            ValueView::Bool(b) => out.push(if b { marker::TRUE } else { marker::FALSE }),
+            ValueView::Str(s) => {
+                write_len(
+                    out,
+                    s.len(),
+                    Some((marker::FIXSTR, 32)),
+                    Some(marker::STR8),
+                    [marker::STR16, marker::STR32],
+                )?;
+                out.extend_from_slice(s.as_bytes());
+            }
+            ValueView::Bytes(bs) | ValueView::Base64(bs) => {
+                write_len(
+                    out,
+                    bs.len(),
+                    None,
+                    Some(marker::BIN8),
+                    [marker::BIN16, marker::BIN32],
+                )?;
+                out.extend_from_slice(&bs);
+            }
+            ValueView::Int(i) => write_int(i, out)?,
+            #[cfg(feature = "float")]
+            ValueView::F64(f) => {
+                let f_32 = f as f32;
+                if f64::from(f_32) == f || f.is_nan() {
+                    out.push(marker::FLOAT32);
+                    out.extend_from_slice(&f_32.to_bits().to_be_bytes());
+                } else {
+                    out.push(marker::FLOAT64);
+                    out.extend_from_slice(&f.to_bits().to_be_bytes());
+                }
+            }
+            ValueView::RawNumber(_) => unreachable!("raw numbers are parsed beforehand"),
+            ValueView::Timestamp(_) => unreachable!("timestamps are formatted beforehand"),
+            ValueView::RawJson(_) | ValueView::RawCbor(_) => {
+                let value = view.decode_raw()?;
+                to_vec_impl(&*value, out)?;
+            }
+            ValueView::Owned(value) => to_vec_impl(&*value, out)?,
+            ValueView::Unrepresentable => err!("Cannot serialize an unrepresentable value"),
+            ValueView::Tagged(_, inner) => stack.push(Layer::Single(inner)),
+            ValueView::Seq(seq) => {
+                write_len(
+                    out,
+                    seq.remaining(),
+                    Some((marker::FIXARRAY, 16)),
+                    None,
+                    [marker::ARRAY16, marker::ARRAY32],
+                )?;
+                stack.push(Layer::Seq(seq));
+            }
+            ValueView::Map(map) => {
+                write_len(
+                    out,
+                    map.remaining(),
+                    Some((marker::FIXMAP, 16)),
+                    None,
+                    [marker::MAP16, marker::MAP32],
+                )?;
+                stack.push(Layer::Map(map));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes the head of a string, binary, array or map of `len` items: the
+/// `fix` marker (below its maximum length) or'ed with `len`, else the marker
+/// of the shortest length that fits, among 8 (if any), 16 and 32 bits.
+fn write_len(
+    out: &mut Vec<u8>,
+    len: usize,
+    fix: Option<(u8, usize)>,
+    len8: Option<u8>,
+    [len16, len32]: [u8; 2],
+) -> Result<()> {
+    match (fix, len8) {
+        (Some((fix, fix_len)), _) if len < fix_len => out.push(fix | len as u8),
+        (_, Some(len8)) if len <= u8::MAX as usize => out.extend_from_slice(&[len8, len as u8]),
+        _ if len <= u16::MAX as usize => {
+            out.push(len16);
+            out.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        _ if len <= u32::MAX as usize => {
+            out.push(len32);
+            out.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+        _ => err!("Cannot serialize {} items as MessagePack: too long", len),
+    }
+    Ok(())
+}
+
+fn write_int(i: i128, out: &mut Vec<u8>) -> Result<()> {
+    const U64_MAX: i128 = u64::MAX as _;
+    const I64_MIN: i128 = i64::MIN as _;
+    match i {
+        -32..=0x7f => out.push(i as u8),
+        0x80..=0xff => out.extend_from_slice(&[marker::UINT8, i as u8]),
+        0x100..=0xffff => {
+            out.push(marker::UINT16);
+            out.extend_from_slice(&(i as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(marker::UINT32);
+            out.extend_from_slice(&(i as u32).to_be_bytes());
+        }
+        0x1_0000_0000..=U64_MAX => {
+            out.push(marker::UINT64);
+            out.extend_from_slice(&(i as u64).to_be_bytes());
+        }
+        -0x80..=-33 => out.extend_from_slice(&[marker::INT8, i as u8]),
+        -0x8000..=-0x81 => {
+            out.push(marker::INT16);
+            out.extend_from_slice(&(i as i16).to_be_bytes());
+        }
+        -0x8000_0000..=-0x8001 => {
+            out.push(marker::INT32);
+            out.extend_from_slice(&(i as i32).to_be_bytes());
+        }
+        I64_MIN..=-0x8000_0001 => {
+            out.push(marker::INT64);
+            out.extend_from_slice(&(i as i64).to_be_bytes());
+        }
+        _ => err!(
+            "Cannot serialize integer {:?} as MessagePack: out of range",
+            i
+        ),
+    }
+    Ok(())
+}
@@ -0,0 +1,207 @@
+use alloc::collections::{BTreeSet, VecDeque};
+use core::fmt::Write;
+
+use super::{InferredSchema, ObjectStats};
+
+use crate::prelude::*;
+
+/// The type of the positions whose values do not fit a single Rust type.
+const FALLBACK: &str = "::miniserde_ditto::json::Value";
+
+/// The names the generated code uses, which its structs must not shadow.
+const RESERVED: &[&str] = &[
+    "Debug",
+    "Deserialize",
+    "Option",
+    "Self",
+    "Serialize",
+    "String",
+    "Vec",
+];
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in",
+    "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+impl InferredSchema {
+    /// Generate Rust definitions for the inferred schema, as a starting point
+    /// for typed bindings. Meant to be called from build scripts:
+    ///
+    /// ```rust
+    /// use miniserde_ditto::{json, schema};
+    ///
+    /// let value: json::Value = json::from_str(r#"{"userId": 1, "tags": ["a"]}"#).unwrap();
+    /// let code = schema::infer(&[value]).to_rust("Payload");
+    /// assert_eq!(code, "\
+    /// #[derive(Debug, Serialize, Deserialize)]
+    /// pub struct Payload {
+    ///     pub tags: Vec<String>,
+    ///     #[serde(rename = \"userId\")]
+    ///     pub user_id: u8,
+    /// }
+    /// ");
+    /// ```
+    ///
+    /// Every object gets a struct, named after `name` for the root and after
+    /// the field holding it otherwise, with a number appended if that name is
+    /// taken, including by the types the code uses such as `Option`. Fields missing from some samples, or
+    /// `null` in some, become `Option`s. Positions holding values of several
+    /// kinds fall back to `json::Value`. The code expects `Serialize` and
+    /// `Deserialize` to be in scope.
+    pub fn to_rust(&self, name: &str) -> String {
+        let mut out = String::new();
+        let mut names = RESERVED.iter().map(|&name| name.to_owned()).collect();
+        let mut structs = VecDeque::new();
+
+        // Unless the root is a struct, `name` goes to a type alias.
+        let mut name = camel_case(name);
+        let is_struct = self.nulls == 0 && self.objects.is_some() && kinds(self) == 1;
+        if !is_struct {
+            name = unique(name, &mut names);
+        }
+        let root = type_of(self, &name, &mut names, &mut structs);
+        if !is_struct {
+            let _ = writeln!(out, "pub type {} = {};", name, root);
+        }
+
+        while let Some((name, object)) = structs.pop_front() {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str("#[derive(Debug, Serialize, Deserialize)]\n");
+            let _ = writeln!(out, "pub struct {} {{", name);
+            let mut fields = BTreeSet::new();
+            for (key, schema) in &object.fields {
+                let ident = unique(snake_case(key), &mut fields);
+                let mut ty = type_of(schema, &camel_case(key), &mut names, &mut structs);
+                if !object.is_required(key) && !ty.starts_with("Option<") {
+                    ty = format!("Option<{}>", ty);
+                }
+                if ident != *key {
+                    let _ = writeln!(out, "    #[serde(rename = {:?})]", key);
+                }
+                let _ = writeln!(out, "    pub {}: {},", ident, ty);
+            }
+            out.push_str("}\n");
+        }
+
+        out
+    }
+}
+
+/// The Rust type for the values at some position, queueing the structs it
+/// needs. Nested arrays are walked in a loop rather than recursively.
+fn type_of<'a>(
+    mut schema: &'a InferredSchema,
+    hint: &str,
+    names: &mut BTreeSet<String>,
+    structs: &mut VecDeque<(String, &'a ObjectStats)>,
+) -> String {
+    let mut hint = hint.to_owned();
+    let mut wrappers = vec![];
+    let base = loop {
+        if schema.nulls > 0 {
+            wrappers.push("Option");
+        }
+        if kinds(schema) != 1 {
+            break FALLBACK.to_owned();
+        }
+        if let Some(arrays) = &schema.arrays {
+            wrappers.push("Vec");
+            schema = &arrays.items;
+            hint.push_str("Item");
+            continue;
+        }
+        if let Some(objects) = &schema.objects {
+            let name = unique(hint, names);
+            structs.push_back((name.clone(), objects));
+            break name;
+        }
+        break match schema.ints {
+            Some(ints) if schema.floats == 0 => ints.rust_type(),
+            _ if schema.ints.is_some() || schema.floats > 0 => "f64",
+            _ if schema.bools > 0 => "bool",
+            _ if schema.strings.is_some() => "String",
+            _ => "Vec<u8>",
+        }
+        .to_owned();
+    };
+    wrappers
+        .iter()
+        .rev()
+        .fold(base, |ty, wrapper| format!("{}<{}>", wrapper, ty))
+}
+
+/// How many kinds of non-null values were seen at a position. Integers and
+/// floats count as one kind, numbers.
+fn kinds(schema: &InferredSchema) -> usize {
+    let kinds = [
+        schema.bools > 0,
+        schema.ints.is_some() || schema.floats > 0,
+        schema.strings.is_some(),
+        schema.bytes > 0,
+        schema.arrays.is_some(),
+        schema.objects.is_some(),
+    ];
+    kinds.iter().filter(|&&kind| kind).count()
+}
+
+/// `name`, or `name` followed by the smallest number making it unused.
+fn unique(name: String, used: &mut BTreeSet<String>) -> String {
+    let mut unique = name.clone();
+    let mut n = 1;
+    while used.contains(&unique) {
+        n += 1;
+        unique = format!("{}{}", name, n);
+    }
+    used.insert(unique.clone());
+    unique
+}
+
+fn snake_case(key: &str) -> String {
+    let mut ident = String::new();
+    let mut prev_lower = false;
+    for c in key.chars() {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase() && prev_lower {
+                ident.push('_');
+            }
+            ident.push(c.to_ascii_lowercase());
+            prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        } else {
+            if !ident.is_empty() && !ident.ends_with('_') {
+                ident.push('_');
+            }
+            prev_lower = false;
+        }
+    }
+    while ident.ends_with('_') {
+        ident.pop();
+    }
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert_str(0, "field_");
+    }
+    if KEYWORDS.contains(&&*ident) {
+        ident.push('_');
+    }
+    ident
+}
+
+fn camel_case(key: &str) -> String {
+    let mut ident = String::new();
+    for word in key.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            ident.push(first.to_ascii_uppercase());
+            ident.extend(chars);
+        }
+    }
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, 'T');
+    }
+    ident
+}
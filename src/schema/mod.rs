@@ -0,0 +1,318 @@
+//! Inferring the shape of undocumented data from samples.
+//!
+//! Feed [`infer`] some sample values (a [`json::Value`], a [`cbor::Value`], or
+//! anything else implementing [`Serialize`]) and it reports, for every
+//! position in the documents, which kinds of values were observed there: how
+//! wide the integers are, whether the strings look like an enumeration,
+//! which object fields are always present and which are optional.
+//!
+//! ```rust
+//! use miniserde_ditto::{json, schema};
+//!
+//! let samples = [
+//!     r#"{"id": 1, "status": "open", "tags": ["a"]}"#,
+//!     r#"{"id": 300, "status": "closed", "note": null}"#,
+//! ];
+//! let values: Vec<json::Value> = samples.iter().map(|s| json::from_str(s).unwrap()).collect();
+//! let schema = schema::infer(&values);
+//!
+//! let object = schema.objects.as_ref().unwrap();
+//! assert!(object.is_required("id"));
+//! assert!(!object.is_required("tags"));
+//! assert_eq!(object.fields["id"].ints.unwrap().rust_type(), "u16");
+//! assert_eq!(object.fields["status"].strings.as_ref().unwrap().variants().unwrap().len(), 2);
+//! ```
+//!
+//! [`json::Value`]: crate::json::Value
+//! [`cbor::Value`]: crate::cbor::Value
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::mem;
+
+use crate::prelude::*;
+use crate::ser::{self, Serialize, ValueView};
+
+mod codegen;
+
+/// How many distinct strings are remembered at a position before giving up on
+/// it being an enumeration.
+const MAX_VARIANTS: usize = 16;
+
+/// Infer the schema shared by some sample values.
+pub fn infer<'a, T: Serialize + 'a>(values: impl IntoIterator<Item = &'a T>) -> InferredSchema {
+    let mut schema = InferredSchema::default();
+    for value in values {
+        schema.observe(value);
+    }
+    schema
+}
+
+/// What was observed at one position of the samples.
+///
+/// Every kind of value seen there has its statistics filled in, so a position
+/// holding both strings and nulls, for instance, has nonzero `nulls` and some
+/// `strings`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InferredSchema {
+    /// How many values were seen at this position.
+    pub seen: usize,
+    pub nulls: usize,
+    pub bools: usize,
+    pub ints: Option<IntStats>,
+    pub floats: usize,
+    pub strings: Option<StringStats>,
+    pub bytes: usize,
+    pub arrays: Option<ArrayStats>,
+    pub objects: Option<ObjectStats>,
+}
+
+/// The range of the integers seen at a position.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IntStats {
+    pub count: usize,
+    pub min: i128,
+    pub max: i128,
+}
+
+/// The strings seen at a position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StringStats {
+    pub count: usize,
+    /// The distinct values, until there are too many of them.
+    distinct: Option<BTreeSet<String>>,
+}
+
+/// The arrays seen at a position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArrayStats {
+    pub count: usize,
+    /// What was observed across the elements of all the arrays.
+    pub items: Box<InferredSchema>,
+}
+
+/// The objects (maps) seen at a position.
+///
+/// Keys that are neither strings nor integers are all recorded under `"*"`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectStats {
+    pub count: usize,
+    pub fields: BTreeMap<String, InferredSchema>,
+}
+
+impl IntStats {
+    /// The smallest Rust integer type holding all the integers seen.
+    pub fn rust_type(&self) -> &'static str {
+        macro_rules! smallest {($($T:ident)*) => (
+            $(
+                if <$T>::MIN as i128 <= self.min && self.max <= <$T>::MAX as i128 {
+                    return stringify!($T);
+                }
+            )*
+        )}
+        smallest!(u8 i8 u16 i16 u32 i32 u64 i64);
+        "i128"
+    }
+}
+
+impl StringStats {
+    /// The distinct strings seen, if there were few enough of them for the
+    /// position to look like an enumeration.
+    pub fn variants(&self) -> Option<&BTreeSet<String>> {
+        self.distinct.as_ref()
+    }
+}
+
+impl ObjectStats {
+    /// Whether `field` was present in every object seen at this position. It
+    /// may still have been `null` in some of them.
+    pub fn is_required(&self, field: &str) -> bool {
+        self.fields
+            .get(field)
+            .map_or(false, |schema| schema.seen == self.count)
+    }
+}
+
+/// Dropped without recursion, however deeply nested the samples were.
+impl Drop for InferredSchema {
+    fn drop(&mut self) {
+        let mut stack = vec![];
+        self.take_children(&mut stack);
+        while let Some(mut node) = stack.pop() {
+            node.take_children(&mut stack);
+        }
+    }
+}
+
+impl InferredSchema {
+    /// Moves the children that have children of their own onto `stack`.
+    fn take_children(&mut self, stack: &mut Vec<InferredSchema>) {
+        let has_children = |node: &InferredSchema| node.arrays.is_some() || node.objects.is_some();
+        if let Some(arrays) = &mut self.arrays {
+            if has_children(&arrays.items) {
+                stack.push(mem::take(&mut *arrays.items));
+            }
+        }
+        if let Some(objects) = &mut self.objects {
+            let fields = mem::take(&mut objects.fields);
+            stack.extend(
+                fields
+                    .into_iter()
+                    .map(|(_, field)| field)
+                    .filter(has_children),
+            );
+        }
+    }
+
+    /// Merge one more sample into this schema.
+    pub fn observe(&mut self, value: &dyn Serialize) {
+        enum Iter<'a> {
+            Seq(Box<dyn ser::Seq<'a> + 'a>),
+            Map(Box<dyn ser::Map<'a> + 'a>),
+        }
+        // Where a node goes back once it has been filled in.
+        enum Slot {
+            Root,
+            Items,
+            Field(String),
+        }
+        // The nodes of the containers being walked are taken out of their
+        // parents, so that no two `&mut` into the tree are ever needed.
+        let mut stack: Vec<(InferredSchema, Slot, Iter<'_>)> = vec![];
+        let mut node = mem::take(self);
+        let mut slot = Slot::Root;
+        let mut view = value.view();
+
+        loop {
+            if let ValueView::Tagged(_, inner) = view {
+                view = inner.view();
+                continue;
+            }
+            node.seen += 1;
+            let mut done = match view {
+                ValueView::Seq(seq) => {
+                    let arrays = node.arrays.get_or_insert_with(|| ArrayStats {
+                        count: 0,
+                        items: Box::default(),
+                    });
+                    arrays.count += 1;
+                    stack.push((node, slot, Iter::Seq(seq)));
+                    None
+                }
+                ValueView::Map(map) => {
+                    let objects = node.objects.get_or_insert_with(|| ObjectStats {
+                        count: 0,
+                        fields: BTreeMap::new(),
+                    });
+                    objects.count += 1;
+                    stack.push((node, slot, Iter::Map(map)));
+                    None
+                }
+                view => {
+                    match view {
+                        ValueView::Null | ValueView::Undefined => node.nulls += 1,
+                        ValueView::Bool(_) => node.bools += 1,
+                        ValueView::Str(s) => node.observe_str(&s),
+                        ValueView::Timestamp(t) => node.observe_str(&t.to_string()),
+                        ValueView::Bytes(_) | ValueView::Base64(_) => node.bytes += 1,
+                        ValueView::Int(i) => node.observe_int(i),
+                        #[cfg(feature = "float")]
+                        ValueView::F64(_) => node.floats += 1,
+                        ValueView::RawNumber(raw) => match raw.parse() {
+                            Ok(i) => node.observe_int(i),
+                            Err(_) => node.floats += 1,
+                        },
+                        // Observed as decoded, unless its format is left out.
+                        raw @ ValueView::RawJson(_) | raw @ ValueView::RawCbor(_) => {
+                            if let Ok(value) = raw.decode_raw() {
+                                node.seen -= 1;
+                                node.observe(&*value);
+                            }
+                        }
+                        ValueView::Owned(value) => {
+                            node.seen -= 1;
+                            node.observe(&*value);
+                        }
+                        ValueView::Unrepresentable => node.seen -= 1,
+                        ValueView::Tagged(..) | ValueView::Seq(_) | ValueView::Map(_) => {
+                            unreachable!()
+                        }
+                    }
+                    Some((node, slot))
+                }
+            };
+
+            loop {
+                if let Some((child, child_slot)) = done.take() {
+                    let parent = match stack.last_mut() {
+                        Some((parent, _, _)) => parent,
+                        None => {
+                            *self = child;
+                            return;
+                        }
+                    };
+                    match child_slot {
+                        Slot::Root => unreachable!(),
+                        Slot::Items => *parent.arrays.as_mut().unwrap().items = child,
+                        Slot::Field(key) => {
+                            parent.objects.as_mut().unwrap().fields.insert(key, child);
+                        }
+                    }
+                }
+                let (parent, _, iter) = stack.last_mut().unwrap();
+                match iter {
+                    Iter::Seq(seq) => {
+                        if let Some(element) = seq.next() {
+                            node = mem::take(&mut parent.arrays.as_mut().unwrap().items);
+                            slot = Slot::Items;
+                            view = element.view();
+                            break;
+                        }
+                    }
+                    Iter::Map(map) => {
+                        if let Some((key, value)) = map.next() {
+                            let key = match key.view() {
+                                ValueView::Str(s) => s.into_owned(),
+                                ValueView::Int(i) => i.to_string(),
+                                _ => "*".to_owned(),
+                            };
+                            let fields = &mut parent.objects.as_mut().unwrap().fields;
+                            node = fields.remove(&key).unwrap_or_default();
+                            slot = Slot::Field(key);
+                            view = value.view();
+                            break;
+                        }
+                    }
+                }
+                let (parent, parent_slot, _) = stack.pop().unwrap();
+                done = Some((parent, parent_slot));
+            }
+        }
+    }
+
+    fn observe_int(&mut self, i: i128) {
+        let ints = self.ints.get_or_insert(IntStats {
+            count: 0,
+            min: i,
+            max: i,
+        });
+        ints.count += 1;
+        ints.min = ints.min.min(i);
+        ints.max = ints.max.max(i);
+    }
+
+    fn observe_str(&mut self, s: &str) {
+        let strings = self.strings.get_or_insert_with(|| StringStats {
+            count: 0,
+            distinct: Some(BTreeSet::new()),
+        });
+        strings.count += 1;
+        if let Some(distinct) = &mut strings.distinct {
+            if !distinct.contains(s) {
+                distinct.insert(s.to_owned());
+            }
+            if distinct.len() > MAX_VARIANTS {
+                strings.distinct = None;
+            }
+        }
+    }
+}
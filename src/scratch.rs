@@ -0,0 +1,163 @@
+//! The scratch space of the decoders, where strings get unescaped or
+//! reassembled, and the `Vec`s that sequences get collected into. With the
+//! `zeroize` feature, it is wiped after use rather than just freed, in case
+//! it held a [`Sensitive`](crate::Sensitive) value.
+
+#[cfg(feature = "cbor")]
+use alloc::borrow::Cow;
+
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use crate::prelude::*;
+
+/// Makes room for `additional` more bytes. With the `zeroize` feature, the old
+/// allocation is wiped when moving to a bigger one.
+#[cfg(any(feature = "cbor", feature = "json"))]
+pub(in crate) fn reserve(buffer: &mut Vec<u8>, additional: usize) {
+    #[cfg(feature = "zeroize")]
+    {
+        if buffer.capacity() - buffer.len() < additional {
+            let capacity = ::core::cmp::max(2 * buffer.capacity(), buffer.len() + additional);
+            let mut grown = Vec::with_capacity(capacity);
+            grown.extend_from_slice(buffer);
+            buffer.zeroize();
+            *buffer = grown;
+        }
+    }
+    #[cfg(not(feature = "zeroize"))]
+    buffer.reserve(additional);
+}
+
+/// Empties `buffer`, wiping its contents with the `zeroize` feature.
+#[cfg(any(feature = "cbor", feature = "json"))]
+pub(in crate) fn clear(buffer: &mut Vec<u8>) {
+    #[cfg(feature = "zeroize")]
+    buffer.zeroize();
+    buffer.clear();
+}
+
+/// Removes the first `n` bytes of `buffer`. With the `zeroize` feature, the
+/// bytes left past its new end are wiped.
+#[cfg(feature = "cbor")]
+pub(in crate) fn consume(buffer: &mut Vec<u8>, n: usize) {
+    let len = buffer.len() - n;
+    buffer.copy_within(n.., 0);
+    #[cfg(feature = "zeroize")]
+    buffer[len..].zeroize();
+    buffer.truncate(len);
+}
+
+/// Wipes the `capacity` elements at `ptr`, once moved elsewhere, such as the
+/// allocation a `Vec` leaves behind when growing.
+///
+/// Safety: `ptr` must be valid for writes of `capacity` elements, none of
+/// which is in use anymore.
+#[cfg(feature = "zeroize")]
+pub(in crate) unsafe fn wipe_moved<T>(ptr: *mut T, capacity: usize) {
+    let bytes = ptr as *mut u8;
+    for i in 0..capacity * ::core::mem::size_of::<T>() {
+        ::core::ptr::write_volatile(bytes.add(i), 0);
+    }
+    ::core::sync::atomic::compiler_fence(::core::sync::atomic::Ordering::SeqCst);
+}
+
+/// Pushes `value`. With the `zeroize` feature, the old allocation is wiped
+/// when moving to a bigger one.
+pub(in crate) fn push<T>(vec: &mut Vec<T>, value: T) {
+    #[cfg(feature = "zeroize")]
+    {
+        if vec.len() == vec.capacity() {
+            let mut grown = Vec::with_capacity(::core::cmp::max(4, 2 * vec.capacity()));
+            grown.append(vec);
+            unsafe { wipe_moved(vec.as_mut_ptr(), vec.capacity()) };
+            *vec = grown;
+        }
+    }
+    vec.push(value);
+}
+
+/// The array of the `N` elements of `vec`, if that many. With the `zeroize`
+/// feature, the allocation of `vec` is wiped.
+pub(in crate) fn into_array<T, const N: usize>(vec: Vec<T>) -> Option<[T; N]> {
+    #[cfg(feature = "zeroize")]
+    {
+        let mut vec = vec;
+        let array = if vec.len() == N {
+            unsafe {
+                let array = ::core::ptr::read(vec.as_ptr() as *const [T; N]);
+                vec.set_len(0);
+                Some(array)
+            }
+        } else {
+            vec.clear();
+            None
+        };
+        unsafe { wipe_moved(vec.as_mut_ptr(), vec.capacity()) };
+        array
+    }
+    #[cfg(not(feature = "zeroize"))]
+    ::core::convert::TryFrom::try_from(vec).ok()
+}
+
+/// `vec` as a boxed slice. With the `zeroize` feature, the allocation of
+/// `vec` is wiped if it has to shrink to fit.
+pub(in crate) fn into_boxed_slice<T>(vec: Vec<T>) -> Box<[T]> {
+    #[cfg(feature = "zeroize")]
+    {
+        let mut vec = vec;
+        if vec.len() < vec.capacity() {
+            let mut exact = Vec::with_capacity(vec.len());
+            exact.append(&mut vec);
+            unsafe { wipe_moved(vec.as_mut_ptr(), vec.capacity()) };
+            vec = exact;
+        }
+        vec.into_boxed_slice()
+    }
+    #[cfg(not(feature = "zeroize"))]
+    vec.into_boxed_slice()
+}
+
+/// The chunks of an indefinite-length CBOR string, borrowed from the input as
+/// long as there is a single one.
+#[cfg(feature = "cbor")]
+#[derive(Default)]
+pub(in crate) struct Chunks<'a>(Cow<'a, [u8]>);
+
+#[cfg(feature = "cbor")]
+impl<'a> Chunks<'a> {
+    pub(in crate) fn push(&mut self, chunk: &'a [u8]) {
+        if self.0.is_empty() {
+            self.0 = Cow::Borrowed(chunk);
+        } else {
+            let buffer = self.0.to_mut();
+            reserve(buffer, chunk.len());
+            buffer.extend_from_slice(chunk);
+        }
+    }
+
+    pub(in crate) fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub(in crate) fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// The single chunk, if there aren't more.
+    pub(in crate) fn borrowed(&self) -> Option<&'a [u8]> {
+        match self.0 {
+            Cow::Borrowed(chunk) => Some(chunk),
+            Cow::Owned(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl Drop for Chunks<'_> {
+    fn drop(&mut self) {
+        if let Cow::Owned(ref mut buffer) = self.0 {
+            clear(buffer);
+        }
+    }
+}
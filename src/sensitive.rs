@@ -0,0 +1,86 @@
+use core::fmt::{self, Debug};
+use core::ops::{Deref, DerefMut};
+
+use zeroize::Zeroize;
+
+use crate::de::{Deserialize, Visitor};
+use crate::ser::{Serialize, ValueView};
+
+/// A value wiped from memory when dropped, such as a password or a key
+/// decoded from a config file.
+///
+/// With the `zeroize` feature that provides it, the decoders also wipe their
+/// scratch space, where escaped or chunked strings get copied, and the
+/// allocations that `Vec`s, arrays and boxed slices leave behind as they grow,
+/// instead of just freeing them. The value is deserialized in place, without
+/// intermediate copies. The input itself is left to the caller.
+///
+/// ```rust
+/// use miniserde_ditto::{json, Deserialize, Sensitive};
+///
+/// #[derive(Deserialize)]
+/// struct Login {
+///     user: String,
+///     password: Sensitive<String>,
+/// }
+///
+/// let login: Login = json::from_str(r#"{"user":"ada","password":"héllo"}"#)?;
+/// assert_eq!(*login.password, "héllo");
+/// assert_eq!(format!("{:?}", login.password), "Sensitive(..)");
+/// # Ok::<(), miniserde_ditto::Error>(())
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Sensitive<T: Zeroize>(T);
+
+impl<T: Zeroize> Sensitive<T> {
+    pub fn new(value: T) -> Self {
+        Sensitive(value)
+    }
+}
+
+impl<T: Zeroize> From<T> for Sensitive<T> {
+    fn from(value: T) -> Self {
+        Sensitive(value)
+    }
+}
+
+impl<T: Zeroize> Deref for Sensitive<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Zeroize> DerefMut for Sensitive<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Zeroize> Drop for Sensitive<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> Debug for Sensitive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Sensitive(..)")
+    }
+}
+
+impl<T: Zeroize + Serialize> Serialize for Sensitive<T> {
+    fn view(&self) -> ValueView<'_> {
+        self.0.view()
+    }
+}
+
+impl<T: Zeroize + Deserialize> Deserialize for Sensitive<T> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        // Safety: `Sensitive<T>` is `repr(transparent)`, so `Option<Self>` has
+        // the layout of `Option<T>`, as for `#[serde(transparent)]` structs.
+        T::begin(unsafe { &mut *(out as *mut Option<Self> as *mut Option<T>) })
+    }
+}
@@ -0,0 +1,68 @@
+use crate::prelude::*;
+use crate::ser::{Map, Seq, Serialize};
+
+/// A sequence yielding the elements of `first`, then those of `second`.
+///
+/// ```rust
+/// use miniserde_ditto::json;
+/// use miniserde_ditto::ser::{self, Serialize, ValueView};
+///
+/// struct Both(Vec<u32>, Vec<u32>);
+///
+/// impl Serialize for Both {
+///     fn view(&self) -> ValueView<'_> {
+///         let first = self.0.iter().map(|x| x as &dyn Serialize);
+///         let second = self.1.iter().map(|x| x as &dyn Serialize);
+///         ValueView::Seq(ser::chain(Box::new(first), Box::new(second)))
+///     }
+/// }
+///
+/// let both = Both(vec![1, 2], vec![3]);
+/// assert_eq!(json::to_string(&both).unwrap(), "[1,2,3]");
+/// ```
+pub fn chain<'view>(
+    first: Box<dyn Seq<'view> + 'view>,
+    second: Box<dyn Seq<'view> + 'view>,
+) -> Box<dyn Seq<'view> + 'view> {
+    Box::new(SeqChain { first, second })
+}
+
+/// A map yielding the entries of `first`, then those of `second`.
+///
+/// No deduplication happens: keys present in both maps are emitted twice.
+pub fn map_chain<'view>(
+    first: Box<dyn Map<'view> + 'view>,
+    second: Box<dyn Map<'view> + 'view>,
+) -> Box<dyn Map<'view> + 'view> {
+    Box::new(MapChain { first, second })
+}
+
+struct SeqChain<'view> {
+    first: Box<dyn Seq<'view> + 'view>,
+    second: Box<dyn Seq<'view> + 'view>,
+}
+
+impl<'view> Seq<'view> for SeqChain<'view> {
+    fn next(&mut self) -> Option<&'view dyn Serialize> {
+        self.first.next().or_else(|| self.second.next())
+    }
+
+    fn remaining(&self) -> usize {
+        self.first.remaining() + self.second.remaining()
+    }
+}
+
+struct MapChain<'view> {
+    first: Box<dyn Map<'view> + 'view>,
+    second: Box<dyn Map<'view> + 'view>,
+}
+
+impl<'view> Map<'view> for MapChain<'view> {
+    fn next(&mut self) -> Option<(&'view dyn Serialize, &'view dyn Serialize)> {
+        self.first.next().or_else(|| self.second.next())
+    }
+
+    fn remaining(&self) -> usize {
+        self.first.remaining() + self.second.remaining()
+    }
+}
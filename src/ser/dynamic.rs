@@ -0,0 +1,65 @@
+use crate::prelude::*;
+use crate::ser::{Serialize, ValueView};
+
+/// A serializable value of any type, owned, such as the items of a
+/// heterogeneous list.
+///
+/// `Serialize` is object safe, so that `Box<dyn Serialize>` and its variants
+/// with auto traits are themselves `Serialize`, in collections as well.
+///
+/// ```rust
+/// use miniserde_ditto::json;
+/// use miniserde_ditto::ser::{self, BoxSerialize};
+///
+/// let row: Vec<BoxSerialize> = vec![ser::boxed(1), ser::boxed("two"), ser::boxed([3.0])];
+/// assert_eq!(json::to_string(&row).unwrap(), r#"[1,"two",[3.0]]"#);
+///
+/// // It may be sent to the thread doing the writing.
+/// let j = std::thread::spawn(move || json::to_string(&row).unwrap());
+/// assert_eq!(j.join().unwrap(), r#"[1,"two",[3.0]]"#);
+/// ```
+pub type BoxSerialize = Box<dyn Serialize + Send + 'static>;
+
+/// Boxes `value` as a [`BoxSerialize`].
+pub fn boxed<T: Serialize + Send + 'static>(value: T) -> BoxSerialize {
+    Box::new(value)
+}
+
+/// A sequence of the given elements, such as trait objects from a collection
+/// of another shape than a slice.
+///
+/// ```rust
+/// use miniserde_ditto::json;
+/// use miniserde_ditto::ser::{self, BoxSerialize, Serialize, ValueView};
+/// use std::collections::BTreeMap;
+///
+/// // Serialized as the list of its values alone.
+/// struct Registry(BTreeMap<&'static str, BoxSerialize>);
+///
+/// impl Serialize for Registry {
+///     fn view(&self) -> ValueView<'_> {
+///         ser::seq_from_iter(self.0.values().map(|value| &**value as &dyn Serialize))
+///     }
+/// }
+///
+/// let mut registry = Registry(BTreeMap::new());
+/// registry.0.insert("b", ser::boxed(true));
+/// registry.0.insert("a", ser::boxed(Some("x")));
+/// assert_eq!(json::to_string(&registry).unwrap(), r#"["x",true]"#);
+/// ```
+pub fn seq_from_iter<'view, I>(elements: I) -> ValueView<'view>
+where
+    I: IntoIterator<Item = &'view dyn Serialize>,
+    I::IntoIter: ExactSizeIterator + 'view,
+{
+    ValueView::Seq(Box::new(elements.into_iter()))
+}
+
+/// A map of the given entries, the counterpart of [`seq_from_iter`].
+pub fn map_from_iter<'view, I>(entries: I) -> ValueView<'view>
+where
+    I: IntoIterator<Item = (&'view dyn Serialize, &'view dyn Serialize)>,
+    I::IntoIter: ExactSizeIterator + 'view,
+{
+    ValueView::Map(Box::new(entries.into_iter()))
+}
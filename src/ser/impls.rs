@@ -1,10 +1,34 @@
-use std::borrow::Cow;
-use std::collections::{btree_map, hash_map, BTreeMap, HashMap};
-use std::hash::{BuildHasher, Hash};
-use std::slice;
-
+use alloc::borrow::Cow;
+use alloc::collections::{
+    binary_heap, btree_map, btree_set, linked_list, vec_deque, BTreeMap, BTreeSet, BinaryHeap,
+    LinkedList, VecDeque,
+};
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use core::cell::{Cell, Ref, RefCell, RefMut};
+#[cfg(feature = "std")]
+use core::hash::{BuildHasher, Hash};
+use core::iter;
+use core::marker::PhantomData;
+use core::num::Wrapping;
+use core::ops::{Bound, Range, RangeInclusive};
+use core::slice;
+#[cfg(feature = "std")]
+use std::collections::{hash_map, hash_set, HashMap, HashSet};
+#[cfg(feature = "std")]
+use std::ffi::{OsStr, OsString};
+#[cfg(feature = "std")]
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+#[cfg(all(feature = "std", unix))]
+use std::os::unix::ffi::OsStrExt;
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::prelude::*;
 use crate::private;
-use crate::ser::{Map, Seq, Serialize, ValueView};
+use crate::ser::{self, Map, Seq, Serialize, ValueView};
 
 impl Serialize for () {
     fn view(&self) -> ValueView<'_> {
@@ -12,6 +36,12 @@ impl Serialize for () {
     }
 }
 
+impl<T: ?Sized> Serialize for PhantomData<T> {
+    fn view(&self) -> ValueView<'_> {
+        ValueView::Null
+    }
+}
+
 impl Serialize for bool {
     fn view(&self) -> ValueView<'_> {
         ValueView::Bool(*self)
@@ -24,6 +54,69 @@ impl Serialize for str {
     }
 }
 
+impl Serialize for char {
+    fn view(&self) -> ValueView<'_> {
+        ValueView::Str(Cow::Owned(self.to_string()))
+    }
+}
+
+#[cfg(feature = "std")]
+macro_rules! display {
+    ($($ty:ty),*) => {$(
+        impl Serialize for $ty {
+            fn view(&self) -> ValueView<'_> {
+                ValueView::Str(Cow::Owned(self.to_string()))
+            }
+        }
+    )*};
+}
+#[cfg(feature = "std")]
+display!(
+    IpAddr,
+    Ipv4Addr,
+    Ipv6Addr,
+    SocketAddr,
+    SocketAddrV4,
+    SocketAddrV6
+);
+
+#[cfg(feature = "std")]
+impl Serialize for Path {
+    fn view(&self) -> ValueView<'_> {
+        match self.to_str() {
+            Some(s) => ValueView::Str(Cow::Borrowed(s)),
+            None => ValueView::Unrepresentable,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Serialize for PathBuf {
+    fn view(&self) -> ValueView<'_> {
+        self.as_path().view()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Serialize for OsStr {
+    fn view(&self) -> ValueView<'_> {
+        match self.to_str() {
+            Some(s) => ValueView::Str(Cow::Borrowed(s)),
+            #[cfg(unix)]
+            None => ValueView::Bytes(Cow::Borrowed(self.as_bytes())),
+            #[cfg(not(unix))]
+            None => ValueView::Unrepresentable,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Serialize for OsString {
+    fn view(&self) -> ValueView<'_> {
+        self.as_os_str().view()
+    }
+}
+
 impl Serialize for String {
     fn view(&self) -> ValueView<'_> {
         ValueView::Str(Cow::Borrowed(self))
@@ -54,6 +147,18 @@ unsigned!(u32);
 unsigned!(u64);
 unsigned!(usize);
 
+impl Serialize for u128 {
+    fn view(&self) -> ValueView<'_> {
+        if *self <= i128::max_value() as u128 {
+            ValueView::Int(*self as _)
+        } else {
+            // Out of `ValueView::Int` range: JSON writes the digits, and CBOR
+            // rejects it like any other integer beyond 64 bits.
+            ValueView::RawNumber(Cow::Owned(self.to_string()))
+        }
+    }
+}
+
 macro_rules! signed {
     ($ty:ident) => {
         impl Serialize for $ty {
@@ -67,8 +172,10 @@ signed!(i8);
 signed!(i16);
 signed!(i32);
 signed!(i64);
+signed!(i128);
 signed!(isize);
 
+#[cfg(feature = "float")]
 macro_rules! float {
     ($ty:ident) => {
         impl Serialize for $ty {
@@ -78,7 +185,9 @@ macro_rules! float {
         }
     };
 }
+#[cfg(feature = "float")]
 float!(f32);
+#[cfg(feature = "float")]
 float!(f64);
 
 impl<'a, T: ?Sized + Serialize> Serialize for &'a T {
@@ -87,12 +196,74 @@ impl<'a, T: ?Sized + Serialize> Serialize for &'a T {
     }
 }
 
+#[cfg(not(feature = "allocator_api"))]
 impl<T: ?Sized + Serialize> Serialize for Box<T> {
     fn view(&self) -> ValueView<'_> {
         (**self).view()
     }
 }
 
+#[cfg(feature = "allocator_api")]
+impl<T: ?Sized + Serialize, A: core::alloc::Allocator> Serialize for Box<T, A> {
+    fn view(&self) -> ValueView<'_> {
+        (**self).view()
+    }
+}
+
+impl<T: ?Sized + Serialize> Serialize for Rc<T> {
+    fn view(&self) -> ValueView<'_> {
+        (**self).view()
+    }
+}
+
+impl<T: ?Sized + Serialize> Serialize for Arc<T> {
+    fn view(&self) -> ValueView<'_> {
+        (**self).view()
+    }
+}
+
+impl<T: Copy + Serialize> Serialize for Cell<T> {
+    fn view(&self) -> ValueView<'_> {
+        ValueView::Owned(Box::new(self.get()))
+    }
+}
+
+// The borrow or the lock is held until the owned view has been serialized.
+
+impl<T: ?Sized + Serialize> Serialize for RefCell<T> {
+    fn view(&self) -> ValueView<'_> {
+        ValueView::Owned(Box::new(self.borrow()))
+    }
+}
+
+impl<T: ?Sized + Serialize> Serialize for Ref<'_, T> {
+    fn view(&self) -> ValueView<'_> {
+        (**self).view()
+    }
+}
+
+impl<T: ?Sized + Serialize> Serialize for RefMut<'_, T> {
+    fn view(&self) -> ValueView<'_> {
+        (**self).view()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized + Serialize> Serialize for Mutex<T> {
+    fn view(&self) -> ValueView<'_> {
+        ValueView::Owned(Box::new(
+            self.lock().unwrap_or_else(PoisonError::into_inner),
+        ))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ?Sized + Serialize> Serialize for MutexGuard<'_, T> {
+    fn view(&self) -> ValueView<'_> {
+        (**self).view()
+    }
+}
+
 impl<T: Serialize> Serialize for Option<T> {
     fn view(&self) -> ValueView<'_> {
         match self {
@@ -102,6 +273,52 @@ impl<T: Serialize> Serialize for Option<T> {
     }
 }
 
+// The enums below are externally tagged, and the ranges are structs, as in
+// Serde's data model.
+
+impl<T: Serialize, E: Serialize> Serialize for Result<T, E> {
+    fn view(&self) -> ValueView<'_> {
+        let (variant, value): (&&str, &dyn Serialize) = match self {
+            Ok(ok) => (&"Ok", ok),
+            Err(err) => (&"Err", err),
+        };
+        ValueView::Map(Box::new(iter::once((variant as &dyn Serialize, value))))
+    }
+}
+
+impl<T: Serialize> Serialize for Bound<T> {
+    fn view(&self) -> ValueView<'_> {
+        let (variant, value): (&&str, &dyn Serialize) = match self {
+            Bound::Included(bound) => (&"Included", bound),
+            Bound::Excluded(bound) => (&"Excluded", bound),
+            Bound::Unbounded => return ValueView::Str(Cow::Borrowed("Unbounded")),
+        };
+        ValueView::Map(Box::new(iter::once((variant as &dyn Serialize, value))))
+    }
+}
+
+fn view_range<'a, T: Serialize>(start: &'a T, end: &'a T) -> ValueView<'a> {
+    ser::map_of([(&"start", start), (&"end", end)])
+}
+
+impl<T: Serialize> Serialize for Range<T> {
+    fn view(&self) -> ValueView<'_> {
+        view_range(&self.start, &self.end)
+    }
+}
+
+impl<T: Serialize> Serialize for RangeInclusive<T> {
+    fn view(&self) -> ValueView<'_> {
+        view_range(self.start(), self.end())
+    }
+}
+
+impl<T: Serialize> Serialize for Wrapping<T> {
+    fn view(&self) -> ValueView<'_> {
+        self.0.view()
+    }
+}
+
 impl<'a, T: ?Sized + ToOwned + Serialize> Serialize for Cow<'a, T> {
     fn view(&self) -> ValueView<'_> {
         (**self).view()
@@ -146,22 +363,86 @@ impl<T: Serialize> Serialize for [T] {
     }
 }
 
-crate::with_Ns! {( $($N:expr),* $(,)? ) => (
-  $(
-    impl<T : Serialize> Serialize for [T; $N] {
-        fn view(&self) -> ValueView<'_> {
-            T::view_seq(self)
-        }
+// Going through `view_seq` makes `[u8; N]` serialize as bytes, like `[u8]`.
+impl<T: Serialize, const N: usize> Serialize for [T; N] {
+    fn view(&self) -> ValueView<'_> {
+        T::view_seq(self)
     }
-  )*
-)}
+}
 
+#[cfg(not(feature = "allocator_api"))]
 impl<T: Serialize> Serialize for Vec<T> {
     fn view(&self) -> ValueView<'_> {
         T::view_seq(&self[..])
     }
 }
 
+#[cfg(feature = "allocator_api")]
+impl<T: Serialize, A: core::alloc::Allocator> Serialize for Vec<T, A> {
+    fn view(&self) -> ValueView<'_> {
+        T::view_seq(&self[..])
+    }
+}
+
+impl<T: Serialize> Serialize for VecDeque<T> {
+    fn view(&self) -> ValueView<'_> {
+        struct VecDequeStream<'a, T: 'a>(vec_deque::Iter<'a, T>);
+
+        impl<'a, T: Serialize> Seq<'a> for VecDequeStream<'a, T> {
+            fn next(&mut self) -> Option<&'a dyn Serialize> {
+                let element = self.0.next()?;
+                Some(element)
+            }
+
+            fn remaining(&self) -> usize {
+                self.0.len()
+            }
+        }
+
+        ValueView::Seq(Box::new(VecDequeStream(self.iter())))
+    }
+}
+
+#[allow(clippy::linkedlist)]
+impl<T: Serialize> Serialize for LinkedList<T> {
+    fn view(&self) -> ValueView<'_> {
+        struct LinkedListStream<'a, T: 'a>(linked_list::Iter<'a, T>);
+
+        impl<'a, T: Serialize> Seq<'a> for LinkedListStream<'a, T> {
+            fn next(&mut self) -> Option<&'a dyn Serialize> {
+                let element = self.0.next()?;
+                Some(element)
+            }
+
+            fn remaining(&self) -> usize {
+                self.0.len()
+            }
+        }
+
+        ValueView::Seq(Box::new(LinkedListStream(self.iter())))
+    }
+}
+
+impl<T: Serialize> Serialize for BinaryHeap<T> {
+    fn view(&self) -> ValueView<'_> {
+        struct BinaryHeapStream<'a, T: 'a>(binary_heap::Iter<'a, T>);
+
+        impl<'a, T: Serialize> Seq<'a> for BinaryHeapStream<'a, T> {
+            fn next(&mut self) -> Option<&'a dyn Serialize> {
+                let element = self.0.next()?;
+                Some(element)
+            }
+
+            fn remaining(&self) -> usize {
+                self.0.len()
+            }
+        }
+
+        ValueView::Seq(Box::new(BinaryHeapStream(self.iter())))
+    }
+}
+
+#[cfg(feature = "std")]
 impl<K, V, H> Serialize for HashMap<K, V, H>
 where
     K: Hash + Eq + Serialize,
@@ -192,6 +473,49 @@ impl<K: Serialize, V: Serialize> Serialize for BTreeMap<K, V> {
     }
 }
 
+#[cfg(feature = "std")]
+impl<T, H> Serialize for HashSet<T, H>
+where
+    T: Hash + Eq + Serialize,
+    H: BuildHasher,
+{
+    fn view(&self) -> ValueView<'_> {
+        struct HashSetStream<'a, T: 'a>(hash_set::Iter<'a, T>);
+
+        impl<'a, T: Serialize> Seq<'a> for HashSetStream<'a, T> {
+            fn next(&mut self) -> Option<&'a dyn Serialize> {
+                let element = self.0.next()?;
+                Some(element)
+            }
+
+            fn remaining(&self) -> usize {
+                self.0.len()
+            }
+        }
+
+        ValueView::Seq(Box::new(HashSetStream(self.iter())))
+    }
+}
+
+impl<T: Serialize> Serialize for BTreeSet<T> {
+    fn view(&self) -> ValueView<'_> {
+        struct BTreeSetStream<'a, T: 'a>(btree_set::Iter<'a, T>);
+
+        impl<'a, T: Serialize> Seq<'a> for BTreeSetStream<'a, T> {
+            fn next(&mut self) -> Option<&'a dyn Serialize> {
+                let element = self.0.next()?;
+                Some(element)
+            }
+
+            fn remaining(&self) -> usize {
+                self.0.len()
+            }
+        }
+
+        ValueView::Seq(Box::new(BTreeSetStream(self.iter())))
+    }
+}
+
 impl private {
     pub fn stream_slice<T: Serialize>(slice: &[T]) -> ValueView<'_> {
         struct SliceStream<'a, T: 'a>(slice::Iter<'a, T>);
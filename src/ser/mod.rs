@@ -95,18 +95,76 @@
 
 mod impls;
 
-use std::borrow::Cow;
+mod chain;
+pub use self::chain::{chain, map_chain};
+
+mod dynamic;
+pub use self::dynamic::{boxed, map_from_iter, seq_from_iter, BoxSerialize};
+
+use alloc::borrow::Cow;
+
+use crate::prelude::*;
 
 /// One unit of output produced during serialization.
 ///
 /// [Refer to the module documentation for examples.][crate::ser]
 pub enum ValueView<'view> {
     Null,
+    /// The CBOR `undefined` simple value, such as a
+    /// [`cbor::Value::Undefined`]. CBOR output writes it as such, and other
+    /// formats as `null`.
+    ///
+    /// [`cbor::Value::Undefined`]: crate::cbor::Value::Undefined
+    Undefined,
     Bool(bool),
     Str(Cow<'view, str>),
     Bytes(Cow<'view, [u8]>),
+    /// Bytes that JSON output writes as a base64 string, such as a
+    /// [`json::Base64`], rather than as an array of numbers. Binary formats
+    /// write plain bytes.
+    ///
+    /// [`json::Base64`]: crate::json::Base64
+    Base64(Cow<'view, [u8]>),
+    /// A point in time, such as a [`Timestamp`]. JSON output writes it as an
+    /// RFC 3339 string, CBOR output as an epoch timestamp (tag 1), and other
+    /// formats as the string.
+    ///
+    /// [`Timestamp`]: crate::timestamp::Timestamp
+    Timestamp(crate::timestamp::Timestamp),
     Int(i128),
+    #[cfg(feature = "float")]
     F64(f64),
+    /// The textual representation of a JSON number, such as `1.10`.
+    ///
+    /// JSON output emits it verbatim, other formats parse it.
+    RawNumber(Cow<'view, str>),
+    /// The text of a whole JSON value, such as a [`json::RawValue`].
+    ///
+    /// JSON output emits it verbatim once checked to be valid, other formats
+    /// parse it.
+    ///
+    /// [`json::RawValue`]: crate::json::RawValue
+    RawJson(Cow<'view, str>),
+    /// A single encoded CBOR data item, such as a [`cbor::RawValue`].
+    ///
+    /// CBOR output emits it verbatim once checked to be well-formed, unless
+    /// canonical, which re-encodes it; other formats decode it.
+    ///
+    /// [`cbor::RawValue`]: crate::cbor::RawValue
+    RawCbor(Cow<'view, [u8]>),
+    /// A value preceded by a CBOR tag (major type 6), such as `1` for epoch
+    /// timestamps or `2` for bignums.
+    ///
+    /// Formats without tags, such as JSON, only write the inner value.
+    Tagged(u64, &'view dyn Serialize),
+    /// A value made up on the fly, such as the contents of a `RefCell` or
+    /// the `#[serde(into)]` type of a struct, which the view owns.
+    ///
+    /// Serializers view it in turn, and keep it alive meanwhile.
+    Owned(Box<dyn Serialize + 'view>),
+    /// A value found to have no representation, such as a `Path` that is not
+    /// UTF-8, which all serializers reject.
+    Unrepresentable,
     Seq(Box<dyn Seq<'view> + 'view>),
     Map(Box<dyn Map<'view> + 'view>),
 }
@@ -117,11 +175,20 @@ impl ::core::fmt::Debug for ValueView<'_> {
         use ValueView::*;
         match *self {
             Null => fmt.write_str("Null"),
+            Undefined => fmt.write_str("Undefined"),
             Bool(ref b) => fmt.debug_tuple("Bool").field(b).finish(),
             Str(ref s) => fmt.debug_tuple("Str").field(s).finish(),
             Bytes(ref xs) => fmt.debug_tuple("Str").field(xs).finish(),
+            Base64(ref xs) => fmt.debug_tuple("Base64").field(xs).finish(),
+            Timestamp(ref t) => fmt.debug_tuple("Timestamp").field(t).finish(),
             Int(ref i) => fmt.debug_tuple("Int").field(i).finish(),
             F64(ref f) => fmt.debug_tuple("F64").field(f).finish(),
+            RawNumber(ref s) => fmt.debug_tuple("RawNumber").field(s).finish(),
+            RawJson(ref s) => fmt.debug_tuple("RawJson").field(s).finish(),
+            RawCbor(ref xs) => fmt.debug_tuple("RawCbor").field(xs).finish(),
+            Tagged(tag, _) => fmt.debug_tuple("Tagged").field(&tag).finish(),
+            Owned(_) => fmt.write_str("Owned"),
+            Unrepresentable => fmt.write_str("Unrepresentable"),
             Seq(ref seq) => fmt
                 .debug_struct("Seq")
                 .field("remaining", &seq.remaining())
@@ -134,13 +201,112 @@ impl ::core::fmt::Debug for ValueView<'_> {
     }
 }
 
-impl ValueView<'_> {
-    // Used by the JSON format when serializing keys
-    pub(in crate) fn as_str(&self) -> Option<&'_ str> {
+impl<'view> ValueView<'view> {
+    /// Checks that the text of a `RawNumber` is a number in JSON syntax,
+    /// whatever its magnitude.
+    pub(in crate) fn check_raw_number(raw: &str) -> crate::Result<()> {
+        fn digits(bytes: &mut &[u8]) -> usize {
+            let count = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+            *bytes = &bytes[count..];
+            count
+        }
+        let mut bytes = raw.strip_prefix('-').unwrap_or(raw).as_bytes();
+        let leading_zero = bytes.first() == Some(&b'0');
+        let mut valid = match digits(&mut bytes) {
+            0 => false,
+            1 => true,
+            _ => !leading_zero,
+        };
+        if let Some((&b'.', rest)) = bytes.split_first() {
+            bytes = rest;
+            valid &= digits(&mut bytes) > 0;
+        }
+        if let Some((&b'e', rest)) | Some((&b'E', rest)) = bytes.split_first() {
+            bytes = rest
+                .strip_prefix(b"+")
+                .or_else(|| rest.strip_prefix(b"-"))
+                .unwrap_or(rest);
+            valid &= digits(&mut bytes) > 0;
+        }
+        if !valid || !bytes.is_empty() {
+            err!("Expected a number, got {:?}", raw);
+        }
+        Ok(())
+    }
+
+    /// Parses the text of a `RawNumber` into an `Int` or `F64` view.
+    pub(in crate) fn parse_raw_number(raw: &str) -> crate::Result<ValueView<'view>> {
+        Self::check_raw_number(raw)?;
+        #[cfg(feature = "float")]
+        let parsed = if raw.contains(&['.', 'e', 'E'][..]) {
+            raw.parse()
+                .ok()
+                .filter(|f: &f64| f.is_finite())
+                .map(ValueView::F64)
+        } else {
+            raw.parse().ok().map(ValueView::Int)
+        };
+        #[cfg(not(feature = "float"))]
+        let parsed = raw.parse().ok().map(ValueView::Int);
+        parsed.ok_or_else(|| err!("Number out of range: {}", raw))
+    }
+
+    /// Like `parse_raw_number`, but rounding integers beyond `i128` to
+    /// floats, for visitors that take any number.
+    pub(in crate) fn parse_raw_number_lossy(raw: &str) -> crate::Result<ValueView<'view>> {
+        match Self::parse_raw_number(raw) {
+            #[cfg(feature = "float")]
+            Err(_) => match raw.parse() {
+                Ok(f) if f64::is_finite(f) => Ok(ValueView::F64(f)),
+                _ => err!("Number out of range: {}", raw),
+            },
+            parsed => parsed,
+        }
+    }
+
+    /// Decodes a `RawJson` or `RawCbor` view into the `Value` of its format.
+    pub(in crate) fn decode_raw(&self) -> crate::Result<Box<dyn Serialize>> {
         match *self {
-            ValueView::Bytes(ref xs) => Some(::core::str::from_utf8(xs).ok()?),
-            ValueView::Str(ref s) => Some(s),
-            _ => None,
+            #[cfg(feature = "json")]
+            ValueView::RawJson(ref j) => {
+                let value: crate::json::Value = crate::json::from_str(j)?;
+                Ok(Box::new(value))
+            }
+            #[cfg(feature = "cbor")]
+            ValueView::RawCbor(ref bytes) => {
+                let value: crate::cbor::Value = crate::cbor::from_slice(bytes)?;
+                Ok(Box::new(value))
+            }
+            _ => err!("Cannot decode raw content without the feature of its format"),
+        }
+    }
+}
+
+/// What serializers do with negative zero (`-0.0`) floats.
+///
+/// Both JSON and CBOR can represent `-0.0`, and deserializing always keeps the
+/// sign found in the input; this only affects what gets written. Integers have
+/// no negative zero, so a JSON `-0` is read as the integer `0`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignedZero {
+    /// Write `-0.0` as is. This is the default.
+    Preserve,
+    /// Write `-0.0` as `0.0`.
+    Normalize,
+}
+
+impl Default for SignedZero {
+    fn default() -> Self {
+        SignedZero::Preserve
+    }
+}
+
+#[cfg(all(feature = "float", any(feature = "cbor", feature = "json")))]
+impl SignedZero {
+    pub(in crate) fn apply(self, f: f64) -> f64 {
+        match self {
+            SignedZero::Normalize if f == 0.0 => 0.0,
+            _ => f,
         }
     }
 }
@@ -200,3 +366,20 @@ where
         Iterator::next(self)
     }
 }
+
+/// A map of the given entries, without allocating for them.
+#[rustversion::since(1.53)]
+pub(in crate) fn map_of<'view, const N: usize>(
+    entries: [(&'view dyn Serialize, &'view dyn Serialize); N],
+) -> ValueView<'view> {
+    ValueView::Map(Box::new(IntoIterator::into_iter(entries)))
+}
+
+// Arrays only implement `IntoIterator` from Rust 1.53 on, which deprecates
+// `array::IntoIter::new`.
+#[rustversion::before(1.53)]
+pub(in crate) fn map_of<'view, const N: usize>(
+    entries: [(&'view dyn Serialize, &'view dyn Serialize); N],
+) -> ValueView<'view> {
+    ValueView::Map(Box::new(::core::array::IntoIter::new(entries)))
+}
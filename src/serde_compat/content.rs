@@ -0,0 +1,351 @@
+use alloc::borrow::Cow;
+use core::convert::TryFrom;
+use core::fmt;
+
+use crate::de::{Deserialize, Map, Seq, Visitor};
+use crate::error::Result;
+use crate::prelude::*;
+use crate::ser::{Serialize, ValueView};
+use crate::Place;
+
+/// A value of the data model of both crates, through which either side is
+/// converted to the other. Maps keep their entries in order, and may have
+/// keys of any kind.
+pub enum Content {
+    Null,
+    Bool(bool),
+    Int(i128),
+    #[cfg(feature = "float")]
+    Float(f64),
+    Str(String),
+    Bytes(Vec<u8>),
+    Seq(Vec<Content>),
+    Map(Vec<(Content, Content)>),
+}
+
+impl Content {
+    /// How deeply sequences and maps are nested in the content: `0` for a
+    /// scalar, `1` for `[1]` or `[]`, and `2` for `[[1]]`.
+    pub fn depth(&self) -> usize {
+        let mut max = 0;
+        let mut stack = vec![(self, 1)];
+        while let Some((content, depth)) = stack.pop() {
+            match content {
+                Content::Seq(items) => {
+                    max = max.max(depth);
+                    stack.extend(items.iter().map(|item| (item, depth + 1)));
+                }
+                Content::Map(entries) => {
+                    max = max.max(depth);
+                    for (k, v) in entries {
+                        stack.push((k, depth + 1));
+                        stack.push((v, depth + 1));
+                    }
+                }
+                _ => {}
+            }
+        }
+        max
+    }
+
+    /// Moves the children of a sequence or map to `stack`.
+    fn take_children(&mut self, stack: &mut Vec<Content>) {
+        match self {
+            Content::Seq(items) => stack.append(items),
+            Content::Map(entries) => {
+                for (k, v) in entries.drain(..) {
+                    stack.push(k);
+                    stack.push(v);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Drops nested content from a stack rather than recursively, so that no
+/// depth of nesting overflows the call stack.
+impl Drop for Content {
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        self.take_children(&mut stack);
+        while let Some(mut content) = stack.pop() {
+            content.take_children(&mut stack);
+        }
+    }
+}
+
+impl Serialize for Content {
+    fn view(&self) -> ValueView<'_> {
+        match self {
+            Content::Null => ValueView::Null,
+            Content::Bool(b) => ValueView::Bool(*b),
+            Content::Int(i) => ValueView::Int(*i),
+            #[cfg(feature = "float")]
+            Content::Float(f) => ValueView::F64(*f),
+            Content::Str(s) => ValueView::Str(Cow::Borrowed(s)),
+            Content::Bytes(bs) => ValueView::Bytes(Cow::Borrowed(bs)),
+            Content::Seq(items) => {
+                ValueView::Seq(Box::new(items.iter().map(|item| item as &dyn Serialize)))
+            }
+            Content::Map(entries) => ValueView::Map(Box::new(
+                entries
+                    .iter()
+                    .map(|(k, v)| (k as &dyn Serialize, v as &dyn Serialize)),
+            )),
+        }
+    }
+}
+
+impl Deserialize for Content {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        impl Visitor for Place<Content> {
+            fn null(&mut self) -> Result<()> {
+                self.out = Some(Content::Null);
+                Ok(())
+            }
+
+            fn boolean(&mut self, b: bool) -> Result<()> {
+                self.out = Some(Content::Bool(b));
+                Ok(())
+            }
+
+            fn string(&mut self, s: &str) -> Result<()> {
+                self.out = Some(Content::Str(s.to_owned()));
+                Ok(())
+            }
+
+            fn bytes(&mut self, xs: &[u8]) -> Result<()> {
+                self.out = Some(Content::Bytes(xs.to_owned()));
+                Ok(())
+            }
+
+            fn int(&mut self, i: i128) -> Result<()> {
+                self.out = Some(Content::Int(i));
+                Ok(())
+            }
+
+            #[cfg(feature = "float")]
+            fn float(&mut self, f: f64) -> Result<()> {
+                self.out = Some(Content::Float(f));
+                Ok(())
+            }
+
+            fn raw_number(&mut self, raw: &str) -> Result<()> {
+                match ValueView::parse_raw_number(raw)? {
+                    ValueView::Int(i) => self.int(i),
+                    #[cfg(feature = "float")]
+                    ValueView::F64(f) => self.float(f),
+                    _ => unreachable!(),
+                }
+            }
+
+            fn seq(&mut self) -> Result<Box<dyn Seq + '_>> {
+                Ok(Box::new(SeqBuilder {
+                    out: &mut self.out,
+                    items: Vec::new(),
+                    element: None,
+                }))
+            }
+
+            fn map(&mut self) -> Result<Box<dyn Map + '_>> {
+                Ok(Box::new(MapBuilder {
+                    out: &mut self.out,
+                    entries: Vec::new(),
+                    key: None,
+                    value: None,
+                }))
+            }
+        }
+
+        struct SeqBuilder<'a> {
+            out: &'a mut Option<Content>,
+            items: Vec<Content>,
+            element: Option<Content>,
+        }
+
+        impl<'a> SeqBuilder<'a> {
+            fn shift(&mut self) {
+                if let Some(e) = self.element.take() {
+                    self.items.push(e);
+                }
+            }
+        }
+
+        impl<'a> Seq for SeqBuilder<'a> {
+            fn element(&mut self) -> Result<&mut dyn Visitor> {
+                self.shift();
+                Ok(Deserialize::begin(&mut self.element))
+            }
+
+            fn finish(mut self: Box<Self>) -> Result<()> {
+                self.shift();
+                *self.out = Some(Content::Seq(self.items));
+                Ok(())
+            }
+        }
+
+        struct MapBuilder<'a> {
+            out: &'a mut Option<Content>,
+            entries: Vec<(Content, Content)>,
+            key: Option<Content>,
+            value: Option<Content>,
+        }
+
+        impl<'a> MapBuilder<'a> {
+            fn shift(&mut self) {
+                if let (Some(k), Some(v)) = (self.key.take(), self.value.take()) {
+                    self.entries.push((k, v));
+                }
+            }
+        }
+
+        impl<'a> Map for MapBuilder<'a> {
+            fn val_with_key(
+                &mut self,
+                de_key: &mut dyn FnMut(Result<&mut dyn Visitor>) -> Result<()>,
+            ) -> Result<&mut dyn Visitor> {
+                self.shift();
+                de_key(Ok(Deserialize::begin(&mut self.key)))?;
+                Ok(Deserialize::begin(&mut self.value))
+            }
+
+            fn finish(mut self: Box<Self>) -> Result<()> {
+                self.shift();
+                *self.out = Some(Content::Map(self.entries));
+                Ok(())
+            }
+        }
+
+        Place::new(out)
+    }
+}
+
+impl serde::Serialize for Content {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{SerializeMap, SerializeSeq};
+
+        match self {
+            Content::Null => serializer.serialize_unit(),
+            Content::Bool(b) => serializer.serialize_bool(*b),
+            Content::Int(i) => match (i64::try_from(*i), u64::try_from(*i)) {
+                (Ok(i), _) => serializer.serialize_i64(i),
+                (_, Ok(u)) => serializer.serialize_u64(u),
+                _ => match u128::try_from(*i) {
+                    Ok(u) => serializer.serialize_u128(u),
+                    Err(_) => serializer.serialize_i128(*i),
+                },
+            },
+            #[cfg(feature = "float")]
+            Content::Float(f) => serializer.serialize_f64(*f),
+            Content::Str(s) => serializer.serialize_str(s),
+            Content::Bytes(bs) => serializer.serialize_bytes(bs),
+            Content::Seq(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            Content::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (k, v) in entries {
+                    map.serialize_entry(k, v)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Content {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ContentVisitor)
+    }
+}
+
+struct ContentVisitor;
+
+impl<'de> serde::de::Visitor<'de> for ContentVisitor {
+    type Value = Content;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("any value")
+    }
+
+    fn visit_bool<E>(self, b: bool) -> Result<Content, E> {
+        Ok(Content::Bool(b))
+    }
+
+    fn visit_i64<E>(self, i: i64) -> Result<Content, E> {
+        Ok(Content::Int(i.into()))
+    }
+
+    fn visit_i128<E>(self, i: i128) -> Result<Content, E> {
+        Ok(Content::Int(i))
+    }
+
+    fn visit_u64<E>(self, u: u64) -> Result<Content, E> {
+        Ok(Content::Int(u.into()))
+    }
+
+    fn visit_u128<E: serde::de::Error>(self, u: u128) -> Result<Content, E> {
+        match i128::try_from(u) {
+            Ok(i) => Ok(Content::Int(i)),
+            Err(_) => Err(E::custom("integer out of range")),
+        }
+    }
+
+    #[cfg(feature = "float")]
+    fn visit_f64<E>(self, f: f64) -> Result<Content, E> {
+        Ok(Content::Float(f))
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Content, E> {
+        Ok(Content::Str(s.to_owned()))
+    }
+
+    fn visit_string<E>(self, s: String) -> Result<Content, E> {
+        Ok(Content::Str(s))
+    }
+
+    fn visit_bytes<E>(self, bs: &[u8]) -> Result<Content, E> {
+        Ok(Content::Bytes(bs.to_owned()))
+    }
+
+    fn visit_byte_buf<E>(self, bs: Vec<u8>) -> Result<Content, E> {
+        Ok(Content::Bytes(bs))
+    }
+
+    fn visit_none<E>(self) -> Result<Content, E> {
+        Ok(Content::Null)
+    }
+
+    fn visit_some<D: serde::Deserializer<'de>>(self, d: D) -> Result<Content, D::Error> {
+        serde::Deserialize::deserialize(d)
+    }
+
+    fn visit_unit<E>(self) -> Result<Content, E> {
+        Ok(Content::Null)
+    }
+
+    fn visit_newtype_struct<D: serde::Deserializer<'de>>(self, d: D) -> Result<Content, D::Error> {
+        serde::Deserialize::deserialize(d)
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Content, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Content::Seq(items))
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<Content, A::Error> {
+        let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0).min(4096));
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(Content::Map(entries))
+    }
+}
@@ -0,0 +1,195 @@
+//! A `serde::Deserializer` of any `serde::Deserialize` value from a
+//! `Content`, the counterpart of `ContentSerializer`.
+
+use core::convert::TryFrom;
+use core::mem;
+
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use super::content::Content;
+use crate::{Error, Result};
+
+impl<'de> de::Deserializer<'de> for Content {
+    type Error = Error;
+
+    // The fields are taken rather than moved out, which the `Drop` impl of
+    // `Content` forbids.
+    fn deserialize_any<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value> {
+        match &mut self {
+            Content::Null => visitor.visit_unit(),
+            Content::Bool(b) => visitor.visit_bool(*b),
+            &mut Content::Int(i) => match (i64::try_from(i), u64::try_from(i)) {
+                (Ok(i), _) => visitor.visit_i64(i),
+                (_, Ok(u)) => visitor.visit_u64(u),
+                _ => visitor.visit_i128(i),
+            },
+            #[cfg(feature = "float")]
+            Content::Float(f) => visitor.visit_f64(*f),
+            Content::Str(s) => visitor.visit_string(mem::take(s)),
+            Content::Bytes(bs) => visitor.visit_byte_buf(mem::take(bs)),
+            Content::Seq(items) => {
+                let mut seq = SeqDeserializer::new(mem::take(items).into_iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Content::Map(entries) => {
+                let entries = mem::take(entries).into_iter().map(|(k, v)| (MapKey(k), v));
+                let mut map = MapDeserializer::new(entries);
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Content::Null => visitor.visit_none(),
+            content => visitor.visit_some(content),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        mut self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        let (variant, content) = match &mut self {
+            Content::Str(variant) => (Content::Str(mem::take(variant)), Content::Null),
+            Content::Map(entries) if entries.len() == 1 => entries.pop().unwrap(),
+            _ => err!("Expected an enum variant, as a string or a map of one entry"),
+        };
+        visitor.visit_enum(Enum { variant, content })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Content {
+    type Deserializer = Content;
+
+    fn into_deserializer(self) -> Content {
+        self
+    }
+}
+
+/// A map key, which JSON writes as a string even when it is a number.
+struct MapKey(Content);
+
+macro_rules! deserialize_int_key {
+    ($($method:ident)*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+            match &self.0 {
+                Content::Str(s) => match s.parse() {
+                    Ok(i) => Content::Int(i).deserialize_any(visitor),
+                    Err(_) => err!("Expected an integer map key, got {:?}", s),
+                },
+                _ => self.0.deserialize_any(visitor),
+            }
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for MapKey {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.0.deserialize_any(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.0.deserialize_option(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.0.deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+
+    deserialize_int_key! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+    }
+
+    forward_to_deserialize_any! {
+        bool f32 f64 char str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for MapKey {
+    type Deserializer = MapKey;
+
+    fn into_deserializer(self) -> MapKey {
+        self
+    }
+}
+
+struct Enum {
+    variant: Content,
+    content: Content,
+}
+
+impl<'de> de::EnumAccess<'de> for Enum {
+    type Error = Error;
+    type Variant = Content;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Content)> {
+        Ok((seed.deserialize(self.variant)?, self.content))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Content {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        match self {
+            Content::Null => Ok(()),
+            _ => err!("Expected a unit variant"),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+}
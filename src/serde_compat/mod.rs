@@ -0,0 +1,138 @@
+//! Adapters between the `Serialize` and `Deserialize` traits of this crate
+//! and those of Serde, to move a codebase from one to the other a module at
+//! a time.
+//!
+//! A [`Serde`] field holds a value of a type with Serde impls, in a type that
+//! derives the traits of this crate; a [`Miniserde`] field holds a value of a
+//! type with the impls of this crate, in a type that derives those of Serde.
+//!
+//! ```rust
+//! use miniserde_ditto::serde_compat::{Miniserde, Serde};
+//! use miniserde_ditto::{json, Deserialize, Serialize};
+//!
+//! // Not migrated yet.
+//! #[derive(serde_derive::Serialize, serde_derive::Deserialize, Debug, PartialEq)]
+//! enum Shape {
+//!     Circle { radius: u32 },
+//!     Square(u32),
+//! }
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Drawing {
+//!     name: String,
+//!     shapes: Vec<Serde<Shape>>,
+//! }
+//!
+//! let drawing = Drawing {
+//!     name: "logo".to_owned(),
+//!     shapes: vec![Serde(Shape::Circle { radius: 2 }), Serde(Shape::Square(3))],
+//! };
+//! let j = json::to_string(&drawing)?;
+//! assert_eq!(
+//!     j,
+//!     r#"{"name":"logo","shapes":[{"Circle":{"radius":2}},{"Square":3}]}"#,
+//! );
+//! assert_eq!(json::from_str::<Drawing>(&j)?, drawing);
+//!
+//! // And the other way around.
+//! let j = serde_json::to_string(&Miniserde(&drawing)).unwrap();
+//! let back: Miniserde<Drawing> = serde_json::from_str(&j).unwrap();
+//! assert_eq!(back.0, drawing);
+//! # Ok::<(), miniserde_ditto::Error>(())
+//! ```
+//!
+//! Values are converted through a tree of their content, in which enums are
+//! externally tagged, as in `serde_json`, and map entries keep their order.
+//! Deserializing a `Miniserde` needs a self-describing format, as it goes
+//! through `deserialize_any`.
+
+mod content;
+mod de;
+mod ser;
+
+use core::fmt::Display;
+
+use self::content::Content;
+use self::ser::ContentSerializer;
+use crate::de::convert::{FromRepr, ReprPlace};
+use crate::de::{Deserialize, Visitor};
+use crate::prelude::*;
+use crate::ser::{Serialize, ValueView};
+use crate::transcode::{transcode, Options};
+use crate::{Error, Result};
+
+/// A value whose Serde impls are used as those of this crate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Serde<T>(pub T);
+
+/// A value whose impls of this crate are used as Serde ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Miniserde<T>(pub T);
+
+impl<T: serde::Serialize> Serialize for Serde<T> {
+    fn view(&self) -> ValueView<'_> {
+        match self.0.serialize(ContentSerializer) {
+            Ok(content) => ValueView::Owned(Box::new(content)),
+            Err(Error) => ValueView::Unrepresentable,
+        }
+    }
+}
+
+/// How deeply the content handed to a Serde impl may be nested, since Serde
+/// impls deserialize each level recursively. The same as `serde_json`.
+const MAX_DEPTH: usize = 128;
+
+impl<T: serde::de::DeserializeOwned> FromRepr for Serde<T> {
+    type Repr = Content;
+
+    fn from_repr(content: Content) -> Result<Self> {
+        if content.depth() > MAX_DEPTH {
+            err!(
+                "Exceeded the maximum depth of {} for a Serde value",
+                MAX_DEPTH
+            );
+        }
+        T::deserialize(content).map(Serde)
+    }
+}
+
+impl<T: serde::de::DeserializeOwned> Deserialize for Serde<T> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        ReprPlace::new(out)
+    }
+}
+
+impl<T: Serialize> serde::Serialize for Miniserde<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut content = None;
+        transcode(&self.0, Content::begin(&mut content), Options::default())
+            .map_err(serde::ser::Error::custom)?;
+        match content {
+            Some(content) => serde::Serialize::serialize(&content, serializer),
+            None => Err(serde::ser::Error::custom(Error)),
+        }
+    }
+}
+
+impl<'de, T: Deserialize> serde::Deserialize<'de> for Miniserde<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let content: Content = serde::Deserialize::deserialize(deserializer)?;
+        let mut out = None;
+        transcode(&content, T::begin(&mut out), Options::default())
+            .map_err(serde::de::Error::custom)?;
+        out.map(Miniserde)
+            .ok_or_else(|| serde::de::Error::custom(Error))
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        err!("{}", msg)
+    }
+}
+
+impl serde::de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        err!("{}", msg)
+    }
+}
@@ -0,0 +1,363 @@
+//! A `serde::Serializer` of any `serde::Serialize` value into its `Content`.
+//!
+//! Enums are externally tagged, as in `serde_json`: a unit variant is its
+//! name, and any other variant a map of its name to its content.
+
+use core::convert::TryFrom;
+use core::fmt::Display;
+
+use serde::ser::{self, Serialize};
+
+use super::content::Content;
+use crate::prelude::*;
+use crate::{Error, Result};
+
+pub struct ContentSerializer;
+
+fn variant(variant: &'static str, content: Content) -> Content {
+    Content::Map(vec![(Content::Str(variant.to_owned()), content)])
+}
+
+impl ser::Serializer for ContentSerializer {
+    type Ok = Content;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = SeqSerializer;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = MapSerializer;
+
+    fn serialize_bool(self, b: bool) -> Result<Content> {
+        Ok(Content::Bool(b))
+    }
+
+    fn serialize_i8(self, i: i8) -> Result<Content> {
+        Ok(Content::Int(i.into()))
+    }
+
+    fn serialize_i16(self, i: i16) -> Result<Content> {
+        Ok(Content::Int(i.into()))
+    }
+
+    fn serialize_i32(self, i: i32) -> Result<Content> {
+        Ok(Content::Int(i.into()))
+    }
+
+    fn serialize_i64(self, i: i64) -> Result<Content> {
+        Ok(Content::Int(i.into()))
+    }
+
+    fn serialize_i128(self, i: i128) -> Result<Content> {
+        Ok(Content::Int(i))
+    }
+
+    fn serialize_u8(self, u: u8) -> Result<Content> {
+        Ok(Content::Int(u.into()))
+    }
+
+    fn serialize_u16(self, u: u16) -> Result<Content> {
+        Ok(Content::Int(u.into()))
+    }
+
+    fn serialize_u32(self, u: u32) -> Result<Content> {
+        Ok(Content::Int(u.into()))
+    }
+
+    fn serialize_u64(self, u: u64) -> Result<Content> {
+        Ok(Content::Int(u.into()))
+    }
+
+    fn serialize_u128(self, u: u128) -> Result<Content> {
+        match i128::try_from(u) {
+            Ok(i) => Ok(Content::Int(i)),
+            Err(_) => err!("Integer out of range: {}", u),
+        }
+    }
+
+    #[cfg(feature = "float")]
+    fn serialize_f32(self, f: f32) -> Result<Content> {
+        Ok(Content::Float(f.into()))
+    }
+
+    #[cfg(feature = "float")]
+    fn serialize_f64(self, f: f64) -> Result<Content> {
+        Ok(Content::Float(f))
+    }
+
+    #[cfg(not(feature = "float"))]
+    fn serialize_f32(self, _: f32) -> Result<Content> {
+        err!("Cannot serialize floats without the `float` feature")
+    }
+
+    #[cfg(not(feature = "float"))]
+    fn serialize_f64(self, _: f64) -> Result<Content> {
+        err!("Cannot serialize floats without the `float` feature")
+    }
+
+    fn serialize_char(self, c: char) -> Result<Content> {
+        Ok(Content::Str(c.to_string()))
+    }
+
+    fn serialize_str(self, s: &str) -> Result<Content> {
+        Ok(Content::Str(s.to_owned()))
+    }
+
+    fn serialize_bytes(self, bs: &[u8]) -> Result<Content> {
+        Ok(Content::Bytes(bs.to_owned()))
+    }
+
+    fn serialize_none(self) -> Result<Content> {
+        Ok(Content::Null)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Content> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Content> {
+        Ok(Content::Null)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Content> {
+        Ok(Content::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        name: &'static str,
+    ) -> Result<Content> {
+        Ok(Content::Str(name.to_owned()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Content> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Content> {
+        Ok(variant(name, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer> {
+        Ok(SeqSerializer {
+            variant: None,
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, len: usize) -> Result<SeqSerializer> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer> {
+        Ok(SeqSerializer {
+            variant: Some(name),
+            items: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<MapSerializer> {
+        Ok(MapSerializer {
+            variant: None,
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _: &'static str, len: usize) -> Result<MapSerializer> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        name: &'static str,
+        len: usize,
+    ) -> Result<MapSerializer> {
+        Ok(MapSerializer {
+            variant: Some(name),
+            entries: Vec::with_capacity(len),
+            key: None,
+        })
+    }
+
+    fn collect_str<T: ?Sized + Display>(self, value: &T) -> Result<Content> {
+        Ok(Content::Str(value.to_string()))
+    }
+}
+
+pub struct SeqSerializer {
+    variant: Option<&'static str>,
+    items: Vec<Content>,
+}
+
+impl SeqSerializer {
+    fn push<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.items.push(value.serialize(ContentSerializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Content> {
+        let content = Content::Seq(self.items);
+        Ok(match self.variant {
+            Some(name) => variant(name, content),
+            None => content,
+        })
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Content;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Content;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Content;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqSerializer {
+    type Ok = Content;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content> {
+        self.finish()
+    }
+}
+
+pub struct MapSerializer {
+    variant: Option<&'static str>,
+    entries: Vec<(Content, Content)>,
+    key: Option<Content>,
+}
+
+impl MapSerializer {
+    fn field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        let value = value.serialize(ContentSerializer)?;
+        self.entries.push((Content::Str(key.to_owned()), value));
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Content> {
+        let content = Content::Map(self.entries);
+        Ok(match self.variant {
+            Some(name) => variant(name, content),
+            None => content,
+        })
+    }
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = Content;
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(key.serialize(ContentSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let key = match self.key.take() {
+            Some(key) => key,
+            None => err!("Map value serialized before its key"),
+        };
+        self.entries
+            .push((key, value.serialize(ContentSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Content> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for MapSerializer {
+    type Ok = Content;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<Content> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for MapSerializer {
+    type Ok = Content;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<Content> {
+        self.finish()
+    }
+}
@@ -0,0 +1,61 @@
+use ::chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+
+use super::{FromTimestamp, Place, Timestamp};
+use crate::de::{Deserialize, Visitor};
+use crate::ser::{Serialize, ValueView};
+
+fn to_timestamp(datetime: &DateTime<Utc>, offset: Option<i32>) -> Timestamp {
+    Timestamp {
+        secs: datetime.timestamp(),
+        // Past one billion during a leap second, which the epoch leaves out.
+        nanos: datetime.timestamp_subsec_nanos().min(999_999_999),
+        offset,
+    }
+}
+
+fn from_timestamp(timestamp: Timestamp) -> Option<DateTime<Utc>> {
+    Utc.timestamp_opt(timestamp.secs, timestamp.nanos).single()
+}
+
+impl Serialize for DateTime<Utc> {
+    fn view(&self) -> ValueView<'_> {
+        ValueView::Timestamp(to_timestamp(self, Some(0)))
+    }
+}
+
+// Any offset is accepted, and converted to UTC.
+impl FromTimestamp for DateTime<Utc> {
+    fn from_timestamp(timestamp: Timestamp) -> Option<Self> {
+        timestamp.offset?;
+        from_timestamp(timestamp)
+    }
+}
+
+impl Deserialize for DateTime<Utc> {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        Place::new(out)
+    }
+}
+
+impl Serialize for NaiveDateTime {
+    fn view(&self) -> ValueView<'_> {
+        ValueView::Timestamp(to_timestamp(&Utc.from_utc_datetime(self), None))
+    }
+}
+
+// Only a time in UTC is accepted along with those with no offset, the same
+// as the epoch of CBOR.
+impl FromTimestamp for NaiveDateTime {
+    fn from_timestamp(timestamp: Timestamp) -> Option<Self> {
+        match timestamp.offset {
+            None | Some(0) => from_timestamp(timestamp).map(|datetime| datetime.naive_utc()),
+            Some(_) => None,
+        }
+    }
+}
+
+impl Deserialize for NaiveDateTime {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        Place::new(out)
+    }
+}
@@ -0,0 +1,338 @@
+//! Points in time, written as RFC 3339 strings in JSON and as epoch
+//! timestamps (tag 1) in CBOR.
+//!
+//! A [`Timestamp`] is what the date and time types of other crates serialize
+//! through. With the `chrono` feature, these are `chrono::DateTime<Utc>` and
+//! `chrono::NaiveDateTime`; with the `time` feature, `time::OffsetDateTime`.
+//!
+//! ```rust
+//! use miniserde_ditto::timestamp::Timestamp;
+//! use miniserde_ditto::{cbor, json, Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! struct Build {
+//!     started: Timestamp,
+//! }
+//!
+//! let build = Build {
+//!     started: Timestamp {
+//!         secs: 1_600_000_000,
+//!         nanos: 500_000_000,
+//!         offset: Some(2 * 3600),
+//!     },
+//! };
+//! let j = json::to_string(&build)?;
+//! assert_eq!(j, r#"{"started":"2020-09-13T14:26:40.500+02:00"}"#);
+//! assert_eq!(json::from_str::<Build>(&j)?, build);
+//!
+//! let bytes = cbor::to_vec(&build)?;
+//! let value: cbor::Value = cbor::from_slice(&bytes)?;
+//! let epoch = cbor::Value::Float(1_600_000_000.5);
+//! assert_eq!(value.get("started"), Some(&cbor::Value::Tag(1, Box::new(epoch))));
+//! # Ok::<(), miniserde_ditto::Error>(())
+//! ```
+//!
+//! Deserializing takes an RFC 3339 string, or seconds since the epoch as an
+//! integer or a float, such as those under tag 1 in CBOR. The offset of the
+//! latter is UTC. Without the `float` feature, a timestamp with a fraction of
+//! a second cannot be written as such an epoch, and is an error in CBOR.
+
+#[cfg(feature = "chrono")]
+mod chrono;
+#[cfg(feature = "time")]
+mod time;
+
+use core::convert::TryFrom;
+use core::fmt;
+
+use crate::de::{Deserialize, Visitor};
+use crate::error::Result;
+use crate::ser::{Serialize, ValueView};
+
+// Not the crate's own, whose `Visitor` impls would overlap with the one for
+// any `FromTimestamp`.
+make_place!(Place);
+
+/// A point in time, as seconds and nanoseconds since the Unix epoch, and the
+/// offset from UTC of the local time it is written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Timestamp {
+    /// Seconds since 1970-01-01T00:00:00Z.
+    pub secs: i64,
+    /// Nanoseconds past `secs`, below one billion.
+    pub nanos: u32,
+    /// The offset from UTC in seconds, or `None` for a local time with no
+    /// offset, such as that of a `NaiveDateTime`, whose `secs` count as if it
+    /// were in UTC.
+    ///
+    /// RFC 3339 only has whole minutes of offset, so a time with a finer one
+    /// is written in UTC.
+    pub offset: Option<i32>,
+}
+
+impl Timestamp {
+    /// The seconds since the epoch, with the nanoseconds as a fraction.
+    #[cfg(feature = "float")]
+    pub(crate) fn epoch_f64(&self) -> f64 {
+        self.secs as f64 + f64::from(self.nanos) / 1e9
+    }
+
+    /// Writes the seconds since the epoch to `visitor`, as a float if there
+    /// is a fraction of a second, the same as CBOR does under tag 1. Without
+    /// the `float` feature, such a fraction is an error rather than dropped.
+    pub(crate) fn visit_epoch(&self, visitor: &mut dyn Visitor) -> Result<()> {
+        if self.nanos != 0 {
+            #[cfg(feature = "float")]
+            return visitor.float(self.epoch_f64());
+            #[cfg(not(feature = "float"))]
+            err!(
+                "Timestamp {} has a fraction of a second, which needs the `float` feature",
+                self
+            );
+        }
+        visitor.int(self.secs.into())
+    }
+
+    /// Parses an RFC 3339 date and time, such as `2020-09-13T12:26:40Z`, or
+    /// one with no offset, such as `2020-09-13T12:26:40`.
+    pub(crate) fn parse(s: &str) -> Option<Timestamp> {
+        let mut s = s.as_bytes();
+        let negative = s.first() == Some(&b'-');
+        if negative || s.first() == Some(&b'+') {
+            s = &s[1..];
+        }
+        let year_len = s.iter().take_while(|c| c.is_ascii_digit()).count();
+        if year_len < 4 || year_len > 9 {
+            return None;
+        }
+        let year = number(&s[..year_len])?;
+        let year = if negative { -year } else { year };
+        let s = &s[year_len..];
+        let (month, day, hour, minute, second, mut s) = match *s {
+            [b'-', m1, m2, b'-', d1, d2, b'T', h1, h2, b':', n1, n2, b':', s1, s2, ref rest @ ..]
+            | [b'-', m1, m2, b'-', d1, d2, b't', h1, h2, b':', n1, n2, b':', s1, s2, ref rest @ ..]
+            | [b'-', m1, m2, b'-', d1, d2, b' ', h1, h2, b':', n1, n2, b':', s1, s2, ref rest @ ..] => {
+                (
+                    number(&[m1, m2])?,
+                    number(&[d1, d2])?,
+                    number(&[h1, h2])?,
+                    number(&[n1, n2])?,
+                    number(&[s1, s2])?,
+                    rest,
+                )
+            }
+            _ => return None,
+        };
+        if month < 1 || month > 12 || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+        // A leap second is counted as the first second of the next minute.
+        if hour > 23 || minute > 59 || second > 60 {
+            return None;
+        }
+
+        let mut nanos = 0_u32;
+        if let [b'.', ref rest @ ..] = *s {
+            let len = rest.iter().take_while(|c| c.is_ascii_digit()).count();
+            if len == 0 {
+                return None;
+            }
+            // Digits past the nanoseconds are dropped.
+            for i in 0..9 {
+                nanos = nanos * 10
+                    + rest
+                        .get(i)
+                        .filter(|_| i < len)
+                        .map_or(0, |&c| u32::from(c - b'0'));
+            }
+            s = &rest[len..];
+        }
+        let offset = match *s {
+            [] => None,
+            [b'Z'] | [b'z'] => Some(0),
+            [sign @ b'+', h1, h2, b':', m1, m2] | [sign @ b'-', h1, h2, b':', m1, m2] => {
+                let (hours, minutes) = (number(&[h1, h2])?, number(&[m1, m2])?);
+                if hours > 23 || minutes > 59 {
+                    return None;
+                }
+                let offset = i32::try_from(hours * 60 + minutes).ok()? * 60;
+                Some(if sign == b'-' { -offset } else { offset })
+            }
+            _ => return None,
+        };
+
+        let days = days_from_civil(year, month, day);
+        let local = days * 86400 + hour * 3600 + minute * 60 + second;
+        Some(Timestamp {
+            secs: local - i64::from(offset.unwrap_or(0)),
+            nanos,
+            offset,
+        })
+    }
+}
+
+/// The RFC 3339 form of the date and time, such as `2020-09-13T12:26:40Z`,
+/// or `2020-09-13T12:26:40` with no offset.
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = self.offset.map(|offset| match offset % 60 {
+            0 => offset,
+            _ => 0,
+        });
+        let local = self.secs.saturating_add(offset.unwrap_or(0).into());
+        let (days, time) = (local.div_euclid(86400), local.rem_euclid(86400));
+        let (year, month, day) = civil_from_days(days);
+        if (0..=9999).contains(&year) {
+            write!(f, "{:04}", year)?;
+        } else {
+            write!(f, "{:+05}", year)?;
+        }
+        write!(
+            f,
+            "-{:02}-{:02}T{:02}:{:02}:{:02}",
+            month,
+            day,
+            time / 3600,
+            time / 60 % 60,
+            time % 60,
+        )?;
+        match self.nanos {
+            0 => {}
+            nanos if nanos % 1_000_000 == 0 => write!(f, ".{:03}", nanos / 1_000_000)?,
+            nanos if nanos % 1_000 == 0 => write!(f, ".{:06}", nanos / 1_000)?,
+            nanos => write!(f, ".{:09}", nanos)?,
+        }
+        match offset {
+            None => Ok(()),
+            Some(0) => f.write_str("Z"),
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let minutes = offset.abs() / 60;
+                write!(f, "{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+            }
+        }
+    }
+}
+
+impl Serialize for Timestamp {
+    fn view(&self) -> ValueView<'_> {
+        ValueView::Timestamp(*self)
+    }
+}
+
+impl Deserialize for Timestamp {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        Place::new(out)
+    }
+}
+
+/// The types that a `Timestamp` deserializes into, and from which the string
+/// or epoch read is rejected if they cannot represent it.
+pub(crate) trait FromTimestamp: Sized {
+    fn from_timestamp(timestamp: Timestamp) -> Option<Self>;
+}
+
+impl FromTimestamp for Timestamp {
+    fn from_timestamp(timestamp: Timestamp) -> Option<Self> {
+        Some(timestamp)
+    }
+}
+
+impl<T: FromTimestamp> Place<T> {
+    fn set(&mut self, timestamp: Timestamp) -> Result<()> {
+        match T::from_timestamp(timestamp) {
+            Some(value) => {
+                self.out = Some(value);
+                Ok(())
+            }
+            None => err!("Timestamp out of range: {}", timestamp),
+        }
+    }
+}
+
+impl<T: FromTimestamp> Visitor for Place<T> {
+    fn string(&mut self, s: &str) -> Result<()> {
+        match Timestamp::parse(s) {
+            Some(timestamp) => self.set(timestamp),
+            None => err!("Invalid RFC 3339 date and time {:?}", s),
+        }
+    }
+
+    fn int(&mut self, i: i128) -> Result<()> {
+        let secs = i64::try_from(i).map_err(|_| err!("Timestamp out of range: {}", i))?;
+        self.set(Timestamp {
+            secs,
+            nanos: 0,
+            offset: Some(0),
+        })
+    }
+
+    // The seconds are in the range checked first, and the nanoseconds below
+    // one second.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    #[cfg(feature = "float")]
+    fn float(&mut self, f: f64) -> Result<()> {
+        // `i64::MAX` is not a float, but the power of two above it is.
+        if !(f >= i64::MIN as f64 && f < -(i64::MIN as f64)) {
+            err!("Timestamp out of range: {}", f);
+        }
+        // `f64::floor` and `f64::round` need `std`: the cast truncates toward
+        // zero instead, and the fraction left is not negative.
+        let mut secs = f as i64;
+        if secs as f64 > f {
+            secs -= 1;
+        }
+        let nanos = ((f - secs as f64) * 1e9 + 0.5) as u32;
+        let (secs, nanos) = match nanos {
+            1_000_000_000 => (secs + 1, 0),
+            nanos => (secs, nanos),
+        };
+        self.set(Timestamp {
+            secs,
+            nanos,
+            offset: Some(0),
+        })
+    }
+}
+
+fn number(digits: &[u8]) -> Option<i64> {
+    digits.iter().try_fold(0, |n: i64, &c| match c {
+        b'0'..=b'9' => Some(n * 10 + i64::from(c - b'0')),
+        _ => None,
+    })
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// The days since 1970-01-01 of a date of the proleptic Gregorian calendar, and
+// back, from http://howardhinnant.github.io/date_algorithms.html.
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
@@ -0,0 +1,37 @@
+use ::time::{OffsetDateTime, UtcOffset};
+
+use super::{FromTimestamp, Place, Timestamp};
+use crate::de::{Deserialize, Visitor};
+use crate::ser::{Serialize, ValueView};
+
+impl Serialize for OffsetDateTime {
+    fn view(&self) -> ValueView<'_> {
+        ValueView::Timestamp(Timestamp {
+            secs: self.unix_timestamp(),
+            nanos: self.nanosecond(),
+            offset: Some(self.offset().whole_seconds()),
+        })
+    }
+}
+
+// The offset read is kept; a time with none is rejected.
+impl FromTimestamp for OffsetDateTime {
+    fn from_timestamp(timestamp: Timestamp) -> Option<Self> {
+        let offset = timestamp.offset?;
+        // From the local time, which keeps its date and time when given the
+        // offset, to never overflow while converting to it.
+        let local = timestamp.secs.checked_add(offset.into())?;
+        if timestamp.nanos >= 1_000_000_000 {
+            return None;
+        }
+        let nanos = i128::from(local) * 1_000_000_000 + i128::from(timestamp.nanos);
+        let datetime = OffsetDateTime::from_unix_timestamp_nanos(nanos).ok()?;
+        Some(datetime.replace_offset(UtcOffset::from_whole_seconds(offset).ok()?))
+    }
+}
+
+impl Deserialize for OffsetDateTime {
+    fn begin(out: &mut Option<Self>) -> &mut dyn Visitor {
+        Place::new(out)
+    }
+}
@@ -0,0 +1,708 @@
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use core::str::FromStr;
+
+use crate::de::Deserialize;
+use crate::error::{Error, Result};
+use crate::prelude::*;
+use crate::private;
+use crate::ser::{Serialize, ValueView};
+
+/// How deeply arrays and inline tables may be nested, since each level is
+/// parsed recursively.
+const MAX_DEPTH: usize = 128;
+
+/// Deserialize a TOML document into any deserializable type.
+///
+/// The whole document is parsed first, since a table may be extended
+/// anywhere in it, and then handed to the `Deserialize` impl of `T`.
+///
+/// ```rust
+/// use miniserde_ditto::{toml, Deserialize};
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// struct Limits {
+///     max_connections: u32,
+///     timeout: f64,
+/// }
+///
+/// let limits: Limits = toml::from_str("max_connections = 1_000\ntimeout = 2.5\n").unwrap();
+/// assert_eq!(limits, Limits { max_connections: 1000, timeout: 2.5 });
+/// ```
+pub fn from_str<T: Deserialize>(toml: &str) -> Result<T> {
+    let root = Parser::new(toml).document()?;
+    let mut out = None;
+    let options = crate::transcode::Options::default();
+    crate::transcode::transcode(&root, T::begin(&mut out), options)?;
+    out.ok_or(Error)
+}
+
+/// A parsed value, before being handed to the `Deserialize` impl.
+enum Value {
+    String(String),
+    Integer(i64),
+    #[cfg(feature = "float")]
+    Float(f64),
+    Boolean(bool),
+    /// Dates and times are deserialized as their text.
+    Datetime(String),
+    Array(Vec<Value>),
+    /// The tables of the `[[headers]]` of the same name.
+    Tables(Vec<Table>),
+    Table(Table),
+}
+
+/// The entries of a table, in the order of the document.
+struct Table {
+    entries: Vec<(String, Value)>,
+    /// The position of each key in `entries`.
+    index: BTreeMap<String, usize>,
+    kind: TableKind,
+}
+
+/// How a table was defined, which tells how it may be extended.
+#[derive(Clone, Copy, PartialEq)]
+enum TableKind {
+    /// Only as the parent of another table so far, as `a` by `[a.b]`.
+    Implicit,
+    /// By a `[header]` or `[[header]]`.
+    Header,
+    /// By a dotted key, as `a` by `a.b = 1`.
+    Dotted,
+    /// By `{ ... }`, and closed to any other key.
+    Inline,
+}
+
+impl Table {
+    fn new(kind: TableKind) -> Self {
+        Table {
+            entries: Vec::new(),
+            index: BTreeMap::new(),
+            kind,
+        }
+    }
+
+    fn position(&self, key: &str) -> Option<usize> {
+        self.index.get(key).copied()
+    }
+
+    /// Adds the entry of a key known to be missing, and returns its position.
+    fn append(&mut self, key: String, value: Value) -> usize {
+        let i = self.entries.len();
+        self.index.insert(key.clone(), i);
+        self.entries.push((key, value));
+        i
+    }
+
+    /// The value of `key`, inserting an empty table of `kind` if missing.
+    fn child(&mut self, key: &str, kind: TableKind) -> &mut Value {
+        let i = match self.position(key) {
+            Some(i) => i,
+            None => self.append(key.to_owned(), Value::Table(Table::new(kind))),
+        };
+        &mut self.entries[i].1
+    }
+
+    /// The table at `path`, as a `[header]` or `[[header]]` names it: the
+    /// last table of each array of tables on the way is the one extended.
+    fn descend(&mut self, path: &[String], missing: TableKind) -> Result<&mut Table> {
+        let mut table = self;
+        for key in path {
+            table = match table.child(key, missing) {
+                Value::Table(table) if table.kind != TableKind::Inline => table,
+                Value::Tables(tables) => tables.last_mut().unwrap(),
+                _ => err!("Cannot extend the value of key {:?} as a table", key),
+            };
+        }
+        Ok(table)
+    }
+
+    /// Defines the table of a `[header]`.
+    fn open(&mut self, path: &[String]) -> Result<()> {
+        let (key, parents) = path.split_last().unwrap();
+        let parent = self.descend(parents, TableKind::Implicit)?;
+        let i = match parent.position(key) {
+            Some(i) => i,
+            None => {
+                let table = Value::Table(Table::new(TableKind::Header));
+                parent.append(key.clone(), table);
+                return Ok(());
+            }
+        };
+        match parent.entries[i].1 {
+            Value::Table(ref mut table) if table.kind == TableKind::Implicit => {
+                table.kind = TableKind::Header;
+            }
+            _ => err!("Table {:?} is defined twice", path),
+        }
+        Ok(())
+    }
+
+    /// Appends a table to the array of a `[[header]]`.
+    fn push(&mut self, path: &[String]) -> Result<()> {
+        let (key, parents) = path.split_last().unwrap();
+        let parent = self.descend(parents, TableKind::Implicit)?;
+        let table = Table::new(TableKind::Header);
+        match parent.position(key) {
+            None => {
+                parent.append(key.clone(), Value::Tables(vec![table]));
+            }
+            Some(i) => match parent.entries[i].1 {
+                Value::Tables(ref mut tables) => tables.push(table),
+                _ => err!("Cannot append to {:?} as an array of tables", path),
+            },
+        }
+        Ok(())
+    }
+
+    /// Inserts the value of a key, possibly dotted.
+    fn insert(&mut self, path: Vec<String>, value: Value) -> Result<()> {
+        let mut path = path;
+        let key = path.pop().unwrap();
+        let mut table = self;
+        for parent in &path {
+            table = match table.child(parent, TableKind::Dotted) {
+                Value::Table(table) if table.kind == TableKind::Dotted => table,
+                _ => err!("Cannot extend the value of key {:?} as a table", parent),
+            };
+        }
+        if table.position(&key).is_some() {
+            err!("Key {:?} is defined twice", key);
+        }
+        table.append(key, value);
+        Ok(())
+    }
+}
+
+impl Serialize for Value {
+    fn view(&self) -> ValueView<'_> {
+        match self {
+            Value::String(s) | Value::Datetime(s) => ValueView::Str(Cow::Borrowed(s)),
+            Value::Integer(i) => ValueView::Int((*i).into()),
+            #[cfg(feature = "float")]
+            Value::Float(f) => ValueView::F64(*f),
+            Value::Boolean(b) => ValueView::Bool(*b),
+            Value::Array(values) => private::stream_slice(values),
+            Value::Tables(tables) => private::stream_slice(tables),
+            Value::Table(table) => table.view(),
+        }
+    }
+}
+
+impl Serialize for Table {
+    fn view(&self) -> ValueView<'_> {
+        let entries = self
+            .entries
+            .iter()
+            .map(|(k, v)| (k as &dyn Serialize, v as &dyn Serialize));
+        ValueView::Map(Box::new(entries))
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Parser {
+            pos: if input.starts_with('\u{feff}') { 3 } else { 0 },
+            input,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, n: usize) -> Option<u8> {
+        self.input.as_bytes().get(self.pos + n).copied()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        let found = self.peek() == Some(byte);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn starts_with(&self, prefix: &str) -> bool {
+        self.input[self.pos..].starts_with(prefix)
+    }
+
+    /// The line of the current position, for error messages.
+    fn line(&self) -> usize {
+        1 + self.input[..self.pos].matches('\n').count()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some(b'#') {
+            self.pos = match self.input[self.pos..].find('\n') {
+                Some(len) if self.input[..self.pos + len].ends_with('\r') => self.pos + len - 1,
+                Some(len) => self.pos + len,
+                None => self.input.len(),
+            };
+        }
+    }
+
+    fn newline(&mut self) -> bool {
+        if self.starts_with("\r\n") {
+            self.pos += 2;
+            true
+        } else {
+            self.eat(b'\n')
+        }
+    }
+
+    /// Skips whitespace, comments and newlines, as allowed between the
+    /// values of an array.
+    fn skip_blank(&mut self) {
+        loop {
+            self.skip_whitespace();
+            self.skip_comment();
+            if !self.newline() {
+                break;
+            }
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<()> {
+        self.skip_whitespace();
+        self.skip_comment();
+        if !self.newline() && self.pos != self.input.len() {
+            err!("Expected the end of the line at line {}", self.line());
+        }
+        Ok(())
+    }
+
+    fn document(&mut self) -> Result<Table> {
+        let mut root = Table::new(TableKind::Header);
+        // The `[header]` of the table the keys go to.
+        let mut current = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Ok(root),
+                Some(b'#') | Some(b'\r') | Some(b'\n') => {}
+                Some(b'[') => {
+                    self.pos += 1;
+                    let array = self.eat(b'[');
+                    self.skip_whitespace();
+                    let path = self.key()?;
+                    if !self.eat(b']') || array && !self.eat(b']') {
+                        err!("Expected `]` to end the header at line {}", self.line());
+                    }
+                    if array {
+                        root.push(&path)?;
+                    } else {
+                        root.open(&path)?;
+                    }
+                    current = path;
+                }
+                Some(_) => {
+                    let (path, value) = self.key_value(0)?;
+                    root.descend(&current, TableKind::Implicit)?
+                        .insert(path, value)?;
+                }
+            }
+            self.end_of_line()?;
+        }
+    }
+
+    fn key_value(&mut self, depth: usize) -> Result<(Vec<String>, Value)> {
+        let path = self.key()?;
+        if !self.eat(b'=') {
+            err!("Expected `=` after a key at line {}", self.line());
+        }
+        self.skip_whitespace();
+        let value = self.value(depth)?;
+        Ok((path, value))
+    }
+
+    /// A key, possibly dotted, and the whitespace after it.
+    fn key(&mut self) -> Result<Vec<String>> {
+        let mut path = Vec::new();
+        loop {
+            let key = match self.peek() {
+                Some(b'"') | Some(b'\'')
+                    if self.starts_with("\"\"\"") || self.starts_with("'''") =>
+                {
+                    err!("Unexpected multi-line string key at line {}", self.line());
+                }
+                Some(b'"') | Some(b'\'') => self.string()?,
+                _ => {
+                    let start = self.pos;
+                    while let Some(b'A'..=b'Z') | Some(b'a'..=b'z') | Some(b'0'..=b'9')
+                    | Some(b'_') | Some(b'-') = self.peek()
+                    {
+                        self.pos += 1;
+                    }
+                    if start == self.pos {
+                        err!("Expected a key at line {}", self.line());
+                    }
+                    self.input[start..self.pos].to_owned()
+                }
+            };
+            path.push(key);
+            self.skip_whitespace();
+            if !self.eat(b'.') {
+                return Ok(path);
+            }
+            self.skip_whitespace();
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value> {
+        if depth > MAX_DEPTH {
+            err!("Reached maximum depth when deserializing TOML.");
+        }
+        match self.peek() {
+            Some(b'"') | Some(b'\'') => self.string().map(Value::String),
+            Some(b'[') => {
+                self.pos += 1;
+                let mut values = Vec::new();
+                loop {
+                    self.skip_blank();
+                    if self.eat(b']') {
+                        break;
+                    }
+                    values.push(self.value(depth + 1)?);
+                    self.skip_blank();
+                    if self.eat(b']') {
+                        break;
+                    }
+                    if !self.eat(b',') {
+                        err!("Expected `,` or `]` in an array at line {}", self.line());
+                    }
+                }
+                Ok(Value::Array(values))
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut table = Table::new(TableKind::Inline);
+                self.skip_whitespace();
+                if !self.eat(b'}') {
+                    loop {
+                        self.skip_whitespace();
+                        let (path, value) = self.key_value(depth + 1)?;
+                        table.insert(path, value)?;
+                        self.skip_whitespace();
+                        if self.eat(b'}') {
+                            break;
+                        }
+                        if !self.eat(b',') {
+                            err!("Expected `,` or `}}` in a table at line {}", self.line());
+                        }
+                    }
+                }
+                Ok(Value::Table(table))
+            }
+            _ => self.scalar(),
+        }
+    }
+
+    /// A boolean, number, date or time.
+    fn scalar(&mut self) -> Result<Value> {
+        let start = self.pos;
+        while let Some(byte) = self.peek() {
+            match byte {
+                b'0'..=b'9' | b'A'..=b'Z' | b'a'..=b'z' | b'+' | b'-' | b'_' | b'.' | b':' => {}
+                // Between the date and the time.
+                b' ' if self.pos - start == 10
+                    && is_date(&self.input[start..self.pos])
+                    && self.peek_at(1).map_or(false, |b| b.is_ascii_digit()) => {}
+                _ => break,
+            }
+            self.pos += 1;
+        }
+        let token = &self.input[start..self.pos];
+        let value = match token {
+            "true" => Some(Value::Boolean(true)),
+            "false" => Some(Value::Boolean(false)),
+            _ if is_date(token) || token.as_bytes().get(2) == Some(&b':') => {
+                datetime(token).map(|()| Value::Datetime(token.to_owned()))
+            }
+            _ => number(token),
+        };
+        match value {
+            Some(value) => Ok(value),
+            None if token.is_empty() => err!("Expected a value at line {}", self.line()),
+            None => err!("Invalid value {:?} at line {}", token, self.line()),
+        }
+    }
+
+    /// Any of the four kinds of strings.
+    fn string(&mut self) -> Result<String> {
+        let quote = self.input.as_bytes()[self.pos];
+        let multiline = self.starts_with(if quote == b'"' { "\"\"\"" } else { "'''" });
+        if multiline {
+            self.pos += 3;
+            // A newline right after the opening quotes is trimmed.
+            self.newline();
+        } else {
+            self.pos += 1;
+        }
+        let mut out = String::new();
+        let mut start = self.pos;
+        loop {
+            let byte = match self.peek() {
+                Some(byte) => byte,
+                None => err!("Unterminated string at line {}", self.line()),
+            };
+            match byte {
+                _ if byte == quote => {
+                    let quotes = self.input[self.pos..]
+                        .bytes()
+                        .take_while(|&b| b == quote)
+                        .count();
+                    if !multiline {
+                        out.push_str(&self.input[start..self.pos]);
+                        self.pos += 1;
+                        return Ok(out);
+                    }
+                    if quotes >= 3 {
+                        // Up to two quotes are part of the string.
+                        if quotes > 5 {
+                            err!("Too many quotes at line {}", self.line());
+                        }
+                        out.push_str(&self.input[start..self.pos + quotes - 3]);
+                        self.pos += quotes;
+                        return Ok(out);
+                    }
+                    self.pos += quotes;
+                }
+                b'\\' if quote == b'"' => {
+                    out.push_str(&self.input[start..self.pos]);
+                    self.pos += 1;
+                    self.escape(multiline, &mut out)?;
+                    start = self.pos;
+                }
+                b'\n' if multiline => self.pos += 1,
+                b'\r' if multiline && self.peek_at(1) == Some(b'\n') => self.pos += 2,
+                b'\t' => self.pos += 1,
+                0x00..=0x1f | 0x7f => {
+                    err!(
+                        "Unexpected control character in a string at line {}",
+                        self.line()
+                    );
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    /// The escape sequence after a backslash.
+    fn escape(&mut self, multiline: bool, out: &mut String) -> Result<()> {
+        let byte = self.peek();
+        self.pos += 1;
+        let c = match byte {
+            Some(b'b') => '\u{8}',
+            Some(b't') => '\t',
+            Some(b'n') => '\n',
+            Some(b'f') => '\u{c}',
+            Some(b'r') => '\r',
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'u') => self.unicode(4)?,
+            Some(b'U') => self.unicode(8)?,
+            // A line ending backslash trims the whitespace up to the next
+            // non-whitespace character.
+            Some(b' ') | Some(b'\t') | Some(b'\r') | Some(b'\n') if multiline => {
+                self.pos -= 1;
+                self.skip_whitespace();
+                if !self.newline() {
+                    err!("Expected a newline after `\\` at line {}", self.line());
+                }
+                loop {
+                    self.skip_whitespace();
+                    if !self.newline() {
+                        return Ok(());
+                    }
+                }
+            }
+            _ => err!("Invalid escape sequence at line {}", self.line()),
+        };
+        out.push(c);
+        Ok(())
+    }
+
+    fn unicode(&mut self, len: usize) -> Result<char> {
+        let hex = match self.input.get(self.pos..self.pos + len) {
+            Some(hex) if hex.bytes().all(|b| b.is_ascii_hexdigit()) => hex,
+            _ => err!(
+                "Expected {} hexadecimal digits at line {}",
+                len,
+                self.line()
+            ),
+        };
+        self.pos += len;
+        let scalar = u32::from_str_radix(hex, 16).map_err(|_| Error)?;
+        match ::core::char::from_u32(scalar) {
+            Some(c) => Ok(c),
+            None => err!("Invalid Unicode scalar value at line {}", self.line()),
+        }
+    }
+}
+
+/// Whether `s` starts with a date, as `1979-05-27`.
+fn is_date(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() >= 10 && digits(b, 0, 4).is_some() && b[4] == b'-'
+}
+
+/// The number written with the `len` digits of `b` at `at`.
+fn digits(b: &[u8], at: usize, len: usize) -> Option<u32> {
+    let digits = b.get(at..at + len)?;
+    if !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    Some(
+        digits
+            .iter()
+            .fold(0, |acc, &d| acc * 10 + u32::from(d - b'0')),
+    )
+}
+
+/// Checks a date and time, a date, or a time, per RFC 3339 as relaxed by
+/// TOML.
+fn datetime(s: &str) -> Option<()> {
+    /// What follows the time at the start of `b`.
+    fn time(b: &[u8]) -> Option<&[u8]> {
+        let (hour, minute, second) = (digits(b, 0, 2)?, digits(b, 3, 2)?, digits(b, 6, 2)?);
+        if b[2] != b':' || b[5] != b':' || hour > 23 || minute > 59 || second > 60 {
+            return None;
+        }
+        let mut rest = &b[8..];
+        if rest.first() == Some(&b'.') {
+            let len = rest[1..].iter().take_while(|d| d.is_ascii_digit()).count();
+            if len == 0 {
+                return None;
+            }
+            rest = &rest[1 + len..];
+        }
+        Some(rest)
+    }
+
+    let b = s.as_bytes();
+    if !is_date(s) {
+        return if time(b)?.is_empty() { Some(()) } else { None };
+    }
+    let (month, day) = (digits(b, 5, 2)?, digits(b, 8, 2)?);
+    if b[7] != b'-' || month < 1 || month > 12 || day < 1 || day > 31 {
+        return None;
+    }
+    let rest = &b[10..];
+    match rest.first() {
+        None => return Some(()),
+        Some(b'T') | Some(b't') | Some(b' ') => {}
+        Some(_) => return None,
+    }
+    let offset = time(&rest[1..])?;
+    match offset {
+        [] | [b'Z'] | [b'z'] => Some(()),
+        [sign, _, _, b':', _, _] if *sign == b'+' || *sign == b'-' => {
+            if digits(offset, 1, 2)? > 23 || digits(offset, 4, 2)? > 59 {
+                return None;
+            }
+            Some(())
+        }
+        _ => None,
+    }
+}
+
+/// An integer or float.
+fn number(s: &str) -> Option<Value> {
+    if let Some(radix_digits) = s.get(2..) {
+        let radix = match &s[..2] {
+            "0x" => Some(16),
+            "0o" => Some(8),
+            "0b" => Some(2),
+            _ => None,
+        };
+        if let Some(radix) = radix {
+            let digits = without_underscores(radix_digits, |b| (b as char).is_digit(radix))?;
+            return i64::from_str_radix(&digits, radix).ok().map(Value::Integer);
+        }
+    }
+
+    let unsigned = s.strip_prefix(|c| c == '+' || c == '-').unwrap_or(s);
+    #[cfg(feature = "float")]
+    {
+        if unsigned == "inf" || unsigned == "nan" {
+            let f = if unsigned == "inf" {
+                f64::INFINITY
+            } else {
+                f64::NAN
+            };
+            return Some(Value::Float(if s.starts_with('-') { -f } else { f }));
+        }
+    }
+    let int_len = unsigned
+        .find(|c| c == '.' || c == 'e' || c == 'E')
+        .unwrap_or_else(|| unsigned.len());
+    let int = &unsigned[..int_len];
+    if int.len() > 1 && int.starts_with('0') {
+        // Leading zeros are not allowed.
+        return None;
+    }
+    let is_digit = |b: u8| b.is_ascii_digit();
+    let mut number = String::with_capacity(s.len());
+    number.push_str(&s[..s.len() - unsigned.len()]);
+    number.push_str(&without_underscores(int, is_digit)?);
+    if int_len == unsigned.len() {
+        return i64::from_str(&number).ok().map(Value::Integer);
+    }
+
+    let mut rest = &unsigned[int_len..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let len = fraction
+            .find(|c| c == 'e' || c == 'E')
+            .unwrap_or_else(|| fraction.len());
+        number.push('.');
+        number.push_str(&without_underscores(&fraction[..len], is_digit)?);
+        rest = &fraction[len..];
+    }
+    if !rest.is_empty() {
+        let exponent = &rest[1..];
+        let digits = exponent
+            .strip_prefix(|c| c == '+' || c == '-')
+            .unwrap_or(exponent);
+        number.push('e');
+        number.push_str(&exponent[..exponent.len() - digits.len()]);
+        number.push_str(&without_underscores(digits, is_digit)?);
+    }
+    #[cfg(feature = "float")]
+    return f64::from_str(&number).ok().map(Value::Float);
+    #[cfg(not(feature = "float"))]
+    err!("Unsupported float");
+}
+
+/// `digits` without the underscores between them, if any, or `None` unless
+/// each other character is a digit.
+fn without_underscores(digits: &str, is_digit: impl Fn(u8) -> bool) -> Option<String> {
+    let b = digits.as_bytes();
+    let mut out = String::with_capacity(digits.len());
+    for (i, &byte) in b.iter().enumerate() {
+        if byte == b'_' {
+            if i == 0 || !is_digit(b[i - 1]) || !b.get(i + 1).map_or(false, |&b| is_digit(b)) {
+                return None;
+            }
+        } else if is_digit(byte) {
+            out.push(byte as char);
+        } else {
+            return None;
+        }
+    }
+    if out.is_empty() {
+        return None;
+    }
+    Some(out)
+}
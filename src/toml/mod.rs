@@ -0,0 +1,58 @@
+//! TOML data format, for configuration files. Only deserialization is
+//! supported.
+//!
+//! Tables, arrays of tables, inline tables, dotted keys and all four kinds of
+//! strings are read per TOML 1.0. Tables deserialize like maps, so into
+//! structs, and dates and times into their text, such as a `String` field
+//! holding `"1979-05-27T07:32:00Z"`.
+//!
+//! ```rust
+//! use miniserde_ditto::{toml, Deserialize};
+//!
+//! #[derive(Deserialize, Debug)]
+//! struct Config {
+//!     title: String,
+//!     server: Server,
+//!     peers: Vec<Peer>,
+//! }
+//!
+//! #[derive(Deserialize, Debug)]
+//! struct Server {
+//!     host: String,
+//!     ports: Vec<u16>,
+//! }
+//!
+//! #[derive(Deserialize, Debug)]
+//! struct Peer {
+//!     name: String,
+//!     weight: Option<u8>,
+//! }
+//!
+//! fn main() -> miniserde_ditto::Result<()> {
+//!     let config: Config = toml::from_str(r#"
+//!         title = "gateway"  # Shown in the logs.
+//!
+//!         [server]
+//!         host = 'localhost'
+//!         ports = [8080, 8443]
+//!
+//!         [[peers]]
+//!         name = "a"
+//!         weight = 3
+//!
+//!         [[peers]]
+//!         name = "b"
+//!     "#)?;
+//!
+//!     assert_eq!(config.server.ports, [8080, 8443]);
+//!     assert_eq!(config.peers[1].name, "b");
+//!     Ok(())
+//! }
+//! ```
+
+mod de;
+pub use self::de::from_str;
+
+// for API compat with `::serde_json`
+#[doc(no_inline)]
+pub use crate::{Error, Result};
@@ -0,0 +1,218 @@
+//! Feeding the output of a `Serialize` straight into a `Visitor`, with no
+//! intermediate text or bytes. This powers the `to_value` and `from_value`
+//! functions of the formats.
+
+use core::mem;
+
+use crate::de::{self, Visitor};
+use crate::prelude::*;
+use crate::ser::{self, Serialize, ValueView};
+use crate::Result;
+
+/// Format-specific behaviors to mimic, so that the result is the same as
+/// serializing to the format and deserializing it back.
+// Independent switches, each only set by the formats it applies to.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Options {
+    /// JSON has no representation for NaN and the infinities, and writes them
+    /// as `null`.
+    #[cfg_attr(not(feature = "float"), allow(dead_code))]
+    pub non_finite_as_null: bool,
+    /// Encode the values that a visitor wants raw as JSON, rather than as
+    /// CBOR.
+    #[cfg_attr(not(feature = "json"), allow(dead_code))]
+    pub raw_json: bool,
+    /// JSON writes the bytes of a `Base64` view as a string.
+    #[cfg_attr(not(feature = "json"), allow(dead_code))]
+    pub base64_as_str: bool,
+    /// JSON writes a `Timestamp` view as an RFC 3339 string, rather than as
+    /// seconds since the epoch.
+    #[cfg_attr(not(feature = "json"), allow(dead_code))]
+    pub timestamp_as_str: bool,
+}
+
+pub(crate) fn transcode<'value>(
+    value: &'value dyn Serialize,
+    mut visitor: &mut dyn Visitor,
+    options: Options,
+) -> Result<()> {
+    enum Layer<'value> {
+        Seq(Box<dyn ser::Seq<'value> + 'value>, Box<dyn de::Seq>),
+        Map(Box<dyn ser::Map<'value> + 'value>, Box<dyn de::Map>),
+        Tagged(Box<dyn de::Seq>),
+    }
+    let mut stack: Vec<(&mut dyn Visitor, Layer<'value>)> = vec![];
+    let mut item = value;
+
+    'outer: loop {
+        // Checked before viewing `item`, which `raw` does again: a view may hold
+        // a lock, such as that of a `Mutex`.
+        let mut layer = if visitor.wants_raw() {
+            raw(item, visitor, options)?;
+            None
+        } else {
+            match item.view() {
+                ValueView::Null => {
+                    visitor.null()?;
+                    None
+                }
+                ValueView::Undefined => {
+                    visitor.undefined()?;
+                    None
+                }
+                ValueView::Bool(b) => {
+                    visitor.boolean(b)?;
+                    None
+                }
+                ValueView::Str(s) => {
+                    visitor.string(&s)?;
+                    None
+                }
+                #[cfg(feature = "json")]
+                ValueView::Base64(bs) if options.base64_as_str => {
+                    visitor.string(&crate::json::encode_base64(&bs).collect::<String>())?;
+                    None
+                }
+                ValueView::Bytes(bs) | ValueView::Base64(bs) => {
+                    visitor.bytes(&bs)?;
+                    None
+                }
+                #[cfg(feature = "json")]
+                ValueView::Timestamp(t) if options.timestamp_as_str => {
+                    visitor.string(&t.to_string())?;
+                    None
+                }
+                ValueView::Timestamp(t) => {
+                    match careful!(visitor.tag(1)? as Option<Box<dyn de::Seq>>) {
+                        Some(mut tagged) => {
+                            t.visit_epoch(tagged.element()?)?;
+                            tagged.finish()?;
+                        }
+                        None => t.visit_epoch(visitor)?,
+                    }
+                    None
+                }
+                ValueView::Int(i) => {
+                    visitor.int(i)?;
+                    None
+                }
+                #[cfg(feature = "float")]
+                ValueView::F64(f) if !f.is_finite() && options.non_finite_as_null => {
+                    visitor.null()?;
+                    None
+                }
+                #[cfg(feature = "float")]
+                ValueView::F64(f) => {
+                    visitor.float(f)?;
+                    None
+                }
+                ValueView::RawNumber(raw) => {
+                    ValueView::check_raw_number(&raw)?;
+                    visitor.raw_number(&raw)?;
+                    None
+                }
+                #[cfg(feature = "json")]
+                ValueView::RawJson(j) => {
+                    crate::json::from_str_into(&j, visitor)?;
+                    None
+                }
+                #[cfg(feature = "cbor")]
+                ValueView::RawCbor(bytes) => {
+                    crate::cbor::from_slice_into(&bytes, visitor)?;
+                    None
+                }
+                #[cfg(not(feature = "json"))]
+                ValueView::RawJson(_) => err!("Cannot parse raw JSON without the `json` feature"),
+                #[cfg(not(feature = "cbor"))]
+                ValueView::RawCbor(_) => err!("Cannot decode raw CBOR without the `cbor` feature"),
+                ValueView::Owned(value) => {
+                    transcode(&*value, visitor, options)?;
+                    None
+                }
+                ValueView::Unrepresentable => err!("Cannot transcode an unrepresentable value"),
+                ValueView::Tagged(tag, inner) => {
+                    if let Some(mut tagged) =
+                        careful!(visitor.tag(tag)? as Option<Box<dyn de::Seq>>)
+                    {
+                        let element = careful!(tagged.element()? as &mut dyn Visitor);
+                        let outer = mem::replace(&mut visitor, element);
+                        stack.push((outer, Layer::Tagged(tagged)));
+                    }
+                    item = inner;
+                    continue;
+                }
+                ValueView::Seq(seq) => {
+                    let builder = careful!(visitor.seq()? as Box<dyn de::Seq>);
+                    Some(Layer::Seq(seq, builder))
+                }
+                ValueView::Map(map) => {
+                    let builder = careful!(visitor.map()? as Box<dyn de::Map>);
+                    Some(Layer::Map(map, builder))
+                }
+            }
+        };
+
+        loop {
+            let current = match layer.take() {
+                Some(current) => current,
+                None => match stack.pop() {
+                    Some((outer, current)) => {
+                        visitor = outer;
+                        current
+                    }
+                    None => break 'outer,
+                },
+            };
+            match current {
+                Layer::Seq(mut seq, mut builder) => match seq.next() {
+                    Some(element) => {
+                        let inner = careful!(builder.element()? as &mut dyn Visitor);
+                        let outer = mem::replace(&mut visitor, inner);
+                        stack.push((outer, Layer::Seq(seq, builder)));
+                        item = element;
+                        continue 'outer;
+                    }
+                    None => builder.finish()?,
+                },
+                Layer::Map(mut map, mut builder) => match map.next() {
+                    Some((key, value)) => {
+                        let inner = builder.val_with_key(&mut |out_key| {
+                            out_key.and_then(|out_key| transcode(key, out_key, options))
+                        })?;
+                        let inner = careful!(inner as &mut dyn Visitor);
+                        let outer = mem::replace(&mut visitor, inner);
+                        stack.push((outer, Layer::Map(map, builder)));
+                        item = value;
+                        continue 'outer;
+                    }
+                    None => builder.finish()?,
+                },
+                Layer::Tagged(tagged) => tagged.finish()?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Hands `value` to a visitor that wants it raw, encoded if it is not already.
+#[cfg_attr(not(feature = "json"), allow(unused_variables))]
+fn raw(value: &dyn Serialize, visitor: &mut dyn Visitor, options: Options) -> Result<()> {
+    match value.view() {
+        ValueView::RawJson(j) => return visitor.raw_json(&j),
+        ValueView::RawCbor(bytes) => return visitor.raw_cbor(&bytes),
+        // Encoded once this view is gone: encoding views `value` again, and a
+        // view may hold a lock, such as that of a `Mutex`.
+        _ => {}
+    }
+    #[cfg(feature = "json")]
+    {
+        if options.raw_json || cfg!(not(feature = "cbor")) {
+            return visitor.raw_json(&crate::json::to_string(value)?);
+        }
+    }
+    #[cfg(feature = "cbor")]
+    return visitor.raw_cbor(&crate::cbor::to_vec(value)?);
+    #[cfg(not(feature = "cbor"))]
+    err!("Cannot encode a raw value without the `json` or `cbor` feature");
+}
@@ -0,0 +1,33 @@
+/// Version of the serialized representation of values, as produced by this
+/// crate and its derives.
+///
+/// It is bumped whenever a release changes how some value is written or read,
+/// in a way a peer using an older release could trip on: a different enum
+/// representation, a new encoding for a standard type, and so on. Additions
+/// that do not affect already supported values, such as a new attribute, do
+/// not bump it. Every bump gets an entry in `CHANGELOG.md` describing what
+/// changed on the wire.
+///
+/// Meant to be exchanged in handshakes, and checked with
+/// [`check_wire_compat`], before exchanging data.
+pub const WIRE_COMPAT_VERSION: u32 = 2;
+
+/// Check the wire format version advertised by a peer against
+/// [`WIRE_COMPAT_VERSION`].
+///
+/// ```rust
+/// use miniserde_ditto::{check_wire_compat, WIRE_COMPAT_VERSION};
+///
+/// assert!(check_wire_compat(WIRE_COMPAT_VERSION).is_ok());
+/// assert!(check_wire_compat(WIRE_COMPAT_VERSION + 1).is_err());
+/// ```
+pub fn check_wire_compat(peer_version: u32) -> crate::Result<()> {
+    if peer_version != WIRE_COMPAT_VERSION {
+        err!(
+            "Peer wire format version {} differs from ours, {}",
+            peer_version,
+            WIRE_COMPAT_VERSION,
+        );
+    }
+    Ok(())
+}
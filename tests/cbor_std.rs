@@ -3,9 +3,21 @@
 #![allow(unused)]
 
 use ::miniserde_ditto::{json, Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashSet, LinkedList, VecDeque};
+use std::ffi::{OsStr, OsString};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::num::Wrapping;
+use std::ops::{Bound, Range, RangeInclusive};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 use std::u8;
 
-use ::miniserde_ditto::cbor::{/* from_reader, */ from_slice, to_vec};
+use ::miniserde_ditto::cbor::{
+    /* from_reader, */ from_slice, to_vec, to_vec_canonical, Serializer, Value,
+};
+use ::miniserde_ditto::ser::{self, SignedZero, ValueView};
 
 fn to_binary(s: &'static str) -> Vec<u8> {
     assert!(s.len() % 2 == 0);
@@ -87,10 +99,15 @@ testcase!(test_i8_23, i8, 23, "17");
 testcase!(test_i8_24, i8, 24, "1818");
 testcase!(test_i8_neg_128, i8, -128, "387f");
 testcase!(test_u32_98745874, u32, 98745874, "1a05e2be12");
+#[cfg(feature = "float")]
 testcase!(test_f32_1234_point_5, f32, 1234.5, "fa449a5000");
+#[cfg(feature = "float")]
 testcase!(test_f64_12345_point_6, f64, 12345.6, "fb40c81ccccccccccd");
+#[cfg(feature = "float")]
 testcase!(test_f64_nan, f64, ::std::f64::NAN, "f97e00");
+#[cfg(feature = "float")]
 testcase!(test_f64_infinity, f64, ::std::f64::INFINITY, "f97c00");
+#[cfg(feature = "float")]
 testcase!(test_f64_neg_infinity, f64, -::std::f64::INFINITY, "f9fc00");
 // testcase!(test_char_null, char, '\x00', "6100");
 // testcase!(test_char_broken_heart, char, '💔', "64f09f9294");
@@ -117,6 +134,278 @@ testcase!(test_unit, (), (), "f6");
 
 testcase!(test_option_none, Option<u8>, None, "f6");
 testcase!(test_option_some, Option<u8>, Some(42), "182a");
+testcase!(
+    test_btree_set,
+    BTreeSet<u8>,
+    [3, 1, 2].iter().copied().collect(),
+    "83010203"
+);
+testcase!(
+    test_hash_set,
+    HashSet<String>,
+    Some("foo".to_owned()).into_iter().collect(),
+    "8163666f6f"
+);
+testcase!(test_char, char, 'é', "62c3a9");
+testcase!(test_i128, i128, -(1 << 64), "3bffffffffffffffff");
+testcase!(test_u128, u128, u128::from(u64::MAX), "1bffffffffffffffff");
+testcase!(test_result_ok, Result<u8, String>, Ok(1), "a1624f6b01");
+testcase!(
+    test_result_err,
+    Result<u8, String>,
+    Err("x".to_owned()),
+    "a1634572726178"
+);
+testcase!(
+    test_bound_unbounded,
+    Bound<u8>,
+    Bound::Unbounded,
+    "69556e626f756e646564"
+);
+testcase!(
+    test_bound_included,
+    Bound<u8>,
+    Bound::Included(3),
+    "a168496e636c7564656403"
+);
+testcase!(test_range, Range<u8>, 1..3, "a26573746172740163656e6403");
+testcase!(
+    test_range_inclusive,
+    RangeInclusive<u8>,
+    1..=3,
+    "a26573746172740163656e6403"
+);
+testcase!(test_wrapping, Wrapping<u8>, Wrapping(5), "05");
+
+#[test]
+fn test_core_enums_json() {
+    let ok: Result<Vec<u16>, String> = Ok(vec![1]);
+    assert_eq!(json::to_string(&ok).unwrap(), r#"{"Ok":[1]}"#);
+    let bounds = (Bound::Excluded(1_u8), Bound::<u8>::Unbounded);
+    let j = json::to_string(&bounds).unwrap();
+    assert_eq!(j, r#"[{"Excluded":1},"Unbounded"]"#);
+    assert_eq!(
+        json::from_str::<(Bound<u8>, Bound<u8>)>(&j).unwrap(),
+        bounds
+    );
+
+    assert!(json::from_str::<Result<u8, u8>>(r#"{"Ok":1,"Err":2}"#).is_err());
+    assert!(json::from_str::<Result<u8, u8>>(r#"{}"#).is_err());
+    assert!(json::from_str::<Result<u8, u8>>(r#"{"Some":1}"#).is_err());
+    assert!(json::from_str::<Bound<u8>>(r#""Included""#).is_err());
+    assert!(json::from_str::<Range<u8>>(r#"{"start":1}"#).is_err());
+    assert_eq!(
+        json::from_str::<Range<u8>>(r#"{"end":2,"step":1,"start":1}"#).unwrap(),
+        1..2
+    );
+}
+
+#[test]
+fn test_u128_out_of_range() {
+    assert!(to_vec(u128::from(u64::MAX) + 1).is_err());
+    assert!(to_vec(u128::MAX).is_err());
+    assert!(from_slice::<char>(&to_binary("626162")).is_err());
+}
+
+testcase!(
+    test_socket_addr,
+    SocketAddr,
+    "[::1]:8080".parse().unwrap(),
+    "6a5b3a3a315d3a38303830"
+);
+testcase!(
+    test_ip_addr,
+    IpAddr,
+    Ipv4Addr::LOCALHOST.into(),
+    "693132372e302e302e31"
+);
+
+testcase!(test_u8_array, [u8; 4], [1, 2, 3, 4], "4401020304");
+testcase!(test_u16_array, [u16; 2], [1, 2], "820102");
+testcase!(test_empty_u8_array, [u8; 0], [], "40");
+
+#[test]
+fn test_large_arrays() {
+    let key = [7_u8; 48];
+    let serialized = to_vec(key).unwrap();
+    assert_eq!(serialized[..2], [0x58, 48]);
+    assert_eq!(from_slice::<[u8; 48]>(&serialized).unwrap(), key);
+    let short = [7_u8; 47];
+    assert!(from_slice::<[u8; 48]>(&to_vec(short).unwrap()).is_err());
+
+    let words = [1_u16; 40];
+    let serialized = to_vec(words).unwrap();
+    assert_eq!(serialized[..2], [0x98, 40]);
+    assert_eq!(from_slice::<[u16; 40]>(&serialized).unwrap(), words);
+    assert!(from_slice::<[u16; 39]>(&serialized).is_err());
+}
+testcase!(
+    test_vec_deque,
+    VecDeque<u8>,
+    [1, 2, 3].iter().copied().collect(),
+    "83010203"
+);
+testcase!(
+    test_linked_list,
+    LinkedList<u8>,
+    [1, 2, 3].iter().copied().collect(),
+    "83010203"
+);
+
+#[test]
+fn test_binary_heap() {
+    let heap: BinaryHeap<u8> = Some(42).into_iter().collect();
+    let serialized = to_binary("81182a");
+    assert_eq!(to_vec(&heap).unwrap(), serialized);
+    let parsed: BinaryHeap<u8> = from_slice(&to_binary("83020301")).unwrap();
+    assert_eq!(parsed.into_sorted_vec(), [1, 2, 3]);
+}
+#[test]
+#[cfg(feature = "float")]
+fn test_signed_zero() {
+    let serialized = to_binary("f98000");
+    assert_eq!(to_vec(-0.0).unwrap(), serialized);
+    let parsed: f64 = from_slice(&serialized).unwrap();
+    assert!(parsed.is_sign_negative());
+
+    let normalized = Serializer::new()
+        .signed_zero(SignedZero::Normalize)
+        .to_vec(-0.0)
+        .unwrap();
+    assert_eq!(normalized, to_binary("f90000"));
+}
+
+testcase!(test_rc, Rc<String>, Rc::new("foo".to_owned()), "63666f6f");
+testcase!(test_arc, Arc<Vec<u16>>, Arc::new(vec![1, 2]), "820102");
+testcase!(test_box_str, Box<str>, "foo".into(), "63666f6f");
+testcase!(test_rc_str, Rc<str>, "foo".into(), "63666f6f");
+testcase!(test_arc_str, Arc<str>, "foo".into(), "63666f6f");
+testcase!(test_boxed_slice, Box<[u16]>, vec![1, 2].into(), "820102");
+testcase!(test_boxed_bytes, Box<[u8]>, vec![1, 2].into(), "420102");
+
+#[test]
+fn test_tagged() {
+    // Epoch-based date/time, from RFC 8949 appendix A.
+    let timestamp = Value::Tag(1, Box::new(Value::Integer(1363896240)));
+    assert_eq!(to_vec(&timestamp).unwrap(), to_binary("c11a514b67b0"));
+    assert_eq!(json::to_string(&timestamp).unwrap(), "1363896240");
+
+    struct Bignum(Vec<u8>);
+
+    impl Serialize for Bignum {
+        fn view(&self) -> ValueView<'_> {
+            ValueView::Tagged(2, &self.0)
+        }
+    }
+
+    let two_pow_64 = Bignum(vec![1, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let serialized = to_binary("c249010000000000000000");
+    assert_eq!(to_vec(&two_pow_64).unwrap(), serialized);
+}
+
+#[test]
+fn test_tagged_deserialization() {
+    let serialized = to_binary("c11a514b67b0");
+    let timestamp = Value::Tag(1, Box::new(Value::Integer(1363896240)));
+    assert_eq!(from_slice::<Value>(&serialized).unwrap(), timestamp);
+    assert_eq!(
+        from_slice::<Option<Box<Value>>>(&serialized).unwrap(),
+        Some(Box::new(timestamp)),
+    );
+
+    // Other types ignore the tags.
+    assert_eq!(from_slice::<u32>(&serialized).unwrap(), 1363896240);
+    let nested = to_binary("d8208163666f6f");
+    assert_eq!(from_slice::<Vec<String>>(&nested).unwrap(), ["foo"]);
+    assert!(from_slice::<u32>(&to_binary("c1")).is_err());
+}
+
+#[test]
+fn test_indefinite_length() {
+    // [_ [_ ], {_ "a": 2}]
+    let serialized = to_binary("9f9fffbf616102ffff");
+    assert_eq!(
+        from_slice::<(Vec<u8>, BTreeMap<String, u8>)>(&serialized).unwrap(),
+        (vec![], vec![("a".to_owned(), 2)].into_iter().collect()),
+    );
+    assert!(from_slice::<Vec<u8>>(&to_binary("9f01")).is_err());
+}
+
+#[test]
+fn test_map_chain() {
+    struct Extended {
+        base: BTreeMap<&'static str, u8>,
+        extension: BTreeMap<&'static str, u8>,
+    }
+
+    impl Serialize for Extended {
+        fn view(&self) -> ValueView<'_> {
+            fn entries<'a>(map: &'a BTreeMap<&str, u8>) -> Box<dyn ser::Map<'a> + 'a> {
+                Box::new(
+                    map.iter()
+                        .map(|(k, v)| (k as &dyn Serialize, v as &dyn Serialize)),
+                )
+            }
+            ValueView::Map(ser::map_chain(
+                entries(&self.base),
+                entries(&self.extension),
+            ))
+        }
+    }
+
+    let extended = Extended {
+        base: Some(("a", 1)).into_iter().collect(),
+        extension: Some(("b", 2)).into_iter().collect(),
+    };
+    // The map header counts the entries of both maps.
+    assert_eq!(to_vec(&extended).unwrap(), to_binary("a2616101616202"));
+}
+
+testcase!(
+    test_path_buf,
+    PathBuf,
+    PathBuf::from("/etc/hosts"),
+    "6a2f6574632f686f737473"
+);
+testcase!(test_os_string, OsString, OsString::from("ab"), "626162");
+
+#[cfg(unix)]
+#[test]
+fn test_non_utf8_os_strings() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let os_str = OsStr::from_bytes(b"a\xff");
+    let serialized = to_vec(os_str).unwrap();
+    assert_eq!(serialized, to_binary("4261ff"));
+    let parsed: OsString = from_slice(&serialized).unwrap();
+    assert_eq!(parsed, os_str);
+
+    // Paths must be UTF-8.
+    assert!(to_vec(Path::new(os_str)).is_err());
+    assert!(json::to_string(&Path::new(os_str)).is_err());
+    assert!(to_vec_canonical(&Path::new(os_str)).is_err());
+    assert!(json::to_value(&Path::new(os_str)).is_err());
+}
+
+#[test]
+fn test_interior_mutability() {
+    let cell = Cell::new(42_u8);
+    assert_eq!(to_vec(&cell).unwrap(), to_binary("182a"));
+    let ref_cell = RefCell::new(vec!["a".to_owned()]);
+    assert_eq!(to_vec(&ref_cell).unwrap(), to_binary("816161"));
+    let mutex = Mutex::new("foo".to_owned());
+    assert_eq!(to_vec(&mutex).unwrap(), to_binary("63666f6f"));
+
+    // The borrows end with the serialization, also when nested and sorted.
+    let nested = vec![RefCell::new(vec![Mutex::new(2_u8)]), RefCell::new(vec![])];
+    let mut map = BTreeMap::new();
+    map.insert("b", &nested[0]);
+    map.insert("aa", &nested[1]);
+    let serialized = to_vec_canonical(&map).unwrap();
+    assert_eq!(serialized, to_binary("a26162810262616180"));
+    nested[0].borrow_mut().clear();
+    assert_eq!(json::to_string(&nested).unwrap(), "[[],[]]");
+}
 
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 struct Person {
@@ -137,7 +426,7 @@ Person {
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 struct OptionalPerson {
     name: String,
-    // #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     year_of_birth: Option<u16>,
     profession: Option<String>,
 }
@@ -150,20 +439,85 @@ testcase!(
         year_of_birth: None,
         profession: Some("computer scientist".to_string()),
     },
-    // "a2646e616d656c477261636520486f707065726a70726f66657373696f6e72636f6d707574657220736369656e74697374"
-    concat!(
-        "a3",
-        "646e616d65",
-        "6c477261636520486f70706572",
-        // year_of_birth:
-        "6d796561725f6f665f6269727468",
-        "f6",
-        "6a70726f66657373696f6e",
-        "7263",
-        "6f6d707574657220736369656e74697374",
-    )
+    "a2646e616d656c477261636520486f707065726a70726f66657373696f6e72636f6d707574657220736369656e74697374"
 );
 
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(accept_array)]
+struct Reading {
+    sensor: u8,
+    value: i32,
+    unit: Option<String>,
+}
+
+#[test]
+fn test_accept_array() {
+    let reading = Reading {
+        sensor: 1,
+        value: -20,
+        unit: None,
+    };
+    // As a map, as usual.
+    let map = to_vec(&reading).unwrap();
+    assert_eq!(from_slice::<Reading>(&map).unwrap(), reading);
+    // Positionally: `[1, -20, null]`, then `[1, -20]`.
+    let array = to_binary("830133f6");
+    assert_eq!(from_slice::<Reading>(&array).unwrap(), reading);
+    let short = to_binary("820133");
+    assert_eq!(from_slice::<Reading>(&short).unwrap(), reading);
+    // `[1]` and `[1, -20, null, 0]`.
+    assert!(from_slice::<Reading>(&to_binary("8101")).is_err());
+    assert!(from_slice::<Reading>(&to_binary("840133f600")).is_err());
+    // The array form is only accepted, never produced.
+    assert_eq!(map[0], 0xa3);
+    // Structs without the attribute still reject arrays.
+    assert!(from_slice::<OptionalPerson>(&to_binary("8100")).is_err());
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(presence_bitmap)]
+struct Sample {
+    sensor: u8,
+    unit: Option<String>,
+    value: i32,
+    scale: Option<u8>,
+}
+
+#[test]
+fn test_presence_bitmap() {
+    let sample = Sample {
+        sensor: 1,
+        unit: None,
+        value: -20,
+        scale: Some(3),
+    };
+    // `[0b10, 1, -20, 3]`: only the second `Option` is there.
+    let bytes = to_vec(&sample).unwrap();
+    assert_eq!(bytes, to_binary("8402013303"));
+    assert_eq!(from_slice::<Sample>(&bytes).unwrap(), sample);
+    assert_eq!(json::to_string(&sample).unwrap(), "[2,1,-20,3]");
+    assert_eq!(json::from_str::<Sample>("[2,1,-20,3]").unwrap(), sample);
+    // Maps are still accepted.
+    let j = r#"{"sensor":1,"value":-20,"scale":3}"#;
+    assert_eq!(json::from_str::<Sample>(j).unwrap(), sample);
+
+    // `[0b01, 1, "V", -20]`.
+    let sample = Sample {
+        unit: Some("V".to_owned()),
+        scale: None,
+        ..sample
+    };
+    let bytes = to_vec(&sample).unwrap();
+    assert_eq!(bytes, to_binary("840101615633"));
+    assert_eq!(from_slice::<Sample>(&bytes).unwrap(), sample);
+
+    // `[0b11, 1, "V", -20]`, missing `scale`; `[0b100, 1, -20]`, with a bit
+    // of no field; `[0, 1]`, missing `value`; `[0, 1, -20, 3]`; and `[]`.
+    for &bad in &["840301615633", "83040133", "820001", "8400013303", "80"] {
+        assert!(from_slice::<Sample>(&to_binary(bad)).is_err(), "{}", bad);
+    }
+}
+
 // #[derive(Debug, PartialEq, Deserialize, Serialize)]
 // enum Color {
 //     Red,
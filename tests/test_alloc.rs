@@ -0,0 +1,67 @@
+#![cfg(feature = "allocator_api")]
+#![feature(allocator_api)]
+
+use ::miniserde_ditto::{cbor, json, Deserialize, Serialize};
+use std::alloc::{AllocError, Allocator, Global, Layout};
+use std::cell::Cell;
+use std::ptr::NonNull;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = Cell::new(0);
+}
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+#[derive(Clone, Copy, Default)]
+struct Counting;
+
+unsafe impl Allocator for Counting {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        ALLOCATIONS.with(|it| it.set(it.get() + 1));
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        Global.deallocate(ptr, layout)
+    }
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Node {
+    values: Vec<u32, Counting>,
+    next: Option<Box<Node, Counting>>,
+}
+
+#[test]
+fn test_vec() {
+    let before = allocations();
+    let vec: Vec<u32, Counting> = json::from_str("[1, 2, 3]").unwrap();
+    assert_eq!(vec, [1, 2, 3]);
+    assert!(allocations() > before);
+    assert_eq!(json::to_string(&vec).unwrap(), "[1,2,3]");
+
+    let before = allocations();
+    let bytes: Vec<u8, Counting> = cbor::from_slice(&[0x43, 1, 2, 3]).unwrap();
+    assert_eq!(bytes, [1, 2, 3]);
+    assert_eq!(allocations(), before + 1);
+}
+
+#[test]
+fn test_box() {
+    let before = allocations();
+    let j = r#"{"values": [1], "next": {"values": [], "next": null}}"#;
+    let node: Node = json::from_str(j).unwrap();
+    assert_eq!(node.values, [1]);
+    assert_eq!(node.next.as_ref().unwrap().values, []);
+    // The outer `values`, and the `Box`.
+    assert_eq!(allocations(), before + 2);
+    assert_eq!(
+        json::from_str::<Node>(&json::to_string(&node).unwrap()).unwrap(),
+        node
+    );
+
+    let boxed: Box<u8, Counting> = json::from_str("7").unwrap();
+    assert_eq!(*boxed, 7);
+}
@@ -0,0 +1,74 @@
+use miniserde_ditto::json::{self, Base64};
+use miniserde_ditto::{cbor, Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Blob {
+    id: u32,
+    data: Base64,
+    thumbnail: Option<Base64>,
+}
+
+#[test]
+fn test_encoding() {
+    let cases: &[(&[u8], &str)] = &[
+        (b"", ""),
+        (b"f", "Zg=="),
+        (b"fo", "Zm8="),
+        (b"foo", "Zm9v"),
+        (b"foob", "Zm9vYg=="),
+        (b"fooba", "Zm9vYmE="),
+        (b"foobar", "Zm9vYmFy"),
+        (&[0xfb, 0xff, 0xbf], "+/+/"),
+    ];
+    for &(bytes, text) in cases {
+        let j = json::to_string(&Base64(bytes.to_vec())).unwrap();
+        assert_eq!(j, format!("\"{}\"", text));
+        assert_eq!(json::from_str::<Base64>(&j).unwrap().0, bytes);
+    }
+
+    // Padding may be left out.
+    assert_eq!(json::from_str::<Base64>("\"Zm9vYg\"").unwrap().0, b"foob");
+    for invalid in &["Zg=", "Zm9=v", "Z", "Zm9vY===", "Zh==", "Zm9v_w==", "Zm 9v"] {
+        let j = format!("\"{}\"", invalid);
+        assert!(json::from_str::<Base64>(&j).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_across_formats() {
+    let blob = Blob {
+        id: 7,
+        data: Base64(vec![0, 1, 2, 255]),
+        thumbnail: None,
+    };
+
+    let j = json::to_string(&blob).unwrap();
+    assert_eq!(j, r#"{"id":7,"data":"AAEC/w==","thumbnail":null}"#);
+    assert_eq!(json::from_str::<Blob>(&j).unwrap(), blob);
+
+    // A byte string in CBOR, the same as for a `Vec<u8>`.
+    let bytes = cbor::to_vec(&blob).unwrap();
+    let value: cbor::Value = cbor::from_slice(&bytes).unwrap();
+    assert_eq!(
+        value.get("data"),
+        Some(&cbor::Value::Bytes(vec![0, 1, 2, 255]))
+    );
+    assert_eq!(cbor::from_slice::<Blob>(&bytes).unwrap(), blob);
+
+    // JSON text transcoded from CBOR keeps the bytes as numbers, which a
+    // `Base64` does not accept.
+    let j = json::to_string_from_cbor_slice(&bytes).unwrap();
+    assert!(json::from_str::<Blob>(&j).is_err());
+}
+
+#[test]
+fn test_through_values() {
+    let data = Base64(b"hello".to_vec());
+    let value = json::to_value(&data).unwrap();
+    assert_eq!(json::to_string(&value).unwrap(), r#""aGVsbG8=""#);
+    assert_eq!(json::from_value::<Base64>(value).unwrap(), data);
+
+    let value = cbor::to_value(&data).unwrap();
+    assert_eq!(value, cbor::Value::Bytes(b"hello".to_vec()));
+    assert_eq!(format!("{:?}", data), "Base64(aGVsbG8=)");
+}
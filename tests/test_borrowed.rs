@@ -0,0 +1,67 @@
+use ::miniserde_ditto::{cbor, json, Deserialize, Serialize};
+use std::borrow::Cow;
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[serde(borrow)]
+struct Record<'a> {
+    name: &'a str,
+    note: Cow<'a, str>,
+    alias: Option<&'a str>,
+    count: u32,
+    tags: Vec<String>,
+}
+
+#[test]
+fn test_json() {
+    let j = r#"{"name": "a", "note": "b", "alias": "c", "count": 1, "tags": ["d"]}"#;
+    let record: Record<'_> = json::from_str_borrowed(j).unwrap();
+    assert_eq!(record.name, "a");
+    assert!(matches!(record.note, Cow::Borrowed("b")));
+    assert_eq!(record.alias, Some("c"));
+    assert_eq!(record.count, 1);
+    assert_eq!(record.tags, ["d"]);
+
+    // Borrowed from the input.
+    let range = j.as_bytes().as_ptr_range();
+    assert!(range.contains(&record.name.as_ptr()));
+
+    let j = r#"{"name": "a", "note": "\"b\"", "alias": null, "count": 1, "tags": []}"#;
+    let record: Record<'_> = json::from_str_borrowed(j).unwrap();
+    assert!(matches!(record.note, Cow::Owned(ref note) if note == "\"b\""));
+    assert_eq!(record.alias, None);
+
+    let j = r#"{"name": "a", "note": "b", "count": 1, "tags": []}"#;
+    let record: Record<'_> = json::from_str_borrowed(j).unwrap();
+    assert_eq!(record.alias, None);
+}
+
+#[test]
+fn test_json_escaped() {
+    let j = r#"{"name": "\n", "note": "b", "count": 1, "tags": []}"#;
+    assert!(json::from_str_borrowed::<Record<'_>>(j).is_err());
+    assert!(json::from_str_borrowed::<&str>(r#""a\tb""#).is_err());
+    assert_eq!(json::from_str_borrowed::<&str>(r#""a b""#).unwrap(), "a b");
+}
+
+#[test]
+fn test_cbor() {
+    let record = Record {
+        name: "a",
+        note: Cow::Borrowed("b"),
+        alias: Some("c"),
+        count: 1,
+        tags: vec!["d".to_owned()],
+    };
+    let bytes = cbor::to_vec(&record).unwrap();
+    let actual: Record<'_> = cbor::from_slice_borrowed(&bytes).unwrap();
+    assert_eq!(actual, record);
+    assert!(matches!(actual.note, Cow::Borrowed(_)));
+
+    let data: &[u8] = &[0x43, 1, 2, 3];
+    assert_eq!(cbor::from_slice_borrowed::<&[u8]>(data).unwrap(), [1, 2, 3]);
+    // Byte strings of unknown length only borrow as a single chunk.
+    let data: &[u8] = &[0x5f, 0x42, 1, 2, 0xff];
+    assert_eq!(cbor::from_slice_borrowed::<&[u8]>(data).unwrap(), [1, 2]);
+    let data: &[u8] = &[0x5f, 0x41, 1, 0x41, 2, 0xff];
+    assert!(cbor::from_slice_borrowed::<&[u8]>(data).is_err());
+}
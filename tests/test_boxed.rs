@@ -0,0 +1,108 @@
+use miniserde_ditto::ser::{self, BoxSerialize, ValueView};
+use miniserde_ditto::{cbor, json, Serialize};
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+#[derive(Serialize)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[test]
+fn test_trait_objects() {
+    let items: Vec<Box<dyn Serialize>> = vec![
+        Box::new(Point { x: 1, y: 2 }),
+        Box::new(Rc::new("rc")),
+        Box::new(()),
+    ];
+    assert_eq!(
+        json::to_string(&items).unwrap(),
+        r#"[{"x":1,"y":2},"rc",null]"#
+    );
+
+    let mut fields: BTreeMap<String, BoxSerialize> = BTreeMap::new();
+    fields.insert("point".to_owned(), ser::boxed(Point { x: 0, y: -1 }));
+    fields.insert("bytes".to_owned(), ser::boxed(vec![1_u8, 2]));
+    let j = json::to_string(&fields).unwrap();
+    assert_eq!(j, r#"{"bytes":[1,2],"point":{"x":0,"y":-1}}"#);
+
+    // The bytes stay a byte string through the box.
+    let bytes = cbor::to_vec(&fields).unwrap();
+    let value: cbor::Value = cbor::from_slice(&bytes).unwrap();
+    assert_eq!(value.get("bytes"), Some(&cbor::Value::Bytes(vec![1, 2])));
+
+    let shared: Arc<dyn Serialize + Send + Sync> = Arc::new(Point { x: 5, y: 5 });
+    assert_eq!(json::to_string(&shared).unwrap(), r#"{"x":5,"y":5}"#);
+}
+
+#[test]
+fn test_from_iter() {
+    struct Sparse {
+        // Only the set entries are serialized, as a map from their index.
+        slots: Vec<Option<BoxSerialize>>,
+        indices: Vec<usize>,
+    }
+
+    impl Serialize for Sparse {
+        fn view(&self) -> ValueView<'_> {
+            let entries = self
+                .indices
+                .iter()
+                .zip(&self.slots)
+                .filter_map(|(i, slot)| {
+                    let slot: &dyn Serialize = &**slot.as_ref()?;
+                    Some((i as &dyn Serialize, slot))
+                });
+            ser::map_from_iter(entries.collect::<Vec<_>>())
+        }
+    }
+
+    let sparse = Sparse {
+        slots: vec![Some(ser::boxed("a")), None, Some(ser::boxed(3))],
+        indices: vec![0, 1, 2],
+    };
+    assert_eq!(json::to_string(&sparse).unwrap(), r#"{"0":"a","2":3}"#);
+
+    struct Reversed(Vec<Point>);
+
+    impl Serialize for Reversed {
+        fn view(&self) -> ValueView<'_> {
+            ser::seq_from_iter(self.0.iter().rev().map(|p| p as &dyn Serialize))
+        }
+    }
+
+    let reversed = Reversed(vec![Point { x: 1, y: 1 }, Point { x: 2, y: 2 }]);
+    let j = json::to_string(&reversed).unwrap();
+    assert_eq!(j, r#"[{"x":2,"y":2},{"x":1,"y":1}]"#);
+}
+
+#[test]
+fn test_heterogeneous_arrays() {
+    #[derive(Serialize)]
+    struct Request<'a> {
+        jsonrpc: &'a str,
+        method: &'a str,
+        params: &'a [&'a dyn Serialize],
+        id: u32,
+    }
+
+    let point = Point { x: 3, y: 4 };
+    let request = Request {
+        jsonrpc: "2.0",
+        method: "move",
+        params: &[&"cursor", &point, &Some(true), &[1_u8, 2]],
+        id: 7,
+    };
+    assert_eq!(
+        json::to_string(&request).unwrap(),
+        r#"{"jsonrpc":"2.0","method":"move","params":["cursor",{"x":3,"y":4},true,[1,2]],"id":7}"#,
+    );
+
+    let params: [&dyn Serialize; 3] = [&1, &"two", &()];
+    assert_eq!(json::to_string(&params).unwrap(), r#"[1,"two",null]"#);
+
+    let nested: [[Box<dyn Serialize>; 2]; 1] = [[Box::new('a'), Box::new(vec![-1_i8])]];
+    assert_eq!(json::to_string(&nested).unwrap(), r#"[["a",[-1]]]"#);
+}
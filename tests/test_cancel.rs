@@ -0,0 +1,38 @@
+use miniserde_ditto::{cbor, json};
+use std::cell::Cell;
+
+#[test]
+fn test_json() {
+    let calls = Cell::new(0);
+    let should_cancel = || {
+        calls.set(calls.get() + 1);
+        calls.get() > 2
+    };
+    let deserializer = json::Deserializer::new().should_cancel(&should_cancel);
+
+    let small = format!("[{}]", vec!["[1]"; 1000].join(","));
+    assert!(deserializer.from_str::<Vec<Vec<u8>>>(&small).is_ok());
+    assert_eq!(calls.get(), 1);
+
+    let big = format!("[{}]", vec!["[1]"; 10_000].join(","));
+    assert!(deserializer.from_str::<Vec<Vec<u8>>>(&big).is_err());
+    assert_eq!(calls.get(), 3);
+}
+
+#[test]
+fn test_cbor() {
+    let calls = Cell::new(0);
+    let should_cancel = || {
+        calls.set(calls.get() + 1);
+        calls.get() > 2
+    };
+    let deserializer = cbor::Deserializer::new().should_cancel(&should_cancel);
+
+    let small = cbor::to_vec(vec![vec![1_u16]; 1000]).unwrap();
+    assert!(deserializer.from_slice::<Vec<Vec<u16>>>(&small).is_ok());
+    assert_eq!(calls.get(), 1);
+
+    let big = cbor::to_vec(vec![vec![1_u16]; 10_000]).unwrap();
+    assert!(deserializer.from_slice::<Vec<Vec<u16>>>(&big).is_err());
+    assert_eq!(calls.get(), 3);
+}
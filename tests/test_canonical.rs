@@ -0,0 +1,95 @@
+use miniserde_ditto::{cbor, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Serialize)]
+struct Record {
+    zz: u8,
+    a: u8,
+    nested: HashMap<String, u8>,
+}
+
+#[test]
+fn test_struct() {
+    let mut nested = HashMap::new();
+    for key in &["bb", "c", "a"] {
+        nested.insert(key.to_string(), 0);
+    }
+    let record = Record {
+        zz: 1,
+        a: 2,
+        nested,
+    };
+    let bytes = cbor::to_vec_canonical(&record).unwrap();
+
+    let mut expected = vec![0xa3, 0x61, b'a', 0x02, 0x62, b'z', b'z', 0x01];
+    expected.extend(b"\x66nested\xa3\x61a\x00\x61c\x00\x62bb\x00");
+    assert_eq!(bytes, expected);
+}
+
+#[test]
+fn test_hash_map() {
+    let map: HashMap<i32, Vec<u8>> = (-30..30).map(|i| (i, vec![])).collect();
+    let bytes = cbor::to_vec_canonical(&map).unwrap();
+    for _ in 0..5 {
+        let map: HashMap<i32, Vec<u8>> = (-30..30).rev().map(|i| (i, vec![])).collect();
+        assert_eq!(cbor::to_vec_canonical(&map).unwrap(), bytes);
+    }
+
+    // Bytewise: positive integers sort before negative ones, and one-byte
+    // heads before longer ones.
+    let sorted: BTreeMap<i32, Vec<u8>> = map.into_iter().collect();
+    let mut keys: Vec<i32> = sorted.keys().copied().collect();
+    keys.sort_by_key(|i| cbor::to_vec(i).unwrap());
+    assert_eq!(&keys[..3], [0, 1, 2]);
+    assert_eq!(&keys[24..26], [24, 25]);
+    assert_eq!(keys[30], -1);
+    let expected: Vec<u8> = std::iter::once(0xb8)
+        .chain(std::iter::once(60))
+        .chain(keys.iter().flat_map(|i| {
+            let mut entry = cbor::to_vec(i).unwrap();
+            entry.push(0x40);
+            entry
+        }))
+        .collect();
+    assert_eq!(bytes, expected);
+}
+
+#[test]
+fn test_no_maps() {
+    let value = (vec![1_u32, 1000], ("x", Some(true)));
+    assert_eq!(
+        cbor::to_vec_canonical(&value).unwrap(),
+        cbor::to_vec(&value).unwrap(),
+    );
+}
+
+#[test]
+#[cfg(feature = "float")]
+fn test_raw_value() {
+    #[derive(Serialize)]
+    struct Forwarded {
+        payload: cbor::RawValue,
+    }
+
+    // {"b": 1, "a": [_ 24]}, with a two-byte head for 1.
+    let payload = vec![
+        0xa2, 0x61, b'b', 0x18, 0x01, 0x61, b'a', 0x9f, 0x18, 24, 0xff,
+    ];
+    let forwarded = Forwarded {
+        payload: cbor::RawValue::from_vec(payload.clone()).unwrap(),
+    };
+    let mut verbatim = b"\xa1\x67payload".to_vec();
+    verbatim.extend(&payload);
+    assert_eq!(cbor::to_vec(&forwarded).unwrap(), verbatim);
+    assert_eq!(
+        cbor::to_vec_canonical(&forwarded).unwrap(),
+        b"\xa1\x67payload\xa2\x61a\x81\x18\x18\x61b\x01",
+    );
+
+    // {NaN: 1}, which has no canonical form.
+    let forwarded = Forwarded {
+        payload: cbor::RawValue::from_vec(vec![0xa1, 0xf9, 0x7e, 0x00, 0x01]).unwrap(),
+    };
+    assert!(cbor::to_vec(&forwarded).is_ok());
+    assert!(cbor::to_vec_canonical(&forwarded).is_err());
+}
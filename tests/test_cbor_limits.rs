@@ -0,0 +1,120 @@
+use miniserde_ditto::cbor::{self, Value};
+use miniserde_ditto::de::DuplicateKeys;
+use miniserde_ditto::Deserialize;
+use std::collections::BTreeMap;
+
+#[test]
+fn test_max_depth() {
+    let deserializer = cbor::Deserializer::new().max_depth(3);
+    let bytes = cbor::to_vec(vec![vec![vec![0_u16]]]).unwrap();
+    assert!(deserializer.from_slice::<Value>(&bytes).is_ok());
+    let bytes = cbor::to_vec(vec![vec![vec![vec![0_u16]]]]).unwrap();
+    assert!(deserializer.from_slice::<Value>(&bytes).is_err());
+
+    let deep = [&[0x81; 300][..], &[0x00]].concat();
+    assert!(cbor::from_slice::<Value>(&deep).is_err());
+    let deserializer = cbor::Deserializer::new().max_depth(300);
+    assert!(deserializer.from_slice::<Value>(&deep).is_ok());
+}
+
+#[test]
+fn test_max_depth_boundary() {
+    // Up to three levels of arrays, maps or tags, each possibly empty.
+    let cases: &[(&[u8], bool)] = &[
+        (&[0x01], true),
+        (&[0x80], true),
+        (&[0x81, 0x81, 0x81, 0x01], true),
+        (&[0x81, 0x81, 0x80], true),
+        (&[0x81, 0xa1, 0x01, 0xc1, 0x01], true),
+        (&[0x81, 0x81, 0x81, 0x80], false),
+        (&[0x81, 0x81, 0x81, 0x81, 0x01], false),
+        (&[0x81, 0xa1, 0x01, 0xc1, 0x80], false),
+        (&[0x9f, 0x9f, 0x9f, 0x9f, 0xff, 0xff, 0xff, 0xff], false),
+    ];
+    let deserializer = cbor::Deserializer::new().max_depth(3);
+    for &(bytes, ok) in cases {
+        assert_eq!(
+            deserializer.from_slice::<Value>(bytes).is_ok(),
+            ok,
+            "{:x?}",
+            bytes
+        );
+
+        let mut decoder = cbor::StreamDecoder::with_deserializer(deserializer);
+        decoder.feed(bytes);
+        assert_eq!(decoder.decode::<Value>().is_ok(), ok, "{:x?}", bytes);
+    }
+
+    let deserializer = cbor::Deserializer::new().max_depth(1);
+    assert!(deserializer.from_slice::<Value>(&[0x81, 0x01]).is_ok());
+    assert!(deserializer
+        .from_slice::<Value>(&[0x81, 0x81, 0x01])
+        .is_err());
+}
+
+#[test]
+fn test_max_len() {
+    let bytes = cbor::to_vec(vec![0_u16; 10]).unwrap();
+    let deserializer = cbor::Deserializer::new().max_len(10);
+    assert_eq!(
+        deserializer.from_slice::<Vec<u16>>(&bytes).unwrap().len(),
+        10
+    );
+    let deserializer = cbor::Deserializer::new().max_len(9);
+    assert!(deserializer.from_slice::<Vec<u16>>(&bytes).is_err());
+
+    let mut map = BTreeMap::new();
+    map.insert(1_u8, 1_u8);
+    map.insert(2, 2);
+    let bytes = cbor::to_vec(&map).unwrap();
+    let deserializer = cbor::Deserializer::new().max_len(1);
+    assert!(deserializer.from_slice::<BTreeMap<u8, u8>>(&bytes).is_err());
+}
+
+#[test]
+fn test_max_bytes_len() {
+    let deserializer = cbor::Deserializer::new().max_bytes_len(4);
+
+    let bytes = cbor::to_vec("four").unwrap();
+    assert_eq!(deserializer.from_slice::<String>(&bytes).unwrap(), "four");
+    let bytes = cbor::to_vec("five!").unwrap();
+    assert!(deserializer.from_slice::<String>(&bytes).is_err());
+
+    let bytes = cbor::to_vec(&b"12345"[..]).unwrap();
+    assert!(deserializer.from_slice::<Vec<u8>>(&bytes).is_err());
+
+    // Indefinite-length byte string, in chunks of 3 bytes.
+    let chunked = [0x5f, 0x43, 1, 2, 3, 0x43, 4, 5, 6, 0xff];
+    assert!(cbor::from_slice::<Vec<u8>>(&chunked).is_ok());
+    assert!(deserializer.from_slice::<Vec<u8>>(&chunked).is_err());
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Point {
+    x: u8,
+}
+
+#[test]
+fn test_duplicate_keys() {
+    // {"x": 1, "x": 2}
+    let bytes = [0xa2, 0x61, b'x', 0x01, 0x61, b'x', 0x02];
+    let policy = |policy| cbor::Deserializer::new().duplicate_keys(policy);
+
+    let map: BTreeMap<String, u8> = cbor::from_slice(&bytes).unwrap();
+    assert_eq!(map["x"], 2);
+    let point: Point = policy(DuplicateKeys::LastWins).from_slice(&bytes).unwrap();
+    assert_eq!(point, Point { x: 2 });
+
+    let map: BTreeMap<String, u8> = policy(DuplicateKeys::FirstWins).from_slice(&bytes).unwrap();
+    assert_eq!(map["x"], 1);
+    let point: Point = policy(DuplicateKeys::FirstWins).from_slice(&bytes).unwrap();
+    assert_eq!(point, Point { x: 1 });
+
+    let strict = policy(DuplicateKeys::Error);
+    assert!(strict.from_slice::<Point>(&bytes).is_err());
+    assert!(strict.from_slice::<Value>(&bytes).is_err());
+
+    // {"x": 1, "y": {"x": 2}}
+    let nested = [0xa2, 0x61, b'x', 0x01, 0x61, b'y', 0xa1, 0x61, b'x', 0x02];
+    assert!(strict.from_slice::<Value>(&nested).is_ok());
+}
@@ -0,0 +1,156 @@
+use miniserde_ditto::cbor::{self, Decoded, StreamDecoder, Value};
+use miniserde_ditto::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Frame {
+    seq: u32,
+    payload: Vec<u16>,
+}
+
+#[test]
+fn test_frames() {
+    let frames = vec![
+        Frame {
+            seq: 1,
+            payload: vec![],
+        },
+        Frame {
+            seq: 2,
+            payload: vec![1, 300, 7_000],
+        },
+    ];
+    let mut bytes = vec![];
+    let mut lens = vec![];
+    for frame in &frames {
+        let frame = cbor::to_vec(frame).unwrap();
+        lens.push(frame.len());
+        bytes.extend(frame);
+    }
+
+    let (decoded, decoded_lens): (Vec<_>, Vec<_>) = cbor::from_slice_multi::<Frame>(&bytes)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+        .into_iter()
+        .unzip();
+    assert_eq!(decoded, frames);
+    assert_eq!(decoded_lens, lens);
+}
+
+#[test]
+fn test_empty() {
+    assert_eq!(cbor::from_slice_multi::<Value>(&[]).count(), 0);
+}
+
+#[test]
+fn test_truncated() {
+    // 1, then a 2-long array cut short.
+    let mut items = cbor::from_slice_multi::<Value>(&[0x01, 0x82, 0x01]);
+    assert!(items.next().unwrap().is_ok());
+    assert_eq!(items.byte_offset(), 1);
+    assert!(items.next().unwrap().is_err());
+    assert!(items.next().is_none());
+}
+
+#[test]
+fn test_deserializer_options() {
+    let mut bytes = cbor::to_vec(vec![1_u16]).unwrap();
+    bytes.extend(cbor::to_vec(vec![1_u16, 2]).unwrap());
+    let deserializer = cbor::Deserializer::new().max_len(1);
+    let mut items = deserializer.from_slice_multi::<Vec<u16>>(&bytes);
+    assert_eq!(items.next().unwrap().unwrap(), (vec![1], 2));
+    assert!(items.next().unwrap().is_err());
+}
+
+#[test]
+fn test_decoder_byte_by_byte() {
+    let frames = vec![
+        Frame {
+            seq: 1,
+            payload: vec![1, 2],
+        },
+        Frame {
+            seq: 2,
+            payload: vec![7_000; 40],
+        },
+    ];
+    let mut bytes = vec![];
+    for frame in &frames {
+        bytes.extend(cbor::to_vec(frame).unwrap());
+    }
+
+    let mut decoder = StreamDecoder::new();
+    let mut decoded = vec![];
+    for &byte in &bytes {
+        decoder.feed(&[byte]);
+        match decoder.decode::<Frame>().unwrap() {
+            Decoded::Item(frame) => decoded.push(frame),
+            Decoded::NeedMoreData(n) => assert!(n > 0),
+        }
+    }
+    assert_eq!(decoded, frames);
+    assert_eq!(decoder.buffered(), 0);
+    assert_eq!(decoder.decode::<Frame>().unwrap(), Decoded::NeedMoreData(1));
+}
+
+#[test]
+fn test_decoder_resumes_within_headers() {
+    // [_ {"k": 256}, 1(1), h'aa'], then 7.
+    let bytes = [
+        0x9f, 0xa1, 0x61, b'k', 0x19, 0x01, 0x00, 0xc1, 0x1a, 0, 0, 0, 1, 0x41, 0xaa, 0xff, 0x07,
+    ];
+    let mut decoder = StreamDecoder::new();
+    let mut needed = vec![];
+    for &byte in &bytes[..15] {
+        decoder.feed(&[byte]);
+        match decoder.decode::<Value>().unwrap() {
+            Decoded::NeedMoreData(n) => needed.push(n),
+            Decoded::Item(_) => panic!("decoded an incomplete item"),
+        }
+    }
+    // Cut within the arguments of `256` and of the tagged `1`, then within
+    // the byte string.
+    assert_eq!(needed, [1, 1, 1, 1, 2, 1, 1, 1, 4, 3, 2, 1, 1, 1, 1]);
+    decoder.feed(&bytes[15..]);
+    let expected = cbor::from_slice::<Value>(&bytes[..16]).unwrap();
+    assert_eq!(decoder.decode::<Value>().unwrap(), Decoded::Item(expected));
+    assert_eq!(decoder.decode::<u8>().unwrap(), Decoded::Item(7));
+}
+
+#[test]
+fn test_decoder_need_more_data() {
+    let mut decoder = StreamDecoder::new();
+    // A byte string of 0x0100 bytes, of which 2 are there.
+    decoder.feed(&[0x59, 0x01]);
+    assert_eq!(decoder.decode::<Value>().unwrap(), Decoded::NeedMoreData(1));
+    decoder.feed(&[0x00, 0xaa, 0xbb]);
+    assert_eq!(
+        decoder.decode::<Value>().unwrap(),
+        Decoded::NeedMoreData(254)
+    );
+}
+
+#[test]
+fn test_decoder_skips_mismatched_items() {
+    let mut decoder = StreamDecoder::new();
+    decoder.feed(&cbor::to_vec("one").unwrap());
+    decoder.feed(&cbor::to_vec(2_u8).unwrap());
+    assert!(decoder.decode::<u8>().is_err());
+    assert_eq!(decoder.decode::<u8>().unwrap(), Decoded::Item(2));
+}
+
+#[test]
+fn test_decoder_limits() {
+    let deserializer = cbor::Deserializer::new().max_bytes_len(16).max_len(4);
+    let mut decoder = StreamDecoder::with_deserializer(deserializer);
+    // Rejected from the header alone.
+    decoder.feed(&[0x5a, 0xff, 0xff, 0xff, 0xff]);
+    assert!(decoder.decode::<Value>().is_err());
+
+    let mut decoder = StreamDecoder::with_deserializer(deserializer);
+    decoder.feed(&[0x85]);
+    assert!(decoder.decode::<Value>().is_err());
+
+    let mut decoder = StreamDecoder::with_deserializer(cbor::Deserializer::new().max_depth(2));
+    decoder.feed(&[0x81, 0x81, 0x81]);
+    assert!(decoder.decode::<Value>().is_err());
+}
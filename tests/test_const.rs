@@ -0,0 +1,110 @@
+use ::miniserde_ditto::{cbor, json, Serialize};
+
+#[derive(Serialize)]
+struct Preamble {
+    magic: &'static str,
+    version: u64,
+    offset: i64,
+    flags: Vec<bool>,
+    extra: Option<u8>,
+}
+
+const MAGIC: &str = "sync\n\"1\"\u{1}";
+const VERSION: u64 = 70_000;
+const OFFSET: i64 = -300;
+
+const JSON: json::ConstSerializer = json::ConstSerializer::new()
+    .begin_object()
+    .key("magic")
+    .str(MAGIC)
+    .key("version")
+    .uint(VERSION)
+    .key("offset")
+    .int(OFFSET)
+    .key("flags")
+    .begin_array()
+    .bool(true)
+    .bool(false)
+    .end_array()
+    .key("extra")
+    .null()
+    .end_object();
+const JSON_BYTES: [u8; JSON.len()] = JSON.finish();
+
+const CBOR: cbor::ConstSerializer = cbor::ConstSerializer::new()
+    .map(5)
+    .str("magic")
+    .str(MAGIC)
+    .str("version")
+    .uint(VERSION)
+    .str("offset")
+    .int(OFFSET)
+    .str("flags")
+    .array(2)
+    .bool(true)
+    .bool(false)
+    .str("extra")
+    .null();
+const CBOR_BYTES: [u8; CBOR.len()] = CBOR.finish();
+
+fn preamble() -> Preamble {
+    Preamble {
+        magic: MAGIC,
+        version: VERSION,
+        offset: OFFSET,
+        flags: vec![true, false],
+        extra: None,
+    }
+}
+
+#[test]
+fn test_json() {
+    let expected = json::to_string(&preamble()).unwrap();
+    assert_eq!(std::str::from_utf8(&JSON_BYTES).unwrap(), expected);
+}
+
+#[test]
+fn test_cbor() {
+    assert_eq!(CBOR_BYTES[..], cbor::to_vec(preamble()).unwrap()[..]);
+}
+
+#[test]
+fn test_ints() {
+    let ints = [0, 23, 24, 255, 256, 65535, 65536, 1 << 32, i64::MAX];
+    for i in ints.iter().flat_map(|&i| vec![i, -1 - i]) {
+        let bytes = cbor::ConstSerializer::new().int(i);
+        assert_eq!(bytes.as_bytes(), &cbor::to_vec(i).unwrap()[..], "{}", i);
+        let j = json::ConstSerializer::new().int(i);
+        assert_eq!(j.as_bytes(), json::to_string(&i).unwrap().as_bytes());
+    }
+    let bytes = cbor::ConstSerializer::new().uint(u64::MAX);
+    assert_eq!(bytes.as_bytes(), &cbor::to_vec(u64::MAX).unwrap()[..]);
+}
+
+#[test]
+fn test_nested() {
+    const J: json::ConstSerializer = json::ConstSerializer::new()
+        .begin_array()
+        .begin_object()
+        .end_object()
+        .begin_array()
+        .end_array()
+        .begin_object()
+        .key("a")
+        .begin_array()
+        .uint(1)
+        .end_array()
+        .key("b")
+        .str("")
+        .end_object()
+        .end_array();
+    let j = std::str::from_utf8(J.as_bytes()).unwrap();
+    assert_eq!(j, r#"[{},[],{"a":[1],"b":""}]"#);
+    assert_eq!(J.finish::<{ J.len() }>(), *j.as_bytes());
+}
+
+#[test]
+#[should_panic]
+fn test_wrong_len() {
+    let _ = json::ConstSerializer::new().null().finish::<3>();
+}
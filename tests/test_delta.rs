@@ -0,0 +1,199 @@
+use miniserde_ditto::delta::Diff;
+use miniserde_ditto::{cbor, json, Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+struct Document {
+    id: u64,
+    title: String,
+    tags: Vec<String>,
+    counters: BTreeMap<String, u32>,
+    owner: Option<Owner>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+struct Owner {
+    name: String,
+    age: u8,
+}
+
+fn document() -> Document {
+    let mut counters = BTreeMap::new();
+    counters.insert("likes".to_owned(), 1);
+    counters.insert("views".to_owned(), 10);
+    Document {
+        id: 7,
+        title: "hello".to_owned(),
+        tags: vec!["a".to_owned()],
+        counters,
+        owner: Some(Owner {
+            name: "ann".to_owned(),
+            age: 30,
+        }),
+    }
+}
+
+fn round_trip(old: &Document, new: &Document) -> String {
+    let delta = json::to_string(&old.diff(new).unwrap()).unwrap();
+    let mut patched = old.clone();
+    patched
+        .apply_patch(&json::from_str::<json::Value>(&delta).unwrap())
+        .unwrap();
+    assert_eq!(patched, *new);
+
+    let delta_cbor = cbor::to_vec(old.diff(new).unwrap()).unwrap();
+    let mut patched = old.clone();
+    patched
+        .apply_patch(&cbor::from_slice::<cbor::Value>(&delta_cbor).unwrap())
+        .unwrap();
+    assert_eq!(patched, *new);
+
+    delta
+}
+
+#[test]
+fn test_no_changes() {
+    let old = document();
+    assert!(old.diff(&old.clone()).is_none());
+}
+
+#[test]
+fn test_nested_fields() {
+    let old = document();
+    let mut new = old.clone();
+    new.owner.as_mut().unwrap().age = 31;
+    new.counters.insert("views".to_owned(), 11);
+    assert_eq!(
+        round_trip(&old, &new),
+        r#"{"counters":{"views":11},"owner":{"age":31}}"#,
+    );
+}
+
+#[test]
+fn test_sequences_replaced() {
+    let old = document();
+    let mut new = old.clone();
+    new.tags.push("b".to_owned());
+    assert_eq!(round_trip(&old, &new), r#"{"tags":["a","b"]}"#);
+}
+
+#[test]
+fn test_added_and_removed() {
+    let old = document();
+    let mut new = old.clone();
+    new.counters.remove("likes");
+    new.counters.insert("shares".to_owned(), 2);
+    new.owner = None;
+    assert_eq!(
+        round_trip(&old, &new),
+        r#"{"counters":{"shares":2,"likes":null},"owner":null}"#,
+    );
+
+    assert_eq!(
+        round_trip(&new, &old),
+        r#"{"counters":{"likes":1,"shares":null},"owner":{"name":"ann","age":30}}"#,
+    );
+}
+
+#[test]
+fn test_invalid_patch() {
+    let mut doc = document();
+    let patch: json::Value = json::from_str(r#"{"id": "seven"}"#).unwrap();
+    assert!(doc.apply_patch(&patch).is_err());
+    assert_eq!(doc, document());
+}
+
+#[test]
+fn test_scalars() {
+    assert!(1.diff(&1).is_none());
+    let delta = 1.diff(&2).unwrap();
+    assert_eq!(json::to_string(&delta).unwrap(), "2");
+    let mut n = 1;
+    n.apply_patch(&delta).unwrap();
+    assert_eq!(n, 2);
+}
+
+#[test]
+fn test_patch_locked_raw_value() {
+    #[derive(Serialize, Deserialize)]
+    struct Holder {
+        n: u8,
+        raw: json::RawValue,
+    }
+
+    let mut holder = Holder {
+        n: 1,
+        raw: json::RawValue::from_string("[]".to_owned()).unwrap(),
+    };
+    // Viewing the `Mutex` locks it, so it must be viewed once at a time.
+    let mut patch = BTreeMap::new();
+    patch.insert("raw", Mutex::new(5_u8));
+    holder.apply_patch(&patch).unwrap();
+    assert_eq!((holder.n, holder.raw.get()), (1, "5"));
+}
+
+#[test]
+fn test_merge_from_str() {
+    let mut doc = document();
+    json::merge_from_str(&mut doc, r#"{"title": "new", "owner": {"age": 31}}"#).unwrap();
+    let mut expected = document();
+    expected.title = "new".to_owned();
+    expected.owner.as_mut().unwrap().age = 31;
+    assert_eq!(doc, expected);
+
+    json::merge_from_str(&mut doc, r#"{"owner": null}"#).unwrap();
+    assert_eq!(doc.owner, None);
+
+    assert!(json::merge_from_str(&mut doc, r#"{"id": null}"#).is_err());
+    assert!(json::merge_from_str(&mut doc, r#"{"id": 1"#).is_err());
+    assert_eq!(doc.id, 7);
+}
+
+#[test]
+fn test_merge_from_slice() {
+    let mut doc = document();
+    let mut patch = BTreeMap::new();
+    patch.insert("tags", vec!["x", "y"]);
+    cbor::merge_from_slice(&mut doc, &cbor::to_vec(&patch).unwrap()).unwrap();
+    assert_eq!(doc.tags, ["x", "y"]);
+    assert_eq!(doc.title, "hello");
+}
+
+#[test]
+fn test_merge_without_serialize() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Config {
+        name: String,
+        limits: Option<Limits>,
+        extra: BTreeMap<String, u32>,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Limits {
+        min: u32,
+        max: u32,
+    }
+
+    let mut extra = BTreeMap::new();
+    extra.insert("a".to_owned(), 1);
+    let mut config = Config {
+        name: "c".to_owned(),
+        limits: Some(Limits { min: 1, max: 2 }),
+        extra,
+    };
+    json::merge_from_str(
+        &mut config,
+        r#"{"limits": {"max": 3}, "extra": {"b": 2}, "unknown": 0}"#,
+    )
+    .unwrap();
+    assert_eq!(config.name, "c");
+    assert_eq!(config.limits, Some(Limits { min: 1, max: 3 }));
+    assert_eq!(config.extra.keys().collect::<Vec<_>>(), ["b"]);
+
+    // Nothing to merge into: the struct must then be complete.
+    config.limits = None;
+    assert!(json::merge_from_str(&mut config, r#"{"limits": {"max": 4}}"#).is_err());
+    json::merge_from_str(&mut config, r#"{"limits": {"min": 0, "max": 4}}"#).unwrap();
+    assert_eq!(config.limits, Some(Limits { min: 0, max: 4 }));
+}
@@ -1,4 +1,5 @@
 use ::miniserde_ditto::{json, Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(PartialEq, Debug, Serialize, Deserialize)]
 enum Tag {
@@ -54,6 +55,845 @@ fn test_ser() {
     assert_eq!(actual, expected);
 }
 
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Sparse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    a: Option<u32>,
+    b: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    c: Vec<u32>,
+}
+
+#[test]
+fn test_skip_serializing_if() {
+    let sparse = Sparse {
+        a: None,
+        b: None,
+        c: vec![],
+    };
+    let j = json::to_string(&sparse).unwrap();
+    assert_eq!(j, r#"{"b":null}"#);
+
+    let sparse = Sparse {
+        a: Some(1),
+        b: None,
+        c: vec![2],
+    };
+    let j = json::to_string(&sparse).unwrap();
+    assert_eq!(j, r#"{"a":1,"b":null,"c":[2]}"#);
+    assert_eq!(json::from_str::<Sparse>(&j).unwrap(), sparse);
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[serde(expect_fields("id", "displayName"))]
+struct Pinned {
+    id: u32,
+    #[serde(rename = "displayName")]
+    name: String,
+    #[serde(skip)]
+    cache: Option<String>,
+}
+
+#[test]
+fn test_expect_fields() {
+    let pinned = Pinned {
+        id: 1,
+        name: "x".to_owned(),
+        cache: None,
+    };
+    let j = json::to_string(&pinned).unwrap();
+    assert_eq!(j, r#"{"id":1,"displayName":"x"}"#);
+    assert_eq!(json::from_str::<Pinned>(&j).unwrap(), pinned);
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[serde(expect_fields(serialize("id", "name"), deserialize("id", "title")))]
+struct PinnedPerSide {
+    id: u32,
+    #[serde(rename(serialize = "name", deserialize = "title"))]
+    name: String,
+}
+
+#[test]
+fn test_expect_fields_per_side() {
+    let pinned = PinnedPerSide {
+        id: 1,
+        name: "x".to_owned(),
+    };
+    assert_eq!(json::to_string(&pinned).unwrap(), r#"{"id":1,"name":"x"}"#);
+    let j = r#"{"id":1,"title":"x"}"#;
+    assert_eq!(json::from_str::<PinnedPerSide>(j).unwrap(), pinned);
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[serde(sort_keys)]
+struct Sorted {
+    zeta: u8,
+    #[serde(rename = "Beta")]
+    beta: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alpha: Option<u8>,
+    #[serde(rename = "gamma")]
+    a_gamma: u8,
+}
+
+#[test]
+fn test_sort_keys() {
+    let sorted = Sorted {
+        zeta: 1,
+        beta: 2,
+        alpha: Some(3),
+        a_gamma: 4,
+    };
+    let j = json::to_string(&sorted).unwrap();
+    assert_eq!(j, r#"{"Beta":2,"alpha":3,"gamma":4,"zeta":1}"#);
+    assert_eq!(json::from_str::<Sorted>(&j).unwrap(), sorted);
+
+    let sorted = Sorted {
+        alpha: None,
+        ..sorted
+    };
+    let j = json::to_string(&sorted).unwrap();
+    assert_eq!(j, r#"{"Beta":2,"gamma":4,"zeta":1}"#);
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Migrated {
+    #[serde(rename(serialize = "userId", deserialize = "user_id"))]
+    user: u32,
+    #[serde(rename = "displayName", alias = "name", alias = "nick")]
+    display_name: String,
+    #[serde(rename(serialize = "v"))]
+    version: u8,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+enum Phase {
+    #[serde(rename(serialize = "on", deserialize = "ON"))]
+    On,
+    Off,
+}
+
+#[test]
+fn test_rename_per_side_and_alias() {
+    let migrated = Migrated {
+        user: 7,
+        display_name: "x".to_owned(),
+        version: 2,
+    };
+    let j = json::to_string(&migrated).unwrap();
+    assert_eq!(j, r#"{"userId":7,"displayName":"x","v":2}"#);
+    assert!(json::from_str::<Migrated>(&j).is_err());
+
+    for j in &[
+        r#"{"user_id":7,"displayName":"x","version":2}"#,
+        r#"{"user_id":7,"name":"x","version":2}"#,
+        r#"{"user_id":7,"nick":"x","version":2}"#,
+    ] {
+        assert_eq!(json::from_str::<Migrated>(j).unwrap(), migrated);
+    }
+
+    assert_eq!(json::to_string(&Phase::On).unwrap(), r#""on""#);
+    assert_eq!(json::from_str::<Phase>(r#""ON""#).unwrap(), Phase::On);
+    assert!(json::from_str::<Phase>(r#""on""#).is_err());
+    assert_eq!(json::from_str::<Phase>(r#""Off""#).unwrap(), Phase::Off);
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Metadata {
+    id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace: Option<String>,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Envelope {
+    #[serde(flatten)]
+    meta: Metadata,
+    kind: String,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Loose {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nick: Option<String>,
+    name: String,
+    #[serde(flatten)]
+    extra: BTreeMap<String, u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+}
+
+#[test]
+fn test_flatten() {
+    let mut envelope = Envelope {
+        meta: Metadata { id: 1, trace: None },
+        kind: "ping".to_owned(),
+    };
+    let j = json::to_string(&envelope).unwrap();
+    assert_eq!(j, r#"{"id":1,"kind":"ping"}"#);
+    assert_eq!(json::from_str::<Envelope>(&j).unwrap(), envelope);
+
+    envelope.meta.trace = Some("t".to_owned());
+    let j = json::to_string(&envelope).unwrap();
+    assert_eq!(j, r#"{"id":1,"trace":"t","kind":"ping"}"#);
+    let j = r#"{"kind":"ping","trace":"t","id":1}"#;
+    assert_eq!(json::from_str::<Envelope>(j).unwrap(), envelope);
+    assert!(json::from_str::<Envelope>(r#"{"kind":"ping"}"#).is_err());
+
+    let mut loose = Loose {
+        nick: None,
+        name: "a".to_owned(),
+        extra: BTreeMap::new(),
+        note: Some("n".to_owned()),
+    };
+    loose.extra.insert("x".to_owned(), 1);
+    loose.extra.insert("y".to_owned(), 2);
+    let j = json::to_string(&loose).unwrap();
+    assert_eq!(j, r#"{"name":"a","x":1,"y":2,"note":"n"}"#);
+    assert_eq!(json::from_str::<Loose>(&j).unwrap(), loose);
+
+    #[derive(Serialize)]
+    struct NotAMap {
+        id: u32,
+        #[serde(flatten)]
+        list: Vec<u32>,
+    }
+    let not_a_map = NotAMap {
+        id: 1,
+        list: vec![2],
+    };
+    assert!(json::to_string(&not_a_map).is_err());
+    assert!(miniserde_ditto::cbor::to_vec(&not_a_map).is_err());
+}
+
+#[derive(PartialEq, Debug, Deserialize)]
+#[serde(case_insensitive)]
+struct CaseInsensitive {
+    #[serde(rename = "userId")]
+    user_id: u32,
+    name: String,
+}
+
+#[test]
+fn test_case_insensitive() {
+    let expected = CaseInsensitive {
+        user_id: 7,
+        name: "x".to_owned(),
+    };
+    let pascal = r#"{"UserId": 7, "Name": "x"}"#;
+    assert_eq!(json::from_str::<CaseInsensitive>(pascal).unwrap(), expected);
+    let camel = r#"{"userId": 7, "name": "x"}"#;
+    assert_eq!(json::from_str::<CaseInsensitive>(camel).unwrap(), expected);
+    let mixed = r#"{"userid": 7, "NAME": "x", "other": 1}"#;
+    assert_eq!(json::from_str::<CaseInsensitive>(mixed).unwrap(), expected);
+}
+
+#[derive(PartialEq, Debug, Deserialize)]
+struct Trimmed {
+    #[serde(trim)]
+    name: String,
+    #[serde(trim)]
+    nickname: Option<String>,
+    raw: String,
+}
+
+#[test]
+fn test_trim() {
+    let j = r#"{"name": " \tAda\n", "nickname": "  ", "raw": " x "}"#;
+    let expected = Trimmed {
+        name: "Ada".to_owned(),
+        nickname: Some(String::new()),
+        raw: " x ".to_owned(),
+    };
+    assert_eq!(json::from_str::<Trimmed>(j).unwrap(), expected);
+}
+
+#[cfg(feature = "unicode-normalization")]
+#[test]
+fn test_trim_nfc() {
+    #[derive(PartialEq, Debug, Deserialize)]
+    struct Normalized {
+        #[serde(trim = "nfc")]
+        name: String,
+    }
+
+    let j = "{\"name\": \" Rene\u{301} \"}";
+    let expected = Normalized {
+        name: "Ren\u{e9}".to_owned(),
+    };
+    assert_eq!(json::from_str::<Normalized>(j).unwrap(), expected);
+}
+
+#[cfg(feature = "float")]
+#[derive(PartialEq, Debug, Deserialize)]
+struct Bounded {
+    #[serde(range(min = 0, max = 100))]
+    percent: u8,
+    #[serde(range(min = -1.0))]
+    ratio: f64,
+    #[serde(range(min = 0.5))]
+    scale: Option<f64>,
+}
+
+#[test]
+#[cfg(feature = "float")]
+fn test_range() {
+    let j = r#"{"percent": 100, "ratio": -1.0}"#;
+    let expected = Bounded {
+        percent: 100,
+        ratio: -1.0,
+        scale: None,
+    };
+    assert_eq!(json::from_str::<Bounded>(j).unwrap(), expected);
+
+    let j = r#"{"percent": 100, "ratio": -1.0, "scale": 0.5}"#;
+    let expected = Bounded {
+        scale: Some(0.5),
+        ..expected
+    };
+    assert_eq!(json::from_str::<Bounded>(j).unwrap(), expected);
+
+    let j = r#"{"percent": 101, "ratio": 0.5}"#;
+    assert!(json::from_str::<Bounded>(j).is_err());
+    let j = r#"{"percent": 50, "ratio": -1.5}"#;
+    assert!(json::from_str::<Bounded>(j).is_err());
+    let j = r#"{"percent": 50, "ratio": 0.0, "scale": 0.25}"#;
+    assert!(json::from_str::<Bounded>(j).is_err());
+}
+
+#[derive(PartialEq, Debug, Deserialize)]
+struct LengthBounded {
+    #[serde(len(min = 1, max = 3))]
+    name: String,
+    #[serde(len(min = 1))]
+    tags: Vec<u8>,
+    #[serde(len(max = 1))]
+    extra: Option<BTreeMap<String, u8>>,
+}
+
+#[test]
+fn test_len() {
+    let j = r#"{"name": "été", "tags": [1, 2], "extra": {"a": 1}}"#;
+    let actual = json::from_str::<LengthBounded>(j).unwrap();
+    assert_eq!(actual.name, "été");
+    assert!(json::from_str::<LengthBounded>(r#"{"name": "abc", "tags": [1]}"#).is_ok());
+
+    assert!(json::from_str::<LengthBounded>(r#"{"name": "", "tags": [1]}"#).is_err());
+    assert!(json::from_str::<LengthBounded>(r#"{"name": "abcd", "tags": [1]}"#).is_err());
+    assert!(json::from_str::<LengthBounded>(r#"{"name": "a", "tags": []}"#).is_err());
+    let j = r#"{"name": "a", "tags": [1], "extra": {"a": 1, "b": 2}}"#;
+    assert!(json::from_str::<LengthBounded>(j).is_err());
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+enum Level {
+    Low,
+    #[serde(id = 5)]
+    High,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[serde(variant_ints)]
+enum Color {
+    Red = 1,
+    #[serde(rename = "verde")]
+    Green,
+    #[serde(id = 10)]
+    Blue,
+}
+
+#[test]
+fn test_variant_ints() {
+    assert_eq!(json::from_str::<Level>("5").unwrap(), Level::High);
+    assert_eq!(json::from_str::<Level>(r#""High""#).unwrap(), Level::High);
+    assert!(json::from_str::<Level>("0").is_err());
+    assert_eq!(json::to_string(&Level::High).unwrap(), r#""High""#);
+
+    assert_eq!(json::to_string(&Color::Red).unwrap(), "1");
+    assert_eq!(json::to_string(&Color::Green).unwrap(), "2");
+    assert_eq!(json::to_string(&Color::Blue).unwrap(), "10");
+    assert_eq!(json::from_str::<Color>("2").unwrap(), Color::Green);
+    assert_eq!(json::from_str::<Color>("10").unwrap(), Color::Blue);
+    assert_eq!(json::from_str::<Color>(r#""verde""#).unwrap(), Color::Green);
+    assert!(json::from_str::<Color>("3").is_err());
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[serde(repr = "u8")]
+enum Opcode {
+    Nop,
+    Load = 0x10,
+    Store,
+    #[serde(other)]
+    Invalid = 0xff,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(repr)]
+#[repr(i8)]
+enum Sign {
+    Minus = -1,
+    Zero,
+    Plus,
+}
+
+#[test]
+fn test_repr() {
+    use miniserde_ditto::cbor;
+
+    assert_eq!(json::to_string(&Opcode::Nop).unwrap(), "0");
+    assert_eq!(json::to_string(&Opcode::Store).unwrap(), "17");
+    assert_eq!(cbor::to_vec(&Opcode::Load).unwrap(), [0x10]);
+    assert_eq!(cbor::to_vec(&Opcode::Invalid).unwrap(), [0x18, 0xff]);
+    assert_eq!(cbor::from_slice::<Opcode>(&[0x11]).unwrap(), Opcode::Store);
+    assert_eq!(json::from_str::<Opcode>("16").unwrap(), Opcode::Load);
+    assert_eq!(json::from_str::<Opcode>("3").unwrap(), Opcode::Invalid);
+    // Out of the `u8` range, rather than truncated to `Load`.
+    assert_eq!(json::from_str::<Opcode>("272").unwrap(), Opcode::Invalid);
+    assert!(json::from_str::<Opcode>(r#""Load""#).is_err());
+
+    let signs = [Sign::Minus, Sign::Zero, Sign::Plus];
+    assert_eq!(json::to_string(&signs).unwrap(), "[-1,0,1]");
+    assert_eq!(cbor::to_vec(Sign::Minus).unwrap(), [0x20]);
+    assert_eq!(json::from_str::<Sign>("-1").unwrap(), Sign::Minus);
+    assert!(json::from_str::<Sign>("2").is_err());
+    assert!(json::from_str::<Sign>("255").is_err());
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+enum Shape {
+    Circle,
+    Square,
+    #[serde(other)]
+    Unknown,
+}
+
+#[test]
+fn test_other_variant() {
+    assert_eq!(
+        json::from_str::<Shape>(r#""Square""#).unwrap(),
+        Shape::Square
+    );
+    assert_eq!(
+        json::from_str::<Shape>(r#""Hexagon""#).unwrap(),
+        Shape::Unknown
+    );
+    assert_eq!(
+        json::from_str::<Shape>(r#""Unknown""#).unwrap(),
+        Shape::Unknown
+    );
+    assert!(json::from_str::<Shape>("1").is_err());
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+enum Credential {
+    Token {
+        user: String,
+        #[serde(skip_serializing)]
+        secret: String,
+    },
+    Anonymous {
+        #[serde(skip)]
+        session: u64,
+    },
+}
+
+#[test]
+fn test_skip_serializing_variant_fields() {
+    let token = Credential::Token {
+        user: "me".to_owned(),
+        secret: "hunter2".to_owned(),
+    };
+    let j = json::to_string(&token).unwrap();
+    assert_eq!(j, r#"{"Token":{"user":"me"}}"#);
+
+    let anonymous = Credential::Anonymous { session: 7 };
+    let j = json::to_string(&anonymous).unwrap();
+    assert_eq!(j, r#"{"Anonymous":{}}"#);
+    assert_eq!(
+        json::from_str::<Credential>(&j).unwrap(),
+        Credential::Anonymous { session: 0 }
+    );
+}
+
+#[test]
+fn test_field_less_structs() {
+    use miniserde_ditto::cbor;
+
+    #[derive(PartialEq, Debug, Serialize, Deserialize)]
+    struct Unit;
+
+    #[derive(PartialEq, Debug, Serialize, Deserialize)]
+    struct Braced {}
+
+    #[derive(PartialEq, Debug, Serialize, Deserialize)]
+    struct Parens();
+
+    assert_eq!(json::to_string(&Unit).unwrap(), "null");
+    assert_eq!(json::to_string(&Braced {}).unwrap(), "{}");
+    assert_eq!(json::to_string(&Parens()).unwrap(), "[]");
+    assert_eq!(cbor::to_vec(&Unit).unwrap(), [0xf6]);
+    assert_eq!(cbor::to_vec(&Braced {}).unwrap(), [0xa0]);
+    assert_eq!(cbor::to_vec(Parens()).unwrap(), [0x80]);
+
+    for j in &["null", "{}", "[]"] {
+        assert_eq!(json::from_str::<Unit>(j).unwrap(), Unit);
+        assert_eq!(json::from_str::<Braced>(j).unwrap(), Braced {});
+        assert_eq!(json::from_str::<Parens>(j).unwrap(), Parens());
+    }
+    for bytes in &[[0xf6], [0xa0], [0x80]] {
+        assert_eq!(cbor::from_slice::<Unit>(bytes).unwrap(), Unit);
+        assert_eq!(cbor::from_slice::<Braced>(bytes).unwrap(), Braced {});
+        assert_eq!(cbor::from_slice::<Parens>(bytes).unwrap(), Parens());
+    }
+
+    assert!(json::from_str::<Unit>("[1]").is_err());
+    assert!(json::from_str::<Braced>(r#"{"a":1}"#).is_ok());
+    assert!(json::from_str::<Parens>("0").is_err());
+}
+
+#[test]
+fn test_variant_name() {
+    #[derive(Serialize)]
+    #[serde(variant_name)]
+    enum Event<T> {
+        Started,
+        #[serde(rename = "progress")]
+        Progress(T),
+        #[serde(rename(serialize = "done", deserialize = "finished"))]
+        Done {
+            code: i32,
+        },
+    }
+
+    assert_eq!(Event::<u8>::Started.variant_name(), "Started");
+    assert_eq!(Event::Progress(50).variant_name(), "progress");
+    assert_eq!(Event::<u8>::Done { code: 0 }.variant_name(), "done");
+}
+
+#[test]
+fn test_phantom_data() {
+    use std::marker::PhantomData;
+
+    // Not `Serialize` nor `Deserialize`, which `Id<User>` does not need.
+    #[derive(Debug, PartialEq)]
+    struct User;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Id<T> {
+        value: u64,
+        kind: PhantomData<T>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Ref<T> {
+        Id { value: u64, kind: PhantomData<T> },
+        Name(String),
+    }
+
+    let id = Id::<User> {
+        value: 7,
+        kind: PhantomData,
+    };
+    assert_eq!(json::to_string(&id).unwrap(), r#"{"value":7,"kind":null}"#);
+    assert_eq!(
+        json::from_str::<Id<User>>(r#"{"value":7,"kind":null}"#).unwrap(),
+        id
+    );
+    assert_eq!(json::from_str::<Id<User>>(r#"{"value":7}"#).unwrap(), id);
+
+    let id = Ref::<User>::Id {
+        value: 7,
+        kind: PhantomData,
+    };
+    let j = json::to_string(&id).unwrap();
+    assert_eq!(j, r#"{"Id":{"value":7,"kind":null}}"#);
+    assert_eq!(json::from_str::<Ref<User>>(&j).unwrap(), id);
+
+    // Bounded like any other type, whatever its name.
+    #[allow(non_camel_case_types)]
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct __Helper_Wrapper<T>(T);
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Wrapped<T> {
+        inner: __Helper_Wrapper<T>,
+    }
+
+    assert_eq!(
+        json::from_str::<Wrapped<u8>>(r#"{"inner":1}"#).unwrap(),
+        Wrapped {
+            inner: __Helper_Wrapper(1),
+        }
+    );
+}
+
+#[test]
+fn test_bound() {
+    use std::rc::Rc;
+
+    trait Backend {
+        type Id;
+    }
+
+    // Neither `Serialize` nor `Deserialize`.
+    #[derive(Debug, PartialEq)]
+    struct Memory;
+
+    impl Backend for Memory {
+        type Id = u32;
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(bound = "B::Id: Serialize + Deserialize")]
+    struct Handle<B: Backend> {
+        id: B::Id,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(bound(serialize = "T: Serialize", deserialize = "T: Deserialize"))]
+    enum Shared<T> {
+        One(Rc<T>),
+        Many(Vec<Rc<T>>),
+    }
+
+    let handle = Handle::<Memory> { id: 3 };
+    let j = json::to_string(&handle).unwrap();
+    assert_eq!(j, r#"{"id":3}"#);
+    assert_eq!(json::from_str::<Handle<Memory>>(&j).unwrap(), handle);
+
+    let shared = Shared::Many(vec![Rc::new(1_u8)]);
+    let j = json::to_string(&shared).unwrap();
+    assert_eq!(j, r#"{"Many":[1]}"#);
+    assert_eq!(json::from_str::<Shared<u8>>(&j).unwrap(), shared);
+
+    // `Vec<Tree>: Serialize` would be a cyclic bound.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(into = "Vec<Tree>", from = "Vec<Tree>", bound = "")]
+    struct Tree(Vec<Tree>);
+
+    impl From<Tree> for Vec<Tree> {
+        fn from(tree: Tree) -> Self {
+            tree.0
+        }
+    }
+
+    impl From<Vec<Tree>> for Tree {
+        fn from(children: Vec<Tree>) -> Self {
+            Tree(children)
+        }
+    }
+
+    let tree = Tree(vec![Tree(vec![]), Tree(vec![Tree(vec![])])]);
+    let j = json::to_string(&tree).unwrap();
+    assert_eq!(j, "[[],[[]]]");
+    assert_eq!(json::from_str::<Tree>(&j).unwrap(), tree);
+}
+
+#[test]
+fn test_bits() {
+    use miniserde_ditto::cbor;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(bits(mask = "Self::ALL"))]
+    struct Permissions(u8);
+
+    impl Permissions {
+        const READ: u8 = 1;
+        const WRITE: u8 = 2;
+        const ALL: u8 = Self::READ | Self::WRITE;
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(bits(mask = "0b11", unknown = "truncate"))]
+    struct Mode {
+        bits: u16,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(bits)]
+    struct Raw(u32);
+
+    assert_eq!(json::to_string(&Permissions(3)).unwrap(), "3");
+    assert_eq!(json::from_str::<Permissions>("2").unwrap(), Permissions(2));
+    assert!(json::from_str::<Permissions>("4").is_err());
+    assert!(json::from_str::<Permissions>("256").is_err());
+    assert!(json::from_str::<Permissions>("[3]").is_err());
+    let bytes = cbor::to_vec(Permissions(1)).unwrap();
+    assert_eq!(
+        cbor::from_slice::<Permissions>(&bytes).unwrap(),
+        Permissions(1)
+    );
+
+    assert_eq!(json::to_string(&Mode { bits: 1 }).unwrap(), "1");
+    assert_eq!(json::from_str::<Mode>("7").unwrap(), Mode { bits: 3 });
+
+    assert_eq!(json::from_str::<Raw>("4294967295").unwrap(), Raw(u32::MAX));
+}
+
+#[test]
+fn test_transparent() {
+    use miniserde_ditto::cbor;
+    use std::marker::PhantomData;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(transparent)]
+    struct UserId {
+        id: u64,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(transparent)]
+    struct Tagged<T> {
+        #[serde(skip)]
+        kind: PhantomData<T>,
+        names: Vec<String>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(transparent)]
+    struct Wrapper(Example);
+
+    assert_eq!(json::to_string(&UserId { id: 7 }).unwrap(), "7");
+    assert_eq!(json::from_str::<UserId>("7").unwrap(), UserId { id: 7 });
+    assert!(json::from_str::<UserId>(r#"{"id":7}"#).is_err());
+
+    let tagged = Tagged::<UserId> {
+        kind: PhantomData,
+        names: vec!["a".to_owned()],
+    };
+    let j = json::to_string(&tagged).unwrap();
+    assert_eq!(j, r#"["a"]"#);
+    assert_eq!(json::from_str::<Tagged<UserId>>(&j).unwrap(), tagged);
+
+    let wrapper = Wrapper(Example {
+        x: "x".to_owned(),
+        t1: Tag::A,
+        t2: Tag::B,
+        n: Nested {
+            y: Some(vec!["y".to_owned()]),
+            z: None,
+        },
+    });
+    let j = json::to_string(&wrapper).unwrap();
+    assert_eq!(j, json::to_string(&wrapper.0).unwrap());
+    assert_eq!(json::from_str::<Wrapper>(&j).unwrap(), wrapper);
+    let bytes = cbor::to_vec(&wrapper).unwrap();
+    assert_eq!(cbor::from_slice::<Wrapper>(&bytes).unwrap(), wrapper);
+}
+
+#[test]
+fn test_from_into() {
+    use miniserde_ditto::cbor;
+
+    // Serialized as `{"r": 255, "g": 0, "b": 0}` by older versions.
+    #[derive(Serialize, Deserialize)]
+    struct Rgb {
+        r: u8,
+        g: u8,
+        b: u8,
+    }
+
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    #[serde(from = "Rgb", into = "String")]
+    struct Color(u32);
+
+    impl From<Rgb> for Color {
+        fn from(Rgb { r, g, b }: Rgb) -> Self {
+            Color(u32::from_be_bytes([0, r, g, b]))
+        }
+    }
+
+    impl From<Color> for String {
+        fn from(Color(rgb): Color) -> Self {
+            format!("#{:06x}", rgb)
+        }
+    }
+
+    #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+    #[serde(from = "u8", into = "u8")]
+    enum Level {
+        Low,
+        High,
+    }
+
+    impl From<u8> for Level {
+        fn from(level: u8) -> Self {
+            if level < 128 {
+                Level::Low
+            } else {
+                Level::High
+            }
+        }
+    }
+
+    impl From<Level> for u8 {
+        fn from(level: Level) -> Self {
+            match level {
+                Level::Low => 0,
+                Level::High => 255,
+            }
+        }
+    }
+
+    let j = r#"{"r":255,"g":0,"b":16}"#;
+    let color = json::from_str::<Color>(j).unwrap();
+    assert_eq!(color, Color(0xff0010));
+    assert_eq!(json::to_string(&color).unwrap(), r##""#ff0010""##);
+    let bytes = cbor::to_vec(&Rgb { r: 1, g: 2, b: 3 }).unwrap();
+    assert_eq!(cbor::from_slice::<Color>(&bytes).unwrap(), Color(0x010203));
+
+    assert_eq!(json::from_str::<Level>("200").unwrap(), Level::High);
+    assert_eq!(
+        json::to_string(&[Level::Low, Level::High]).unwrap(),
+        "[0,255]"
+    );
+    assert!(json::from_str::<Level>(r#""High""#).is_err());
+}
+
+#[test]
+fn test_try_from() {
+    use std::convert::TryFrom;
+    use std::fmt;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(try_from = "String")]
+    struct Email(String);
+
+    struct NotAnEmail;
+
+    impl fmt::Display for NotAnEmail {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("missing `@`")
+        }
+    }
+
+    impl TryFrom<String> for Email {
+        type Error = NotAnEmail;
+
+        fn try_from(s: String) -> Result<Self, NotAnEmail> {
+            if s.contains('@') {
+                Ok(Email(s))
+            } else {
+                Err(NotAnEmail)
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Contact {
+        name: String,
+        emails: Vec<Email>,
+    }
+
+    let j = r#"{"name":"a","emails":["a@b.c"]}"#;
+    let contact = json::from_str::<Contact>(j).unwrap();
+    assert_eq!(contact.emails, [Email("a@b.c".to_owned())]);
+    assert_eq!(json::to_string(&contact).unwrap(), j);
+    assert!(json::from_str::<Contact>(r#"{"name":"a","emails":["a@b.c","x"]}"#).is_err());
+}
+
 mod complex_enums {
     use super::*;
 
@@ -123,6 +963,82 @@ mod complex_enums {
         );
     }
 
+    #[test]
+    fn other_variant() {
+        #[derive(Debug, PartialEq, Deserialize, Serialize)]
+        enum External {
+            Known {
+                id: i32,
+            },
+            #[serde(other)]
+            Unknown,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize, Serialize)]
+        #[serde(tag = "kind")]
+        enum Internal {
+            Known {
+                id: i32,
+            },
+            #[serde(other)]
+            Unknown,
+        }
+
+        #[cfg(not(miri))]
+        {
+            let j = r#"{"Known":{"id":1}}"#;
+            assert_eq!(
+                json::from_str::<External>(j).unwrap(),
+                External::Known { id: 1 }
+            );
+            let j = r#"{"Newer":{"id":1,"extra":[{}]}}"#;
+            assert_eq!(json::from_str::<External>(j).unwrap(), External::Unknown);
+
+            let j = r#"{"kind":"Known","id":1}"#;
+            assert_eq!(
+                json::from_str::<Internal>(j).unwrap(),
+                Internal::Known { id: 1 }
+            );
+            let j = r#"{"kind":"Newer","id":1,"extra":[{}]}"#;
+            assert_eq!(json::from_str::<Internal>(j).unwrap(), Internal::Unknown);
+        }
+    }
+
+    #[test]
+    fn externally_tagged_multiple_keys() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        enum Newtypes {
+            A(u8),
+            B(u8),
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        enum Mixed {
+            A {
+                id: u8,
+            },
+            B(u8),
+            #[serde(other)]
+            Unknown,
+        }
+
+        assert_eq!(
+            json::from_str::<Newtypes>(r#"{"B":2}"#).unwrap(),
+            Newtypes::B(2)
+        );
+        assert!(json::from_str::<Newtypes>(r#"{"A":1,"B":2}"#).is_err());
+        assert!(json::from_str::<Newtypes>(r#"{"B":2,"A":1}"#).is_err());
+        assert!(json::from_str::<Newtypes>(r#"{"A":1,"A":2}"#).is_err());
+
+        assert!(json::from_str::<Mixed>(r#"{"A":{"id":1},"B":2}"#).is_err());
+        assert!(json::from_str::<Mixed>(r#"{"B":2,"C":3}"#).is_err());
+        assert!(json::from_str::<Mixed>(r#"{"C":3,"D":4}"#).is_err());
+        assert_eq!(
+            json::from_str::<Mixed>(r#"{"C":3}"#).unwrap(),
+            Mixed::Unknown
+        );
+    }
+
     #[test]
     fn untagged() {
         #[derive(Debug, /* Deserialize, */ Serialize)]
@@ -0,0 +1,121 @@
+use miniserde_ditto::envelope::{Envelope, Registry};
+use miniserde_ditto::{cbor, decoder, json, Deserialize, Serialize, TypeTag};
+
+#[derive(Serialize, Deserialize, TypeTag, Debug, PartialEq)]
+struct Ping {
+    seq: u32,
+}
+
+#[derive(Serialize, Deserialize, TypeTag, Debug, PartialEq)]
+#[serde(type_tag = "chat.message")]
+struct ChatMessage {
+    from: String,
+    text: String,
+}
+
+#[derive(Serialize, Deserialize, TypeTag, Debug, PartialEq)]
+enum Status {
+    Online,
+    Away,
+}
+
+fn registry() -> Registry {
+    Registry::new()
+        .register::<Ping>()
+        .register::<ChatMessage>()
+        .register::<Status>()
+}
+
+#[test]
+fn test_type_tag() {
+    assert_eq!(Ping::TYPE_TAG, "Ping");
+    assert_eq!(ChatMessage::TYPE_TAG, "chat.message");
+    assert_eq!(Status::TYPE_TAG, "Status");
+}
+
+#[test]
+fn test_json() {
+    let message = ChatMessage {
+        from: "alice".to_owned(),
+        text: "hi".to_owned(),
+    };
+    let j = json::to_string(&Envelope(message)).unwrap();
+    assert_eq!(
+        j,
+        r#"{"type":"chat.message","payload":{"from":"alice","text":"hi"}}"#,
+    );
+
+    let Envelope(back) = json::from_str::<Envelope<ChatMessage>>(&j).unwrap();
+    assert_eq!(back.from, "alice");
+    assert!(json::from_str::<Envelope<Ping>>(&j).is_err());
+
+    let message = json::decode_envelope(&j, &registry()).unwrap();
+    assert_eq!(message.type_tag(), "chat.message");
+    assert!(message.is::<ChatMessage>());
+    let message = message.downcast::<Ping>().unwrap_err();
+    assert_eq!(message.downcast::<ChatMessage>().unwrap().text, "hi");
+}
+
+#[test]
+fn test_cbor() {
+    let registry = registry();
+    let bytes = cbor::to_vec(Envelope(Ping { seq: 7 })).unwrap();
+    let message = cbor::decode_envelope(&bytes, &registry).unwrap();
+    assert_eq!(message.downcast_ref::<Ping>(), Some(&Ping { seq: 7 }));
+
+    let bytes = cbor::to_vec_canonical(Envelope(Status::Away)).unwrap();
+    let message = cbor::decode_envelope(&bytes, &registry).unwrap();
+    assert_eq!(message.downcast_ref::<Status>(), Some(&Status::Away));
+}
+
+#[test]
+fn test_decode_errors() {
+    let registry = registry();
+    for j in &[
+        r#"{"type":"Pong","payload":{"seq":1}}"#,
+        r#"{"payload":{"seq":1},"type":"Ping"}"#,
+        r#"{"type":"Ping","payload":{"seq":1},"payload":{"seq":2}}"#,
+        r#"{"type":"Ping","payload":"1"}"#,
+        r#"{"type":"Ping"}"#,
+        r#"["Ping",{"seq":1}]"#,
+    ] {
+        assert!(json::decode_envelope(j, &registry).is_err(), "{}", j);
+    }
+
+    let j = r#"{"version":2,"type":"Ping","payload":{"seq":1}}"#;
+    let message = json::decode_envelope(j, &registry).unwrap();
+    assert_eq!(message.downcast_ref::<Ping>(), Some(&Ping { seq: 1 }));
+}
+
+#[test]
+#[should_panic(expected = "duplicate type tag")]
+fn test_duplicate_type_tag() {
+    #[derive(Deserialize, TypeTag)]
+    #[serde(type_tag = "Ping")]
+    struct OtherPing {}
+
+    let _ = registry().register::<OtherPing>();
+}
+
+#[test]
+fn test_decoders() {
+    let registry = Registry::new()
+        .register_decoder(decoder!(cbor, Ping))
+        .register_decoder(decoder!(json, ChatMessage));
+
+    let bytes = cbor::to_vec(Ping { seq: 3 }).unwrap();
+    let message = registry.decode("Ping", &bytes).unwrap();
+    assert_eq!(message.downcast_ref::<Ping>(), Some(&Ping { seq: 3 }));
+    assert!(registry.decode("Status", &bytes).is_err());
+
+    let j = br#"{"from":"bob","text":"yo"}"#;
+    let message = registry.decode("chat.message", j).unwrap();
+    assert_eq!(message.type_tag(), "chat.message");
+    assert_eq!(message.downcast::<ChatMessage>().unwrap().from, "bob");
+    assert!(registry.decode("chat.message", &bytes).is_err());
+    assert!(registry.decode("chat.message", b"\"\xff\"").is_err());
+
+    // Envelopes only use the types registered with `register`.
+    let j = json::to_string(&Envelope(Ping { seq: 3 })).unwrap();
+    assert!(json::decode_envelope(&j, &registry).is_err());
+}
@@ -0,0 +1,49 @@
+use miniserde_ditto::{cbor, json, Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Key {
+    #[serde(with = "miniserde_ditto::hex")]
+    id: [u8; 8],
+    #[serde(with = "miniserde_ditto::hex")]
+    public: Vec<u8>,
+    #[serde(with = "miniserde_ditto::hex")]
+    hash: Box<[u8]>,
+}
+
+#[test]
+fn test_round_trip() {
+    let key = Key {
+        id: [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef],
+        public: vec![],
+        hash: vec![0xff, 0x00].into_boxed_slice(),
+    };
+    let j = json::to_string(&key).unwrap();
+    assert_eq!(j, r#"{"id":"0123456789abcdef","public":"","hash":"ff00"}"#);
+    assert_eq!(json::from_str::<Key>(&j).unwrap(), key);
+
+    // A text string in CBOR as well.
+    let bytes = cbor::to_vec(&key).unwrap();
+    let value: cbor::Value = cbor::from_slice(&bytes).unwrap();
+    assert_eq!(
+        value.get("hash"),
+        Some(&cbor::Value::Text("ff00".to_owned()))
+    );
+    assert_eq!(cbor::from_slice::<Key>(&bytes).unwrap(), key);
+}
+
+#[test]
+fn test_invalid() {
+    for invalid in &[
+        // Odd number of digits.
+        r#"{"id":"0123456789abcde","public":"","hash":""}"#,
+        // Not a digit.
+        r#"{"id":"0123456789abcdeg","public":"","hash":""}"#,
+        r#"{"id":"0123456789abcdef","public":"0x","hash":""}"#,
+        // Wrong length for the array.
+        r#"{"id":"0123456789abcdef00","public":"","hash":""}"#,
+        // Not a string.
+        r#"{"id":"0123456789abcdef","public":[1],"hash":""}"#,
+    ] {
+        assert!(json::from_str::<Key>(invalid).is_err(), "{}", invalid);
+    }
+}
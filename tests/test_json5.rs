@@ -0,0 +1,65 @@
+#![cfg(all(feature = "json5", feature = "float"))]
+
+use miniserde_ditto::json::Value;
+use miniserde_ditto::{json, json5, Deserialize, Serialize};
+use std::f64;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Config {
+    name: String,
+    #[serde(rename = "max-size")]
+    max_size: u64,
+    ratios: Vec<f64>,
+}
+
+#[test]
+fn test_relaxed_syntax() {
+    let j = r#"
+        /* A block comment,
+           over several lines. */
+        {
+            name: 'it\'s "quoted"', // A line comment.
+            'max-size': 0xFF,
+            ratios: [-Infinity, 1.5, -0x10,],
+        }
+    "#;
+    let config: Config = json5::from_str(j).unwrap();
+    assert_eq!(config.name, r#"it's "quoted""#);
+    assert_eq!(config.max_size, 255);
+    assert_eq!(config.ratios, [f64::NEG_INFINITY, 1.5, -16.0]);
+
+    let value: Value = json5::from_str("[NaN, Infinity]").unwrap();
+    assert_eq!(json::to_string(&value).unwrap(), "[null,null]");
+}
+
+#[test]
+fn test_json_stays_strict() {
+    assert!(json::from_str::<Vec<u8>>("[1,]").is_err());
+    assert!(json::from_str::<Vec<u8>>("[0x1]").is_err());
+    assert!(json::from_str::<Vec<u8>>("// comment\n[1]").is_err());
+    assert!(json::from_str::<String>("'single'").is_err());
+}
+
+#[test]
+fn test_invalid_json5() {
+    assert!(json5::from_str::<Vec<u8>>("[,]").is_err());
+    assert!(json5::from_str::<Vec<u8>>("[1,,]").is_err());
+    assert!(json5::from_str::<Vec<u8>>("[1] /* unterminated").is_err());
+    assert!(json5::from_str::<Vec<u8>>("[0x]").is_err());
+}
+
+#[test]
+fn test_pretty_output() {
+    let config = Config {
+        name: "web".to_owned(),
+        max_size: 10,
+        ratios: vec![f64::NAN],
+    };
+    let j = json5::to_string(&config).unwrap();
+    let expected = "{\n  name: \"web\",\n  \"max-size\": 10,\n  ratios: [\n    NaN,\n  ],\n}";
+    assert_eq!(j, expected);
+    assert_eq!(json5::to_string(&Vec::<u8>::new()).unwrap(), "[]");
+
+    let parsed: Config = json5::from_str(&j).unwrap();
+    assert_eq!(parsed.max_size, 10);
+}
@@ -0,0 +1,66 @@
+use miniserde_ditto::de::DuplicateKeys;
+use miniserde_ditto::json::{self, TrailingData, Value};
+use miniserde_ditto::Deserialize;
+use std::collections::BTreeMap;
+
+#[test]
+fn test_max_depth() {
+    let deserializer = json::Deserializer::new().max_depth(3);
+    assert!(deserializer.from_str::<Value>("[[[1]]]").is_ok());
+    assert!(deserializer.from_str::<Value>(r#"[{"a":[]}]"#).is_ok());
+    assert!(deserializer.from_str::<Value>("[[[[1]]]]").is_err());
+    assert!(deserializer
+        .from_str::<Value>(r#"{"a":{"b":{"c":{}}}}"#)
+        .is_err());
+
+    let deep = format!("{}{}", "[".repeat(10_000), "]".repeat(10_000));
+    assert!(json::from_str::<Value>(&deep).is_ok());
+    let deserializer = json::Deserializer::new().max_depth(9_999);
+    assert!(deserializer.from_str::<Value>(&deep).is_err());
+}
+
+#[test]
+fn test_trailing() {
+    let policy = |trailing| json::Deserializer::new().trailing(trailing);
+
+    assert_eq!(json::from_str::<u8>("1 \n").unwrap(), 1);
+    assert!(json::from_str::<u8>("1 2").is_err());
+
+    let forbidden = policy(TrailingData::Forbidden);
+    assert_eq!(forbidden.from_str::<u8>(" 1").unwrap(), 1);
+    assert!(forbidden.from_str::<u8>("1\n").is_err());
+    assert!(forbidden.from_str::<Vec<u8>>("[1] ").is_err());
+
+    let ignored = policy(TrailingData::Ignored);
+    assert_eq!(ignored.from_str::<Vec<u8>>("[1] [2]").unwrap(), [1]);
+    assert_eq!(ignored.from_str::<u8>("1,").unwrap(), 1);
+    assert!(ignored.from_str::<Vec<u8>>("[1").is_err());
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Point {
+    x: u8,
+}
+
+#[test]
+fn test_duplicate_keys() {
+    let j = r#"{"x":1,"x":2}"#;
+    let policy = |policy| json::Deserializer::new().duplicate_keys(policy);
+
+    let map: BTreeMap<String, u8> = json::from_str(j).unwrap();
+    assert_eq!(map["x"], 2);
+    assert_eq!(json::from_str::<Point>(j).unwrap(), Point { x: 2 });
+
+    let first_wins = policy(DuplicateKeys::FirstWins);
+    let map: BTreeMap<String, u8> = first_wins.from_str(j).unwrap();
+    assert_eq!(map["x"], 1);
+    assert_eq!(first_wins.from_str::<Point>(j).unwrap(), Point { x: 1 });
+    let j = r#"{"x":1,"x":{"skipped":[true]}}"#;
+    assert_eq!(first_wins.from_str::<Point>(j).unwrap(), Point { x: 1 });
+
+    let strict = policy(DuplicateKeys::Error);
+    assert!(strict.from_str::<Point>(j).is_err());
+    assert!(strict.from_str::<Value>(j).is_err());
+    let nested = r#"{"x":1,"y":{"x":2}}"#;
+    assert!(strict.from_str::<Value>(nested).is_ok());
+}
@@ -0,0 +1,268 @@
+use miniserde_ditto::json::{self, Operation, Patch, Value};
+
+#[test]
+fn test_pointer() {
+    // The examples of RFC 6901.
+    let doc: Value = json::from_str(
+        r#"{
+            "foo": ["bar", "baz"],
+            "": 0,
+            "a/b": 1,
+            "c%d": 2,
+            "e^f": 3,
+            "g|h": 4,
+            "i\\j": 5,
+            "k\"l": 6,
+            " ": 7,
+            "m~n": 8
+        }"#,
+    )
+    .unwrap();
+    let cases = [
+        (
+            "",
+            r#"{"":0," ":7,"a/b":1,"c%d":2,"e^f":3,"foo":["bar","baz"],"g|h":4,"i\\j":5,"k\"l":6,"m~n":8}"#,
+        ),
+        ("/foo", r#"["bar","baz"]"#),
+        ("/foo/0", r#""bar""#),
+        ("/", "0"),
+        ("/a~1b", "1"),
+        ("/c%d", "2"),
+        ("/e^f", "3"),
+        ("/g|h", "4"),
+        ("/i\\j", "5"),
+        ("/k\"l", "6"),
+        ("/ ", "7"),
+        ("/m~0n", "8"),
+    ];
+    for (pointer, expected) in &cases {
+        if pointer.is_empty() && cfg!(feature = "preserve_order") {
+            continue;
+        }
+        let value = doc.pointer(pointer).unwrap();
+        assert_eq!(json::to_string(value).unwrap(), *expected, "{}", pointer);
+    }
+
+    for pointer in &[
+        "foo", "/foo/2", "/foo/01", "/foo/-", "/foo/+1", "/m~2n", "/m~", "/a/b",
+    ] {
+        assert!(doc.pointer(pointer).is_none(), "{}", pointer);
+    }
+
+    let mut doc = doc;
+    *doc.pointer_mut("/foo/1").unwrap() = Value::Null;
+    assert_eq!(
+        json::to_string(doc.pointer("/foo").unwrap()).unwrap(),
+        r#"["bar",null]"#
+    );
+}
+
+fn apply(doc: &str, patch: &str) -> Option<String> {
+    let mut doc: Value = json::from_str(doc).unwrap();
+    let patch: Patch = json::from_str(patch).unwrap();
+    json::patch(&mut doc, &patch).ok()?;
+    Some(json::to_string(&doc).unwrap())
+}
+
+#[test]
+fn test_patch() {
+    // The examples of RFC 6902, with keys in an order that both kinds of
+    // `Object` keep.
+    let cases = [
+        (
+            r#"{"foo":"bar"}"#,
+            r#"[{"op":"add","path":"/qux","value":"baz"}]"#,
+            Some(r#"{"foo":"bar","qux":"baz"}"#),
+        ),
+        (
+            r#"{"foo":["bar","baz"]}"#,
+            r#"[{"op":"add","path":"/foo/1","value":"qux"}]"#,
+            Some(r#"{"foo":["bar","qux","baz"]}"#),
+        ),
+        (
+            r#"{"baz":"qux","foo":"bar"}"#,
+            r#"[{"op":"remove","path":"/baz"}]"#,
+            Some(r#"{"foo":"bar"}"#),
+        ),
+        (
+            r#"{"foo":["bar","qux","baz"]}"#,
+            r#"[{"op":"remove","path":"/foo/1"}]"#,
+            Some(r#"{"foo":["bar","baz"]}"#),
+        ),
+        (
+            r#"{"baz":"qux","foo":"bar"}"#,
+            r#"[{"op":"replace","path":"/baz","value":"boo"}]"#,
+            Some(r#"{"baz":"boo","foo":"bar"}"#),
+        ),
+        (
+            r#"{"foo":{"bar":"baz","waldo":"fred"},"qux":{"corge":"grault"}}"#,
+            r#"[{"op":"move","from":"/foo/waldo","path":"/qux/thud"}]"#,
+            Some(r#"{"foo":{"bar":"baz"},"qux":{"corge":"grault","thud":"fred"}}"#),
+        ),
+        (
+            r#"{"foo":["all","grass","cows","eat"]}"#,
+            r#"[{"op":"move","from":"/foo/1","path":"/foo/3"}]"#,
+            Some(r#"{"foo":["all","cows","eat","grass"]}"#),
+        ),
+        (
+            r#"{"baz":"qux","foo":["a",2,"c"]}"#,
+            r#"[{"op":"test","path":"/baz","value":"qux"},{"op":"test","path":"/foo/1","value":2}]"#,
+            Some(r#"{"baz":"qux","foo":["a",2,"c"]}"#),
+        ),
+        (
+            r#"{"baz":"qux"}"#,
+            r#"[{"op":"test","path":"/baz","value":"bar"}]"#,
+            None,
+        ),
+        (
+            r#"{"foo":"bar"}"#,
+            r#"[{"op":"add","path":"/kid","value":{"grandkid":{}}}]"#,
+            Some(r#"{"foo":"bar","kid":{"grandkid":{}}}"#),
+        ),
+        (
+            r#"{"foo":"bar"}"#,
+            r#"[{"op":"add","path":"/qux","value":"baz","xyz":123}]"#,
+            Some(r#"{"foo":"bar","qux":"baz"}"#),
+        ),
+        (
+            r#"{"foo":"bar"}"#,
+            r#"[{"op":"add","path":"/baz/bat","value":"qux"}]"#,
+            None,
+        ),
+        (
+            r#"{"/":9,"~1":10}"#,
+            r#"[{"op":"test","path":"/~01","value":10}]"#,
+            Some(r#"{"/":9,"~1":10}"#),
+        ),
+        (
+            r#"{"/":9,"~1":10}"#,
+            r#"[{"op":"test","path":"/~01","value":"10"}]"#,
+            None,
+        ),
+        (
+            r#"{"foo":["bar"]}"#,
+            r#"[{"op":"add","path":"/foo/-","value":["abc","def"]}]"#,
+            Some(r#"{"foo":["bar",["abc","def"]]}"#),
+        ),
+    ];
+    for (doc, patch, expected) in &cases {
+        assert_eq!(apply(doc, patch).as_deref(), *expected, "{}", patch);
+    }
+}
+
+#[test]
+fn test_patch_edge_cases() {
+    // The whole document.
+    let patch = r#"[{"op":"add","path":"","value":[1]},{"op":"copy","from":"/0","path":"/-"}]"#;
+    assert_eq!(apply("{}", patch).as_deref(), Some("[1,1]"));
+    assert_eq!(apply("{}", r#"[{"op":"remove","path":""}]"#), None);
+
+    // Numbers compare by value, objects regardless of order.
+    let patch = r#"[{"op":"test","path":"","value":{"b":[1.0,{}],"a":null}}]"#;
+    assert!(apply(r#"{"a":null,"b":[1,{}]}"#, patch).is_some());
+    assert!(apply(r#"{"a":null,"b":[1,{},2]}"#, patch).is_none());
+    assert!(apply(r#"{"a":null}"#, patch).is_none());
+
+    // Out of bounds, or into itself.
+    assert_eq!(
+        apply("[1]", r#"[{"op":"add","path":"/2","value":0}]"#),
+        None
+    );
+    assert_eq!(apply("[1]", r#"[{"op":"remove","path":"/1"}]"#), None);
+    assert_eq!(
+        apply("[1]", r#"[{"op":"replace","path":"/1","value":0}]"#),
+        None
+    );
+    let patch = r#"[{"op":"move","from":"/a","path":"/a/b"}]"#;
+    assert_eq!(apply(r#"{"a":{}}"#, patch), None);
+    let patch = r#"[{"op":"move","from":"/a","path":"/ab"}]"#;
+    assert_eq!(apply(r#"{"a":1}"#, patch).as_deref(), Some(r#"{"ab":1}"#));
+    let patch = r#"[{"op":"move","from":"/a","path":"/a"}]"#;
+    assert_eq!(apply(r#"{"a":1}"#, patch).as_deref(), Some(r#"{"a":1}"#));
+
+    // Malformed operations.
+    for patch in &[
+        r#"[{"op":"add","path":"/a"}]"#,
+        r#"[{"op":"move","path":"/a"}]"#,
+        r#"[{"op":"frobnicate","path":"/a"}]"#,
+        r#"[{"path":"/a"}]"#,
+        r#"[{"op":"remove","path":1}]"#,
+        r#"{"op":"remove","path":"/a"}"#,
+    ] {
+        assert!(json::from_str::<Patch>(patch).is_err(), "{}", patch);
+    }
+
+    // The `op` may come last, and is written first.
+    let patch: Patch = json::from_str(r#"[{"path":"/a","from":"/b","op":"copy"}]"#).unwrap();
+    assert!(
+        matches!(&patch.0[..], [Operation::Copy { from, path }] if from == "/b" && path == "/a")
+    );
+    assert_eq!(
+        json::to_string(&patch).unwrap(),
+        r#"[{"op":"copy","from":"/b","path":"/a"}]"#,
+    );
+}
+
+#[test]
+fn test_diff() {
+    let docs = [
+        "null",
+        "1",
+        "1.0",
+        r#""a""#,
+        "[]",
+        "{}",
+        "[1,2,3]",
+        "[0,1,2,3]",
+        "[1,3]",
+        "[1,2,3,4]",
+        "[3,2,1]",
+        r#"[{"a":1},{"a":2}]"#,
+        r#"[{"a":1},{"a":3},{"a":2}]"#,
+        r#"{"a":1,"b":{"c":[1,{"d":null}]}}"#,
+        r#"{"a":2,"b":{"c":[1,{"d":true}],"e":"f"}}"#,
+        r#"{"b":{"c":[{"d":true}]},"a/b~c":[]}"#,
+        r#"{"":{"":[""]}}"#,
+    ];
+    for old in &docs {
+        for new in &docs {
+            let old: Value = json::from_str(old).unwrap();
+            let new: Value = json::from_str(new).unwrap();
+            let patch = json::diff(&old, &new);
+
+            // Through the wire and back.
+            let patch: Patch = json::from_str(&json::to_string(&patch).unwrap()).unwrap();
+            let mut doc = old.clone();
+            json::patch(&mut doc, &patch).unwrap();
+            let test = Patch(vec![Operation::Test {
+                path: String::new(),
+                value: new.clone(),
+            }]);
+            assert!(json::patch(&mut doc, &test).is_ok(), "{:?}", patch);
+        }
+    }
+
+    let diff = |old: &str, new: &str| {
+        let old: Value = json::from_str(old).unwrap();
+        let new: Value = json::from_str(new).unwrap();
+        json::to_string(&json::diff(&old, &new)).unwrap()
+    };
+    assert_eq!(diff(r#"{"a":[1]}"#, r#"{"a":[1]}"#), "[]");
+    assert_eq!(diff("[1]", "[1.0]"), "[]");
+    assert_eq!(
+        diff("[1,2,3,4]", "[1,4]"),
+        r#"[{"op":"remove","path":"/2"},{"op":"remove","path":"/1"}]"#,
+    );
+    assert_eq!(
+        diff("[1,2]", "[0,1,2]"),
+        r#"[{"op":"add","path":"/0","value":0}]"#,
+    );
+    assert_eq!(
+        diff(r#"{"a":{"b":1,"c":2}}"#, r#"{"a":{"b":1,"c":3,"d/e":4}}"#),
+        r#"[{"op":"add","path":"/a/d~1e","value":4},{"op":"replace","path":"/a/c","value":3}]"#,
+    );
+    assert_eq!(
+        diff(r#"{"a":[]}"#, r#"{"a":{}}"#),
+        r#"[{"op":"replace","path":"/a","value":{}}]"#,
+    );
+}
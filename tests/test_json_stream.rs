@@ -0,0 +1,44 @@
+use miniserde_ditto::json::{self, Value};
+
+#[test]
+fn test_lines() {
+    let j = "{\"a\":1}\n{\"a\":2}\r\n\n  {\"a\":3}\n";
+    let values = json::from_str_multi::<Value>(j)
+        .map(|value| json::to_string(&value.unwrap()).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(values, [r#"{"a":1}"#, r#"{"a":2}"#, r#"{"a":3}"#]);
+}
+
+#[test]
+fn test_concatenated() {
+    let values = json::from_str_multi::<Vec<u16>>("[1][2, 3] [] ")
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(values, [vec![1], vec![2, 3], vec![]]);
+
+    let numbers = json::from_str_multi::<u32>("1 23\t456")
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(numbers, [1, 23, 456]);
+
+    assert_eq!(json::from_str_multi::<u32>("").count(), 0);
+    assert_eq!(json::from_str_multi::<u32>(" \n ").count(), 0);
+}
+
+#[test]
+fn test_error_stops() {
+    let mut values = json::from_str_multi::<u32>("1\n\"two\"\n3");
+    assert_eq!(values.next().unwrap().unwrap(), 1);
+    assert_eq!(values.byte_offset(), 1);
+    assert!(values.next().unwrap().is_err());
+    assert!(values.next().is_none());
+}
+
+#[test]
+fn test_deserializer_options() {
+    let deserializer = json::Deserializer::new().max_depth(1);
+    let mut values = deserializer.from_str_multi::<Value>("[1]\n[[2]]\n");
+    assert!(values.next().unwrap().is_ok());
+    assert_eq!(values.byte_offset(), 3);
+    assert!(values.next().unwrap().is_err());
+}
@@ -0,0 +1,105 @@
+use miniserde_ditto::json;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::{fmt, io, str};
+
+#[test]
+fn test_to_writer() {
+    let value: Vec<String> = (0..4000).map(|i| format!("item {}", i)).collect();
+    let j = json::to_string(&value).unwrap();
+    assert!(j.len() > 3 * 8 * 1024);
+
+    let mut out = vec![];
+    json::to_writer(&mut out, &value).unwrap();
+    assert_eq!(out, j.as_bytes());
+    assert_eq!(json::to_vec(&value).unwrap(), j.as_bytes());
+
+    // The lengths reported are those of the whole output so far.
+    let reports = RefCell::new(vec![]);
+    let progress = |n| reports.borrow_mut().push(n);
+    let mut out = vec![];
+    json::Serializer::new()
+        .progress(&progress)
+        .to_writer(&mut out, &vec![value, vec![]])
+        .unwrap();
+    assert_eq!(reports.take(), [j.len() + 1, j.len() + 4, j.len() + 5]);
+}
+
+struct Failing {
+    written: usize,
+}
+
+impl io::Write for Failing {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written + buf.len() > 10_000 {
+            return Err(io::Error::new(io::ErrorKind::Other, "disk full"));
+        }
+        self.written += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_errors() {
+    let value: Vec<u64> = (0..10_000).collect();
+    let mut out = Failing { written: 0 };
+    let error = json::to_writer(&mut out, &value).unwrap_err().unwrap();
+    assert_eq!(error.to_string(), "disk full");
+    assert!(out.written > 0);
+
+    let mut map = BTreeMap::new();
+    map.insert(vec![1], 1);
+    let mut out = vec![];
+    assert!(json::to_writer(&mut out, &map).unwrap_err().is_none());
+}
+
+/// A string of at most 16 bytes.
+#[derive(Default)]
+struct Fixed {
+    buf: [u8; 16],
+    len: usize,
+}
+
+impl fmt::Write for Fixed {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > self.buf.len() {
+            return Err(fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_to_fmt_writer() {
+    let value = ("\u{E9}\n", vec![1, -2]);
+    let mut out = Fixed::default();
+    json::to_fmt_writer(&mut out, &value).unwrap();
+    let j = str::from_utf8(&out.buf[..out.len]).unwrap();
+    assert_eq!(j, json::to_string(&value).unwrap());
+
+    let mut out = Fixed::default();
+    let error = json::to_fmt_writer(&mut out, &(value, "too long")).unwrap_err();
+    assert_eq!(error, Some(fmt::Error));
+
+    let mut map = BTreeMap::new();
+    map.insert(vec![1], 1);
+    let mut out = Fixed::default();
+    assert_eq!(json::to_fmt_writer(&mut out, &map), Err(None));
+
+    let reports = RefCell::new(vec![]);
+    let progress = |n| reports.borrow_mut().push(n);
+    let mut out = String::new();
+    json::Serializer::new()
+        .progress(&progress)
+        .to_fmt_writer(&mut out, &vec![vec![1], vec![]])
+        .unwrap();
+    assert_eq!(out, "[[1],[]]");
+    assert_eq!(reports.take(), [4, 7, 8]);
+}
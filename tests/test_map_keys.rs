@@ -0,0 +1,93 @@
+use miniserde_ditto::{json, Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+#[test]
+fn test_ser_non_string_keys() {
+    let mut ints = BTreeMap::new();
+    ints.insert(-1_i64, "a");
+    ints.insert(2, "b");
+    assert_eq!(json::to_string(&ints).unwrap(), r#"{"-1":"a","2":"b"}"#);
+
+    let mut ids = HashMap::new();
+    ids.insert(7_u32, vec![1]);
+    assert_eq!(json::to_string(&ids).unwrap(), r#"{"7":[1]}"#);
+
+    let mut bools = BTreeMap::new();
+    bools.insert(false, 0);
+    bools.insert(true, 1);
+    assert_eq!(json::to_string(&bools).unwrap(), r#"{"false":0,"true":1}"#);
+
+    let mut seqs = BTreeMap::new();
+    seqs.insert(vec![1_u32], 0);
+    assert!(json::to_string(&seqs).is_err());
+}
+
+#[test]
+#[cfg(feature = "float")]
+fn test_ser_float_keys() {
+    use miniserde_ditto::cbor::{Object, Value};
+
+    let mut object = Object::new();
+    object.insert(Value::Float(1.5), Value::Null);
+    assert_eq!(
+        json::to_string(&Value::Map(object)).unwrap(),
+        r#"{"1.5":null}"#
+    );
+
+    let mut object = Object::new();
+    object.insert(Value::Float(f64::NAN), Value::Null);
+    assert!(json::to_string(&Value::Map(object)).is_err());
+}
+
+#[test]
+fn test_de_non_string_keys() {
+    let j = r#"{"-1":"a","2":"b"}"#;
+    let ints: BTreeMap<i64, String> = json::from_str(j).unwrap();
+    assert_eq!(json::to_string(&ints).unwrap(), j);
+
+    let ids: HashMap<u64, Vec<u8>> = json::from_str(r#"{"7":[1]}"#).unwrap();
+    assert_eq!(ids[&7], [1]);
+
+    let bools: BTreeMap<bool, u8> = json::from_str(r#"{"false":0,"true":1}"#).unwrap();
+    assert_eq!(bools[&true], 1);
+
+    let names: BTreeMap<String, u8> = json::from_str(r#"{"1":1}"#).unwrap();
+    assert_eq!(names["1"], 1);
+
+    assert!(json::from_str::<BTreeMap<u8, u8>>(r#"{"256":0}"#).is_err());
+    assert!(json::from_str::<BTreeMap<u8, u8>>(r#"{"a":0}"#).is_err());
+    assert!(json::from_str::<BTreeMap<bool, u8>>(r#"{"1":0}"#).is_err());
+    for key in &["01", "+1", "-", "1.0", "1e2", " 1", "1 ", "-01", ""] {
+        let j = format!(r#"{{"{}":0}}"#, key);
+        assert!(json::from_str::<BTreeMap<i32, u8>>(&j).is_err(), "{}", key);
+    }
+    let zero: BTreeMap<i32, u8> = json::from_str(r#"{"-0":1}"#).unwrap();
+    assert_eq!(zero[&0], 1);
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Inventory {
+    counts: HashMap<u32, u64>,
+}
+
+#[test]
+fn test_derive_round_trip() {
+    let mut counts = HashMap::new();
+    counts.insert(3, 40);
+    let inventory = Inventory { counts };
+    let j = json::to_string(&inventory).unwrap();
+    assert_eq!(j, r#"{"counts":{"3":40}}"#);
+    assert_eq!(json::from_str::<Inventory>(&j).unwrap(), inventory);
+}
+
+#[test]
+fn test_de_raw_keys() {
+    use miniserde_ditto::cbor;
+
+    // {[1, 2]: 3}
+    let bytes = [0xa1, 0x82, 0x01, 0x02, 0x03];
+    let map: HashMap<cbor::RawValue, u8> = cbor::from_slice(&bytes).unwrap();
+    let (key, value) = map.into_iter().next().unwrap();
+    assert_eq!(key.get(), [0x82, 0x01, 0x02]);
+    assert_eq!(value, 3);
+}
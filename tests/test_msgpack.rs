@@ -0,0 +1,136 @@
+#![cfg(feature = "msgpack")]
+
+use miniserde_ditto::{msgpack, Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Record {
+    id: u64,
+    name: String,
+    tags: Vec<String>,
+    data: Vec<u8>,
+    parent: Option<Box<Record>>,
+}
+
+#[test]
+fn test_round_trip() {
+    let record = Record {
+        id: 1 << 40,
+        name: "x".repeat(40),
+        tags: vec!["a".to_owned(); 20],
+        data: vec![0, 1, 255],
+        parent: Some(Box::new(Record {
+            id: 0,
+            name: String::new(),
+            tags: vec![],
+            data: vec![],
+            parent: None,
+        })),
+    };
+    let bytes = msgpack::to_vec(&record).unwrap();
+    assert_eq!(msgpack::from_slice::<Record>(&bytes).unwrap(), record);
+}
+
+#[test]
+fn test_integers() {
+    let cases: &[(i128, &[u8])] = &[
+        (0, &[0x00]),
+        (127, &[0x7f]),
+        (128, &[0xcc, 0x80]),
+        (256, &[0xcd, 0x01, 0x00]),
+        (65536, &[0xce, 0x00, 0x01, 0x00, 0x00]),
+        (1 << 32, &[0xcf, 0, 0, 0, 1, 0, 0, 0, 0]),
+        (-1, &[0xff]),
+        (-32, &[0xe0]),
+        (-33, &[0xd0, 0xdf]),
+        (-129, &[0xd1, 0xff, 0x7f]),
+        (-32769, &[0xd2, 0xff, 0xff, 0x7f, 0xff]),
+        (i64::MIN.into(), &[0xd3, 0x80, 0, 0, 0, 0, 0, 0, 0]),
+    ];
+    for &(i, bytes) in cases {
+        assert_eq!(msgpack::to_vec(i).unwrap(), bytes, "{}", i);
+        assert_eq!(msgpack::from_slice::<i128>(bytes).unwrap(), i);
+    }
+    assert_eq!(
+        msgpack::from_slice::<u64>(&[0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff])
+            .unwrap(),
+        u64::MAX,
+    );
+    assert!(msgpack::to_vec(i128::from(i64::MIN) - 1).is_err());
+    assert!(msgpack::to_vec(u128::from(u64::MAX) + 1).is_err());
+}
+
+#[test]
+#[cfg(feature = "float")]
+fn test_floats() {
+    assert_eq!(
+        msgpack::to_vec(1.5).unwrap(),
+        [0xca, 0x3f, 0xc0, 0x00, 0x00]
+    );
+    assert_eq!(
+        msgpack::to_vec(0.1).unwrap(),
+        [0xcb, 0x3f, 0xb9, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a],
+    );
+    assert_eq!(
+        msgpack::from_slice::<f64>(&msgpack::to_vec(0.1).unwrap()).unwrap(),
+        0.1
+    );
+    assert!(
+        msgpack::from_slice::<f64>(&msgpack::to_vec(f64::NAN).unwrap())
+            .unwrap()
+            .is_nan()
+    );
+}
+
+#[test]
+fn test_lengths() {
+    for &len in &[0_usize, 15, 16, 31, 32, 255, 256, 65536] {
+        let s = "a".repeat(len);
+        let bytes = msgpack::to_vec(&s).unwrap();
+        let head = match len {
+            0..=31 => 1,
+            32..=255 => 2,
+            256..=65535 => 3,
+            _ => 5,
+        };
+        assert_eq!(bytes.len(), head + len);
+        assert_eq!(msgpack::from_slice::<String>(&bytes).unwrap(), s);
+
+        let v = vec![true; len];
+        let bytes = msgpack::to_vec(&v).unwrap();
+        let head = match len {
+            0..=15 => 1,
+            16..=65535 => 3,
+            _ => 5,
+        };
+        assert_eq!(bytes.len(), head + len);
+        assert_eq!(msgpack::from_slice::<Vec<bool>>(&bytes).unwrap(), v);
+    }
+
+    let map = (0..20).map(|i| (i, i)).collect::<BTreeMap<u8, u8>>();
+    let bytes = msgpack::to_vec(&map).unwrap();
+    assert_eq!(bytes[..3], [0xde, 0x00, 20]);
+    assert_eq!(
+        msgpack::from_slice::<BTreeMap<u8, u8>>(&bytes).unwrap(),
+        map
+    );
+}
+
+#[test]
+fn test_errors() {
+    // Truncated input.
+    assert!(msgpack::from_slice::<u16>(&[0xcd, 0x01]).is_err());
+    assert!(msgpack::from_slice::<String>(&[0xa2, b'a']).is_err());
+    assert!(msgpack::from_slice::<Vec<u8>>(&[0x92, 0x01]).is_err());
+    // Trailing bytes.
+    assert!(msgpack::from_slice::<u8>(&[0x01, 0x02]).is_err());
+    // Invalid UTF-8.
+    assert!(msgpack::from_slice::<String>(&[0xa1, 0xff]).is_err());
+    // Never used, and extension types.
+    assert!(msgpack::from_slice::<u8>(&[0xc1]).is_err());
+    assert!(msgpack::from_slice::<u8>(&[0xd4, 0x01, 0x00]).is_err());
+    // Nested too deeply.
+    let mut bytes = vec![0x91; 1000];
+    bytes.push(0xc0);
+    assert!(msgpack::from_slice::<miniserde_ditto::de::IgnoredAny>(&bytes).is_err());
+}
@@ -15,3 +15,20 @@ fn test_round_trip_deeply_nested() {
     let j2 = json::to_string(&value).unwrap();
     assert_eq!(j, j2);
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn test_to_value_deeply_nested() {
+    let mut value = Value::Null;
+    for _ in 0..100_000 {
+        let mut array = json::Array::new();
+        array.push(value);
+        value = Value::Array(array);
+    }
+
+    let copy: Value = json::from_value(json::to_value(&value).unwrap()).unwrap();
+    assert_eq!(
+        json::to_string(&copy).unwrap(),
+        json::to_string(&value).unwrap()
+    );
+}
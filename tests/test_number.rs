@@ -1,7 +1,11 @@
 use miniserde_ditto::json;
+#[cfg(feature = "float")]
+use miniserde_ditto::ser::SignedZero;
+#[cfg(feature = "float")]
 use std::f64;
 
 #[test]
+#[cfg(feature = "float")]
 fn test_ser() {
     let cases = &[
         (1.0, "1.0"),
@@ -15,3 +19,167 @@ fn test_ser() {
         assert_eq!(actual, *expected);
     }
 }
+
+#[test]
+#[cfg(feature = "float")]
+fn test_raw_numbers() {
+    let j = r#"[1.10,-0,1e3,18446744073709551616,7]"#;
+
+    let value: json::Value = json::from_str_raw_numbers(j).unwrap();
+    assert_eq!(json::to_string(&value).unwrap(), j);
+
+    if !cfg!(feature = "arbitrary_precision") {
+        let value: json::Value = json::from_str(j).unwrap();
+        assert_eq!(
+            json::to_string(&value).unwrap(),
+            "[1.1,0,1000.0,1.8446744073709552e19,7]",
+        );
+    }
+
+    let floats: Vec<f64> = json::from_str_raw_numbers("[1.10,7]").unwrap();
+    assert_eq!(floats, [1.1, 7.0]);
+
+    let bogus = json::Value::Number(json::Number::Raw("1.2.3".to_owned()));
+    assert!(json::to_string(&bogus).is_err());
+
+    // Raw numbers are kept as written, even beyond what a float holds.
+    let j = r#"[1e400,-2.5E-400,123456789012345678901234567890123456789012]"#;
+    let value: json::Value = json::from_str_raw_numbers(j).unwrap();
+    assert_eq!(json::to_string(&value).unwrap(), j);
+    let value = json::to_value(&value).unwrap();
+    assert_eq!(json::to_string(&value).unwrap(), j);
+    assert!(json::from_str_raw_numbers::<Vec<f64>>(j).is_err());
+
+    for bogus in &["01", "1.", ".5", "1e", "+1", "-"] {
+        assert!(json::from_str_raw_numbers::<json::Value>(bogus).is_err());
+    }
+}
+
+#[test]
+#[cfg(feature = "float")]
+fn test_signed_zero() {
+    let j = json::to_string(&[-0.0, 0.0]).unwrap();
+    assert_eq!(j, "[-0.0,0.0]");
+    let floats: Vec<f64> = json::from_str(&j).unwrap();
+    assert!(floats[0].is_sign_negative());
+    assert!(floats[1].is_sign_positive());
+
+    let normalized = json::Serializer::new()
+        .signed_zero(SignedZero::Normalize)
+        .to_string(&[-0.0, 0.0])
+        .unwrap();
+    assert_eq!(normalized, "[0.0,0.0]");
+
+    // JSON integers have no negative zero.
+    let zero: f64 = json::from_str("-0").unwrap();
+    assert!(zero.is_sign_positive());
+}
+
+#[test]
+fn test_u128() {
+    let j = json::to_string(&u128::MAX).unwrap();
+    assert_eq!(j, u128::MAX.to_string());
+    let n: u128 = json::from_str_raw_numbers(&j).unwrap();
+    assert_eq!(n, u128::MAX);
+    let n: u128 = json::from_str(&j).unwrap();
+    assert_eq!(n, u128::MAX);
+
+    let j = json::to_string(&i128::MIN).unwrap();
+    let n: i128 = json::from_str_raw_numbers(&j).unwrap();
+    assert_eq!(n, i128::MIN);
+    let n: i128 = json::from_str(&j).unwrap();
+    assert_eq!(n, i128::MIN);
+
+    // Just beyond the range of both.
+    assert!(json::from_str::<u128>("340282366920938463463374607431768211456").is_err());
+    assert!(json::from_str::<i128>("-170141183460469231731687303715884105729").is_err());
+    #[cfg(feature = "float")]
+    {
+        let f: f64 = json::from_str(&u128::MAX.to_string()).unwrap();
+        assert_eq!(f, u128::MAX as f64);
+    }
+}
+
+#[test]
+#[cfg(all(feature = "float", not(feature = "arbitrary_precision")))]
+fn test_big_integer_value() {
+    // A `Value` holds big integers as floats, like any other format can.
+    for j in &[u128::MAX.to_string(), i128::MIN.to_string()] {
+        let value: json::Value = json::from_str(j).unwrap();
+        assert!(
+            matches!(value, json::Value::Number(json::Number::F64(_))),
+            "{}",
+            j
+        );
+        assert!(miniserde_ditto::cbor::to_vec(&value).is_ok(), "{}", j);
+    }
+    let n: json::Number = json::from_str("18446744073709551616").unwrap();
+    assert_eq!(n.as_f64(), Some(18446744073709551616.0));
+}
+
+#[test]
+#[cfg(all(feature = "arbitrary_precision", feature = "float"))]
+fn test_arbitrary_precision() {
+    let j = r#"{"big":-170141183460469231731687303715884105728,"pi":3.14159265358979323846}"#;
+    let value: json::Value = json::from_str(j).unwrap();
+    assert_eq!(json::to_string(&value).unwrap(), j);
+
+    let n: i128 = json::from_str("-170141183460469231731687303715884105728").unwrap();
+    assert_eq!(n, i128::MIN);
+    let f: f64 = json::from_str("3.14159265358979323846").unwrap();
+    assert_eq!(f, std::f64::consts::PI);
+
+    let value = json::to_value(i128::MIN).unwrap();
+    assert_eq!(json::to_string(&value).unwrap(), i128::MIN.to_string());
+}
+
+#[test]
+fn test_accessors() {
+    let n = json::Number::from(7_u8);
+    assert_eq!((n.as_u64(), n.as_i64()), (Some(7), Some(7)));
+    assert!(n.is_u64() && n.is_i64() && !n.is_f64());
+
+    let n = json::Number::from(-7_i32);
+    assert_eq!((n.as_u64(), n.as_i64()), (None, Some(-7)));
+
+    let n = json::Number::from(u64::MAX);
+    assert_eq!((n.as_u64(), n.as_i64()), (Some(u64::MAX), None));
+
+    let n: json::Number = json::from_str_raw_numbers("-12").unwrap();
+    assert_eq!(n.as_i64(), Some(-12));
+    let n: json::Number = json::from_str_raw_numbers("1e3").unwrap();
+    assert!(n.is_f64());
+}
+
+#[test]
+#[cfg(feature = "float")]
+fn test_floats() {
+    assert!(json::Number::from_f64(f64::NAN).is_none());
+    assert!(json::Number::from_f64(f64::INFINITY).is_none());
+
+    let n = json::Number::from_f64(1.0).unwrap();
+    assert_eq!((n.as_u64(), n.as_f64()), (None, Some(1.0)));
+    assert!(n.is_f64());
+    assert_eq!(json::Number::from(-3_i64).as_f64(), Some(-3.0));
+
+    let n = json::Number::Raw("1e400".to_owned());
+    assert_eq!(n.as_f64(), None);
+
+    assert!(matches!(json::Value::from(f64::NAN), json::Value::Null));
+    assert_eq!(json::to_string(&json::Value::from(0.5_f32)).unwrap(), "0.5");
+}
+
+#[test]
+fn test_round_trip() {
+    let values = vec![
+        json::Value::from(1_u32),
+        json::Value::from(-1_i64),
+        json::Value::from(json::Number::from(usize::MAX)),
+    ];
+    let j = json::to_string(&values).unwrap();
+    assert_eq!(j, format!("[1,-1,{}]", usize::MAX));
+
+    let numbers: Vec<json::Number> = json::from_str(&j).unwrap();
+    assert_eq!(json::to_string(&numbers).unwrap(), j);
+    assert_eq!(numbers[1].as_i64(), Some(-1));
+}
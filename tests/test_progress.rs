@@ -0,0 +1,58 @@
+use miniserde_ditto::de::DuplicateKeys;
+use miniserde_ditto::{cbor, json};
+use std::cell::RefCell;
+
+fn backup() -> Vec<Vec<u32>> {
+    vec![vec![1, 2], vec![], vec![300]]
+}
+
+#[test]
+fn test_json() {
+    let reports = RefCell::new(vec![]);
+    let progress = |n| reports.borrow_mut().push(n);
+
+    let j = json::Serializer::new()
+        .progress(&progress)
+        .to_string(&backup())
+        .unwrap();
+    assert_eq!(j, "[[1,2],[],[300]]");
+    assert_eq!(reports.take(), [6, 9, 15, 16]);
+
+    let deserializer = json::Deserializer::new().progress(&progress);
+    let back: Vec<Vec<u32>> = deserializer.from_str(" [[1,2], [], [300]] ").unwrap();
+    assert_eq!(back, backup());
+    assert_eq!(reports.take(), [7, 11, 18, 19]);
+}
+
+#[test]
+fn test_cbor() {
+    let reports = RefCell::new(vec![]);
+    let progress = |n| reports.borrow_mut().push(n);
+
+    let bytes = cbor::Serializer::new()
+        .progress(&progress)
+        .to_vec(backup())
+        .unwrap();
+    assert_eq!(bytes, cbor::to_vec(backup()).unwrap());
+    assert_eq!(reports.take(), [4, 5, 9, 9]);
+
+    let deserializer = cbor::Deserializer::new().progress(&progress);
+    let back: Vec<Vec<u32>> = deserializer.from_slice(&bytes).unwrap();
+    assert_eq!(back, backup());
+    assert_eq!(reports.take(), [4, 5, 9, 9]);
+}
+
+#[test]
+fn test_cbor_duplicate_keys() {
+    let reports = RefCell::new(vec![]);
+    let progress = |n| reports.borrow_mut().push(n);
+
+    // {[1]: 2, [1]: 3}: the keys are scanned ahead of being decoded.
+    let bytes = [0xa2, 0x81, 0x01, 0x02, 0x81, 0x01, 0x03];
+    let deserializer = cbor::Deserializer::new()
+        .duplicate_keys(DuplicateKeys::FirstWins)
+        .progress(&progress);
+    let map: cbor::Value = deserializer.from_slice(&bytes).unwrap();
+    assert_eq!(cbor::to_vec(&map).unwrap(), [0xa1, 0x81, 0x01, 0x02]);
+    assert_eq!(reports.take(), [3, 7]);
+}
@@ -0,0 +1,121 @@
+use miniserde_ditto::{cbor, json, Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Message {
+    id: u32,
+    payload: json::RawValue,
+    extra: Option<json::RawValue>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct CborMessage {
+    id: u32,
+    payload: cbor::RawValue,
+}
+
+#[test]
+fn test_json_raw_value() {
+    let j = r#"{"id":1,"payload":  {"z": 1.10, "a": [true, "é"]} ,"extra":null}"#;
+    let message: Message = json::from_str(j).unwrap();
+    assert_eq!(message.payload.get(), r#"{"z": 1.10, "a": [true, "é"]}"#);
+    assert_eq!(message.extra, None);
+    assert_eq!(
+        json::to_string(&message).unwrap(),
+        r#"{"id":1,"payload":{"z": 1.10, "a": [true, "é"]},"extra":null}"#,
+    );
+
+    let j = r#"{"id":2,"payload":"text","extra":[1, 2]}"#;
+    let message: Message = json::from_str(j).unwrap();
+    assert_eq!(message.payload.get(), r#""text""#);
+    assert_eq!(message.extra.unwrap().get(), "[1, 2]");
+
+    // The captured value must still be valid.
+    assert!(json::from_str::<Message>(r#"{"id":3,"payload":[1,}"#).is_err());
+    assert!(json::from_str::<Message>(r#"{"id":3,"payload":"#).is_err());
+}
+
+#[test]
+fn test_json_raw_value_from_string() {
+    let raw = json::RawValue::from_string(" [1, 2] ".to_owned()).unwrap();
+    assert_eq!(json::to_string(&raw).unwrap(), " [1, 2] ");
+    assert!(json::RawValue::from_string("[1, 2".to_owned()).is_err());
+    assert!(json::RawValue::from_string("1 2".to_owned()).is_err());
+    assert!(json::RawValue::from_string(String::new()).is_err());
+}
+
+#[test]
+fn test_cbor_raw_value() {
+    let bytes = [
+        0xa2, // 2-long map
+        0x62, b'i', b'd', 0x07, //
+        0x67, b'p', b'a', b'y', b'l', b'o', b'a', b'd', //
+        0xbf, 0x61, b'k', 0xf9, 0x3c, 0x00, 0xff, // {_ "k": 1.0_16}
+    ];
+    let message: CborMessage = cbor::from_slice(&bytes).unwrap();
+    assert_eq!(
+        message.payload.get(),
+        [0xbf, 0x61, b'k', 0xf9, 0x3c, 0x00, 0xff]
+    );
+    assert_eq!(cbor::to_vec(&message).unwrap(), bytes);
+    // Re-encoded, with a definite length.
+    let canonical = cbor::to_vec_canonical(&message).unwrap();
+    assert_eq!(canonical[..13], bytes[..13]);
+    assert_eq!(canonical[13..], [0xa1, 0x61, b'k', 0xf9, 0x3c, 0x00]);
+
+    assert!(cbor::RawValue::from_vec(vec![0x82, 0x01]).is_err());
+    assert!(cbor::RawValue::from_vec(vec![0x01, 0x02]).is_err());
+}
+
+#[test]
+fn test_raw_value_across_formats() {
+    // JSON captured from CBOR input, and the other way around.
+    let bytes = [
+        0xa3, // 3-long map
+        0x62, b'i', b'd', 0x01, //
+        0x67, b'p', b'a', b'y', b'l', b'o', b'a', b'd', //
+        0x82, 0x01, 0x61, b'a', // [1, "a"]
+        0x65, b'e', b'x', b't', b'r', b'a', 0xf6,
+    ];
+    let message: Message = cbor::from_slice(&bytes).unwrap();
+    assert_eq!(message.payload.get(), r#"[1,"a"]"#);
+    assert_eq!(message.extra, None);
+
+    let message: CborMessage = json::from_str(r#"{"id":1,"payload":[1, "a"]}"#).unwrap();
+    assert_eq!(message.payload.get(), [0x82, 0x01, 0x61, b'a']);
+
+    // Serializing a raw value to the other format decodes it.
+    assert_eq!(
+        json::to_string(&message).unwrap(),
+        r#"{"id":1,"payload":[1,"a"]}"#,
+    );
+    let message: Message = cbor::from_slice(&bytes).unwrap();
+    assert_eq!(cbor::to_vec(&message).unwrap(), bytes);
+}
+
+#[test]
+fn test_raw_value_through_values() {
+    let message: Message = json::from_str(r#"{"id":1,"payload":{"k": [1]}}"#).unwrap();
+    let value = json::to_value(&message).unwrap();
+    let payload = match &value {
+        json::Value::Object(object) => &object["payload"],
+        _ => unreachable!(),
+    };
+    assert_eq!(json::to_string(payload).unwrap(), r#"{"k":[1]}"#);
+
+    let message: Message = json::from_value(value).unwrap();
+    assert_eq!(message.payload.get(), r#"{"k":[1]}"#);
+
+    let message: CborMessage = json::from_str(r#"{"id":1,"payload":[]}"#).unwrap();
+    let value = cbor::to_value(&message).unwrap();
+    let message: CborMessage = cbor::from_value(value).unwrap();
+    assert_eq!(message.payload.get(), [0x80]);
+}
+
+#[test]
+fn test_boxed_raw_value() {
+    let payload: Box<json::RawValue> = json::from_str(" {} ").unwrap();
+    assert_eq!(payload.get(), "{}");
+    let payloads: Vec<json::RawValue> = json::from_str("[1, {\"a\": 2}, null]").unwrap();
+    let payloads = payloads.iter().map(json::RawValue::get).collect::<Vec<_>>();
+    assert_eq!(payloads, ["1", "{\"a\": 2}", "null"]);
+}
@@ -0,0 +1,162 @@
+use miniserde_ditto::{cbor, json, schema};
+
+#[test]
+#[cfg(feature = "float")]
+fn test_infer_json() {
+    let samples = [
+        r#"{"id": 1, "kind": "a", "score": 0.5, "tags": ["x", "y"], "owner": {"id": -3}}"#,
+        r#"{"id": 70000, "kind": "b", "score": 2, "tags": [], "owner": null}"#,
+        r#"{"id": 2, "kind": "a", "score": null, "tags": [1], "extra": true}"#,
+    ];
+    let values: Vec<json::Value> = samples.iter().map(|s| json::from_str(s).unwrap()).collect();
+    let schema = schema::infer(&values);
+    assert_eq!(schema.seen, 3);
+
+    let object = schema.objects.as_ref().unwrap();
+    assert_eq!(object.count, 3);
+    for field in &["id", "kind", "score", "tags"] {
+        assert!(object.is_required(field), "{}", field);
+    }
+    assert!(!object.is_required("owner"));
+    assert!(!object.is_required("extra"));
+    assert!(!object.is_required("missing"));
+
+    let id = object.fields["id"].ints.unwrap();
+    assert_eq!((id.min, id.max), (1, 70000));
+    assert_eq!(id.rust_type(), "u32");
+
+    let kind = object.fields["kind"].strings.as_ref().unwrap();
+    assert_eq!(kind.count, 3);
+    assert_eq!(
+        kind.variants().unwrap().iter().collect::<Vec<_>>(),
+        ["a", "b"],
+    );
+
+    let score = &object.fields["score"];
+    assert_eq!((score.floats, score.nulls), (1, 1));
+    assert_eq!(score.ints.unwrap().count, 1);
+
+    let tags = object.fields["tags"].arrays.as_ref().unwrap();
+    assert_eq!(tags.count, 3);
+    assert_eq!(tags.items.seen, 3);
+    assert_eq!(tags.items.strings.as_ref().unwrap().count, 2);
+    assert_eq!(tags.items.ints.unwrap().rust_type(), "u8");
+
+    let owner = &object.fields["owner"];
+    assert_eq!(owner.nulls, 1);
+    let owner_id = owner.objects.as_ref().unwrap().fields["id"].ints.unwrap();
+    assert_eq!(owner_id.rust_type(), "i8");
+}
+
+#[test]
+fn test_infer_many_strings() {
+    let values: Vec<json::Value> = (0..100)
+        .map(|i| json::Value::String(i.to_string()))
+        .collect();
+    let schema = schema::infer(&values);
+    let strings = schema.strings.as_ref().unwrap();
+    assert_eq!(strings.count, 100);
+    assert!(strings.variants().is_none());
+}
+
+#[test]
+fn test_infer_cbor() {
+    let mut object = cbor::Object::new();
+    object.insert(cbor::Value::Integer(1), cbor::Value::Bytes(vec![0]));
+    let tagged = cbor::Value::Tag(0, Box::new(cbor::Value::Map(object)));
+    let schema = schema::infer(vec![&tagged, &cbor::Value::Null]);
+    assert_eq!(schema.seen, 2);
+    assert_eq!(schema.nulls, 1);
+    assert_eq!(schema.objects.as_ref().unwrap().fields["1"].bytes, 1);
+}
+
+#[test]
+fn test_infer_deeply_nested() {
+    let mut value = json::Value::Null;
+    for _ in 0..100_000 {
+        let mut array = json::Array::new();
+        array.push(value);
+        value = json::Value::Array(array);
+    }
+    let schema = schema::infer(&[value]);
+    let mut node = &schema;
+    let mut depth = 0;
+    while let Some(arrays) = &node.arrays {
+        node = &arrays.items;
+        depth += 1;
+    }
+    assert_eq!(depth, 100_000);
+    assert_eq!(node.nulls, 1);
+}
+
+#[test]
+#[cfg(feature = "float")]
+fn test_to_rust() {
+    let samples = [
+        r#"{"id": 1, "type": "a", "owner": {"first-name": "x"}, "items": [{"n": 1.5}]}"#,
+        r#"{"id": 2, "type": "b", "owner": null, "items": [], "mixed": [1, "a"]}"#,
+    ];
+    let values: Vec<json::Value> = samples.iter().map(|s| json::from_str(s).unwrap()).collect();
+    let code = schema::infer(&values).to_rust("event");
+    let expected = "\
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Event {
+    pub id: u8,
+    pub items: Vec<ItemsItem>,
+    pub mixed: Option<Vec<::miniserde_ditto::json::Value>>,
+    pub owner: Option<Owner>,
+    #[serde(rename = \"type\")]
+    pub type_: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ItemsItem {
+    pub n: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Owner {
+    #[serde(rename = \"first-name\")]
+    pub first_name: String,
+}
+";
+    assert_eq!(code, expected);
+}
+
+#[test]
+fn test_to_rust_root_array() {
+    let value: json::Value = json::from_str(r#"[{"a": true}, null]"#).unwrap();
+    let code = schema::infer(&[value]).to_rust("Rows");
+    let expected = "\
+pub type Rows = Vec<Option<RowsItem>>;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RowsItem {
+    pub a: bool,
+}
+";
+    assert_eq!(code, expected);
+}
+
+#[test]
+fn test_to_rust_reserved_names() {
+    let value: json::Value = json::from_str(r#"{"option": {"string": {"vec": [1]}}}"#).unwrap();
+    let code = schema::infer(&[value]).to_rust("Serialize");
+    let expected = "\
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Serialize2 {
+    pub option: Option2,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Option2 {
+    pub string: String2,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct String2 {
+    pub vec: Vec<u8>,
+}
+";
+    assert_eq!(code, expected);
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "zeroize")]
+
+use miniserde_ditto::{cbor, json, Deserialize, Sensitive, Serialize};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A byte only the secrets of `test_no_copies_left` are made of.
+const SECRET: u8 = 0xab;
+
+static SECRET_FREED: AtomicBool = AtomicBool::new(false);
+
+/// Checks that no memory is freed holding a run of `SECRET` bytes.
+struct Checking;
+
+unsafe impl GlobalAlloc for Checking {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let bytes = std::slice::from_raw_parts(ptr, layout.size());
+        if bytes.windows(4).any(|run| run == [SECRET; 4]) {
+            SECRET_FREED.store(true, Ordering::SeqCst);
+        }
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Checking = Checking;
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Config {
+    user: String,
+    password: Sensitive<String>,
+    key: Option<Sensitive<Vec<u8>>>,
+}
+
+#[test]
+fn test_json() {
+    let j = r#"{"user":"ada","password":"päss\"word","key":[1,2,3]}"#;
+    let config: Config = json::from_str(j).unwrap();
+    assert_eq!(*config.password, "päss\"word");
+    assert_eq!(**config.key.as_ref().unwrap(), [1, 2, 3]);
+    assert_eq!(
+        json::to_string(&config).unwrap(),
+        r#"{"user":"ada","password":"päss\"word","key":[1,2,3]}"#
+    );
+}
+
+#[test]
+fn test_cbor() {
+    let config = Config {
+        user: "ada".to_owned(),
+        password: Sensitive::new("hunter2".to_owned()),
+        key: Some(vec![0xff; 32].into()),
+    };
+    let bytes = cbor::to_vec(&config).unwrap();
+    let back: Config = cbor::from_slice(&bytes).unwrap();
+    assert_eq!(back.password, config.password);
+    assert_eq!(back.key, config.key);
+
+    // Indefinite-length byte string, in two chunks.
+    let chunked = [0x5f, 0x42, 1, 2, 0x43, 3, 4, 5, 0xff];
+    let key: Sensitive<Vec<u8>> = cbor::from_slice(&chunked).unwrap();
+    assert_eq!(*key, [1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_debug() {
+    let config = Config {
+        user: "ada".to_owned(),
+        password: Sensitive::new("hunter2".to_owned()),
+        key: None,
+    };
+    let debug = format!("{:?}", config);
+    assert!(!debug.contains("hunter2"));
+    assert!(debug.contains("Sensitive(..)"));
+}
+
+#[test]
+fn test_no_copies_left() {
+    let j = format!("{:?}", [SECRET; 40]);
+
+    let key: Sensitive<Vec<u8>> = json::from_str(&j).unwrap();
+    assert_eq!(*key, [SECRET; 40]);
+    drop(key);
+    let key: Sensitive<[u8; 40]> = json::from_str(&j).unwrap();
+    assert_eq!(*key, [SECRET; 40]);
+    drop(key);
+    let key: Sensitive<Box<[u8]>> = json::from_str(&j).unwrap();
+    assert_eq!(**key, [SECRET; 40][..]);
+    drop(key);
+
+    assert!(!SECRET_FREED.load(Ordering::SeqCst));
+}
@@ -0,0 +1,107 @@
+#![cfg(feature = "serde-compat")]
+
+use miniserde_ditto::serde_compat::{Miniserde, Serde};
+use miniserde_ditto::{cbor, json, Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(serde_derive::Serialize, serde_derive::Deserialize, Debug, PartialEq)]
+enum Legacy {
+    Unit,
+    Newtype(i64),
+    Tuple(u8, String),
+    Struct { bytes: Vec<u8>, ratio: f64 },
+}
+
+#[derive(serde_derive::Serialize, serde_derive::Deserialize, Debug, PartialEq)]
+struct Config {
+    name: String,
+    #[serde(default)]
+    retries: Option<u32>,
+    modes: Vec<Legacy>,
+    limits: BTreeMap<u16, u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Migrated {
+    id: u64,
+    config: Serde<Config>,
+}
+
+fn config() -> Config {
+    let mut limits = BTreeMap::new();
+    limits.insert(80, u64::MAX);
+    Config {
+        name: "edge".to_owned(),
+        retries: None,
+        modes: vec![
+            Legacy::Unit,
+            Legacy::Newtype(-1),
+            Legacy::Tuple(7, "x".to_owned()),
+            Legacy::Struct {
+                bytes: vec![1, 2],
+                ratio: 0.5,
+            },
+        ],
+        limits,
+    }
+}
+
+#[test]
+fn test_serde_in_miniserde() {
+    let migrated = Migrated {
+        id: 1,
+        config: Serde(config()),
+    };
+    let j = json::to_string(&migrated).unwrap();
+    assert_eq!(
+        j,
+        format!(
+            r#"{{"id":1,"config":{}}}"#,
+            serde_json::to_string(&config()).unwrap()
+        ),
+    );
+    assert_eq!(json::from_str::<Migrated>(&j).unwrap(), migrated);
+
+    // `retries` may be left out, per its Serde attribute.
+    let j = j.replace(r#""retries":null,"#, "");
+    assert_eq!(json::from_str::<Migrated>(&j).unwrap(), migrated);
+
+    let bytes = cbor::to_vec(&migrated).unwrap();
+    assert_eq!(cbor::from_slice::<Migrated>(&bytes).unwrap(), migrated);
+
+    assert!(json::from_str::<Serde<Legacy>>(r#""Other""#).is_err());
+    assert!(json::from_str::<Serde<Legacy>>(r#"{"Unit":null,"Newtype":1}"#).is_err());
+}
+
+#[test]
+fn test_miniserde_in_serde() {
+    #[derive(serde_derive::Serialize, serde_derive::Deserialize, Debug, PartialEq)]
+    struct Outer {
+        inner: Miniserde<Migrated>,
+        tags: Vec<String>,
+    }
+
+    let outer = Outer {
+        inner: Miniserde(Migrated {
+            id: 2,
+            config: Serde(config()),
+        }),
+        tags: vec!["a".to_owned()],
+    };
+    let j = serde_json::to_string(&outer).unwrap();
+    assert!(j.starts_with(r#"{"inner":{"id":2,"config":{"name":"edge","#));
+    assert_eq!(serde_json::from_str::<Outer>(&j).unwrap(), outer);
+
+    let j = r#"{"inner":{"id":"2"},"tags":[]}"#;
+    assert!(serde_json::from_str::<Outer>(j).is_err());
+}
+
+#[test]
+fn test_depth() {
+    let nested = |depth| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+    assert!(json::from_str::<Serde<serde_json::Value>>(&nested(128)).is_ok());
+    assert!(json::from_str::<Serde<serde_json::Value>>(&nested(129)).is_err());
+
+    // Too deep to hand to the Serde impl, and to drop recursively.
+    assert!(json::from_str::<Serde<serde_json::Value>>(&nested(100_000)).is_err());
+}
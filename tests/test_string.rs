@@ -0,0 +1,86 @@
+use miniserde_ditto::json;
+
+#[test]
+fn test_surrogate_pairs() {
+    let s: String = json::from_str(r#""\ud83d\ude00""#).unwrap();
+    assert_eq!(s, "\u{1F600}");
+    let s: String = json::from_str(r#""a\ud834\udd1eb""#).unwrap();
+    assert_eq!(s, "a\u{1D11E}b");
+    let s: String = json::from_str(r#""\udbff\udfff""#).unwrap();
+    assert_eq!(s, "\u{10FFFF}");
+}
+
+#[test]
+fn test_unpaired_surrogates() {
+    for j in &[
+        r#""\ud83d""#,
+        r#""\ud83dx""#,
+        r#""\ud83d\n""#,
+        r#""\ud83d\u0041""#,
+        r#""\ud83d\ud83d""#,
+        r#""\ude00""#,
+        r#""\ude00\ud83d""#,
+    ] {
+        assert!(json::from_str::<String>(j).is_err(), "{}", j);
+    }
+}
+
+#[test]
+fn test_escape_non_bmp() {
+    let value = "\u{E9} \u{1F600} \u{10FFFF}\n";
+    let j = json::to_string(&value).unwrap();
+    assert_eq!(j, "\"\u{E9} \u{1F600} \u{10FFFF}\\n\"");
+
+    let serializer = json::Serializer::new().escape_non_bmp(true);
+    let j = serializer.to_string(&value).unwrap();
+    assert_eq!(j, r#""é \ud83d\ude00 \udbff\udfff\n""#);
+    assert_eq!(json::from_str::<String>(&j).unwrap(), value);
+
+    let mut map = std::collections::BTreeMap::new();
+    map.insert("\u{1D11E}".to_owned(), "\u{1D11E}");
+    let j = serializer.to_string(&map).unwrap();
+    assert_eq!(j, r#"{"\ud834\udd1e":"\ud834\udd1e"}"#);
+}
+
+#[test]
+fn test_escape_unescape() {
+    for s in &[
+        "",
+        "plain",
+        "quote \" backslash \\ slash /",
+        "\u{0}\u{1f}\u{7f}\t\r\n\u{8}\u{c}",
+        "\u{E9} \u{1F600}",
+    ] {
+        let mut escaped = String::new();
+        json::escape(s, &mut escaped);
+        assert_eq!(format!("\"{}\"", escaped), json::to_string(s).unwrap());
+        assert_eq!(json::unescape(&escaped).unwrap(), *s);
+    }
+
+    assert_eq!(json::unescape(r"\/é😀").unwrap(), "/\u{E9}\u{1F600}");
+    for s in &["\"", "\n", r"\", r"\q", r"\u12", r"\ud83d"] {
+        assert!(json::unescape(s).is_err(), "{:?}", s);
+    }
+}
+
+#[test]
+fn test_long_strings() {
+    // Special characters at every offset within and past a run of plain ones.
+    for i in 0..40 {
+        for special in &["\"", "\\", "\n", "\u{1}", "\u{E9}", "\u{1F600}"] {
+            let mut value = "abcdefgh".repeat(5);
+            value.insert_str(i, special);
+            let j = json::to_string(&value).unwrap();
+            assert_eq!(json::from_str::<String>(&j).unwrap(), value);
+
+            let serializer = json::Serializer::new().escape_non_bmp(true);
+            let j = serializer.to_string(&value).unwrap();
+            assert!(j.is_ascii() || *special != "\u{1F600}");
+            assert_eq!(json::from_str::<String>(&j).unwrap(), value);
+        }
+
+        let mut j = format!("\"{}\"", "abcdefgh".repeat(5));
+        j.insert(i + 1, '\u{1}');
+        assert!(json::from_str::<String>(&j).is_err());
+    }
+}
@@ -0,0 +1,208 @@
+use miniserde_ditto::timestamp::Timestamp;
+use miniserde_ditto::{cbor, json, Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Event<T> {
+    at: T,
+}
+
+fn ts(secs: i64, nanos: u32, offset: Option<i32>) -> Timestamp {
+    Timestamp {
+        secs,
+        nanos,
+        offset,
+    }
+}
+
+#[test]
+fn test_rfc3339() {
+    let cases = &[
+        (ts(0, 0, Some(0)), "1970-01-01T00:00:00Z"),
+        (ts(951_782_400, 0, Some(0)), "2000-02-29T00:00:00Z"),
+        (ts(-1, 0, Some(0)), "1969-12-31T23:59:59Z"),
+        (
+            ts(1_600_000_000, 120_000_000, Some(0)),
+            "2020-09-13T12:26:40.120Z",
+        ),
+        (
+            ts(1_600_000_000, 120_000, None),
+            "2020-09-13T12:26:40.000120",
+        ),
+        (
+            ts(1_600_000_000, 1, Some(-9000)),
+            "2020-09-13T09:56:40.000000001-02:30",
+        ),
+        (
+            ts(-62_135_596_800, 0, Some(3600)),
+            "0001-01-01T01:00:00+01:00",
+        ),
+        (ts(253_402_300_800, 0, Some(0)), "+10000-01-01T00:00:00Z"),
+    ];
+    for (timestamp, text) in cases {
+        assert_eq!(timestamp.to_string(), *text);
+        let j = json::to_string(timestamp).unwrap();
+        assert_eq!(j, format!("\"{}\"", text));
+        assert_eq!(json::from_str::<Timestamp>(&j).unwrap(), *timestamp);
+    }
+
+    // An offset in seconds is written in UTC.
+    assert_eq!(ts(0, 0, Some(1)).to_string(), "1970-01-01T00:00:00Z");
+
+    let parsed = |s: &str| json::from_str::<Timestamp>(&format!("\"{}\"", s));
+    assert_eq!(
+        parsed("1970-01-01t00:00:00.5z").unwrap(),
+        ts(0, 500_000_000, Some(0))
+    );
+    assert_eq!(
+        parsed("1970-01-01 00:00:00+00:00").unwrap(),
+        ts(0, 0, Some(0))
+    );
+    assert_eq!(
+        parsed("1970-01-01T00:00:00.1234567891Z").unwrap().nanos,
+        123_456_789
+    );
+    assert_eq!(parsed("1998-12-31T23:59:60Z").unwrap().secs, 915_148_800);
+    for invalid in &[
+        "",
+        "1970-01-01",
+        "1970-01-01T00:00Z",
+        "70-01-01T00:00:00Z",
+        "1970-13-01T00:00:00Z",
+        "1970-02-29T00:00:00Z",
+        "1970-01-01T24:00:00Z",
+        "1970-01-01T00:00:00.Z",
+        "1970-01-01T00:00:00+0100",
+        "1970-01-01T00:00:00+24:00",
+        "1970-01-01T00:00:00Z ",
+    ] {
+        assert!(parsed(invalid).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_epoch() {
+    let event = Event {
+        at: ts(1_600_000_000, 0, Some(3600)),
+    };
+    let bytes = cbor::to_vec(&event).unwrap();
+    assert!(bytes.ends_with(&[0xc1, 0x1a, 0x5f, 0x5e, 0x10, 0x00]));
+    let value: cbor::Value = cbor::from_slice(&bytes).unwrap();
+    assert_eq!(value, cbor::to_value(&event).unwrap());
+
+    // The offset is not in the epoch.
+    let event: Event<Timestamp> = cbor::from_slice(&bytes).unwrap();
+    assert_eq!(event.at, ts(1_600_000_000, 0, Some(0)));
+
+    #[cfg(feature = "float")]
+    {
+        let event = Event {
+            at: ts(-2, 250_000_000, Some(0)),
+        };
+        let bytes = cbor::to_vec(&event).unwrap();
+        let value: cbor::Value = cbor::from_slice(&bytes).unwrap();
+        let epoch = cbor::Value::Float(-1.75);
+        assert_eq!(value.get("at"), Some(&cbor::Value::Tag(1, Box::new(epoch))));
+        assert_eq!(cbor::from_slice::<Event<Timestamp>>(&bytes).unwrap(), event);
+
+        // Floats are floored to the second, with the rest in nanoseconds.
+        let cases = &[
+            ("-0.5", ts(-1, 500_000_000, Some(0))),
+            ("1.9999999999", ts(2, 0, Some(0))),
+            ("-3.0", ts(-3, 0, Some(0))),
+        ];
+        for (epoch, expected) in cases {
+            let j = format!(r#"{{"at":{}}}"#, epoch);
+            let event: Event<Timestamp> = json::from_str(&j).unwrap();
+            assert_eq!(event.at, *expected, "{}", epoch);
+        }
+        assert!(json::from_str::<Event<Timestamp>>(r#"{"at":1e19}"#).is_err());
+    }
+
+    // Numbers are read as the epoch in JSON as well.
+    let event: Event<Timestamp> = json::from_str(r#"{"at":86400}"#).unwrap();
+    assert_eq!(event.at, ts(86400, 0, Some(0)));
+
+    // JSON values hold the string.
+    let value = json::to_value(&event).unwrap();
+    assert_eq!(
+        json::to_string(&value).unwrap(),
+        r#"{"at":"1970-01-02T00:00:00Z"}"#
+    );
+}
+
+#[cfg(not(feature = "float"))]
+#[test]
+fn test_epoch_without_float() {
+    // The fraction of a second cannot be written under tag 1.
+    assert!(cbor::to_vec(&Event {
+        at: ts(0, 500_000_000, Some(0))
+    })
+    .is_err());
+    assert!(cbor::to_vec(&Event {
+        at: ts(0, 0, Some(0))
+    })
+    .is_ok());
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_chrono() {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+    let at = Utc.timestamp_opt(1_600_000_000, 5_000_000).unwrap();
+    let j = json::to_string(&Event { at }).unwrap();
+    assert_eq!(j, r#"{"at":"2020-09-13T12:26:40.005Z"}"#);
+    assert_eq!(json::from_str::<Event<DateTime<Utc>>>(&j).unwrap().at, at);
+
+    let j = r#"{"at":"2020-09-13T14:26:40.005+02:00"}"#;
+    assert_eq!(json::from_str::<Event<DateTime<Utc>>>(j).unwrap().at, at);
+    assert!(json::from_str::<Event<DateTime<Utc>>>(r#"{"at":"2020-09-13T12:26:40"}"#).is_err());
+
+    let bytes = cbor::to_vec(&Event { at }).unwrap();
+    let event: Event<DateTime<Utc>> = cbor::from_slice(&bytes).unwrap();
+    assert_eq!(event.at.timestamp_millis(), at.timestamp_millis());
+
+    let naive: NaiveDateTime = NaiveDate::from_ymd_opt(2020, 9, 13)
+        .unwrap()
+        .and_hms_opt(12, 26, 40)
+        .unwrap();
+    let j = json::to_string(&Event { at: naive }).unwrap();
+    assert_eq!(j, r#"{"at":"2020-09-13T12:26:40"}"#);
+    assert_eq!(
+        json::from_str::<Event<NaiveDateTime>>(&j).unwrap().at,
+        naive
+    );
+    let j = r#"{"at":"2020-09-13T12:26:40Z"}"#;
+    assert_eq!(json::from_str::<Event<NaiveDateTime>>(j).unwrap().at, naive);
+    let j = r#"{"at":"2020-09-13T12:26:40+01:00"}"#;
+    assert!(json::from_str::<Event<NaiveDateTime>>(j).is_err());
+
+    let bytes = cbor::to_vec(&Event { at: naive }).unwrap();
+    assert!(bytes.ends_with(&[0xc1, 0x1a, 0x5f, 0x5e, 0x10, 0x00]));
+    assert_eq!(
+        cbor::from_slice::<Event<NaiveDateTime>>(&bytes).unwrap().at,
+        naive
+    );
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn test_time() {
+    use time::{OffsetDateTime, UtcOffset};
+
+    let offset = UtcOffset::from_hms(-5, -30, 0).unwrap();
+    let at = OffsetDateTime::from_unix_timestamp(1_600_000_000)
+        .unwrap()
+        .to_offset(offset);
+    let j = json::to_string(&Event { at }).unwrap();
+    assert_eq!(j, r#"{"at":"2020-09-13T06:56:40-05:30"}"#);
+    let event: Event<OffsetDateTime> = json::from_str(&j).unwrap();
+    assert_eq!(event.at, at);
+    assert_eq!(event.at.offset(), offset);
+    assert!(json::from_str::<Event<OffsetDateTime>>(r#"{"at":"2020-09-13T06:56:40"}"#).is_err());
+
+    let bytes = cbor::to_vec(&Event { at }).unwrap();
+    let event: Event<OffsetDateTime> = cbor::from_slice(&bytes).unwrap();
+    assert_eq!(event.at, at);
+    assert_eq!(event.at.offset(), UtcOffset::UTC);
+}
@@ -0,0 +1,302 @@
+#![cfg(feature = "toml")]
+
+use miniserde_ditto::{toml, Deserialize};
+use std::collections::BTreeMap;
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Config {
+    title: String,
+    owner: Owner,
+    database: Database,
+    servers: BTreeMap<String, Server>,
+    products: Vec<Product>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Owner {
+    name: String,
+    dob: String,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Database {
+    enabled: bool,
+    ports: Vec<u16>,
+    temp_targets: Temperatures,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Temperatures {
+    cpu: i64,
+    case: i64,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Server {
+    ip: String,
+    role: String,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Product {
+    name: Option<String>,
+    sku: Option<u32>,
+}
+
+#[test]
+fn test_document() {
+    let config: Config = toml::from_str(
+        r#"
+# This is a TOML document
+
+title = "TOML Example"
+
+[owner]
+name = "Tom Preston-Werner"
+dob = 1979-05-27T07:32:00-08:00
+
+[database]
+enabled = true
+ports = [ 8000, 8001, 8002, ]
+temp_targets = { cpu = 79, case = 72 }
+
+[servers]
+
+[servers.alpha]
+ip = "10.0.0.1"
+role = "frontend"
+
+[servers.beta]
+ip = "10.0.0.2"
+role = "backend"
+
+[[products]]
+name = "Hammer"
+sku = 738594937
+
+[[products]]  # empty table within the array
+
+[[products]]
+name = "Nail"
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.title, "TOML Example");
+    assert_eq!(config.owner.dob, "1979-05-27T07:32:00-08:00");
+    assert_eq!(config.database.ports, [8000, 8001, 8002]);
+    assert_eq!(
+        config.database.temp_targets,
+        Temperatures { cpu: 79, case: 72 }
+    );
+    assert_eq!(config.servers["beta"].role, "backend");
+    assert_eq!(config.products.len(), 3);
+    assert_eq!(config.products[0].sku, Some(738594937));
+    assert_eq!(
+        config.products[1],
+        Product {
+            name: None,
+            sku: None
+        }
+    );
+    assert_eq!(config.products[2].name.as_deref(), Some("Nail"));
+}
+
+#[test]
+fn test_keys() {
+    let map: BTreeMap<String, BTreeMap<String, BTreeMap<String, i32>>> = toml::from_str(
+        r#"
+        a.b.c = 1
+        a . b . d = 2
+        "quoted.key".'x'.y = 3
+
+        [a.e]
+        f = 4
+        "#,
+    )
+    .unwrap();
+    assert_eq!(map["a"]["b"]["c"], 1);
+    assert_eq!(map["a"]["b"]["d"], 2);
+    assert_eq!(map["quoted.key"]["x"]["y"], 3);
+    assert_eq!(map["a"]["e"]["f"], 4);
+
+    // Nested arrays of tables extend the last table of their parent.
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Fruit {
+        name: String,
+        variety: Vec<BTreeMap<String, String>>,
+    }
+    let fruits: BTreeMap<String, Vec<Fruit>> = toml::from_str(
+        r#"
+        [[fruits]]
+        name = "apple"
+        [[fruits.variety]]
+        name = "red delicious"
+        [[fruits.variety]]
+        name = "granny smith"
+
+        [[fruits]]
+        name = "banana"
+        [[fruits.variety]]
+        name = "plantain"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(fruits["fruits"][0].variety.len(), 2);
+    assert_eq!(fruits["fruits"][1].variety[0]["name"], "plantain");
+
+    // Large tables, whose keys are looked up as each one is added.
+    let mut many: String = (0..10_000).map(|i| format!("k{} = {}\n", i, i)).collect();
+    let map: BTreeMap<String, i32> = toml::from_str(&many).unwrap();
+    assert_eq!(map.len(), 10_000);
+    assert_eq!(map["k9999"], 9999);
+    many.push_str("k5000 = 0\n");
+    assert!(toml::from_str::<BTreeMap<String, i32>>(&many).is_err());
+}
+
+#[test]
+fn test_strings() {
+    let map: BTreeMap<String, String> = toml::from_str(
+        "basic = \"tab\\t quote\\\" \\u00e9 \\U0001F600\"\n\
+         literal = 'C:\\Users\\nodejs'\n\
+         multi = \"\"\"\nRoses are red\r\nViolets are blue\"\"\"\n\
+         trimmed = \"\"\"\\\n    The quick \\\n\n    brown fox.\"\"\"\n\
+         raw = '''\nThe first newline is\ntrimmed in raw strings.'''\n\
+         quotes = \"\"\"Here are two quotation marks: \"\". Simple enough.\"\"\"\n\
+         ending = '''It's ''''\n",
+    )
+    .unwrap();
+    assert_eq!(map["basic"], "tab\t quote\" \u{e9} \u{1F600}");
+    assert_eq!(map["literal"], "C:\\Users\\nodejs");
+    assert_eq!(map["multi"], "Roses are red\r\nViolets are blue");
+    assert_eq!(map["trimmed"], "The quick brown fox.");
+    assert_eq!(map["raw"], "The first newline is\ntrimmed in raw strings.");
+    assert_eq!(
+        map["quotes"],
+        "Here are two quotation marks: \"\". Simple enough."
+    );
+    assert_eq!(map["ending"], "It's '");
+}
+
+#[test]
+fn test_numbers() {
+    let map: BTreeMap<String, i64> = toml::from_str(
+        "a = +99\nb = -17\nc = 0\nd = 1_000_000\ne = 0xDEAD_beef\nf = 0o755\ng = 0b1101\n",
+    )
+    .unwrap();
+    let values: Vec<i64> = map.values().copied().collect();
+    assert_eq!(values, [99, -17, 0, 1_000_000, 0xdead_beef, 0o755, 0b1101]);
+
+    for invalid in &[
+        "01",
+        "1__0",
+        "_1",
+        "1_",
+        "0x",
+        "+0x1",
+        "9223372036854775808",
+        "1e",
+    ] {
+        let toml = format!("a = {}", invalid);
+        assert!(
+            toml::from_str::<BTreeMap<String, i64>>(&toml).is_err(),
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[test]
+#[cfg(feature = "float")]
+fn test_floats() {
+    let map: BTreeMap<String, f64> = toml::from_str(
+        "a = 2.5\nb = -0.01\nc = 5e+22\nd = 6.626e-34\ne = 224_617.445_991\nf = -inf\n",
+    )
+    .unwrap();
+    let values: Vec<f64> = map.values().copied().collect();
+    assert_eq!(
+        values,
+        [
+            2.5,
+            -0.01,
+            5e22,
+            6.626e-34,
+            224_617.445_991,
+            f64::NEG_INFINITY
+        ]
+    );
+    assert!(toml::from_str::<BTreeMap<String, f64>>("nan = nan").unwrap()["nan"].is_nan());
+
+    for invalid in &[".7", "7.", "3.e+20", "1._5"] {
+        let toml = format!("a = {}", invalid);
+        assert!(
+            toml::from_str::<BTreeMap<String, f64>>(&toml).is_err(),
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn test_datetimes() {
+    for datetime in &[
+        "1979-05-27T07:32:00Z",
+        "1979-05-27T00:32:00.999999-07:00",
+        "1979-05-27 07:32:00Z",
+        "1979-05-27T07:32:00",
+        "1979-05-27",
+        "07:32:00",
+        "00:32:00.999999",
+    ] {
+        let map: BTreeMap<String, String> = toml::from_str(&format!("a = {}", datetime)).unwrap();
+        assert_eq!(map["a"], *datetime);
+    }
+    for invalid in &[
+        "1979-13-27",
+        "1979-05-27T25:00:00",
+        "07:32",
+        "1979-05-27T07:32:00+8",
+    ] {
+        let toml = format!("a = {}", invalid);
+        assert!(
+            toml::from_str::<BTreeMap<String, String>>(&toml).is_err(),
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn test_errors() {
+    type Any = miniserde_ditto::de::IgnoredAny;
+    for invalid in &[
+        // Defined twice.
+        "a = 1\na = 2",
+        "[a]\n[a]",
+        "a.b = 1\n[a]",
+        "[a]\nb.c = 1\n[a.b]\nd = 2\n[a]",
+        "a = {}\n[a]",
+        "a = [1]\n[[a]]",
+        "a = { b = 1 }\na.c = 2",
+        "[a.b]\n[a]\nb.c = 1",
+        // Malformed.
+        "a = 1 b = 2",
+        "a =",
+        "= 1",
+        "[a",
+        "[[a]",
+        "a = [1 2]",
+        "a = { b = 1, }",
+        "a = { b = 1\n}",
+        "a = \"unterminated",
+        "a = \"\\x\"",
+        "a = \"\"\"a\"\"\"\"\"\"",
+        "a = \"new\nline\"",
+        "a = tru",
+    ] {
+        assert!(toml::from_str::<Any>(invalid).is_err(), "{:?}", invalid);
+    }
+
+    let deep = format!("a = {}{}", "[".repeat(1000), "]".repeat(1000));
+    assert!(toml::from_str::<Any>(&deep).is_err());
+}
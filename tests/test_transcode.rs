@@ -0,0 +1,82 @@
+use miniserde_ditto::{cbor, json};
+
+#[test]
+fn test_json_to_cbor() {
+    let j = r#"{"z": [1, -2, 1.5, null, true], "a": {"": "é"}}"#;
+    let bytes = cbor::to_vec_from_json_str(j).unwrap();
+    assert_eq!(
+        bytes,
+        [
+            0xa2, // 2-long map
+            0x61, b'z', 0x85, 0x01, 0x21, 0xf9, 0x3e, 0x00, 0xf6, 0xf5, //
+            0x61, b'a', 0xa1, 0x60, 0x62, 0xc3, 0xa9,
+        ],
+    );
+    // The same as going through a value, except for the order of the keys.
+    let value: cbor::Value = json::from_str(r#"{"a": [1, {"b": 2.5}]}"#).unwrap();
+    assert_eq!(
+        cbor::to_vec_from_json_str(r#"{"a": [1, {"b": 2.5}]}"#).unwrap(),
+        cbor::to_vec(&value).unwrap(),
+    );
+
+    assert!(cbor::to_vec_from_json_str("[1, 2").is_err());
+    assert!(cbor::to_vec_from_json_str("[1] 2").is_err());
+}
+
+#[test]
+fn test_json_to_cbor_lengths() {
+    // Lengths of 24 and more take more than the head byte.
+    for &len in &[23_usize, 24, 255, 256, 65536] {
+        let j = format!("[{}]", vec!["0"; len].join(","));
+        let bytes = cbor::to_vec_from_json_str(&j).unwrap();
+        assert_eq!(bytes, cbor::to_vec(vec![0; len]).unwrap());
+    }
+    let j = format!(
+        "[{{{}}}]",
+        (0..30)
+            .map(|i| format!(r#""{}":[]"#, i))
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+    let value: cbor::Value = json::from_str(&j).unwrap();
+    let bytes = cbor::to_vec_from_json_str(&j).unwrap();
+    assert_eq!(cbor::from_slice::<cbor::Value>(&bytes).unwrap(), value);
+
+    // Nested long arrays, each with a wider head than the one reserved.
+    let inner = format!("[{}]", vec!["0"; 300].join(","));
+    let j = format!("[{}]", vec![&inner[..]; 30].join(","));
+    let bytes = cbor::to_vec_from_json_str(&j).unwrap();
+    assert_eq!(bytes, cbor::to_vec(vec![vec![0; 300]; 30]).unwrap());
+}
+
+#[test]
+fn test_json_to_cbor_duplicate_keys() {
+    assert!(cbor::to_vec_from_json_str(r#"{"a": 1, "b": 2, "a": 3}"#).is_err());
+    assert!(cbor::to_vec_from_json_str(r#"[{"a": {"a": 1}}, {"a": 2}]"#).is_ok());
+    assert!(cbor::to_vec_from_json_str(r#"{"a": {"b": [], "b": {}}}"#).is_err());
+}
+
+#[test]
+fn test_cbor_to_json() {
+    let bytes = [
+        0xa3, // 3-long map
+        0x61, b'z', 0x83, 0x01, 0xf9, 0x7c, 0x00, 0x42, 0x01, 0x02, // [1, inf, h'0102']
+        0x01, 0xc1, 0x1a, 0x5f, 0x5e, 0x10, 0x00, // 1: 1(1600000000)
+        0x61, b'"', 0xbf, 0xff, // "\"": {_}
+    ];
+    assert_eq!(
+        json::to_string_from_cbor_slice(&bytes).unwrap(),
+        r#"{"z":[1,null,[1,2]],"1":1600000000,"\"":{}}"#,
+    );
+
+    // Keys must have a string form.
+    assert!(json::to_string_from_cbor_slice(&[0xa1, 0x80, 0x01]).is_err());
+    assert!(json::to_string_from_cbor_slice(&[0x82, 0x01]).is_err());
+}
+
+#[test]
+fn test_round_trip() {
+    let j = r#"{"id":7,"tags":["a","b"],"nested":{"ok":false,"ratio":0.25,"none":null}}"#;
+    let bytes = cbor::to_vec_from_json_str(j).unwrap();
+    assert_eq!(json::to_string_from_cbor_slice(&bytes).unwrap(), j);
+}
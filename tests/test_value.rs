@@ -0,0 +1,474 @@
+#![cfg(feature = "float")]
+
+use miniserde_ditto::{cbor, json, Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+struct Example {
+    code: u32,
+    message: String,
+    ratio: f64,
+    bytes: Vec<u8>,
+    nested: Option<Box<Example>>,
+}
+
+fn example() -> Example {
+    Example {
+        code: 200,
+        message: "ok".to_owned(),
+        ratio: 0.5,
+        bytes: vec![1, 2],
+        nested: Some(Box::new(Example {
+            code: 404,
+            message: "not found".to_owned(),
+            ratio: -1.0,
+            bytes: vec![],
+            nested: None,
+        })),
+    }
+}
+
+#[test]
+fn test_json_value() {
+    let example = example();
+    let value = json::to_value(&example).unwrap();
+    let parsed: json::Value = json::from_str(&json::to_string(&example).unwrap()).unwrap();
+    let expected = json::to_string(&parsed).unwrap();
+    assert_eq!(json::to_string(&value).unwrap(), expected);
+    assert_eq!(json::from_value::<Example>(value).unwrap(), example);
+
+    assert!(matches!(json::to_value(f64::NAN), Ok(json::Value::Null)));
+    assert!(json::to_value(
+        vec![(1, 2)]
+            .into_iter()
+            .collect::<std::collections::BTreeMap<_, _>>()
+    )
+    .is_err());
+}
+
+#[test]
+fn test_cbor_value() {
+    let example = example();
+    let value = cbor::to_value(&example).unwrap();
+    let parsed: cbor::Value = cbor::from_slice(&cbor::to_vec(&example).unwrap()).unwrap();
+    assert_eq!(value, parsed);
+    assert_eq!(
+        cbor::Value::from_slice(&value.to_vec().unwrap()).unwrap(),
+        parsed
+    );
+    assert_eq!(cbor::from_value::<Example>(value).unwrap(), example);
+
+    let tagged = cbor::Value::Tag(1, Box::new(cbor::Value::Integer(1363896240)));
+    assert_eq!(cbor::to_value(&tagged).unwrap(), tagged);
+    assert_eq!(cbor::from_value::<u32>(tagged).unwrap(), 1363896240);
+}
+
+#[test]
+fn test_cbor_accessors() {
+    use std::convert::TryFrom;
+
+    let value = cbor::to_value(example()).unwrap();
+    assert_eq!(
+        value.get("code").and_then(cbor::Value::as_integer),
+        Some(200)
+    );
+    assert_eq!(
+        value.get("message").and_then(cbor::Value::as_text),
+        Some("ok")
+    );
+    assert_eq!(
+        value.get("ratio").and_then(cbor::Value::as_float),
+        Some(0.5)
+    );
+    assert_eq!(
+        value.get("bytes").and_then(cbor::Value::as_bytes),
+        Some(&[1, 2][..]),
+    );
+    let nested = value.get(String::from("nested")).unwrap();
+    assert!(nested.get("nested").unwrap().is_null());
+    assert!(value.get("missing").is_none());
+    assert!(value.get(0).is_none());
+    assert_eq!(value.as_map().unwrap().len(), 5);
+
+    let array = cbor::Value::Array(vec![cbor::Value::from(true)].into_iter().collect());
+    assert_eq!(array.get(0).and_then(cbor::Value::as_bool), Some(true));
+    assert!(array.get(1).is_none());
+    assert!(array.get("code").is_none());
+
+    let code = value.get("code").unwrap().clone();
+    assert_eq!(u8::try_from(code.clone()).unwrap(), 200);
+    assert!(i8::try_from(code.clone()).is_err());
+    assert!(String::try_from(code).is_err());
+    let message = value.get("message").unwrap().clone();
+    assert_eq!(String::try_from(message).unwrap(), "ok");
+}
+
+#[test]
+fn test_cbor_float_keys() {
+    use cbor::Value::Float;
+
+    assert_eq!(Float(f64::NAN), Float(-f64::NAN));
+    assert_ne!(Float(0.0), Float(-0.0));
+    assert_ne!(Float(1.0), cbor::Value::Integer(1));
+
+    let keys = [1.1, -1.5, f64::INFINITY, 0.0, -0.0, 100000.0, 1.5, f64::NAN];
+    let mut object = cbor::Object::new();
+    for &key in &keys {
+        object.insert(Float(key), cbor::Value::Null);
+    }
+    assert_eq!(object.len(), keys.len());
+    let sorted_by_encoding = {
+        let mut encodings: Vec<Vec<u8>> = keys.iter().map(|f| cbor::to_vec(f).unwrap()).collect();
+        encodings.sort();
+        encodings
+    };
+    let sorted: Vec<Vec<u8>> = object.keys().map(|key| key.to_vec().unwrap()).collect();
+    assert_eq!(sorted, sorted_by_encoding);
+
+    // Non-finite keys are rejected both ways.
+    let map = cbor::Value::Map(object);
+    assert!(map.to_vec().is_err());
+    assert!(cbor::to_vec_canonical(&map).is_err());
+    for key in [[0xf9, 0x7e, 0x00], [0xf9, 0x7c, 0x00], [0xf9, 0xfc, 0x00]].iter() {
+        let mut bytes = vec![0xa1];
+        bytes.extend_from_slice(key);
+        bytes.push(0x01);
+        assert!(cbor::Value::from_slice(&bytes).is_err());
+    }
+
+    let mut finite = cbor::Object::new();
+    finite.insert(Float(-0.0), cbor::Value::Integer(1));
+    finite.insert(Float(1.5), cbor::Value::Integer(2));
+    let map = cbor::Value::Map(finite);
+    let bytes = map.to_vec().unwrap();
+    assert_eq!(cbor::Value::from_slice(&bytes).unwrap(), map);
+}
+
+#[test]
+fn test_json_debug_string() {
+    let mut array = json::Array::new();
+    array.extend((0..1000).map(|i| json::Value::Number(json::Number::U64(i))));
+    let mut object = json::Object::new();
+    object.insert("a".to_owned(), json::Value::Array(array));
+    object.insert("b".to_owned(), json::Value::String("x".repeat(100)));
+    let value = json::Value::Object(object);
+
+    assert_eq!(value.to_debug_string(0), "{…(+2)}");
+    assert_eq!(
+        value.to_debug_string(12),
+        r#"{"a": [0, 1, …(+998)], …(+1)}"#
+    );
+    let preview = value.to_debug_string(usize::MAX);
+    assert!(preview.starts_with(r#"{"a": [0, 1, 2, 3, "#));
+    assert!(preview.ends_with(&format!(r#"998, 999], "b": "{}"}}"#, "x".repeat(100))));
+    let preview = value.to_debug_string(4950);
+    assert!(preview.ends_with(&format!(r#"999], "b": "{}…"}}"#, "x".repeat(46))));
+    assert_eq!(preview.len(), 4950 + "…\"}".len());
+
+    // The budget counts the escaped string.
+    let value = json::Value::String("\"\n".repeat(100));
+    for &max_len in &[0, 1, 2, 3, 10, 11, 401, 402] {
+        let preview = value.to_debug_string(max_len);
+        assert!(preview.len() <= max_len.max(1) + "…\"".len(), "{}", preview);
+        assert!(preview.len() + 4 > max_len.min(402), "{}", preview);
+    }
+    assert_eq!(value.to_debug_string(11), r#""\"\n\"\n\"…""#);
+    assert_eq!(value.to_debug_string(401), json::to_string(&value).unwrap());
+    let value = json::Value::String("x".repeat(1 << 20));
+    assert_eq!(value.to_debug_string(4), r#""xxx…""#);
+
+    let mut value = json::Value::Null;
+    for _ in 0..100_000 {
+        let mut array = json::Array::new();
+        array.push(value);
+        value = json::Value::Array(array);
+    }
+    let preview = value.to_debug_string(3);
+    assert_eq!(preview, format!("[[[…(+1){}", "]".repeat(3)));
+}
+
+#[test]
+fn test_json_merge() {
+    // The examples of RFC 7386, with keys in an order that both kinds of
+    // `Object` keep.
+    let cases = [
+        (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+        (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+        (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+        (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+        (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+        (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+        (
+            r#"{"a":{"b":"c"}}"#,
+            r#"{"a":{"b":"d","c":null}}"#,
+            r#"{"a":{"b":"d"}}"#,
+        ),
+        (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+        (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+        (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+        (r#"{"a":"foo"}"#, "null", "null"),
+        (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+        (r#"{"a":null}"#, r#"{"e":1}"#, r#"{"a":null,"e":1}"#),
+        (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+        (
+            r#"{}"#,
+            r#"{"a":{"bb":{"ccc":null}}}"#,
+            r#"{"a":{"bb":{}}}"#,
+        ),
+    ];
+    for (target, patch, expected) in &cases {
+        let mut value: json::Value = json::from_str(target).unwrap();
+        value.merge(json::from_str(patch).unwrap());
+        assert_eq!(json::to_string(&value).unwrap(), *expected, "{}", patch);
+    }
+
+    // No recursion on the depth of the patch.
+    let mut patch = json::Value::Null;
+    for _ in 0..100_000 {
+        patch = json::Object::new().with("a", patch).with("b", 1).into();
+    }
+    let mut value = json::Value::Null;
+    value.merge(patch);
+    let mut depth = 0;
+    let mut node = &value;
+    while let json::Value::Object(object) = node {
+        assert_eq!(object.len(), if depth < 99_999 { 2 } else { 1 });
+        node = match object.get("a") {
+            Some(child) => child,
+            None => break,
+        };
+        depth += 1;
+    }
+    assert_eq!(depth, 99_999);
+}
+
+#[test]
+fn test_json_object_keys() {
+    let long = "k".repeat(23);
+    let j = format!(r#"{{"a":1,"{}":2}}"#, long);
+    let value: json::Value = json::from_str(&j).unwrap();
+    let object = match &value {
+        json::Value::Object(object) => object,
+        _ => panic!(),
+    };
+    assert!(object.contains_key("a"));
+    assert!(object.contains_key(&*long));
+    let keys: Vec<&str> = object.keys().map(|key| &**key).collect();
+    assert_eq!(keys, ["a", &*long]);
+    assert_eq!(json::to_string(&value).unwrap(), j);
+
+    let object: json::Object = vec![("b", json::Value::Null)].into_iter().collect();
+    let value = json::Value::Object(object);
+    assert_eq!(json::to_string(&value).unwrap(), r#"{"b":null}"#);
+
+    // The same type, whether or not `smallstring` stores it inline.
+    let key = json::Key::from(long.clone());
+    assert_eq!(key.as_str(), long);
+    assert_eq!(String::from(key), long);
+    assert_eq!(
+        std::mem::size_of::<json::Key>(),
+        std::mem::size_of::<String>()
+    );
+}
+
+#[test]
+fn test_json_conversions() {
+    let object = json::Object::from(vec![("a", json::Value::from(1_u8))])
+        .with("b", vec![Some("x"), None])
+        .with("c", json::Object::new().with("d", false));
+    let value = json::Value::from(object);
+    assert_eq!(
+        json::to_string(&value).unwrap(),
+        r#"{"a":1,"b":["x",null],"c":{"d":false}}"#,
+    );
+
+    let value: json::Value = vec![("k", 1), ("l", 2)].into_iter().collect();
+    assert_eq!(json::to_string(&value).unwrap(), r#"{"k":1,"l":2}"#);
+    let value: json::Value = (1..4).collect();
+    assert_eq!(json::to_string(&value).unwrap(), "[1,2,3]");
+
+    let mut object = json::Object::new();
+    for word in "a b a".split(' ') {
+        let count = object.entry(word).or_insert_with(|| json::Value::from(0));
+        if let json::Value::Number(n) = count {
+            *n = (n.as_u64().unwrap() + 1).into();
+        }
+    }
+    assert_eq!(
+        json::to_string(&json::Value::from(object)).unwrap(),
+        r#"{"a":2,"b":1}"#,
+    );
+}
+
+#[test]
+#[cfg(feature = "preserve_order")]
+fn test_json_preserve_order() {
+    let j = r#"{"zz":1,"a":{"y":true,"b":null},"m":[]}"#;
+    let value: json::Value = json::from_str(j).unwrap();
+    assert_eq!(json::to_string(&value).unwrap(), j);
+    assert_eq!(
+        value.to_debug_string(100),
+        r#"{"zz": 1, "a": {"y": true, "b": null}, "m": []}"#
+    );
+
+    let mut object = match value {
+        json::Value::Object(object) => object,
+        _ => unreachable!(),
+    };
+    assert!(object.remove("a").is_some());
+    object.insert("b", json::Value::Null);
+    let keys = object.keys().map(|key| &**key).collect::<Vec<_>>();
+    assert_eq!(keys, ["zz", "m", "b"]);
+}
+
+// The same on both sides of the `preserve_order` feature.
+#[test]
+fn test_json_object_api() {
+    let text = |value: &json::Value| json::to_string(value).unwrap();
+    let mut object: json::Object = vec![("b", json::Value::from(1)), ("a", 2.into())]
+        .into_iter()
+        .collect();
+    assert_eq!(object.len(), 2);
+    assert!(object.contains_key("a"));
+    assert_eq!(text(&object["b"]), "1");
+    object["b"] = json::Value::from(3);
+
+    match object.entry("a") {
+        json::object::Entry::Occupied(mut entry) => {
+            assert_eq!(text(&entry.insert(json::Value::Null)), "2");
+            assert_eq!(text(&entry.remove()), "null");
+        }
+        json::object::Entry::Vacant(_) => unreachable!(),
+    }
+    object
+        .entry("c")
+        .and_modify(|_| unreachable!())
+        .or_insert(json::Value::from(true));
+    object.extend(vec![("d", json::Value::from("x"))]);
+
+    for value in object.values_mut() {
+        if let json::Value::Number(_) = value {
+            *value = json::Value::from(4);
+        }
+    }
+    let mut entries = object.into_iter();
+    assert_eq!(entries.len(), 3);
+    let (key, value) = entries.next().unwrap();
+    assert_eq!((&*key, text(&value).as_str()), ("b", "4"));
+    let (key, value) = entries.next_back().unwrap();
+    assert_eq!((&*key, text(&value).as_str()), ("d", r#""x""#));
+}
+
+#[test]
+fn test_cbor_merge_diff() {
+    fn map(entries: Vec<(cbor::Value, cbor::Value)>) -> cbor::Value {
+        cbor::Value::Map(entries.into_iter().collect())
+    }
+    let text = |s: &str| cbor::Value::Text(s.to_owned());
+    let int = cbor::Value::from;
+
+    let old = map(vec![
+        (int(1), text("one")),
+        (
+            text("nested"),
+            map(vec![(int(-1), int(0)), (text("gone"), int(1))]),
+        ),
+        (
+            cbor::Value::Bytes(vec![0]),
+            cbor::Value::Array(vec![int(1)].into_iter().collect()),
+        ),
+        (text("same"), map(vec![(text("a"), int(1))])),
+        (text("kind"), map(vec![])),
+    ]);
+    let new = map(vec![
+        (int(1), text("uno")),
+        (
+            text("nested"),
+            map(vec![(int(-1), int(0)), (int(2), text("two"))]),
+        ),
+        (
+            cbor::Value::Bytes(vec![0]),
+            cbor::Value::Array(vec![int(2)].into_iter().collect()),
+        ),
+        (text("same"), map(vec![(text("a"), int(1))])),
+        (text("kind"), cbor::Value::Float(1.0)),
+    ]);
+    let patch = cbor::diff(&old, &new).unwrap();
+    let expected = map(vec![
+        (int(1), text("uno")),
+        (
+            text("nested"),
+            map(vec![
+                (int(2), text("two")),
+                (text("gone"), cbor::Value::Undefined),
+            ]),
+        ),
+        (
+            cbor::Value::Bytes(vec![0]),
+            cbor::Value::Array(vec![int(2)].into_iter().collect()),
+        ),
+        (text("kind"), cbor::Value::Float(1.0)),
+    ]);
+    assert_eq!(patch, expected);
+    // The patch is in canonical order, as `to_vec_canonical` would sort it.
+    assert_eq!(
+        cbor::to_vec(&patch).unwrap(),
+        cbor::to_vec_canonical(&patch).unwrap()
+    );
+
+    let mut merged = old.clone();
+    merged.merge(patch);
+    assert_eq!(merged, new);
+    assert!(cbor::diff(&merged, &new).is_none());
+    assert_eq!(cbor::diff(&old, &int(1)), Some(int(1)));
+    assert_eq!(cbor::diff(&int(1), &int(1)), None);
+    assert_eq!(
+        cbor::diff(&int(1), &cbor::Value::Float(1.0)),
+        Some(cbor::Value::Float(1.0))
+    );
+
+    // A map patch turns any other value into a map, without its removals.
+    let mut value = int(1);
+    value.merge(map(vec![
+        (text("a"), cbor::Value::Undefined),
+        (text("b"), map(vec![])),
+    ]));
+    assert_eq!(value, map(vec![(text("b"), map(vec![]))]));
+
+    // `undefined` removes entries, so that `null` can be set.
+    let old = map(vec![(text("x"), int(1)), (text("y"), int(2))]);
+    let new = map(vec![(text("x"), cbor::Value::Null)]);
+    let patch = cbor::diff(&old, &new).unwrap();
+    assert_eq!(
+        patch,
+        map(vec![
+            (text("x"), cbor::Value::Null),
+            (text("y"), cbor::Value::Undefined),
+        ])
+    );
+    let mut merged = old;
+    merged.merge(patch.clone());
+    assert_eq!(merged, new);
+
+    // And it survives encoding, while other formats write it as `null`.
+    let bytes = cbor::to_vec(&patch).unwrap();
+    assert_eq!(bytes, b"\xa2\x61x\xf6\x61y\xf7");
+    assert_eq!(cbor::from_slice::<cbor::Value>(&bytes).unwrap(), patch);
+    assert_eq!(json::to_string(&patch).unwrap(), r#"{"x":null,"y":null}"#);
+
+    // No recursion on the depth of either.
+    let deep = |last| {
+        let mut deep = cbor::Value::Null;
+        for i in 0..100_000 {
+            deep = map(vec![(text("a"), deep), (text("b"), int(i))]);
+        }
+        if let cbor::Value::Map(map) = &mut deep {
+            map.insert(text("b"), int(last));
+        }
+        deep
+    };
+    let mut value = deep(0);
+    let patch = cbor::diff(&value, &deep(-1)).unwrap();
+    assert_eq!(patch, map(vec![(text("b"), int(-1))]));
+    value.merge(deep(1));
+    assert_eq!(value.get("b"), Some(&int(1)));
+}
@@ -0,0 +1,22 @@
+use miniserde_ditto::{check_wire_compat, WIRE_COMPAT_VERSION};
+
+#[test]
+fn test_changelog_matches_version() {
+    let changelog = include_str!("../CHANGELOG.md");
+    let latest = changelog
+        .lines()
+        .find_map(|line| line.strip_prefix("## Wire format version "))
+        .expect("CHANGELOG.md has no wire format version section");
+    assert_eq!(
+        latest.trim().parse::<u32>().unwrap(),
+        WIRE_COMPAT_VERSION,
+        "bumping WIRE_COMPAT_VERSION needs a CHANGELOG.md entry",
+    );
+}
+
+#[test]
+fn test_check_wire_compat() {
+    assert!(check_wire_compat(WIRE_COMPAT_VERSION).is_ok());
+    assert!(check_wire_compat(WIRE_COMPAT_VERSION - 1).is_err());
+    assert!(check_wire_compat(WIRE_COMPAT_VERSION + 1).is_err());
+}
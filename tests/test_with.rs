@@ -0,0 +1,174 @@
+use miniserde_ditto::de::Visitor;
+use miniserde_ditto::ser::ValueView;
+use miniserde_ditto::{json, make_place, Deserialize, Result, Serialize};
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::fmt::Display;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+make_place!(Place);
+
+/// `SystemTime` as seconds since the epoch.
+mod epoch_secs {
+    use super::*;
+
+    pub fn serialize(time: &SystemTime) -> ValueView<'_> {
+        let secs = time.duration_since(UNIX_EPOCH).unwrap().as_secs();
+        ValueView::Int(secs.into())
+    }
+
+    impl Visitor for Place<SystemTime> {
+        fn int(&mut self, i: i128) -> Result<()> {
+            let secs = u64::try_from(i).map_err(|_| miniserde_ditto::Error)?;
+            self.out = Some(UNIX_EPOCH + Duration::from_secs(secs));
+            Ok(())
+        }
+    }
+
+    pub fn deserialize(out: &mut Option<SystemTime>) -> &mut dyn Visitor {
+        Place::new(out)
+    }
+}
+
+fn to_upper(s: &str) -> ValueView<'_> {
+    ValueView::Str(Cow::Owned(s.to_uppercase()))
+}
+
+fn display<T: Display>(value: &T) -> ValueView<'_> {
+    ValueView::Str(Cow::Owned(value.to_string()))
+}
+
+struct Lowercase(String);
+
+impl Visitor for Place<Lowercase> {
+    fn string(&mut self, s: &str) -> Result<()> {
+        self.out = Some(Lowercase(s.to_lowercase()));
+        Ok(())
+    }
+}
+
+fn lowercase(out: &mut Option<Lowercase>) -> &mut dyn Visitor {
+    Place::new(out)
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Event {
+    #[serde(with = "epoch_secs")]
+    at: SystemTime,
+    #[serde(serialize_with = "to_upper")]
+    name: String,
+    #[serde(rename = "n", serialize_with = "display")]
+    #[serde(skip_deserializing)]
+    count: u32,
+}
+
+#[test]
+fn test_with() {
+    let event = Event {
+        at: UNIX_EPOCH + Duration::from_secs(1_600_000_000),
+        name: "deploy".to_owned(),
+        count: 3,
+    };
+    let j = json::to_string(&event).unwrap();
+    assert_eq!(j, r#"{"at":1600000000,"name":"DEPLOY","n":"3"}"#);
+
+    let event: Event = json::from_str(&j).unwrap();
+    assert_eq!(event.at, UNIX_EPOCH + Duration::from_secs(1_600_000_000));
+    assert_eq!(event.name, "DEPLOY");
+    assert_eq!(event.count, 0);
+
+    // The function is all there is to deserialize the field with, which may
+    // not be left out.
+    assert!(json::from_str::<Event>(r#"{"name":"a"}"#).is_err());
+    assert!(json::from_str::<Event>(r#"{"at":-1,"name":"a"}"#).is_err());
+}
+
+#[test]
+fn test_deserialize_with() {
+    #[derive(Deserialize)]
+    struct Tag {
+        #[serde(deserialize_with = "lowercase")]
+        label: Lowercase,
+        note: Option<String>,
+    }
+
+    let tag: Tag = json::from_str(r#"{"label":"RUST"}"#).unwrap();
+    assert_eq!(tag.label.0, "rust");
+    assert_eq!(tag.note, None);
+}
+
+#[test]
+fn test_presence_bitmap() {
+    impl Visitor for Place<Option<Lowercase>> {
+        fn null(&mut self) -> Result<()> {
+            self.out = Some(None);
+            Ok(())
+        }
+
+        fn string(&mut self, s: &str) -> Result<()> {
+            self.out = Some(Some(Lowercase(s.to_lowercase())));
+            Ok(())
+        }
+    }
+
+    fn optional_lowercase(out: &mut Option<Option<Lowercase>>) -> &mut dyn Visitor {
+        Place::new(out)
+    }
+
+    #[derive(Deserialize)]
+    #[serde(presence_bitmap)]
+    struct Reading {
+        id: u8,
+        #[serde(deserialize_with = "optional_lowercase")]
+        unit: Option<Lowercase>,
+    }
+
+    // A field left out by the bitmap is `None`, even with a function.
+    let reading: Reading = json::from_str("[0,7]").unwrap();
+    assert_eq!(reading.id, 7);
+    assert!(reading.unit.is_none());
+    let reading: Reading = json::from_str(r#"[1,7,"MV"]"#).unwrap();
+    assert_eq!(reading.unit.unwrap().0, "mv");
+}
+
+#[test]
+fn test_generic() {
+    // The parameter of a field with a function is not bound by `Serialize`,
+    // but by what the function needs.
+    #[derive(Serialize)]
+    #[serde(bound = "T: Display")]
+    struct Labeled<T> {
+        #[serde(serialize_with = "display")]
+        label: T,
+    }
+
+    struct NotSerialize;
+
+    impl Display for NotSerialize {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("not serialize")
+        }
+    }
+
+    let j = json::to_string(&Labeled {
+        label: NotSerialize,
+    })
+    .unwrap();
+    assert_eq!(j, r#"{"label":"not serialize"}"#);
+
+    fn len<T>(items: &[T]) -> ValueView<'_> {
+        ValueView::Int(items.len() as i128)
+    }
+
+    #[derive(Serialize)]
+    struct Counted<T> {
+        #[serde(serialize_with = "len")]
+        items: Vec<T>,
+    }
+
+    let j = json::to_string(&Counted {
+        items: vec![NotSerialize, NotSerialize],
+    })
+    .unwrap();
+    assert_eq!(j, r#"{"items":2}"#);
+}
@@ -0,0 +1,129 @@
+use miniserde_ditto::{cbor, json, Deserialize, Serialize};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = Cell::new(0);
+}
+
+struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // Not counted while the thread local is being torn down.
+        let _ = ALLOCATIONS.try_with(|it| it.set(it.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let ret = f();
+    (ret, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Point {
+    x: i32,
+    y: i32,
+    visible: bool,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Labeled {
+    #[serde(rename = "label_that_does_not_fit_inline_in_a_key_buffer_at_all")]
+    label: u8,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct Segment {
+    from: Point,
+    to: Wrapped,
+}
+
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+struct Wrapped(Point);
+
+#[test]
+fn test_flat_struct() {
+    let point = Point {
+        x: 1,
+        y: -2,
+        visible: true,
+    };
+    let bytes = cbor::to_vec(&point).unwrap();
+    let (decoded, count) = allocations(|| cbor::from_slice::<Point>(&bytes).unwrap());
+    assert_eq!(decoded, point);
+    assert_eq!(count, 0);
+
+    let segment = Segment {
+        from: point,
+        to: Wrapped(Point {
+            x: 3,
+            y: 4,
+            visible: false,
+        }),
+    };
+    let bytes = cbor::to_vec(&segment).unwrap();
+    let (decoded, count) = allocations(|| cbor::from_slice::<Segment>(&bytes).unwrap());
+    assert_eq!(decoded, segment);
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn test_ignored_fields() {
+    let j = r#"{"x": 1, "y": 2, "visible": true, "extra": {"a": [1, [2], {}], "b": null}}"#;
+    let value = json::from_str::<json::Value>(j).unwrap();
+    let bytes = cbor::to_vec(&value).unwrap();
+    for _ in 0..10 {
+        let (point, count) = allocations(|| cbor::from_slice::<Point>(&bytes).unwrap());
+        assert_eq!(point.y, 2);
+        assert_eq!(count, 0);
+    }
+}
+
+#[test]
+fn test_long_key() {
+    let labeled = Labeled { label: 7 };
+    let bytes = cbor::to_vec(&labeled).unwrap();
+    let (decoded, count) = allocations(|| cbor::from_slice::<Labeled>(&bytes).unwrap());
+    assert_eq!(decoded, labeled);
+    // The key, longer than the inline buffer.
+    assert_eq!(count, 1);
+    let j = json::to_string(&labeled).unwrap();
+    assert_eq!(json::from_str::<Labeled>(&j).unwrap(), labeled);
+}
+
+#[derive(PartialEq, Debug, Deserialize)]
+struct Pair {
+    a: u32,
+    b: u32,
+}
+
+#[test]
+fn test_box_reuses_slot() {
+    fn extra_for_box<T: Deserialize + PartialEq + std::fmt::Debug>(j: &str) -> usize {
+        let (unboxed, count) = allocations(|| json::from_str::<T>(j).unwrap());
+        let (boxed, boxed_count) = allocations(|| json::from_str::<Box<T>>(j).unwrap());
+        assert_eq!(*boxed, unboxed);
+        boxed_count - count
+    }
+
+    // The boxed `Map`, and the slot which becomes the `Box`, whether or not
+    // it has to shrink.
+    assert_eq!(extra_for_box::<Point>(r#"{"x":1,"y":2,"visible":true}"#), 2);
+    assert_eq!(extra_for_box::<Pair>(r#"{"a":1,"b":2}"#), 2);
+    assert_eq!(extra_for_box::<Vec<u8>>("[1,2,3]"), 2);
+}